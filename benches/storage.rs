@@ -0,0 +1,158 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+//! Benchmarks for the storage hot paths, at 10k, 100k and 1M stored bookings.
+//!
+//! Run with ```cargo bench --bench storage```. Storage reads and writes its snapshots in the
+//! working directory, so the benchmarks run in a temporary directory rather than the project
+//! directory, leaving any local data untouched.
+
+#![allow(clippy::needless_return)]
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use room_booking_service::pagination::Pagination;
+use room_booking_service::seed::{self, SeedOptions};
+use room_booking_service::storage;
+use room_booking_service::storage::booking_filter::BookingFilter;
+use room_booking_service::storage::room_booking::RoomBooking;
+use room_booking_service::storage::snapshot;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// The numbers of stored bookings each benchmark is run against.
+static SIZES: [usize; 3] = [10_000, 100_000, 1_000_000];
+
+/// Changes to the temporary directory the benchmarks run in, creating it if needed.
+fn enter_workspace() {
+    let workspace: PathBuf = std::env::temp_dir().join("room_booking_service_bench");
+    fs::create_dir_all(&workspace).expect("failed to create the benchmark directory");
+    std::env::set_current_dir(&workspace).expect("failed to enter the benchmark directory");
+}
+
+/// Replaces the stored bookings with a generated set of the provided size, by writing a snapshot
+/// to the benchmark directory and loading it. Each set is generated once and reused between
+/// benchmarks. Any other storage files written by earlier benchmarks are removed first.
+fn prepare(size: usize) {
+    enter_workspace();
+    let fixture: String = format!("fixture-{}-v{}.bin", size, snapshot::VERSION);
+
+    if fs::metadata(&fixture).is_err() {
+        let bookings: HashMap<u32, RoomBooking> = seed::generate(&SeedOptions::new(size))
+            .into_iter()
+            .zip(1..)
+            .map(|(mut booking, booking_id)| {
+                booking.set_booking_id(booking_id);
+                return (booking_id, booking);
+            })
+            .collect();
+        let content: Vec<u8> = snapshot::encode(&bookings).expect("failed to write fixture");
+        fs::write(&fixture, content).expect("failed to write fixture");
+    }
+
+    for entry in fs::read_dir(".").expect("failed to read the benchmark directory") {
+        let path: PathBuf = entry
+            .expect("failed to read the benchmark directory")
+            .path();
+        if path.extension().is_some_and(|extension| extension == "dat") {
+            fs::remove_file(path).expect("failed to remove storage file");
+        }
+    }
+
+    fs::copy(&fixture, "booking.dat").expect("failed to copy fixture");
+    storage::load_snapshot().expect("failed to load fixture");
+}
+
+/// Returns a new booking which may be created, with no id or status.
+fn new_booking() -> RoomBooking {
+    let mut booking: RoomBooking = seed::generate(&SeedOptions::new(1)).remove(0);
+    booking.status = None;
+    return booking;
+}
+
+fn create(c: &mut Criterion) {
+    let mut group = c.benchmark_group("create");
+    group.sample_size(10);
+
+    for size in SIZES {
+        prepare(size);
+        let booking: RoomBooking = new_booking();
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter(|| storage::create(black_box(booking.clone()), true));
+        });
+    }
+
+    group.finish();
+}
+
+fn lookups(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lookup");
+
+    for size in SIZES {
+        prepare(size);
+        let booking_id: u32 = (size / 2) as u32;
+        let customer_id: u32 = storage::fetch_by_id(booking_id).unwrap().customer_id;
+
+        group.bench_with_input(BenchmarkId::new("by_id", size), &size, |b, _| {
+            b.iter(|| storage::fetch_by_id(black_box(booking_id)));
+        });
+        group.bench_with_input(BenchmarkId::new("by_customer_id", size), &size, |b, _| {
+            b.iter(|| storage::fetch_by_customer_id(black_box(customer_id)));
+        });
+    }
+
+    group.finish();
+}
+
+fn scans(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scan");
+    group.sample_size(10);
+
+    let filter = BookingFilter {
+        status: Some("Confirmed".to_string()),
+        ..Default::default()
+    };
+    let pagination = Pagination::new(Some(100), Some(1000));
+
+    for size in SIZES {
+        prepare(size);
+
+        group.bench_with_input(BenchmarkId::new("count_filtered", size), &size, |b, _| {
+            b.iter(|| storage::count_filtered(black_box(&filter)));
+        });
+        group.bench_with_input(BenchmarkId::new("fetch_page", size), &size, |b, _| {
+            b.iter(|| storage::fetch_page(black_box(&filter), black_box(&pagination)));
+        });
+        group.bench_with_input(BenchmarkId::new("iter_filtered", size), &size, |b, _| {
+            b.iter(|| storage::iter_filtered(black_box(&filter)).count());
+        });
+        group.bench_with_input(BenchmarkId::new("fetch_filtered", size), &size, |b, _| {
+            b.iter(|| storage::fetch_filtered(black_box(&filter)));
+        });
+    }
+
+    group.finish();
+}
+
+fn snapshots(c: &mut Criterion) {
+    let mut group = c.benchmark_group("snapshot");
+    group.sample_size(10);
+
+    for size in SIZES {
+        prepare(size);
+
+        group.bench_with_input(BenchmarkId::new("save", size), &size, |b, _| {
+            b.iter(|| assert!(storage::save_bookings()));
+        });
+        group.bench_with_input(BenchmarkId::new("load", size), &size, |b, _| {
+            b.iter(|| storage::load_snapshot().unwrap());
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, create, lookups, scans, snapshots);
+criterion_main!(benches);
@@ -0,0 +1,227 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+//! Requests for the administration endpoints, other than those exchanging files, such as imports
+//! and the ledger export.
+
+use crate::client::{encode, BookingClient, Query};
+use crate::error::ClientError;
+use crate::models::*;
+use hyper::Method;
+
+impl BookingClient {
+    /// Fetches every inventory adjustment.
+    pub async fn get_inventory_adjustments(&self) -> Result<Vec<InventoryAdjustment>, ClientError> {
+        return self.get("/admin/inventory/adjustments").await;
+    }
+
+    /// Creates an inventory adjustment. Returns the adjustment, with its id.
+    ///
+    /// # Arguments
+    ///
+    /// * `adjustment` - The adjustment to create
+    pub async fn create_inventory_adjustment(
+        &self,
+        adjustment: &InventoryAdjustment,
+    ) -> Result<InventoryAdjustment, ClientError> {
+        return self
+            .send(
+                Method::POST,
+                "/admin/inventory/adjustments",
+                Some(adjustment),
+            )
+            .await;
+    }
+
+    /// Fetches an inventory adjustment.
+    ///
+    /// # Arguments
+    ///
+    /// * `adjustment_id` - The id of the adjustment
+    pub async fn get_inventory_adjustment(
+        &self,
+        adjustment_id: u32,
+    ) -> Result<InventoryAdjustment, ClientError> {
+        return self
+            .get(&format!("/admin/inventory/adjustments/{}", adjustment_id))
+            .await;
+    }
+
+    /// Deletes an inventory adjustment. Returns the deleted adjustment.
+    ///
+    /// # Arguments
+    ///
+    /// * `adjustment_id` - The id of the adjustment
+    pub async fn delete_inventory_adjustment(
+        &self,
+        adjustment_id: u32,
+    ) -> Result<InventoryAdjustment, ClientError> {
+        let path: String = format!("/admin/inventory/adjustments/{}", adjustment_id);
+        return self.send::<(), _>(Method::DELETE, &path, None).await;
+    }
+
+    /// Fetches every exchange rate override.
+    pub async fn get_exchange_rate_overrides(
+        &self,
+    ) -> Result<Vec<ExchangeRateOverride>, ClientError> {
+        return self.get("/admin/exchange-rates").await;
+    }
+
+    /// Sets the exchange rate for a currency, used instead of the rate from the exchange rate
+    /// source until it is deleted.
+    ///
+    /// # Arguments
+    ///
+    /// * `currency` - The ISO 4217 code of the currency, e.g. 'EUR'
+    /// * `rate` - The rate to use
+    pub async fn set_exchange_rate_override(
+        &self,
+        currency: &str,
+        rate: &ExchangeRateOverride,
+    ) -> Result<ExchangeRateOverride, ClientError> {
+        let path: String = format!("/admin/exchange-rates/{}", encode(currency));
+        return self.send(Method::PUT, &path, Some(rate)).await;
+    }
+
+    /// Deletes the exchange rate override for a currency. Returns the deleted override.
+    ///
+    /// # Arguments
+    ///
+    /// * `currency` - The ISO 4217 code of the currency, e.g. 'EUR'
+    pub async fn delete_exchange_rate_override(
+        &self,
+        currency: &str,
+    ) -> Result<ExchangeRateOverride, ClientError> {
+        let path: String = format!("/admin/exchange-rates/{}", encode(currency));
+        return self.send::<(), _>(Method::DELETE, &path, None).await;
+    }
+
+    /// Fetches the circuit breaker state and call metrics of each outbound integration.
+    pub async fn get_integrations(&self) -> Result<Vec<CircuitMetrics>, ClientError> {
+        return self.get("/admin/integrations").await;
+    }
+
+    /// Fetches the lock wait, scan and snapshot write metrics of the storage layer.
+    pub async fn get_storage_metrics(&self) -> Result<StorageMetrics, ClientError> {
+        return self.get("/admin/storage/metrics").await;
+    }
+
+    /// Fetches the booking events waiting to be delivered.
+    pub async fn get_outbox(&self) -> Result<Vec<OutboxEvent>, ClientError> {
+        return self.get("/admin/outbox").await;
+    }
+
+    /// Fetches the booking events which failed delivery.
+    pub async fn get_dead_letters(&self) -> Result<Vec<OutboxEvent>, ClientError> {
+        return self.get("/admin/dead-letters").await;
+    }
+
+    /// Fetches the feature flags, and whether each feature is enabled.
+    pub async fn get_feature_flags(&self) -> Result<FeatureFlags, ClientError> {
+        return self.get("/admin/features").await;
+    }
+
+    /// Reloads the hotel settings and feature flags without restarting the server. Returns the
+    /// feature flags now in use.
+    pub async fn reload_config(&self) -> Result<FeatureFlags, ClientError> {
+        return self
+            .send::<(), _>(Method::POST, "/admin/config/reload", None)
+            .await;
+    }
+
+    /// Fetches the screening decisions made for new bookings.
+    pub async fn get_screening_decisions(&self) -> Result<Vec<ScreeningDecision>, ClientError> {
+        return self.get("/admin/screening").await;
+    }
+
+    /// Fetches a booking event which failed delivery.
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence` - The sequence number of the event's change
+    pub async fn get_dead_letter(&self, sequence: u64) -> Result<OutboxEvent, ClientError> {
+        return self.get(&format!("/admin/dead-letters/{}", sequence)).await;
+    }
+
+    /// Moves a booking event which failed delivery back to the outbox. Returns the event.
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence` - The sequence number of the event's change
+    pub async fn redeliver_dead_letter(&self, sequence: u64) -> Result<OutboxEvent, ClientError> {
+        let path: String = format!("/admin/dead-letters/{}/redeliver", sequence);
+        return self.send::<(), _>(Method::POST, &path, None).await;
+    }
+
+    /// Fetches the changes made to bookings and every other record since a cursor.
+    ///
+    /// # Arguments
+    ///
+    /// * `after` - The cursor returned by the previous request, if any
+    /// * `limit` - The maximum number of changes to return
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let page = client.get_cdc(Some(cursor), Some(100)).await?;
+    /// cursor = page.next_cursor;
+    /// ```
+    pub async fn get_cdc(
+        &self,
+        after: Option<u64>,
+        limit: Option<usize>,
+    ) -> Result<CdcPage, ClientError> {
+        let path: String = Query::default()
+            .push("after", after)
+            .push("limit", limit)
+            .to_path("/cdc");
+        return self.get(&path).await;
+    }
+
+    /// Replays historical booking events from the change log to a configured webhook. Returns a
+    /// report of the events delivered.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_seq` - The sequence number of the first change to replay
+    /// * `to_seq` - The sequence number of the last change to replay
+    /// * `target` - The URL of the webhook
+    pub async fn replay_events(
+        &self,
+        from_seq: u64,
+        to_seq: u64,
+        target: &str,
+    ) -> Result<ReplayReport, ClientError> {
+        let path: String = Query::default()
+            .push("from_seq", Some(from_seq))
+            .push("to_seq", Some(to_seq))
+            .push("target", Some(target))
+            .to_path("/admin/events/replay");
+        return self.send::<(), _>(Method::POST, &path, None).await;
+    }
+
+    /// Applies the data retention policy. Returns a report of the bookings removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `dry_run` - Whether to only report the bookings which would be removed, or the
+    ///   configured setting if not provided
+    pub async fn apply_retention(
+        &self,
+        dry_run: Option<bool>,
+    ) -> Result<RetentionReport, ClientError> {
+        let path: String = Query::default()
+            .push("dry_run", dry_run)
+            .to_path("/admin/retention");
+        return self.send::<(), _>(Method::POST, &path, None).await;
+    }
+
+    /// Removes every sandbox booking. Returns the ids of the bookings removed.
+    pub async fn wipe_sandbox(&self) -> Result<Vec<u32>, ClientError> {
+        return self
+            .send::<(), _>(Method::DELETE, "/admin/sandbox", None)
+            .await;
+    }
+}
@@ -0,0 +1,681 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+//! Requests for room bookings, their payments and add-ons, and lists and reports of bookings.
+
+use crate::client::{encode, BookingClient, Query};
+use crate::error::ClientError;
+use crate::models::*;
+use hyper::header::HeaderMap;
+use hyper::Method;
+
+/// Describes a page of the booking list
+#[derive(Clone, PartialEq, Debug)]
+pub struct BookingList {
+    /// The bookings on the page
+    pub bookings: Vec<RoomBooking>,
+    /// The number of bookings matching the filter, across every page
+    pub total: usize,
+}
+
+impl BookingList {
+    /// Returns the cursor to request the page following this one with, or None if this page was
+    /// not full, so is the last page.
+    ///
+    /// # Arguments
+    ///
+    /// * `pagination` - The pagination window the page was requested with
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let after = list.next_cursor(&pagination);
+    /// ```
+    pub fn next_cursor(&self, pagination: &Pagination) -> Option<u32> {
+        if pagination.limit != Some(self.bookings.len()) {
+            return None;
+        }
+
+        return self.bookings.last().and_then(|booking| booking.booking_id);
+    }
+}
+
+impl BookingClient {
+    /// Creates a room booking. Returns the booking, along with links to the actions available for
+    /// it.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking` - The booking to create, without a booking id or status
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let created = client.create_booking(&booking).await?;
+    /// ```
+    pub async fn create_booking(
+        &self,
+        booking: &RoomBooking,
+    ) -> Result<BookingResource, ClientError> {
+        return self.send(Method::POST, "/booking", Some(booking)).await;
+    }
+
+    /// Creates a booking even if it is likely a duplicate of a booking created moments earlier by
+    /// the same customer, for the same room type and overlapping dates. Returns the created
+    /// booking, along with links to the actions available for it.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking` - The booking to create, without a booking id or status
+    pub async fn create_duplicate_booking(
+        &self,
+        booking: &RoomBooking,
+    ) -> Result<BookingResource, ClientError> {
+        let path: &str = "/booking?allow_duplicate=true";
+        return self.send(Method::POST, path, Some(booking)).await;
+    }
+
+    /// Checks whether a room booking would be created, without creating it. Returns whether the
+    /// booking would be created, along with its status, price and the deposit required, or why
+    /// it would be rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking` - The booking to check, without a booking id or status
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let validation = client.validate_booking(&booking).await?;
+    /// ```
+    pub async fn validate_booking(
+        &self,
+        booking: &RoomBooking,
+    ) -> Result<BookingValidation, ClientError> {
+        return self
+            .send(Method::POST, "/booking/validate", Some(booking))
+            .await;
+    }
+
+    /// Fetches the booking with the provided booking id, along with links to the actions
+    /// available for it.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let booking = client.get_booking(1).await?;
+    /// ```
+    pub async fn get_booking(&self, booking_id: u32) -> Result<BookingResource, ClientError> {
+        return self.get(&format!("/booking/{}", booking_id)).await;
+    }
+
+    /// Checks whether a booking exists with the provided booking id.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let exists = client.booking_exists(1).await?;
+    /// ```
+    pub async fn booking_exists(&self, booking_id: u32) -> Result<bool, ClientError> {
+        let path: String = format!("/booking/{}", booking_id);
+        return match self.request::<()>(Method::HEAD, &path, None).await {
+            Ok(_) => Ok(true),
+            Err(error) if error.status() == Some(404) => Ok(false),
+            Err(error) => Err(error),
+        };
+    }
+
+    /// Fetches the itemized invoice of a booking, with its dates and amounts formatted for the
+    /// hotel's language and region.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    pub async fn get_invoice(&self, booking_id: u32) -> Result<Invoice, ClientError> {
+        return self.get(&format!("/booking/{}/invoice", booking_id)).await;
+    }
+
+    /// Fetches the rates and taxes in effect when a booking was priced.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    pub async fn get_pricing(&self, booking_id: u32) -> Result<PricingRecord, ClientError> {
+        return self.get(&format!("/booking/{}/pricing", booking_id)).await;
+    }
+
+    /// Fetches the amount paid and outstanding for a booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    pub async fn get_balance(&self, booking_id: u32) -> Result<Balance, ClientError> {
+        return self.get(&format!("/booking/{}/balance", booking_id)).await;
+    }
+
+    /// Records a payment against a booking. Returns the booking's updated balance.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    /// * `payment` - The payment received
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let balance = client.record_payment(1, &payment).await?;
+    /// ```
+    pub async fn record_payment(
+        &self,
+        booking_id: u32,
+        payment: &Payment,
+    ) -> Result<Balance, ClientError> {
+        let path: String = format!("/booking/{}/payments", booking_id);
+        return self.send(Method::POST, &path, Some(payment)).await;
+    }
+
+    /// Refunds part or all of the amount paid for a booking. Returns the refund made.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    /// * `refund` - The refund to make
+    pub async fn refund(&self, booking_id: u32, refund: &Refund) -> Result<Refund, ClientError> {
+        let path: String = format!("/booking/{}/refund", booking_id);
+        return self.send(Method::POST, &path, Some(refund)).await;
+    }
+
+    /// Attaches an add-on from the add-on catalog to a booking. Returns the updated booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    /// * `addon` - The add-on to attach
+    pub async fn add_addon(
+        &self,
+        booking_id: u32,
+        addon: &BookingAddon,
+    ) -> Result<BookingResource, ClientError> {
+        let path: String = format!("/booking/{}/addons", booking_id);
+        return self.send(Method::POST, &path, Some(addon)).await;
+    }
+
+    /// Removes an add-on from a booking. Returns the updated booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    /// * `code` - The code of the add-on to remove, e.g. 'breakfast'
+    pub async fn remove_addon(
+        &self,
+        booking_id: u32,
+        code: &str,
+    ) -> Result<BookingResource, ClientError> {
+        let path: String = format!("/booking/{}/addons/{}", booking_id, encode(code));
+        return self.send::<(), _>(Method::DELETE, &path, None).await;
+    }
+
+    /// Fetches the history of a booking, including every status transition and field change.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    pub async fn get_history(
+        &self,
+        booking_id: u32,
+    ) -> Result<Vec<BookingHistoryEntry>, ClientError> {
+        return self.get(&format!("/booking/{}/history", booking_id)).await;
+    }
+
+    /// Checks in the guest of a confirmed booking, which must be paid in full. Returns the updated
+    /// booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    pub async fn check_in(&self, booking_id: u32) -> Result<BookingResource, ClientError> {
+        return self.transition(booking_id, "check-in").await;
+    }
+
+    /// Marks a checked in booking as complete, when the guest checks out. Returns the updated
+    /// booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    pub async fn complete(&self, booking_id: u32) -> Result<BookingResource, ClientError> {
+        return self.transition(booking_id, "complete").await;
+    }
+
+    /// Cancels a booking, optionally recording the reason it was cancelled. Returns the updated
+    /// booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    /// * `cancellation` - The reason code and comment to store on the booking, if any
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let cancellation = Cancellation {
+    ///     reason: CancellationReason::ChangeOfPlans,
+    ///     comment: None,
+    /// };
+    /// client.cancel(1, Some(&cancellation)).await?;
+    /// ```
+    pub async fn cancel(
+        &self,
+        booking_id: u32,
+        cancellation: Option<&Cancellation>,
+    ) -> Result<BookingResource, ClientError> {
+        let path: String = format!("/booking/{}", booking_id);
+        return self.send(Method::DELETE, &path, cancellation).await;
+    }
+
+    /// Marks a booking as a no-show, applying the no-show fee. Returns the updated booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    pub async fn no_show(&self, booking_id: u32) -> Result<BookingResource, ClientError> {
+        return self.transition(booking_id, "no-show").await;
+    }
+
+    /// Reinstates a cancelled booking, if a room is still available. Returns the updated booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    pub async fn reinstate(&self, booking_id: u32) -> Result<BookingResource, ClientError> {
+        return self.transition(booking_id, "reinstate").await;
+    }
+
+    /// Confirms a booking pending payment. Returns the updated booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    pub async fn confirm_payment(&self, booking_id: u32) -> Result<BookingResource, ClientError> {
+        return self.transition(booking_id, "confirm-payment").await;
+    }
+
+    /// Approves a booking held pending review by a screening rule. Returns the updated booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    pub async fn approve(&self, booking_id: u32) -> Result<BookingResource, ClientError> {
+        return self.transition(booking_id, "approve").await;
+    }
+
+    /// Sends a status transition for a booking.
+    async fn transition(
+        &self,
+        booking_id: u32,
+        action: &str,
+    ) -> Result<BookingResource, ClientError> {
+        let path: String = format!("/booking/{}/{}", booking_id, action);
+        return self.send::<(), _>(Method::PUT, &path, None).await;
+    }
+
+    /// Updates the status of several bookings at once. Returns the outcome for each booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `update` - The booking ids to update, and the status to set
+    pub async fn update_statuses(
+        &self,
+        update: &BulkStatusUpdate,
+    ) -> Result<Vec<BulkStatusResult>, ClientError> {
+        return self
+            .send(Method::POST, "/bookings/status", Some(update))
+            .await;
+    }
+
+    /// Quotes the price of a booking, without creating it.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking` - The booking to quote for
+    /// * `currency` - The ISO 4217 code of the currency to quote in, if not the hotel's
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let quote = client.quote(&booking, Some("EUR")).await?;
+    /// ```
+    pub async fn quote(
+        &self,
+        booking: &RoomBooking,
+        currency: Option<&str>,
+    ) -> Result<PriceBreakdown, ClientError> {
+        let path: String = Query::default()
+            .push("currency", currency)
+            .to_path("/bookings/quote");
+        return self.send(Method::POST, &path, Some(booking)).await;
+    }
+
+    /// Creates a series of recurring bookings.
+    ///
+    /// # Arguments
+    ///
+    /// * `recurring` - The booking to repeat, and how often to repeat it
+    pub async fn create_recurring(
+        &self,
+        recurring: &RecurringBooking,
+    ) -> Result<Vec<BookingResource>, ClientError> {
+        return self
+            .send(Method::POST, "/bookings/recurring", Some(recurring))
+            .await;
+    }
+
+    /// Fetches the bookings in a series of recurring bookings.
+    ///
+    /// # Arguments
+    ///
+    /// * `series_id` - The id of the series
+    pub async fn get_series(&self, series_id: u32) -> Result<Vec<RoomBooking>, ClientError> {
+        return self.get(&format!("/bookings/series/{}", series_id)).await;
+    }
+
+    /// Updates every booking in a series of recurring bookings. Returns the updated bookings.
+    ///
+    /// # Arguments
+    ///
+    /// * `series_id` - The id of the series
+    /// * `update` - The changes to make to each booking
+    pub async fn update_series(
+        &self,
+        series_id: u32,
+        update: &SeriesUpdate,
+    ) -> Result<Vec<RoomBooking>, ClientError> {
+        let path: String = format!("/bookings/series/{}", series_id);
+        return self.send(Method::PATCH, &path, Some(update)).await;
+    }
+
+    /// Cancels every booking in a series of recurring bookings. Returns the outcome for each
+    /// booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `series_id` - The id of the series
+    pub async fn cancel_series(
+        &self,
+        series_id: u32,
+    ) -> Result<Vec<BulkStatusResult>, ClientError> {
+        let path: String = format!("/bookings/series/{}", series_id);
+        return self.send::<(), _>(Method::DELETE, &path, None).await;
+    }
+
+    /// Fetches a page of the bookings matching a filter, in booking id order.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The criteria bookings must match
+    /// * `pagination` - The page to fetch, by offset or by cursor
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let filter = BookingFilter {
+    ///     customer_id: Some(1),
+    ///     ..Default::default()
+    /// };
+    /// let list = client.list_bookings(&filter, &Pagination::new(Some(50), None)).await?;
+    /// ```
+    pub async fn list_bookings(
+        &self,
+        filter: &BookingFilter,
+        pagination: &Pagination,
+    ) -> Result<BookingList, ClientError> {
+        let path: String = filter_query(filter)
+            .push("limit", pagination.limit)
+            .push("offset", pagination.offset)
+            .push("after", pagination.after)
+            .to_path("/bookings");
+        let (headers, content): (HeaderMap, Vec<u8>) =
+            self.request::<()>(Method::GET, &path, None).await?;
+        let bookings: Vec<RoomBooking> = serde_json::from_slice(&content)?;
+        let total: usize = headers
+            .get("X-Total-Count")
+            .and_then(|total| total.to_str().ok())
+            .and_then(|total| total.parse().ok())
+            .unwrap_or(bookings.len());
+
+        return Ok(BookingList { bookings, total });
+    }
+
+    /// Fetches the summary of each booking matching a filter, holding only its id, dates, status
+    /// and room type, ordered by booking id.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The criteria bookings must match
+    /// * `pagination` - The page to fetch, by offset or by cursor
+    pub async fn list_booking_summaries(
+        &self,
+        filter: &BookingFilter,
+        pagination: &Pagination,
+    ) -> Result<Vec<RoomBookingSummary>, ClientError> {
+        let path: String = filter_query(filter)
+            .push("limit", pagination.limit)
+            .push("offset", pagination.offset)
+            .push("after", pagination.after)
+            .push("view", Some("summary"))
+            .to_path("/bookings");
+        return self.get(&path).await;
+    }
+
+    /// Counts the bookings matching a filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The criteria bookings must match
+    pub async fn count_bookings(&self, filter: &BookingFilter) -> Result<usize, ClientError> {
+        return self
+            .get(&filter_query(filter).to_path("/bookings/count"))
+            .await;
+    }
+
+    /// Reports the cancelled bookings matching a filter, by cancellation reason.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The criteria bookings must match. The status is ignored.
+    pub async fn cancellation_report(
+        &self,
+        filter: &BookingFilter,
+    ) -> Result<CancellationReport, ClientError> {
+        return self
+            .get(&filter_query(filter).to_path("/bookings/cancellations"))
+            .await;
+    }
+
+    /// Fetches the changes made to bookings since a cursor.
+    ///
+    /// # Arguments
+    ///
+    /// * `since` - The cursor returned by the previous request, if any
+    /// * `limit` - The maximum number of changes to return
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let changes = client.get_changes(Some(cursor), Some(100)).await?;
+    /// cursor = changes.next_cursor;
+    /// ```
+    pub async fn get_changes(
+        &self,
+        since: Option<u64>,
+        limit: Option<usize>,
+    ) -> Result<ChangeSet, ClientError> {
+        let path: String = Query::default()
+            .push("since", since)
+            .push("limit", limit)
+            .to_path("/bookings/changes");
+        return self.get(&path).await;
+    }
+
+    /// Fetches the bookings created, updated and deleted since a sync token.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The sync token returned by the previous sync, or None for a full sync
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let delta = client.sync(Some(token)).await?;
+    /// token = delta.sync_token;
+    /// ```
+    pub async fn sync(&self, token: Option<u64>) -> Result<BookingDelta, ClientError> {
+        let path: String = Query::default()
+            .push("token", token)
+            .to_path("/bookings/sync");
+        return self.get(&path).await;
+    }
+
+    /// Fetches the bookings arriving on a date, in the hotel's local time.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - The date (YYYY-MM-DD), or today if not provided
+    pub async fn get_arrivals(&self, date: Option<&str>) -> Result<Vec<RoomBooking>, ClientError> {
+        let path: String = Query::default()
+            .push("date", date)
+            .to_path("/bookings/arrivals");
+        return self.get(&path).await;
+    }
+
+    /// Fetches the bookings departing on a date, in the hotel's local time.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - The date (YYYY-MM-DD), or today if not provided
+    pub async fn get_departures(
+        &self,
+        date: Option<&str>,
+    ) -> Result<Vec<RoomBooking>, ClientError> {
+        let path: String = Query::default()
+            .push("date", date)
+            .to_path("/bookings/departures");
+        return self.get(&path).await;
+    }
+
+    /// Fetches every booking made by a customer.
+    ///
+    /// # Arguments
+    ///
+    /// * `customer_id` - The id of the customer
+    pub async fn get_customer_bookings(
+        &self,
+        customer_id: u32,
+    ) -> Result<Vec<RoomBooking>, ClientError> {
+        return self
+            .get(&format!("/bookings/customer/{}", customer_id))
+            .await;
+    }
+
+    /// Fetches every booking checking in on a date.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - The check in date (YYYY-MM-DD)
+    pub async fn get_bookings_on_date(&self, date: &str) -> Result<Vec<RoomBooking>, ClientError> {
+        return self.get(&format!("/bookings/date/{}", encode(date))).await;
+    }
+
+    /// Fetches every booking for a room type.
+    ///
+    /// # Arguments
+    ///
+    /// * `room_type_id` - The id of the room type
+    pub async fn get_room_type_bookings(
+        &self,
+        room_type_id: u8,
+    ) -> Result<Vec<RoomBooking>, ClientError> {
+        return self
+            .get(&format!("/bookings/room-type/{}", room_type_id))
+            .await;
+    }
+
+    /// Exports every booking and booking change held for a customer.
+    ///
+    /// # Arguments
+    ///
+    /// * `customer_id` - The id of the customer
+    pub async fn export_customer(&self, customer_id: u32) -> Result<CustomerExport, ClientError> {
+        return self
+            .get(&format!("/customers/{}/export", customer_id))
+            .await;
+    }
+
+    /// Erases the personal data held for a customer. Returns the number of bookings and changes
+    /// anonymized.
+    ///
+    /// # Arguments
+    ///
+    /// * `customer_id` - The id of the customer
+    pub async fn anonymize_customer(&self, customer_id: u32) -> Result<Anonymization, ClientError> {
+        let path: String = format!("/customers/{}/anonymize", customer_id);
+        return self.send::<(), _>(Method::POST, &path, None).await;
+    }
+}
+
+/// Builds the query parameters for a booking filter.
+fn filter_query(filter: &BookingFilter) -> Query {
+    return Query::default()
+        .push("customer_id", filter.customer_id)
+        .push("room_type_id", filter.room_type_id)
+        .push("status", filter.status.as_ref())
+        .push("from", filter.from.as_ref())
+        .push("to", filter.to.as_ref())
+        .push("sandbox", filter.sandbox);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bookings::*;
+
+    #[test]
+    fn next_page_cursor() {
+        let mut booking: RoomBooking =
+            room_booking_service::seed::generate(&room_booking_service::seed::SeedOptions::new(1))
+                .remove(0);
+        booking.booking_id = Some(7);
+        let list = BookingList {
+            bookings: vec![booking],
+            total: 2,
+        };
+
+        assert_eq!(list.next_cursor(&Pagination::new(Some(1), None)), Some(7));
+        assert_eq!(list.next_cursor(&Pagination::new(Some(2), None)), None);
+        assert_eq!(list.next_cursor(&Pagination::new(None, None)), None);
+    }
+
+    #[test]
+    fn encode_filter() {
+        let filter = BookingFilter {
+            customer_id: Some(1),
+            status: Some("NoShow".to_string()),
+            from: Some("2024-01-01".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            filter_query(&filter).to_path("/bookings"),
+            "/bookings?customer_id=1&status=NoShow&from=2024-01-01"
+        );
+    }
+}
@@ -0,0 +1,221 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+//! Requests for the add-on catalog, promo codes, sell restrictions, availability, exchange rates,
+//! corporate accounts and agent commissions.
+
+use crate::client::{encode, BookingClient, Query};
+use crate::error::ClientError;
+use crate::models::*;
+use hyper::Method;
+
+impl BookingClient {
+    /// Fetches every add-on which may be attached to a booking, with its price.
+    pub async fn get_addons(&self) -> Result<Vec<Addon>, ClientError> {
+        return self.get("/addons").await;
+    }
+
+    /// Fetches every promo code.
+    pub async fn get_promo_codes(&self) -> Result<Vec<PromoCode>, ClientError> {
+        return self.get("/promo-codes").await;
+    }
+
+    /// Creates a promo code, replacing any existing code with the same code.
+    ///
+    /// # Arguments
+    ///
+    /// * `promo_code` - The promo code to create
+    pub async fn create_promo_code(
+        &self,
+        promo_code: &PromoCode,
+    ) -> Result<PromoCode, ClientError> {
+        return self
+            .send(Method::POST, "/promo-codes", Some(promo_code))
+            .await;
+    }
+
+    /// Fetches a promo code.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The code entered by guests, e.g. 'SUMMER10'
+    pub async fn get_promo_code(&self, code: &str) -> Result<PromoCode, ClientError> {
+        return self.get(&format!("/promo-codes/{}", encode(code))).await;
+    }
+
+    /// Deletes a promo code. Returns the deleted promo code.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The code entered by guests, e.g. 'SUMMER10'
+    pub async fn delete_promo_code(&self, code: &str) -> Result<PromoCode, ClientError> {
+        let path: String = format!("/promo-codes/{}", encode(code));
+        return self.send::<(), _>(Method::DELETE, &path, None).await;
+    }
+
+    /// Fetches every sell restriction.
+    pub async fn get_restrictions(&self) -> Result<Vec<SellRestriction>, ClientError> {
+        return self.get("/restrictions").await;
+    }
+
+    /// Creates a sell restriction. Returns the restriction, with its id.
+    ///
+    /// # Arguments
+    ///
+    /// * `restriction` - The restriction to create
+    pub async fn create_restriction(
+        &self,
+        restriction: &SellRestriction,
+    ) -> Result<SellRestriction, ClientError> {
+        return self
+            .send(Method::POST, "/restrictions", Some(restriction))
+            .await;
+    }
+
+    /// Fetches a sell restriction.
+    ///
+    /// # Arguments
+    ///
+    /// * `restriction_id` - The id of the restriction
+    pub async fn get_restriction(
+        &self,
+        restriction_id: u32,
+    ) -> Result<SellRestriction, ClientError> {
+        return self.get(&format!("/restrictions/{}", restriction_id)).await;
+    }
+
+    /// Deletes a sell restriction. Returns the deleted restriction.
+    ///
+    /// # Arguments
+    ///
+    /// * `restriction_id` - The id of the restriction
+    pub async fn delete_restriction(
+        &self,
+        restriction_id: u32,
+    ) -> Result<SellRestriction, ClientError> {
+        let path: String = format!("/restrictions/{}", restriction_id);
+        return self.send::<(), _>(Method::DELETE, &path, None).await;
+    }
+
+    /// Fetches the bookings and remaining capacity of a room type on each day of a month.
+    ///
+    /// # Arguments
+    ///
+    /// * `room_type_id` - The id of the room type
+    /// * `month` - The month (YYYY-MM), or the current month if not provided
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let calendar = client.get_availability_calendar(3, Some("2024-06")).await?;
+    /// ```
+    pub async fn get_availability_calendar(
+        &self,
+        room_type_id: u8,
+        month: Option<&str>,
+    ) -> Result<AvailabilityCalendar, ClientError> {
+        let path: String = Query::default()
+            .push("room_type_id", Some(room_type_id))
+            .push("month", month)
+            .to_path("/availability/calendar");
+        return self.get(&path).await;
+    }
+
+    /// Checks the availability and indicative price of several stays.
+    ///
+    /// # Arguments
+    ///
+    /// * `queries` - The stays to check
+    pub async fn check_availability(
+        &self,
+        queries: &[AvailabilityQuery],
+    ) -> Result<Vec<AvailabilityResult>, ClientError> {
+        return self
+            .send(Method::POST, "/availability/check", Some(&queries))
+            .await;
+    }
+
+    /// Fetches the rate prices are converted from the hotel's currency into a currency at.
+    ///
+    /// # Arguments
+    ///
+    /// * `currency` - The ISO 4217 code of the currency, e.g. 'EUR'
+    pub async fn get_exchange_rate(&self, currency: &str) -> Result<ExchangeRate, ClientError> {
+        return self
+            .get(&format!("/exchange-rates/{}", encode(currency)))
+            .await;
+    }
+
+    /// Fetches every corporate account.
+    pub async fn get_accounts(&self) -> Result<Vec<CorporateAccount>, ClientError> {
+        return self.get("/accounts").await;
+    }
+
+    /// Creates a corporate account. Returns the account, with its id.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The account to create
+    pub async fn create_account(
+        &self,
+        account: &CorporateAccount,
+    ) -> Result<CorporateAccount, ClientError> {
+        return self.send(Method::POST, "/accounts", Some(account)).await;
+    }
+
+    /// Fetches a corporate account.
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - The id of the account
+    pub async fn get_account(&self, account_id: u32) -> Result<CorporateAccount, ClientError> {
+        return self.get(&format!("/accounts/{}", account_id)).await;
+    }
+
+    /// Replaces a corporate account. Returns the updated account.
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - The id of the account
+    /// * `account` - The updated account
+    pub async fn update_account(
+        &self,
+        account_id: u32,
+        account: &CorporateAccount,
+    ) -> Result<CorporateAccount, ClientError> {
+        let path: String = format!("/accounts/{}", account_id);
+        return self.send(Method::PUT, &path, Some(account)).await;
+    }
+
+    /// Reports the bookings made against each corporate account in a month.
+    ///
+    /// # Arguments
+    ///
+    /// * `month` - The month (YYYY-MM), or the current month if not provided
+    pub async fn get_account_report(
+        &self,
+        month: Option<&str>,
+    ) -> Result<Vec<AccountReport>, ClientError> {
+        let path: String = Query::default()
+            .push("month", month)
+            .to_path("/accounts/report");
+        return self.get(&path).await;
+    }
+
+    /// Reports the commission due to each travel agent for a month.
+    ///
+    /// # Arguments
+    ///
+    /// * `month` - The month (YYYY-MM), or the current month if not provided
+    pub async fn get_agent_commissions(
+        &self,
+        month: Option<&str>,
+    ) -> Result<Vec<AgentCommissions>, ClientError> {
+        let path: String = Query::default()
+            .push("month", month)
+            .to_path("/agents/commissions");
+        return self.get(&path).await;
+    }
+}
@@ -0,0 +1,178 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::error::ClientError;
+use crate::models::ErrorResponse;
+use hyper::body::{self, Body};
+use hyper::client::HttpConnector;
+use hyper::header::{HeaderMap, CONTENT_TYPE};
+use hyper::{Client, Method, Request, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Display;
+
+/// A client for the room booking service API. The client holds a pool of connections to the
+/// service, so should be created once and shared, rather than created for each request. Cloning
+/// the client shares its connection pool.
+#[derive(Clone)]
+pub struct BookingClient {
+    base_url: String,
+    http: Client<HttpConnector>,
+}
+
+impl BookingClient {
+    /// Creates a client for an instance of the service.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_url` - The URL the API version is mounted at, e.g. 'http://127.0.0.1:8000/v1'
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let client = BookingClient::new("http://127.0.0.1:8000/v1");
+    /// ```
+    pub fn new(base_url: &str) -> BookingClient {
+        return BookingClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http: Client::new(),
+        };
+    }
+
+    /// Returns the URL the client sends requests to.
+    pub fn base_url(&self) -> &str {
+        return &self.base_url;
+    }
+
+    /// Sends a GET request, and deserializes the response body.
+    pub(crate) async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, ClientError> {
+        return self.send::<(), T>(Method::GET, path, None).await;
+    }
+
+    /// Sends a request, with an optional JSON body, and deserializes the response body.
+    pub(crate) async fn send<B: Serialize, T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<T, ClientError> {
+        let (_, content): (HeaderMap, Vec<u8>) = self.request(method, path, body).await?;
+        return Ok(serde_json::from_slice(&content)?);
+    }
+
+    /// Sends a request, with an optional JSON body. Returns the headers and body of the response,
+    /// or an error if the service responded with an unsuccessful status.
+    pub(crate) async fn request<B: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<(HeaderMap, Vec<u8>), ClientError> {
+        let builder = Request::builder()
+            .method(method)
+            .uri(format!("{}{}", self.base_url, path));
+        let request: Request<Body> = match body {
+            Some(body) => builder
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_vec(body)?))?,
+            None => builder.body(Body::empty())?,
+        };
+
+        let response = self.http.request(request).await?;
+        let status: StatusCode = response.status();
+        let headers: HeaderMap = response.headers().clone();
+        let content: Vec<u8> = body::to_bytes(response.into_body()).await?.to_vec();
+
+        if !status.is_success() {
+            return Err(status_error(status, &content));
+        }
+
+        return Ok((headers, content));
+    }
+}
+
+/// Creates the error for an unsuccessful response, including the description of the error and
+/// any conflict given in the response body.
+fn status_error(status: StatusCode, content: &[u8]) -> ClientError {
+    let response: Option<ErrorResponse> = serde_json::from_slice::<ErrorResponse>(content).ok();
+
+    return ClientError::Status {
+        status: status.as_u16(),
+        error: response.as_ref().map(|response| response.error.clone()),
+        conflict: response.and_then(|response| response.conflict),
+    };
+}
+
+/// Builds the query string of a request, omitting parameters which are not provided.
+#[derive(Default)]
+pub(crate) struct Query {
+    parameters: Vec<String>,
+}
+
+impl Query {
+    /// Adds a parameter to the query, if it is provided.
+    pub(crate) fn push<T: Display>(mut self, name: &str, value: Option<T>) -> Query {
+        if let Some(value) = value {
+            self.parameters
+                .push(format!("{}={}", name, encode(&value.to_string())));
+        }
+
+        return self;
+    }
+
+    /// Appends the query string to a path.
+    pub(crate) fn to_path(&self, path: &str) -> String {
+        if self.parameters.is_empty() {
+            return path.to_string();
+        }
+
+        return format!("{}?{}", path, self.parameters.join("&"));
+    }
+}
+
+/// Percent-encodes a value for use in a path segment or query parameter, leaving only unreserved
+/// characters unencoded.
+pub(crate) fn encode(value: &str) -> String {
+    return value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::*;
+
+    #[test]
+    fn build_query() {
+        let query = Query::default()
+            .push("customer_id", Some(1))
+            .push("status", None::<&str>)
+            .push("comment", Some("Zoë & co"));
+        assert_eq!(
+            query.to_path("/bookings"),
+            "/bookings?customer_id=1&comment=Zo%C3%AB%20%26%20co"
+        );
+        assert_eq!(Query::default().to_path("/bookings"), "/bookings");
+    }
+
+    #[test]
+    fn describe_status_error() {
+        let error: ClientError = status_error(StatusCode::CONFLICT, br#"{"error":"Cancelled"}"#);
+        assert_eq!(error.status(), Some(409));
+        assert_eq!(
+            error.to_string(),
+            "The service responded with 409: Cancelled"
+        );
+
+        let error: ClientError = status_error(StatusCode::NOT_FOUND, b"");
+        assert_eq!(error.to_string(), "The service responded with 404");
+    }
+}
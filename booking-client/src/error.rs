@@ -0,0 +1,116 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::models::BookingConflict;
+use std::error::Error;
+use std::fmt;
+
+/// Describes why a request to the room booking service failed
+#[derive(Debug)]
+pub enum ClientError {
+    /// The request could not be built, e.g. because the base URL is invalid
+    Request(String),
+    /// The request could not be sent, or the response could not be read
+    Http(hyper::Error),
+    /// The service responded with an unsuccessful status. The error is the description given in
+    /// the response body, if any, and the conflict describes the current state of the booking if
+    /// a status change conflicted with it.
+    Status {
+        status: u16,
+        error: Option<String>,
+        conflict: Option<Box<BookingConflict>>,
+    },
+    /// The response body did not match the expected model
+    Decode(serde_json::Error),
+}
+
+impl ClientError {
+    /// Returns the HTTP status the service responded with, if it responded with an
+    /// unsuccessful status.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// if error.status() == Some(404) {
+    ///     println!("Booking not found");
+    /// }
+    /// ```
+    pub fn status(&self) -> Option<u16> {
+        return match self {
+            ClientError::Status { status, .. } => Some(*status),
+            _ => None,
+        };
+    }
+
+    /// Returns the current state of the booking a status change conflicted with, if the service
+    /// responded with ```409 Conflict``` for a status change.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// if let Some(conflict) = error.conflict() {
+    ///     println!("The booking may move to {:?}", conflict.allowed_statuses);
+    /// }
+    /// ```
+    pub fn conflict(&self) -> Option<&BookingConflict> {
+        return match self {
+            ClientError::Status {
+                conflict: Some(conflict),
+                ..
+            } => Some(conflict),
+            _ => None,
+        };
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            ClientError::Request(error) => write!(f, "Invalid request: {}", error),
+            ClientError::Http(error) => write!(f, "Request failed: {}", error),
+            ClientError::Status {
+                status,
+                error: Some(error),
+                ..
+            } => write!(f, "The service responded with {}: {}", status, error),
+            ClientError::Status {
+                status,
+                error: None,
+                ..
+            } => {
+                write!(f, "The service responded with {}", status)
+            }
+            ClientError::Decode(error) => write!(f, "Unexpected response: {}", error),
+        };
+    }
+}
+
+impl Error for ClientError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        return match self {
+            ClientError::Http(error) => Some(error),
+            ClientError::Decode(error) => Some(error),
+            _ => None,
+        };
+    }
+}
+
+impl From<hyper::Error> for ClientError {
+    fn from(error: hyper::Error) -> ClientError {
+        return ClientError::Http(error);
+    }
+}
+
+impl From<hyper::http::Error> for ClientError {
+    fn from(error: hyper::http::Error) -> ClientError {
+        return ClientError::Request(error.to_string());
+    }
+}
+
+impl From<serde_json::Error> for ClientError {
+    fn from(error: serde_json::Error) -> ClientError {
+        return ClientError::Decode(error);
+    }
+}
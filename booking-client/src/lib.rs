@@ -0,0 +1,28 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+//! A typed async client for the room booking service API.
+//!
+//! Requests and responses use the same models as the service itself, re-exported from
+//! ```models```, so a change to the API's types is a compile error in its clients, rather than a
+//! failure at runtime.
+//!
+//! ```
+//! let client = BookingClient::new("http://127.0.0.1:8000/v1");
+//! let booking = client.create_booking(&booking).await?;
+//! let fetched = client.get_booking(booking.booking.booking_id.unwrap()).await?;
+//! ```
+
+#![allow(clippy::needless_return)]
+
+pub mod admin;
+pub mod bookings;
+pub mod catalog;
+pub mod client;
+pub mod error;
+pub mod models;
+
+pub use client::BookingClient;
+pub use error::ClientError;
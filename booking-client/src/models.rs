@@ -0,0 +1,52 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+//! The models used in requests to and responses from the room booking service, shared with the
+//! service itself.
+
+pub use room_booking_service::addons::{Addon, AddonCharge};
+pub use room_booking_service::agents::AgentCommissions;
+pub use room_booking_service::circuit_breaker::CircuitMetrics;
+pub use room_booking_service::features::{Feature, FeatureFlags};
+pub use room_booking_service::links::{BookingResource, Link};
+pub use room_booking_service::outbox::ReplayReport;
+pub use room_booking_service::pagination::Pagination;
+pub use room_booking_service::pricing::{
+    FormattedInvoice, FormattedLine, Invoice, PriceBreakdown, PriceLine,
+};
+pub use room_booking_service::responders::{ErrorResponse, FieldError};
+pub use room_booking_service::storage::anonymization::Anonymization;
+pub use room_booking_service::storage::availability::{
+    AvailabilityCalendar, AvailabilityQuery, AvailabilityResult,
+};
+pub use room_booking_service::storage::booking_addon::BookingAddon;
+pub use room_booking_service::storage::booking_change::{BookingChange, ChangeSet, ChangeType};
+pub use room_booking_service::storage::booking_conflict::{BookingConflict, ConflictReason};
+pub use room_booking_service::storage::booking_delta::{BookingDelta, BookingTombstone};
+pub use room_booking_service::storage::booking_filter::BookingFilter;
+pub use room_booking_service::storage::booking_history::BookingHistoryEntry;
+pub use room_booking_service::storage::booking_summary::{BookingView, RoomBookingSummary};
+pub use room_booking_service::storage::booking_validation::BookingValidation;
+pub use room_booking_service::storage::bulk_status::{BulkStatusResult, BulkStatusUpdate};
+pub use room_booking_service::storage::cancellation::{
+    Cancellation, CancellationReason, CancellationReport,
+};
+pub use room_booking_service::storage::cdc_record::{CdcData, CdcOperation, CdcPage, CdcRecord};
+pub use room_booking_service::storage::corporate_account::{AccountReport, CorporateAccount};
+pub use room_booking_service::storage::customer_export::CustomerExport;
+pub use room_booking_service::storage::exchange_rate::{ExchangeRate, ExchangeRateOverride};
+pub use room_booking_service::storage::inventory_adjustment::InventoryAdjustment;
+pub use room_booking_service::storage::metrics::{
+    HistogramBucket, LatencyHistogram, StorageMetrics,
+};
+pub use room_booking_service::storage::outbox_event::{EventTopic, OutboxEvent};
+pub use room_booking_service::storage::payment::{Balance, Payment, Refund};
+pub use room_booking_service::storage::pricing_record::PricingRecord;
+pub use room_booking_service::storage::promo_code::{Discount, DiscountType, PromoCode};
+pub use room_booking_service::storage::recurring_booking::{RecurringBooking, SeriesUpdate};
+pub use room_booking_service::storage::retention::RetentionReport;
+pub use room_booking_service::storage::room_booking::{BookingStatus, RoomBooking};
+pub use room_booking_service::storage::screening_decision::{ScreeningAction, ScreeningDecision};
+pub use room_booking_service::storage::sell_restriction::SellRestriction;
@@ -0,0 +1,21 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes a single billing period of a long stay booking, which is billed monthly
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BillingMilestone {
+    /// The first night of the billing period, and the date the amount is due (YYYY-MM-DD)
+    pub period_start: String,
+    /// The date the billing period ends, which is not included in the period (YYYY-MM-DD)
+    pub period_end: String,
+    /// The number of nights in the billing period
+    pub nights: u32,
+    /// The amount due for the billing period, in the minor unit of the hotel's currency
+    pub amount: u64,
+}
@@ -0,0 +1,39 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Defines how the price of an add-on is charged
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub enum AddonCharge {
+    /// The price is charged once for the stay
+    PerStay,
+    /// The price is charged for each night of the stay
+    PerNight,
+}
+
+/// Describes an add-on attached to a booking, such as breakfast or parking
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BookingAddon {
+    /// The code of the add-on in the add-on catalog, e.g. 'breakfast'
+    pub code: String,
+    /// The number of the add-on required, e.g. the number of parking spaces, defaulting to 1
+    #[serde(default = "default_quantity")]
+    pub quantity: u32,
+    /// The price of a single add-on, in the minor unit of the hotel's currency. This is set from
+    /// the catalog when the add-on is attached, so later price changes do not affect the booking.
+    #[serde(default)]
+    pub unit_price: Option<u64>,
+    /// How the add-on is charged, set from the catalog when the add-on is attached
+    #[serde(default)]
+    pub charge: Option<AddonCharge>,
+}
+
+/// Returns the default quantity of an add-on.
+fn default_quantity() -> u32 {
+    return 1;
+}
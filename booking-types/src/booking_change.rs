@@ -0,0 +1,82 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::room_booking::RoomBooking;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Defines the kinds of mutation recorded in the change log
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub enum ChangeType {
+    /// A new booking was created
+    Created,
+    /// An existing booking was modified
+    Updated,
+    /// A booking was cancelled
+    Cancelled,
+    /// The guest did not arrive for a booking, and any no-show fee was applied
+    NoShow,
+    /// The personal data of a booking was erased, at the request of its customer
+    Anonymized,
+}
+
+/// Describes a single mutation applied to a booking
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BookingChange {
+    /// A monotonically increasing number identifying the change
+    pub sequence: u64,
+    /// The time the change was made, in RFC 3339 format
+    #[serde(default)]
+    pub recorded_at: String,
+    pub change_type: ChangeType,
+    /// The state of the booking after the change was applied
+    pub booking: RoomBooking,
+}
+
+/// Describes a list of changes, along with the cursor to use when requesting the next set
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeSet {
+    pub changes: Vec<BookingChange>,
+    /// The sequence number of the last change returned, or the requested cursor if there were
+    /// no new changes
+    pub next_cursor: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::booking_change::*;
+    use crate::room_booking::BookingStatus;
+
+    #[test]
+    fn deserialize_change() {
+        let json: &str = r#"{
+            "sequence": 3,
+            "changeType": "Cancelled",
+            "booking": {
+                "bookingId": 1,
+                "customerId": 2,
+                "roomTypeId": 3,
+                "checkInDate": "2024-01-01",
+                "checkOutDate": "2024-01-03",
+                "status": "Cancelled",
+                "addons": [{ "code": "breakfast" }]
+            }
+        }"#;
+
+        let change: BookingChange = serde_json::from_str(json).unwrap();
+        assert_eq!(change.change_type, ChangeType::Cancelled);
+        assert_eq!(change.recorded_at, "");
+        assert_eq!(change.booking.status, Some(BookingStatus::Cancelled));
+        assert_eq!(change.booking.guests, 1);
+        assert_eq!(change.booking.addons[0].quantity, 1);
+        assert!(!change.booking.holds_room());
+
+        let value: serde_json::Value = serde_json::to_value(&change).unwrap();
+        assert_eq!(value["booking"]["checkInDate"], "2024-01-01");
+        assert_eq!(value["changeType"], "Cancelled");
+    }
+}
@@ -0,0 +1,46 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Defines the reasons a booking may be cancelled for
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash, Debug)]
+pub enum CancellationReason {
+    /// The guest's plans changed, and they no longer need the room
+    ChangeOfPlans,
+    /// The guest found a cheaper rate elsewhere
+    FoundCheaperRate,
+    /// The guest could not travel, e.g. due to a cancelled flight
+    TravelDisruption,
+    /// The guest or a member of their party was unwell
+    Illness,
+    /// The booking was made in error, or duplicates another booking
+    BookedInError,
+    /// The booking was cancelled by the hotel
+    HotelInitiated,
+    /// Any other reason, which should be described in the comment
+    Other,
+    /// The deposit for the booking was not paid in time
+    PaymentNotReceived,
+}
+
+/// Describes why a booking was cancelled
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+#[schemars(example = "example_cancellation")]
+pub struct Cancellation {
+    pub reason: CancellationReason,
+    /// Any further details provided by the guest or member of staff cancelling the booking
+    pub comment: Option<String>,
+}
+
+/// Returns the example cancellation shown in the API documentation.
+fn example_cancellation() -> Cancellation {
+    return Cancellation {
+        reason: CancellationReason::ChangeOfPlans,
+        comment: Some("Conference moved to the following month".to_string()),
+    };
+}
@@ -0,0 +1,48 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Defines how a discount reduces the price of a booking
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub enum DiscountType {
+    /// The price is reduced by a percentage
+    Percentage,
+    /// The price is reduced by a fixed amount
+    FixedAmount,
+}
+
+/// Describes a discount applied to the price of a booking
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Discount {
+    pub discount_type: DiscountType,
+    /// The percentage taken off the price, or the amount taken off the price in the minor unit of
+    /// the hotel's currency, depending on the discount type
+    pub value: u64,
+}
+
+impl Discount {
+    /// Returns the amount taken off a price by the discount, which is never more than the price.
+    ///
+    /// # Arguments
+    ///
+    /// * `price` - The price before the discount, in the minor unit of the hotel's currency
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let amount = discount.amount(10000);
+    /// ```
+    pub fn amount(&self, price: u64) -> u64 {
+        let amount: u64 = match self.discount_type {
+            DiscountType::Percentage => (price * self.value + 50) / 100,
+            DiscountType::FixedAmount => self.value,
+        };
+
+        return amount.min(price);
+    }
+}
@@ -0,0 +1,35 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+//! The booking models and event payloads shared across services. Bookings, booking changes and
+//! outbox events are serialized exactly as the room booking service stores and sends them, so
+//! producers and consumers deserialize the same shapes.
+//!
+//! The crate only depends on serde and schemars, so it can be used by any service, whatever web
+//! framework it uses. Rules which depend on the hotel's configuration, such as booking
+//! validation, are applied by the room booking service.
+//!
+//! ```
+//! let change: BookingChange = serde_json::from_slice(&body)?;
+//! if change.change_type == ChangeType::Cancelled {
+//!     release_room(change.booking.booking_id);
+//! }
+//! ```
+
+#![allow(clippy::needless_return)]
+
+pub mod billing_milestone;
+pub mod booking_addon;
+pub mod booking_change;
+pub mod cancellation;
+pub mod discount;
+pub mod outbox_event;
+pub mod payment;
+pub mod pricing_record;
+pub mod room_booking;
+
+pub use booking_change::{BookingChange, ChangeSet, ChangeType};
+pub use outbox_event::OutboxEvent;
+pub use room_booking::{BookingStatus, RoomBooking};
@@ -0,0 +1,99 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::booking_change::{BookingChange, ChangeType};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Defines the topics booking events are delivered under, which webhooks may subscribe to
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash, Debug)]
+pub enum EventTopic {
+    /// A new booking was created
+    Created,
+    /// An existing booking was modified, other than by a payment or refund
+    Updated,
+    /// A booking was cancelled
+    Cancelled,
+    /// The guest did not arrive for a booking
+    NoShow,
+    /// A payment or refund was recorded for a booking, or a payment was reversed
+    Payment,
+    /// The personal data of a booking was erased
+    Anonymized,
+}
+
+impl EventTopic {
+    /// Returns the topic a booking change is delivered under. Payments are recorded as updates,
+    /// so are given their topic when the change is recorded, rather than by this function.
+    ///
+    /// # Arguments
+    ///
+    /// * `change_type` - The type of the booking change
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let topic = EventTopic::of(&ChangeType::Cancelled);
+    /// ```
+    pub fn of(change_type: &ChangeType) -> EventTopic {
+        return match change_type {
+            ChangeType::Created => EventTopic::Created,
+            ChangeType::Updated => EventTopic::Updated,
+            ChangeType::Cancelled => EventTopic::Cancelled,
+            ChangeType::NoShow => EventTopic::NoShow,
+            ChangeType::Anonymized => EventTopic::Anonymized,
+        };
+    }
+}
+
+/// Describes an event waiting in the outbox to be delivered to the events webhook. Events are
+/// recorded alongside the booking change they describe, and removed once delivered. Events which
+/// could not be delivered after the configured number of attempts are moved to the dead-letter
+/// store, until redelivery is requested.
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxEvent {
+    /// The booking change delivered as the event. Its sequence number identifies the event, and
+    /// is sent as the idempotency key, as an event may be delivered more than once.
+    pub change: BookingChange,
+    /// The topic the event is delivered under
+    pub topic: EventTopic,
+    /// The URLs of the webhooks the event has already been delivered to, so they are skipped
+    /// when delivery is retried
+    #[serde(default)]
+    pub delivered_to: Vec<String>,
+    /// The number of failed attempts to deliver the event
+    pub attempts: u32,
+    /// A description of the last failed attempt to deliver the event, if any
+    pub last_error: Option<String>,
+    /// The time the event was moved to the dead-letter store, in RFC 3339 format, if it has been
+    #[serde(default)]
+    pub dead_lettered_at: Option<String>,
+}
+
+impl OutboxEvent {
+    /// Creates an event for a booking change, which has not yet been delivered.
+    ///
+    /// # Arguments
+    ///
+    /// * `change` - The booking change
+    /// * `topic` - The topic the event is delivered under
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let event = OutboxEvent::new(change, EventTopic::Created);
+    /// ```
+    pub fn new(change: BookingChange, topic: EventTopic) -> OutboxEvent {
+        return OutboxEvent {
+            change,
+            topic,
+            delivered_to: Vec::new(),
+            attempts: 0,
+            last_error: None,
+            dead_lettered_at: None,
+        };
+    }
+}
@@ -0,0 +1,62 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes a payment received for a booking
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+#[schemars(example = "example_payment")]
+pub struct Payment {
+    /// The amount paid, in the minor unit of the hotel's currency
+    pub amount: u64,
+    /// The payment provider's reference for the payment, if any
+    #[serde(default)]
+    pub reference: Option<String>,
+    /// When the payment was recorded, as an RFC 3339 timestamp. This is set automatically, and
+    /// should be excluded when recording a payment.
+    #[serde(default)]
+    pub received_at: Option<String>,
+}
+
+/// Describes a refund made for a booking
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+#[schemars(example = "example_refund")]
+pub struct Refund {
+    /// The amount refunded, in the minor unit of the hotel's currency
+    pub amount: u64,
+    /// Why the amount was refunded, if known
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// The payment provider's reference for the refund, if any
+    #[serde(default)]
+    pub reference: Option<String>,
+    /// When the refund was recorded, as an RFC 3339 timestamp. This is set automatically.
+    #[serde(default)]
+    pub refunded_at: Option<String>,
+}
+
+/// Returns the example payment shown in the API documentation, as it would be sent to record a
+/// payment.
+fn example_payment() -> Payment {
+    return Payment {
+        amount: 15000,
+        reference: Some("pi_3NkQ2b".to_string()),
+        received_at: None,
+    };
+}
+
+/// Returns the example refund shown in the API documentation, as it would be sent to request a
+/// refund.
+fn example_refund() -> Refund {
+    return Refund {
+        amount: 5000,
+        reason: Some("Room unavailable for one night".to_string()),
+        reference: None,
+        refunded_at: None,
+    };
+}
@@ -0,0 +1,88 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Defines how a tax is charged
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub enum TaxCharge {
+    /// The tax is a percentage of the price of the booking, after any discount, e.g. VAT
+    Percentage,
+    /// The tax is a fixed amount for each guest for each night, e.g. a city tax
+    PerPersonPerNight,
+}
+
+/// Describes a single line of a quote or invoice
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceLine {
+    pub description: String,
+    pub quantity: u64,
+    /// The price of a single unit, in the minor unit of the hotel's currency
+    pub unit_price: u64,
+    /// The price of the line, in the minor unit of the hotel's currency
+    pub amount: u64,
+}
+
+impl PriceLine {
+    /// Creates a price line for a number of units at the same price.
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - A description of the line
+    /// * `quantity` - The number of units
+    /// * `unit_price` - The price of a single unit
+    pub fn new(description: String, quantity: u64, unit_price: u64) -> PriceLine {
+        return PriceLine {
+            description,
+            quantity,
+            unit_price,
+            amount: quantity * unit_price,
+        };
+    }
+}
+
+/// Describes the rate charged for a night of a booking
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NightlyPrice {
+    /// The night (YYYY-MM-DD)
+    pub date: String,
+    /// The rate plan the night was charged at, or null if it was charged the room type's rate
+    pub rate_plan: Option<String>,
+    /// The rate charged for the night, in the minor unit of the hotel's currency
+    pub rate: u64,
+}
+
+/// Describes a tax charged on a booking
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedTax {
+    pub name: String,
+    pub charge: TaxCharge,
+    /// The rate of the tax, in hundredths of a percent for percentage taxes, or the minor unit
+    /// of the hotel's currency for other taxes
+    pub rate: u64,
+}
+
+/// Describes the rates and taxes in effect when a booking was priced. Invoices for the booking
+/// are charged from this record, so later changes to the configured rates and taxes do not
+/// change them.
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PricingRecord {
+    /// When the booking was priced, as an RFC 3339 timestamp
+    pub priced_at: String,
+    /// The ISO 4217 code of the currency prices are given in
+    pub currency: String,
+    /// The rate charged for each night, for bookings charged by the night. Day use bookings and
+    /// long stays are charged a single rate or by billing period, so have no nightly prices.
+    pub nightly_prices: Vec<NightlyPrice>,
+    /// The room charges
+    pub room_lines: Vec<PriceLine>,
+    /// The taxes charged, other than those the booking is exempt from
+    pub taxes: Vec<AppliedTax>,
+}
@@ -0,0 +1,392 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::billing_milestone::BillingMilestone;
+use crate::booking_addon::BookingAddon;
+use crate::cancellation::Cancellation;
+use crate::discount::Discount;
+use crate::payment::{Payment, Refund};
+use crate::pricing_record::PricingRecord;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Defines the allowed values for the status of a booking. Snapshots save each status as its
+/// position in this list, so new statuses must be added at the end.
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub enum BookingStatus {
+    /// A booking that has been paid for, but the user has not yet checked in
+    Confirmed,
+    /// A booking where the guest has checked out of their room
+    Complete,
+    /// A booking that the user has cancelled
+    Cancelled,
+    /// A booking where the user did not arrive on their check in date
+    NoShow,
+    /// A booking which requires a deposit, which has not yet been paid
+    PendingPayment,
+    /// A booking held for review by a screening rule, which is confirmed once approved
+    PendingReview,
+    /// A booking where the user has checked in to their room, but not yet checked out
+    CheckedIn,
+}
+
+impl BookingStatus {
+    /// Every booking status, in the order they are declared.
+    pub const ALL: [BookingStatus; 7] = [
+        BookingStatus::Confirmed,
+        BookingStatus::Complete,
+        BookingStatus::Cancelled,
+        BookingStatus::NoShow,
+        BookingStatus::PendingPayment,
+        BookingStatus::PendingReview,
+        BookingStatus::CheckedIn,
+    ];
+
+    /// Returns the name of the status, as used in request and response bodies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let name = BookingStatus::NoShow.name();
+    /// ```
+    pub fn name(&self) -> &'static str {
+        return match self {
+            BookingStatus::Confirmed => "Confirmed",
+            BookingStatus::Complete => "Complete",
+            BookingStatus::Cancelled => "Cancelled",
+            BookingStatus::NoShow => "NoShow",
+            BookingStatus::PendingPayment => "PendingPayment",
+            BookingStatus::PendingReview => "PendingReview",
+            BookingStatus::CheckedIn => "CheckedIn",
+        };
+    }
+}
+
+impl fmt::Display for BookingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.name());
+    }
+}
+
+/// The error returned when a string is not the name of a booking status
+#[derive(Clone, PartialEq, Debug)]
+pub struct ParseStatusError(pub String);
+
+impl fmt::Display for ParseStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{} is not a valid booking status", self.0);
+    }
+}
+
+impl Error for ParseStatusError {}
+
+impl FromStr for BookingStatus {
+    type Err = ParseStatusError;
+
+    /// Converts the name of a booking status, e.g. 'Complete', into the status.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let status: BookingStatus = "Complete".parse()?;
+    /// ```
+    fn from_str(value: &str) -> Result<BookingStatus, ParseStatusError> {
+        return BookingStatus::ALL
+            .into_iter()
+            .find(|status| status.name() == value)
+            .ok_or_else(|| ParseStatusError(value.to_string()));
+    }
+}
+
+/// Describes a single room booking. Fields which are not listed are rejected, so a misspelt field
+/// is reported rather than ignored.
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[schemars(example = "example_booking")]
+pub struct RoomBooking {
+    pub booking_id: Option<u32>,
+    pub customer_id: u32,
+    pub room_type_id: u8,
+    pub check_in_date: String,
+    pub check_out_date: String,
+    pub status: Option<BookingStatus>,
+    /// The time the guest expects to arrive on their check in date (HH:MM)
+    #[serde(default)]
+    pub expected_arrival_time: Option<String>,
+    /// The time the guest has requested to check out on their check out date, if later than the
+    /// standard check out time (HH:MM)
+    #[serde(default)]
+    pub late_check_out_time: Option<String>,
+    /// Whether the booking is a day use booking, checking in and out on the same date, using the
+    /// separate pool of day use rooms
+    #[serde(default)]
+    pub day_use: bool,
+    /// The time a day use booking starts (HH:MM)
+    #[serde(default)]
+    pub start_time: Option<String>,
+    /// The time a day use booking ends (HH:MM)
+    #[serde(default)]
+    pub end_time: Option<String>,
+    /// The monthly billing periods of a long stay booking. These are calculated when the booking
+    /// is created.
+    #[serde(default)]
+    pub billing_milestones: Vec<BillingMilestone>,
+    /// The id of the series of recurring bookings this booking belongs to, if any
+    #[serde(default)]
+    pub series_id: Option<u32>,
+    /// Why the booking was cancelled, if it has been cancelled and a reason was given
+    #[serde(default)]
+    pub cancellation: Option<Cancellation>,
+    /// The fee charged when the guest did not arrive, in the minor unit of the hotel's currency
+    #[serde(default)]
+    pub no_show_fee: Option<u64>,
+    /// The time the deposit for a booking pending payment must be paid by, in RFC 3339 format,
+    /// after which the booking is cancelled
+    #[serde(default)]
+    pub payment_due_by: Option<String>,
+    /// The add-ons attached to the booking, such as breakfast or parking
+    #[serde(default)]
+    pub addons: Vec<BookingAddon>,
+    /// The promo code entered when the booking was made, if any
+    #[serde(default)]
+    pub promo_code: Option<String>,
+    /// The discount given by the promo code. This is set when the code is redeemed, so later
+    /// changes to the code do not affect the booking.
+    #[serde(default)]
+    pub discount: Option<Discount>,
+    /// The number of guests staying, defaulting to 1
+    #[serde(default = "default_guests")]
+    pub guests: u32,
+    /// The number of the guests who are children, included in ```guests```
+    #[serde(default)]
+    pub children: u32,
+    /// The names of the taxes the booking is exempt from, e.g. 'VAT'
+    #[serde(default)]
+    pub tax_exemptions: Vec<String>,
+    /// The id of the corporate account the booking is made against, if any
+    #[serde(default)]
+    pub account_id: Option<u32>,
+    /// The id of the travel agent the booking was made by, if any
+    #[serde(default)]
+    pub agent_id: Option<u32>,
+    /// The id of the reservation in the channel manager, for bookings made through an online
+    /// travel agent
+    #[serde(default)]
+    pub channel_reservation_id: Option<String>,
+    /// The names of the rate plans the booking's nights were charged at when it was made
+    #[serde(default)]
+    pub rate_plans: Vec<String>,
+    /// The rates and taxes in effect when the booking was priced
+    #[serde(default)]
+    pub pricing: Option<PricingRecord>,
+    /// The payments received for the booking
+    #[serde(default)]
+    pub payments: Vec<Payment>,
+    /// The refunds made for the booking
+    #[serde(default)]
+    pub refunds: Vec<Refund>,
+    /// The time the booking was created (RFC 3339), set when the booking is created
+    #[serde(default)]
+    pub created_at: Option<String>,
+    /// The time the booking was last changed (RFC 3339), set each time the booking is changed
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    /// The time the booking was cancelled (RFC 3339), if it is cancelled
+    #[serde(default)]
+    pub cancelled_at: Option<String>,
+    /// Whether the booking was made in the sandbox, to test an integration. Sandbox bookings do
+    /// not hold a real room, are left out of reports and events, and are removed periodically.
+    #[serde(default)]
+    pub sandbox: bool,
+}
+
+/// The format used for booking dates
+pub static DATE_FORMAT: &str = "%Y-%m-%d";
+/// The format used for booking times
+pub static TIME_FORMAT: &str = "%H:%M";
+
+/// Returns the default number of guests staying.
+pub fn default_guests() -> u32 {
+    return 1;
+}
+
+/// Returns the example booking shown in the API documentation, as it would be sent to create a
+/// booking.
+fn example_booking() -> RoomBooking {
+    return RoomBooking {
+        booking_id: None,
+        customer_id: 1,
+        room_type_id: 2,
+        check_in_date: "2030-06-01".to_string(),
+        check_out_date: "2030-06-04".to_string(),
+        status: None,
+        expected_arrival_time: Some("15:30".to_string()),
+        late_check_out_time: None,
+        day_use: false,
+        start_time: None,
+        end_time: None,
+        billing_milestones: Vec::new(),
+        series_id: None,
+        cancellation: None,
+        no_show_fee: None,
+        payment_due_by: None,
+        addons: Vec::new(),
+        promo_code: Some("SUMMER10".to_string()),
+        discount: None,
+        guests: 2,
+        children: 0,
+        tax_exemptions: Vec::new(),
+        account_id: None,
+        agent_id: None,
+        channel_reservation_id: None,
+        rate_plans: Vec::new(),
+        pricing: None,
+        payments: Vec::new(),
+        refunds: Vec::new(),
+        created_at: None,
+        updated_at: None,
+        cancelled_at: None,
+        sandbox: false,
+    };
+}
+
+impl RoomBooking {
+    /// Checks whether the booking holds a room, meaning it has not been cancelled and the guest has
+    /// not failed to arrive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let occupied = booking.holds_room();
+    /// ```
+    pub fn holds_room(&self) -> bool {
+        return !matches!(
+            self.status,
+            Some(BookingStatus::Cancelled) | Some(BookingStatus::NoShow)
+        );
+    }
+
+    /// Sets the booking id of the current booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - A u32 which holds the id of the booking
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// booking.set_booking_id(1);
+    /// ```
+    pub fn set_booking_id(&mut self, booking_id: u32) {
+        self.booking_id = Some(booking_id);
+    }
+
+    /// Sets the status of the current booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_status` - A BookingStatus enum value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// booking.set_status(BookingStatus::Cancelled);
+    ///
+    pub fn set_status(&mut self, booking_status: BookingStatus) {
+        self.status = Some(booking_status);
+    }
+
+    /// Removes the whitespace surrounding each text field of the current booking, such as the
+    /// dates and the promo code. Optional fields left empty are unset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// booking.trim();
+    /// ```
+    pub fn trim(&mut self) {
+        for field in [&mut self.check_in_date, &mut self.check_out_date] {
+            *field = field.trim().to_string();
+        }
+
+        for field in [
+            &mut self.expected_arrival_time,
+            &mut self.late_check_out_time,
+            &mut self.start_time,
+            &mut self.end_time,
+            &mut self.promo_code,
+            &mut self.channel_reservation_id,
+        ] {
+            *field = field
+                .take()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty());
+        }
+
+        for exemption in self.tax_exemptions.iter_mut() {
+            *exemption = exemption.trim().to_string();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::room_booking::*;
+
+    #[test]
+    fn status_round_trip() {
+        for status in BookingStatus::ALL {
+            assert_eq!(
+                status.to_string().parse::<BookingStatus>(),
+                Ok(status.clone())
+            );
+            assert_eq!(
+                serde_json::to_string(&status).unwrap(),
+                format!("\"{}\"", status)
+            );
+        }
+
+        assert_eq!(
+            "complete".parse::<BookingStatus>(),
+            Err(ParseStatusError("complete".to_string()))
+        );
+    }
+
+    #[test]
+    fn reject_unknown_fields() {
+        let json: &str = r#"{
+            "customerId": 1,
+            "roomTypeId": 2,
+            "checkinDate": "2024-01-01",
+            "checkInDate": "2024-01-01",
+            "checkOutDate": "2024-01-03"
+        }"#;
+
+        let error = serde_json::from_str::<RoomBooking>(json).unwrap_err();
+        assert!(error.to_string().starts_with("unknown field `checkinDate`"));
+    }
+
+    #[test]
+    fn trim_booking() {
+        let json: &str = r#"{
+            "customerId": 1,
+            "roomTypeId": 2,
+            "checkInDate": " 2024-01-01",
+            "checkOutDate": "2024-01-03 ",
+            "promoCode": "  ",
+            "taxExemptions": [" VAT "]
+        }"#;
+
+        let mut booking: RoomBooking = serde_json::from_str(json).unwrap();
+        booking.trim();
+        assert_eq!(booking.check_in_date, "2024-01-01");
+        assert_eq!(booking.check_out_date, "2024-01-03");
+        assert_eq!(booking.promo_code, None);
+        assert_eq!(booking.tax_exemptions, vec!["VAT".to_string()]);
+    }
+}
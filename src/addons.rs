@@ -0,0 +1,177 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::config;
+use crate::storage::booking_addon::BookingAddon;
+pub use booking_types::booking_addon::AddonCharge;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes an add-on in the catalog, which may be attached to a booking
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Addon {
+    /// The code used to attach the add-on to a booking, e.g. 'breakfast'
+    pub code: String,
+    /// The name of the add-on shown to guests
+    pub name: String,
+    /// The price of the add-on, in the minor unit of the hotel's currency
+    pub price: u64,
+    pub charge: AddonCharge,
+}
+
+/// Returns the add-ons which may be attached to a booking.
+///
+/// # Examples
+///
+/// ```
+/// let addons = addons::catalog();
+/// ```
+pub fn catalog() -> Vec<Addon> {
+    return config::hotel().addons;
+}
+
+/// Returns the add-on in the catalog with the provided code, if any.
+///
+/// # Arguments
+///
+/// * `code` - The code of the add-on
+///
+/// # Examples
+///
+/// ```
+/// let breakfast = addons::find("breakfast");
+/// ```
+pub fn find(code: &str) -> Option<Addon> {
+    return catalog().into_iter().find(|addon| addon.code == code);
+}
+
+/// Checks each add-on attached to a booking exists in the catalog, and is only attached once.
+/// Returns a list describing each problem found, which is empty if the add-ons are valid.
+///
+/// # Arguments
+///
+/// * `addons` - The add-ons attached to a booking
+///
+/// # Examples
+///
+/// ```
+/// let errors = addons::validate(&booking.addons);
+/// ```
+pub fn validate(addons: &[BookingAddon]) -> Vec<String> {
+    let catalog: Vec<Addon> = catalog();
+    let mut errors: Vec<String> = Vec::new();
+
+    for (index, addon) in addons.iter().enumerate() {
+        if !catalog.iter().any(|item| item.code == addon.code) {
+            errors.push(format!("{} is not a known add-on", addon.code));
+        }
+
+        if addon.quantity == 0 {
+            errors.push(format!("The quantity of {} must be at least 1", addon.code));
+        }
+
+        if addons[..index].iter().any(|other| other.code == addon.code) {
+            errors.push(format!("{} must only be added once", addon.code));
+        }
+    }
+
+    return errors;
+}
+
+/// Sets the price and charge of each add-on from the catalog, for add-ons which are not already
+/// priced.
+///
+/// # Arguments
+///
+/// * `addons` - The add-ons attached to a booking
+///
+/// # Examples
+///
+/// ```
+/// addons::price(&mut booking.addons);
+/// ```
+pub fn price(addons: &mut [BookingAddon]) {
+    for addon in addons.iter_mut().filter(|addon| addon.unit_price.is_none()) {
+        if let Some(item) = find(&addon.code) {
+            addon.unit_price = Some(item.price);
+            addon.charge = Some(item.charge);
+        }
+    }
+}
+
+/// Sets the price and charge of each add-on from the catalog, replacing any price already set,
+/// e.g. by a client creating a booking.
+///
+/// # Arguments
+///
+/// * `addons` - The add-ons attached to a booking
+///
+/// # Examples
+///
+/// ```
+/// addons::reprice(&mut booking.addons);
+/// ```
+pub fn reprice(addons: &mut [BookingAddon]) {
+    for addon in addons.iter_mut() {
+        addon.unit_price = None;
+        addon.charge = None;
+    }
+
+    price(addons);
+}
+
+/// Returns the default add-on catalog.
+pub fn default_catalog() -> Vec<Addon> {
+    return vec![
+        Addon {
+            code: "breakfast".to_string(),
+            name: "Breakfast".to_string(),
+            price: 1500,
+            charge: AddonCharge::PerNight,
+        },
+        Addon {
+            code: "parking".to_string(),
+            name: "Parking".to_string(),
+            price: 1000,
+            charge: AddonCharge::PerNight,
+        },
+        Addon {
+            code: "airport-transfer".to_string(),
+            name: "Airport transfer".to_string(),
+            price: 3500,
+            charge: AddonCharge::PerStay,
+        },
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::addons::*;
+
+    #[test]
+    fn validate_addons() {
+        let addon = |code: &str, quantity: u32| BookingAddon {
+            code: code.to_string(),
+            quantity,
+            unit_price: None,
+            charge: None,
+        };
+
+        assert!(validate(&[addon("breakfast", 2), addon("parking", 1)]).is_empty());
+        assert_eq!(validate(&[addon("spa", 1)]).len(), 1);
+        assert_eq!(
+            validate(&[addon("parking", 1), addon("parking", 0)]).len(),
+            2
+        );
+
+        let mut addons: Vec<BookingAddon> = vec![addon("airport-transfer", 1)];
+        addons[0].unit_price = Some(1);
+        reprice(&mut addons);
+        assert_eq!(addons[0].unit_price, Some(3500));
+        assert_eq!(addons[0].charge, Some(AddonCharge::PerStay));
+    }
+}
@@ -0,0 +1,155 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::config;
+use crate::pricing::{self, PriceBreakdown};
+use crate::storage::room_booking::{BookingStatus, RoomBooking};
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes a travel agent which makes bookings on behalf of guests, in return for a commission
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct TravelAgent {
+    pub agent_id: u32,
+    /// The name of the agency
+    pub name: String,
+    /// The commission paid to the agent, as a percentage of the price of each booking before tax,
+    /// in hundredths of a percent, e.g. 1000 for 10%
+    pub commission_rate: u64,
+}
+
+/// Describes the commission due on a single booking made by a travel agent
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CommissionLine {
+    pub booking_id: Option<u32>,
+    pub check_in_date: String,
+    pub check_out_date: String,
+    pub status: Option<BookingStatus>,
+    /// The price of the booking before tax, in the minor unit of the hotel's currency
+    pub revenue: u64,
+    /// The commission due on the booking, in the minor unit of the hotel's currency
+    pub commission: u64,
+}
+
+/// Describes the commission due to a travel agent for its bookings
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentCommissions {
+    pub agent_id: u32,
+    pub name: String,
+    pub commission_rate: u64,
+    pub bookings: Vec<CommissionLine>,
+    /// The total price of the bookings before tax, in the minor unit of the hotel's currency
+    pub revenue: u64,
+    /// The total commission due, in the minor unit of the hotel's currency
+    pub commission: u64,
+}
+
+impl AgentCommissions {
+    /// Creates a report of the commission due to a travel agent, pricing each of its bookings.
+    /// Bookings which cannot be priced are included with a price of 0, and sandbox bookings are
+    /// left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `agent` - The agent to report on
+    /// * `bookings` - The bookings made by the agent
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let report = AgentCommissions::new(agent, &bookings);
+    /// ```
+    pub fn new(agent: TravelAgent, bookings: &[RoomBooking]) -> AgentCommissions {
+        let bookings: Vec<CommissionLine> = bookings
+            .iter()
+            .filter(|booking| !booking.sandbox)
+            .map(|booking| {
+                let revenue: u64 = pricing::quote(booking).map_or(0, |quote| revenue(&quote));
+
+                return CommissionLine {
+                    booking_id: booking.booking_id,
+                    check_in_date: booking.check_in_date.clone(),
+                    check_out_date: booking.check_out_date.clone(),
+                    status: booking.status.clone(),
+                    revenue,
+                    commission: commission(revenue, agent.commission_rate),
+                };
+            })
+            .collect();
+
+        return AgentCommissions {
+            agent_id: agent.agent_id,
+            name: agent.name,
+            commission_rate: agent.commission_rate,
+            revenue: bookings.iter().map(|booking| booking.revenue).sum(),
+            commission: bookings.iter().map(|booking| booking.commission).sum(),
+            bookings,
+        };
+    }
+}
+
+/// Returns the travel agents which may make bookings.
+///
+/// # Examples
+///
+/// ```
+/// let agents = agents::agents();
+/// ```
+pub fn agents() -> Vec<TravelAgent> {
+    return config::hotel().agents;
+}
+
+/// Checks the travel agent a booking is made by exists. Returns a list describing each problem
+/// found, which is empty if the booking was not made by an agent, or the agent exists.
+///
+/// # Arguments
+///
+/// * `agent_id` - The id of the agent the booking was made by, if any
+///
+/// # Examples
+///
+/// ```
+/// let errors = agents::validate(booking.agent_id);
+/// ```
+pub fn validate(agent_id: Option<u32>) -> Vec<String> {
+    return match agent_id {
+        Some(agent_id) if !agents().iter().any(|agent| agent.agent_id == agent_id) => {
+            vec![format!("Travel agent {} does not exist", agent_id)]
+        }
+        _ => Vec::new(),
+    };
+}
+
+/// Returns the price of a booking before tax.
+fn revenue(quote: &PriceBreakdown) -> u64 {
+    return quote.total - quote.taxes.iter().map(|line| line.amount).sum::<u64>();
+}
+
+/// Returns the commission due on an amount, rounding to the nearest minor unit.
+///
+/// # Arguments
+///
+/// * `amount` - The amount the commission is due on
+/// * `rate` - The commission rate, in hundredths of a percent
+fn commission(amount: u64, rate: u64) -> u64 {
+    return (amount * rate + 5000) / 10000;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::agents::*;
+
+    #[test]
+    fn calculate_commission() {
+        assert_eq!(commission(28500, 1000), 2850);
+        assert_eq!(commission(999, 1250), 125);
+        assert_eq!(commission(0, 1000), 0);
+        assert!(validate(None).is_empty());
+        assert_eq!(validate(Some(1)).len(), 1);
+    }
+}
@@ -0,0 +1,394 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::circuit_breaker::CallError;
+use crate::config::{self, ChannelManagerConfig, RoomInventory};
+use crate::local_time;
+use crate::redaction;
+use crate::retry;
+use crate::state_machine::TransitionError;
+use crate::storage;
+use crate::storage::inventory_adjustment::{adjusted_inventory, InventoryAdjustment};
+use crate::storage::room_booking::{RoomBooking, DATE_FORMAT};
+use chrono::{Duration, NaiveDate};
+use hyper::body::{self, Body};
+use hyper::{Client, Method, Request};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::serde::json;
+use rocket::tokio::time::interval;
+use rocket::Rocket;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Defines the types of reservation message sent by the channel manager
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Debug)]
+pub enum MessageType {
+    /// A new reservation
+    New,
+    /// A change to the room type, dates or guests of an existing reservation
+    Modify,
+    /// The cancellation of an existing reservation
+    Cancel,
+}
+
+/// Describes a reservation message received from the channel manager
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelReservation {
+    /// The id of the reservation in the channel manager
+    pub reservation_id: String,
+    pub message_type: MessageType,
+    /// The channel manager's code for the room type booked
+    pub room_code: String,
+    /// The arrival date (YYYY-MM-DD)
+    pub arrival: String,
+    /// The departure date (YYYY-MM-DD)
+    pub departure: String,
+    #[serde(default)]
+    pub adults: u32,
+    #[serde(default)]
+    pub children: u32,
+}
+
+/// Describes a page of reservation messages received from the channel manager
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReservationFeed {
+    pub reservations: Vec<ChannelReservation>,
+    /// The cursor to request the next messages from, if any
+    pub next_cursor: Option<String>,
+}
+
+/// Describes the number of rooms of a room type available on a date, as sent to the channel
+/// manager
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailabilityUpdate {
+    pub room_code: String,
+    pub date: String,
+    pub available: u32,
+}
+
+impl ChannelReservation {
+    /// Maps the reservation into a booking, using the room type mapping of the channel manager
+    /// settings. Returns an error if the room code is not mapped to a room type.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The channel manager settings
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let booking = reservation.to_booking(&settings)?;
+    /// ```
+    pub fn to_booking(&self, settings: &ChannelManagerConfig) -> Result<RoomBooking, String> {
+        let room_type_id: u8 = match settings.room_type_for(&self.room_code) {
+            Some(room_type_id) => room_type_id,
+            None => return Err(format!("Room code {} is not mapped", self.room_code)),
+        };
+
+        return Ok(RoomBooking {
+            booking_id: None,
+            customer_id: settings.customer_id,
+            room_type_id,
+            check_in_date: self.arrival.clone(),
+            check_out_date: self.departure.clone(),
+            status: None,
+            expected_arrival_time: None,
+            late_check_out_time: None,
+            day_use: false,
+            start_time: None,
+            end_time: None,
+            billing_milestones: Vec::new(),
+            series_id: None,
+            cancellation: None,
+            no_show_fee: None,
+            payment_due_by: None,
+            addons: Vec::new(),
+            promo_code: None,
+            discount: None,
+            guests: (self.adults + self.children).max(1),
+            tax_exemptions: Vec::new(),
+            account_id: None,
+            agent_id: None,
+            channel_reservation_id: Some(self.reservation_id.clone()),
+            children: self.children,
+            rate_plans: Vec::new(),
+            pricing: None,
+            payments: Vec::new(),
+            refunds: Vec::new(),
+            created_at: None,
+            updated_at: None,
+            cancelled_at: None,
+            sandbox: false,
+        });
+    }
+}
+
+/// Applies a reservation message received from the channel manager, creating, updating or
+/// cancelling the booking for the reservation. Cancelling a reservation which is already
+/// cancelled succeeds without making any change.
+///
+/// # Arguments
+///
+/// * `reservation` - The reservation message
+/// * `settings` - The channel manager settings
+///
+/// # Examples
+///
+/// ```
+/// channel_manager::apply(&reservation, &settings)?;
+/// ```
+pub fn apply(
+    reservation: &ChannelReservation,
+    settings: &ChannelManagerConfig,
+) -> Result<(), String> {
+    if reservation.message_type == MessageType::Cancel {
+        return match storage::cancel_channel_reservation(&reservation.reservation_id) {
+            Ok(_) | Err(TransitionError::Illegal { .. }) => Ok(()),
+            Err(error) => Err(error.to_string()),
+        };
+    }
+
+    let booking: RoomBooking = reservation.to_booking(settings)?;
+    return storage::sync_channel_reservation(booking).map(|_| ());
+}
+
+/// Returns the number of rooms of each mapped room type available on each date from today, for
+/// the number of days set by the channel manager settings, after any inventory adjustments and
+/// including any overbooking allowance. Room types which are not limited by the hotel's room
+/// inventory are not included.
+///
+/// # Arguments
+///
+/// * `settings` - The channel manager settings
+///
+/// # Examples
+///
+/// ```
+/// let updates = channel_manager::availability(&settings);
+/// ```
+pub fn availability(settings: &ChannelManagerConfig) -> Vec<AvailabilityUpdate> {
+    let today: NaiveDate = local_time::today();
+    let mut updates: Vec<AvailabilityUpdate> = Vec::new();
+
+    let adjustments: Vec<InventoryAdjustment> = storage::fetch_adjustments();
+
+    for room_type in &settings.room_types {
+        let inventory: RoomInventory = match config::inventory_for(room_type.room_type_id) {
+            Some(inventory) => inventory,
+            None => continue,
+        };
+        let occupied: HashMap<String, usize> = storage::occupied_rooms(room_type.room_type_id);
+
+        for day in 0..settings.availability_days {
+            let date: String = (today + Duration::days(day as i64))
+                .format(DATE_FORMAT)
+                .to_string();
+            let rooms: usize =
+                adjusted_inventory(&inventory, &adjustments, &date).sellable() as usize;
+            let available: usize = rooms.saturating_sub(occupied.get(&date).copied().unwrap_or(0));
+
+            updates.push(AvailabilityUpdate {
+                room_code: room_type.code.clone(),
+                date,
+                available: available as u32,
+            });
+        }
+    }
+
+    return updates;
+}
+
+/// Sends a request to the channel manager API using the outbound retry policy, returning the
+/// response body. Returns an error if the circuit is open, or every attempt fails or has an
+/// unsuccessful response status.
+async fn send(
+    settings: &ChannelManagerConfig,
+    method: Method,
+    path: &str,
+    body: String,
+) -> Result<Vec<u8>, CallError> {
+    return retry::call("channel_manager", || {
+        request(settings, method.clone(), path, body.clone())
+    })
+    .await;
+}
+
+/// Sends a request to the channel manager API, returning the response body.
+async fn request(
+    settings: &ChannelManagerConfig,
+    method: Method,
+    path: &str,
+    body: String,
+) -> Result<Vec<u8>, CallError> {
+    let request: Request<Body> = Request::builder()
+        .method(method)
+        .uri(format!("{}{}", settings.url.trim_end_matches('/'), path))
+        .header("Authorization", format!("Bearer {}", settings.api_key))
+        .header("Content-Type", "application/json")
+        .body(Body::from(body))?;
+
+    let response = Client::new().request(request).await?;
+    let status = response.status();
+    let content = body::to_bytes(response.into_body()).await?;
+
+    if !status.is_success() {
+        return Err(format!("The channel manager responded with {}", status).into());
+    }
+
+    return Ok(content.to_vec());
+}
+
+/// Pushes the current availability of each mapped room type to the channel manager.
+async fn push_availability(settings: &ChannelManagerConfig) -> Result<usize, CallError> {
+    let updates: Vec<AvailabilityUpdate> = availability(settings);
+    if updates.is_empty() {
+        return Ok(0);
+    }
+
+    send(
+        settings,
+        Method::PUT,
+        "/availability",
+        json::to_string(&updates)?,
+    )
+    .await?;
+    return Ok(updates.len());
+}
+
+/// Pulls reservation messages from the channel manager, starting from the provided cursor, and
+/// applies each message. The cursor is moved past each page of messages once it is applied.
+/// Returns the number of messages which were applied.
+async fn pull_reservations(
+    settings: &ChannelManagerConfig,
+    cursor: &mut Option<String>,
+) -> Result<usize, CallError> {
+    let mut applied: usize = 0;
+
+    loop {
+        let path: String = match cursor {
+            Some(cursor) => format!("/reservations?since={}", cursor),
+            None => "/reservations".to_string(),
+        };
+        let content: Vec<u8> = send(settings, Method::GET, &path, String::new()).await?;
+        let feed: ReservationFeed = json::from_slice(&content)?;
+
+        for reservation in &feed.reservations {
+            match apply(reservation, settings) {
+                Ok(_) => applied += 1,
+                Err(error) => redaction::error(&format!(
+                    "Unable to apply channel manager reservation {}: {}",
+                    reservation.reservation_id, error
+                )),
+            }
+        }
+
+        match feed.next_cursor {
+            Some(next_cursor) if feed.reservations.is_empty() => {
+                *cursor = Some(next_cursor);
+                return Ok(applied);
+            }
+            Some(next_cursor) => *cursor = Some(next_cursor),
+            None => return Ok(applied),
+        }
+    }
+}
+
+/// A fairing which starts a background task when the server launches, if a channel manager is
+/// configured. The task pushes room availability to the channel manager, and pulls reservations
+/// from it, on the interval set by the channel manager settings.
+pub struct ChannelSync;
+
+#[rocket::async_trait]
+impl Fairing for ChannelSync {
+    fn info(&self) -> Info {
+        return Info {
+            name: "Channel manager synchronization",
+            kind: Kind::Liftoff,
+        };
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<rocket::Orbit>) {
+        let mut settings: ChannelManagerConfig = match config::hotel().channel_manager {
+            Some(settings) => settings,
+            None => return,
+        };
+
+        let shutdown = rocket.shutdown();
+        rocket::tokio::spawn(async move {
+            let mut sync = interval(std::time::Duration::from_secs(
+                settings.sync_interval_seconds.max(1),
+            ));
+            let mut cursor: Option<String> = None;
+
+            loop {
+                rocket::tokio::select! {
+                    _ = sync.tick() => {}
+                    _ = shutdown.clone() => return,
+                }
+
+                // The API key may have been rotated in the secret store since the last sync.
+                if let Some(current) = config::hotel().channel_manager {
+                    settings.api_key = current.api_key;
+                }
+
+                match pull_reservations(&settings, &mut cursor).await {
+                    Ok(0) => {}
+                    Ok(applied) => redaction::info(&format!(
+                        "Applied {} channel manager reservations",
+                        applied
+                    )),
+                    Err(error) => redaction::error(&format!(
+                        "Unable to pull channel manager reservations: {}",
+                        error
+                    )),
+                }
+
+                if let Err(error) = push_availability(&settings).await {
+                    redaction::error(&format!(
+                        "Unable to push availability to the channel manager: {}",
+                        error
+                    ));
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::channel_manager::*;
+    use crate::config::RoomTypeCode;
+    use crate::storage::room_booking::BookingRules;
+
+    #[test]
+    fn map_reservation() {
+        let settings = ChannelManagerConfig {
+            room_types: vec![RoomTypeCode {
+                code: "DBL".to_string(),
+                room_type_id: 2,
+            }],
+            customer_id: 99,
+            ..Default::default()
+        };
+        let mut reservation: ChannelReservation = json::from_str(
+            r#"{"reservationId":"ABC123","messageType":"New","roomCode":"DBL",
+            "arrival":"2020-01-01","departure":"2020-01-03","adults":2,"children":1}"#,
+        )
+        .unwrap();
+
+        let booking: RoomBooking = reservation.to_booking(&settings).unwrap();
+        assert_eq!(booking.room_type_id, 2);
+        assert_eq!(booking.customer_id, 99);
+        assert_eq!(booking.guests, 3);
+        assert_eq!(booking.channel_reservation_id, Some("ABC123".to_string()));
+        assert!(booking.validate().is_empty());
+
+        reservation.room_code = "TWN".to_string();
+        assert!(reservation.to_booking(&settings).is_err());
+    }
+}
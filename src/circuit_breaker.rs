@@ -0,0 +1,316 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::config::{self, CircuitBreakerConfig};
+use once_cell::sync::Lazy;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The error type returned by calls made through a circuit breaker.
+pub type CallError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A lazily initialised HashMap containing the circuit of each outbound integration, keyed by the
+/// name of the integration.
+static CIRCUITS: Lazy<Mutex<HashMap<&'static str, Circuit>>> = Lazy::new(|| {
+    let map: HashMap<&'static str, Circuit> = HashMap::new();
+    Mutex::new(map)
+});
+
+/// Defines the states of a circuit
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub enum CircuitState {
+    /// Calls are made as normal
+    Closed,
+    /// Calls are rejected without being made, as the integration has recently failed
+    Open,
+    /// A single probing call is allowed, to check whether the integration has recovered
+    HalfOpen,
+}
+
+/// Defines the reasons a call may be rejected without being made
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CircuitError {
+    /// The circuit is open, or already probing the integration
+    Open(&'static str),
+    /// The integration already has the maximum number of calls in flight
+    Saturated(&'static str),
+}
+
+impl fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            CircuitError::Open(name) => write!(f, "The circuit for {} is open", name),
+            CircuitError::Saturated(name) => {
+                write!(f, "Too many calls to {} are already in flight", name)
+            }
+        };
+    }
+}
+
+impl std::error::Error for CircuitError {}
+
+/// Describes the state and call counts of the circuit for an outbound integration
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitMetrics {
+    /// The name of the integration
+    pub name: String,
+    pub state: CircuitState,
+    /// The number of calls which have failed since the last successful call
+    pub consecutive_failures: u32,
+    /// The number of calls currently in flight
+    pub in_flight: u32,
+    /// The number of calls which succeeded
+    pub successes: u64,
+    /// The number of calls which failed
+    pub failures: u64,
+    /// The number of calls rejected without being made, because the circuit was open or the
+    /// integration had too many calls in flight
+    pub rejected: u64,
+    /// The number of calls which were retried after failing
+    pub retries: u64,
+    /// The number of calls which failed because they took longer than the configured timeout
+    pub timeouts: u64,
+}
+
+/// The state of the circuit for a single outbound integration.
+#[derive(Clone, Debug)]
+struct Circuit {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    in_flight: u32,
+    successes: u64,
+    failures: u64,
+    rejected: u64,
+    retries: u64,
+    timeouts: u64,
+}
+
+impl Circuit {
+    /// Creates a closed circuit, with no calls made.
+    fn new() -> Circuit {
+        return Circuit {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+            in_flight: 0,
+            successes: 0,
+            failures: 0,
+            rejected: 0,
+            retries: 0,
+            timeouts: 0,
+        };
+    }
+
+    /// Checks whether a call may be made, recording it as in flight if so. An open circuit moves
+    /// to half open once it has been open for the configured time, allowing a single probing call.
+    fn acquire(
+        &mut self,
+        name: &'static str,
+        settings: &CircuitBreakerConfig,
+        now: Instant,
+    ) -> Result<(), CircuitError> {
+        let open_for: Duration = Duration::from_secs(settings.open_seconds);
+
+        let result: Result<(), CircuitError> = match self.state {
+            CircuitState::Open
+                if self
+                    .opened_at
+                    .is_some_and(|opened_at| now.duration_since(opened_at) >= open_for) =>
+            {
+                self.state = CircuitState::HalfOpen;
+                Ok(())
+            }
+            CircuitState::Open => Err(CircuitError::Open(name)),
+            CircuitState::HalfOpen if self.in_flight > 0 => Err(CircuitError::Open(name)),
+            _ if self.in_flight >= settings.max_concurrent_calls => {
+                Err(CircuitError::Saturated(name))
+            }
+            _ => Ok(()),
+        };
+
+        match result {
+            Ok(_) => self.in_flight += 1,
+            Err(_) => self.rejected += 1,
+        }
+
+        return result;
+    }
+
+    /// Records the outcome of a call. A successful call closes the circuit, and the circuit is
+    /// opened by a failed probing call, or once the configured number of calls fail in a row.
+    fn record(&mut self, success: bool, settings: &CircuitBreakerConfig, now: Instant) {
+        self.in_flight = self.in_flight.saturating_sub(1);
+
+        if success {
+            self.successes += 1;
+            self.consecutive_failures = 0;
+            self.state = CircuitState::Closed;
+            self.opened_at = None;
+            return;
+        }
+
+        self.failures += 1;
+        self.consecutive_failures += 1;
+
+        if self.state == CircuitState::HalfOpen
+            || self.consecutive_failures >= settings.failure_threshold
+        {
+            self.state = CircuitState::Open;
+            self.opened_at = Some(now);
+        }
+    }
+
+    /// Returns the metrics of the circuit.
+    fn metrics(&self, name: &str) -> CircuitMetrics {
+        return CircuitMetrics {
+            name: name.to_string(),
+            state: self.state,
+            consecutive_failures: self.consecutive_failures,
+            in_flight: self.in_flight,
+            successes: self.successes,
+            failures: self.failures,
+            rejected: self.rejected,
+            retries: self.retries,
+            timeouts: self.timeouts,
+        };
+    }
+}
+
+/// Makes a call to an outbound integration through the integration's circuit breaker. The call is
+/// rejected without being made if the circuit is open, or the integration already has the
+/// maximum number of calls in flight, so a failing integration does not hold up the service.
+///
+/// # Arguments
+///
+/// * `name` - The name of the integration, e.g. 'channel_manager'
+/// * `request` - The call to make
+///
+/// # Examples
+///
+/// ```
+/// let response = circuit_breaker::call("channel_manager", send(request)).await?;
+/// ```
+pub async fn call<T, E, F>(name: &'static str, request: F) -> Result<T, CallError>
+where
+    E: Into<CallError>,
+    F: Future<Output = Result<T, E>>,
+{
+    let settings: CircuitBreakerConfig = config::hotel().circuit_breaker;
+
+    if let Ok(mut circuits) = CIRCUITS.lock() {
+        circuits.entry(name).or_insert_with(Circuit::new).acquire(
+            name,
+            &settings,
+            Instant::now(),
+        )?;
+    }
+
+    let result: Result<T, E> = request.await;
+
+    if let Ok(mut circuits) = CIRCUITS.lock() {
+        circuits.entry(name).or_insert_with(Circuit::new).record(
+            result.is_ok(),
+            &settings,
+            Instant::now(),
+        );
+    }
+
+    return result.map_err(|error| error.into());
+}
+
+/// Records that a call to an outbound integration is being retried.
+///
+/// # Arguments
+///
+/// * `name` - The name of the integration
+pub fn record_retry(name: &'static str) {
+    if let Ok(mut circuits) = CIRCUITS.lock() {
+        circuits.entry(name).or_insert_with(Circuit::new).retries += 1;
+    }
+}
+
+/// Records that a call to an outbound integration timed out.
+///
+/// # Arguments
+///
+/// * `name` - The name of the integration
+pub fn record_timeout(name: &'static str) {
+    if let Ok(mut circuits) = CIRCUITS.lock() {
+        circuits.entry(name).or_insert_with(Circuit::new).timeouts += 1;
+    }
+}
+
+/// Returns the metrics of the circuit for each outbound integration which has been called,
+/// ordered by name.
+///
+/// # Examples
+///
+/// ```
+/// let metrics = circuit_breaker::metrics();
+/// ```
+pub fn metrics() -> Vec<CircuitMetrics> {
+    let mut metrics: Vec<CircuitMetrics> = match CIRCUITS.lock() {
+        Ok(circuits) => circuits
+            .iter()
+            .map(|(name, circuit)| circuit.metrics(name))
+            .collect(),
+        Err(_) => return Vec::new(),
+    };
+
+    metrics.sort_by(|a, b| a.name.cmp(&b.name));
+    return metrics;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::circuit_breaker::*;
+
+    #[test]
+    fn open_and_probe_circuit() {
+        let settings = CircuitBreakerConfig {
+            failure_threshold: 2,
+            open_seconds: 30,
+            max_concurrent_calls: 1,
+        };
+        let start: Instant = Instant::now();
+        let mut circuit = Circuit::new();
+
+        assert!(circuit.acquire("test", &settings, start).is_ok());
+        assert_eq!(
+            circuit.acquire("test", &settings, start),
+            Err(CircuitError::Saturated("test"))
+        );
+        circuit.record(false, &settings, start);
+        assert!(circuit.acquire("test", &settings, start).is_ok());
+        circuit.record(false, &settings, start);
+        assert_eq!(circuit.state, CircuitState::Open);
+
+        assert!(circuit.acquire("test", &settings, start).is_err());
+        let later: Instant = start + Duration::from_secs(30);
+        assert!(circuit.acquire("test", &settings, later).is_ok());
+        assert_eq!(circuit.state, CircuitState::HalfOpen);
+        circuit.record(false, &settings, later);
+        assert_eq!(circuit.state, CircuitState::Open);
+
+        let recovered: Instant = later + Duration::from_secs(30);
+        assert!(circuit.acquire("test", &settings, recovered).is_ok());
+        circuit.record(true, &settings, recovered);
+        assert_eq!(circuit.state, CircuitState::Closed);
+
+        let metrics: CircuitMetrics = circuit.metrics("test");
+        assert_eq!(
+            (metrics.successes, metrics.failures, metrics.rejected),
+            (1, 3, 2)
+        );
+    }
+}
@@ -0,0 +1,331 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::import::{self, ImportReport};
+use crate::loadgen::{self, LoadgenOptions, Mix};
+use crate::seed::{self, SeedOptions};
+use crate::storage;
+use crate::storage::booking_filter::BookingFilter;
+use crate::storage::ledger::{self, LedgerEntry};
+use crate::storage::room_booking::{RoomBooking, DATE_FORMAT};
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rocket::http::ContentType;
+use rocket::serde::json::serde_json;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+/// A hotel room booking management service
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Defines the commands supported by the service. The service is started if no command is given.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Start the HTTP server
+    Serve(ServeArgs),
+    /// Export every booking to a file, or to stdout if no file is given
+    Export {
+        /// The format of the exported bookings
+        #[arg(long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+        /// The path of the file to write
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Export the accounting ledger for a day as CSV, to a file or to stdout if no file is given
+    Ledger {
+        /// The day to export (YYYY-MM-DD). Today, in the hotel's timezone, if not given.
+        #[arg(long)]
+        date: Option<String>,
+        /// The path of the file to write
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Import bookings from a CSV or JSON file
+    Import {
+        /// The path of the file to import
+        file: PathBuf,
+        /// The format of the file. Detected from the file extension if not given.
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+    },
+    /// Remove superseded entries from the change log, and rewrite the snapshot. This discards the
+    /// history of each booking, other than its latest change.
+    CompactSnapshot,
+    /// Check the integrity of the snapshot and change log
+    Verify,
+    /// Send a mix of create, read and cancel requests to a running instance, and report the
+    /// latency of each
+    Loadgen(LoadgenArgs),
+}
+
+/// Defines the options available when generating load
+#[derive(Args, Debug)]
+pub struct LoadgenArgs {
+    /// The base URL of the running instance, including the API version
+    #[arg(long, default_value = "http://127.0.0.1:8000/v1")]
+    pub url: String,
+    /// The total number of requests to send
+    #[arg(long, default_value_t = 1000)]
+    pub requests: usize,
+    /// The number of requests sent at the same time
+    #[arg(long, default_value_t = 10)]
+    pub concurrency: usize,
+    /// The relative weight of each operation, e.g. create=2,read=7,cancel=1
+    #[arg(long, default_value = "create=2,read=7,cancel=1")]
+    pub mix: Mix,
+}
+
+/// Defines the options available when starting the HTTP server
+#[derive(Args, Debug, Default)]
+pub struct ServeArgs {
+    /// Generate this many random bookings before starting the server
+    #[arg(long)]
+    pub seed: Option<usize>,
+    /// The earliest check in date of generated bookings (YYYY-MM-DD)
+    #[arg(long, requires = "seed")]
+    pub seed_from: Option<String>,
+    /// The latest check in date of generated bookings (YYYY-MM-DD)
+    #[arg(long, requires = "seed")]
+    pub seed_to: Option<String>,
+    /// Generated bookings use room type ids between 1 and this value
+    #[arg(long, requires = "seed")]
+    pub seed_room_types: Option<u8>,
+}
+
+/// Defines the file formats supported by the import and export commands
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+impl ServeArgs {
+    /// Returns the options used to generate bookings before starting the server, if requested.
+    pub fn seed_options(&self) -> Result<Option<SeedOptions>, String> {
+        let count: usize = match self.seed {
+            Some(count) => count,
+            None => return Ok(None),
+        };
+
+        let mut options = SeedOptions::new(count);
+
+        if let Some(from) = &self.seed_from {
+            options.from = NaiveDate::parse_from_str(from, DATE_FORMAT)
+                .map_err(|_| format!("Invalid value for --seed-from: {}", from))?;
+        }
+
+        if let Some(to) = &self.seed_to {
+            options.to = NaiveDate::parse_from_str(to, DATE_FORMAT)
+                .map_err(|_| format!("Invalid value for --seed-to: {}", to))?;
+        }
+
+        if let Some(room_types) = self.seed_room_types {
+            options.room_types = room_types;
+        }
+
+        return Ok(Some(options));
+    }
+
+    /// Generates and stores random bookings, if requested.
+    pub fn seed(&self) {
+        match self.seed_options() {
+            Ok(Some(options)) => {
+                let seeded: usize = storage::import(seed::generate(&options))
+                    .iter()
+                    .filter(|result| result.is_ok())
+                    .count();
+                println!("Seeded {} bookings...", seeded);
+            }
+            Ok(None) => {}
+            Err(err) => println!("An error occurred seeding bookings: {}", err),
+        }
+    }
+}
+
+/// Writes every booking to a file, or stdout if no file is given. Bookings are fetched from
+/// storage in batches and written as they are reached, so the whole list is never held in memory.
+///
+/// # Arguments
+///
+/// * `format` - The format to write the bookings in
+/// * `output` - The path of the file to write
+pub fn export(format: Format, output: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    let bookings = storage::iter_filtered(&BookingFilter::default());
+    let destination: Box<dyn Write> = match &output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(std::io::stdout()),
+    };
+    let mut destination = BufWriter::new(destination);
+    let mut exported: usize = 0;
+
+    match format {
+        Format::Json => {
+            destination.write_all(b"[")?;
+            for booking in bookings {
+                if exported > 0 {
+                    destination.write_all(b",")?;
+                }
+                destination.write_all(b"\n")?;
+                serde_json::to_writer_pretty(&mut destination, &booking)?;
+                exported += 1;
+            }
+            destination.write_all(b"\n]\n")?;
+        }
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(&mut destination);
+            for booking in bookings {
+                writer.serialize(&booking)?;
+                exported += 1;
+            }
+            writer.flush()?;
+        }
+    }
+
+    destination.flush()?;
+
+    if let Some(path) = output {
+        eprintln!("Exported {} bookings to {}", exported, path.display());
+    }
+
+    return Ok(());
+}
+
+/// Writes the accounting ledger for a day as CSV, to a file or stdout if no file is given.
+///
+/// # Arguments
+///
+/// * `date` - The day to export (YYYY-MM-DD), today if not given
+/// * `output` - The path of the file to write
+pub fn ledger(
+    date: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let date: NaiveDate = ledger::parse_date(date.as_deref())?;
+    let bookings = storage::iter_filtered(&BookingFilter::default());
+    let entries: Vec<LedgerEntry> = LedgerEntry::for_date(bookings, date);
+    let content: Vec<u8> = ledger::to_csv(&entries)?;
+
+    match output {
+        Some(path) => {
+            File::create(&path)?.write_all(&content)?;
+            eprintln!(
+                "Exported {} ledger entries to {}",
+                entries.len(),
+                path.display()
+            );
+        }
+        None => std::io::stdout().write_all(&content)?,
+    }
+
+    return Ok(());
+}
+
+/// Imports bookings from a file, printing the outcome of any rows which could not be imported.
+///
+/// # Arguments
+///
+/// * `file` - The path of the file to import
+/// * `format` - The format of the file, detected from the file extension if not given
+pub fn import(file: PathBuf, format: Option<Format>) -> Result<(), Box<dyn std::error::Error>> {
+    let format: Format = match format {
+        Some(format) => format,
+        None => match file.extension().and_then(|extension| extension.to_str()) {
+            Some("csv") => Format::Csv,
+            Some("json") => Format::Json,
+            _ => return Err("Unable to detect file format, use --format".into()),
+        },
+    };
+
+    let content_type: ContentType = match format {
+        Format::Csv => ContentType::CSV,
+        Format::Json => ContentType::JSON,
+    };
+
+    let content: String = std::fs::read_to_string(&file)?;
+    let rows: Vec<Result<RoomBooking, String>> = import::parse(&content_type, &content)?;
+    let report: ImportReport = ImportReport::new(import::import_rows(rows));
+
+    for row in report.rows.iter().filter(|row| !row.errors.is_empty()) {
+        println!("Row {}: {}", row.row, row.errors.join(", "));
+    }
+
+    println!(
+        "Imported {} bookings, rejected {}",
+        report.imported, report.rejected
+    );
+    return Ok(());
+}
+
+/// Sends generated load to a running instance, printing the latency percentiles of each operation.
+///
+/// # Arguments
+///
+/// * `args` - The options for the run
+pub async fn loadgen(args: LoadgenArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let options = LoadgenOptions {
+        url: args.url,
+        requests: args.requests,
+        concurrency: args.concurrency,
+        mix: args.mix,
+    };
+
+    eprintln!(
+        "Sending {} requests to {}, {} at a time...",
+        options.requests, options.url, options.concurrency
+    );
+    print!("{}", loadgen::run(&options).await);
+    return Ok(());
+}
+
+/// Compacts the change log and rewrites the snapshot.
+pub fn compact_snapshot() -> Result<(), Box<dyn std::error::Error>> {
+    let removed: usize = storage::compact()?;
+    println!("Removed {} superseded changes", removed);
+    return Ok(());
+}
+
+/// Checks the integrity of the snapshot and change log, printing any problems found.
+pub fn verify() -> Result<(), Box<dyn std::error::Error>> {
+    let problems: Vec<String> = storage::verify();
+
+    for problem in &problems {
+        println!("{}", problem);
+    }
+
+    if !problems.is_empty() {
+        return Err(format!("Found {} problems", problems.len()).into());
+    }
+
+    println!("No problems found");
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::*;
+
+    #[test]
+    fn parse_arguments() {
+        let cli = Cli::parse_from(["service", "serve", "--seed", "10", "--seed-room-types", "3"]);
+        match cli.command {
+            Some(Command::Serve(args)) => {
+                let options: SeedOptions = args.seed_options().unwrap().unwrap();
+                assert_eq!(options.count, 10);
+                assert_eq!(options.room_types, 3);
+            }
+            _ => panic!("Expected the serve command"),
+        }
+
+        assert!(Cli::parse_from(["service"]).command.is_none());
+        assert!(Cli::try_parse_from(["service", "serve", "--seed-to", "2020-01-01"]).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use chrono::{DateTime, Duration, Utc};
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+
+/// Provides the current time to time-dependent behaviour, such as payment expiry, the no-show
+/// check and promo code validity, so it can be controlled in tests.
+pub trait Clock {
+    /// Returns the current time.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// A clock reading the system time.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        return Utc::now();
+    }
+}
+
+/// A clock which only moves when it is set or advanced.
+pub struct ManualClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl ManualClock {
+    /// Creates a clock stopped at the provided time.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The time the clock reads
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let clock = ManualClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap());
+    /// ```
+    pub fn new(now: DateTime<Utc>) -> ManualClock {
+        return ManualClock {
+            now: Mutex::new(now),
+        };
+    }
+
+    /// Sets the time the clock reads.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The time the clock reads
+    pub fn set(&self, now: DateTime<Utc>) {
+        if let Ok(mut time) = self.now.lock() {
+            *time = now;
+        }
+    }
+
+    /// Moves the clock forward.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - How far to move the clock
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// clock.advance(Duration::hours(24));
+    /// ```
+    pub fn advance(&self, duration: Duration) {
+        if let Ok(mut time) = self.now.lock() {
+            *time += duration;
+        }
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        return match self.now.lock() {
+            Ok(time) => *time,
+            Err(_) => Utc::now(),
+        };
+    }
+}
+
+thread_local! {
+    /// The clock used on the current thread in place of the system clock, if any.
+    static SCOPED_CLOCK: RefCell<Option<Arc<dyn Clock>>> = RefCell::new(None);
+}
+
+/// Returns the current time, from the clock in scope on the current thread, or the system clock.
+///
+/// # Examples
+///
+/// ```
+/// let now = clock::now();
+/// ```
+pub fn now() -> DateTime<Utc> {
+    let scoped: Option<DateTime<Utc>> =
+        SCOPED_CLOCK.with(|clock| clock.borrow().as_ref().map(|clock| clock.now()));
+
+    return scoped.unwrap_or_else(|| SystemClock.now());
+}
+
+/// Runs a function with the provided clock in place of the system clock on the current thread,
+/// restoring the previous clock afterwards. Other threads, such as background tasks, are not
+/// affected, so tests using different clocks may run in parallel.
+///
+/// # Arguments
+///
+/// * `clock` - The clock to use
+/// * `function` - The function to run
+///
+/// # Examples
+///
+/// ```
+/// let clock = Arc::new(ManualClock::new(time));
+/// let result = clock::scoped(clock.clone(), || storage::status(1, BookingStatus::NoShow));
+/// ```
+pub fn scoped<T>(clock: Arc<dyn Clock>, function: impl FnOnce() -> T) -> T {
+    let previous: Option<Arc<dyn Clock>> = SCOPED_CLOCK.with(|scoped| scoped.replace(Some(clock)));
+    let result: T = function();
+    SCOPED_CLOCK.with(|scoped| scoped.replace(previous));
+
+    return result;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn scoped_manual_clock() {
+        let start: DateTime<Utc> = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let clock: Arc<ManualClock> = Arc::new(ManualClock::new(start));
+
+        scoped(clock.clone(), || {
+            assert_eq!(now(), start);
+
+            clock.advance(Duration::hours(36));
+            assert_eq!(now(), start + Duration::hours(36));
+
+            clock.set(start);
+            assert_eq!(now(), start);
+        });
+
+        assert!(now() > start);
+    }
+}
@@ -0,0 +1,150 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use flate2::write::GzEncoder;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{ContentType, Header};
+use rocket::{Request, Response};
+use std::io::{Cursor, Write};
+
+/// Responses smaller than this size (in bytes) are sent uncompressed, as the saving is negligible.
+static MIN_COMPRESSION_SIZE: usize = 1024;
+
+/// Defines the supported content encodings, in order of preference
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+impl Encoding {
+    /// Returns the name of the encoding, as used in the ```Content-Encoding``` header
+    fn name(&self) -> &'static str {
+        return match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+        };
+    }
+
+    /// Selects the preferred encoding accepted by the client, based on the value of the
+    /// ```Accept-Encoding``` header. Encodings with a quality value of 0 are treated as refused.
+    ///
+    /// # Arguments
+    ///
+    /// * `accept_encoding` - The value of the ```Accept-Encoding``` request header
+    fn negotiate(accept_encoding: &str) -> Option<Encoding> {
+        let accepted: Vec<&str> = accept_encoding
+            .split(',')
+            .filter_map(|item| {
+                let mut parts = item.split(';');
+                let name: &str = parts.next()?.trim();
+                let refused: bool = parts.any(|param| match param.trim().strip_prefix("q=") {
+                    Some(quality) => quality.parse::<f32>() == Ok(0.0),
+                    None => false,
+                });
+
+                match refused {
+                    true => None,
+                    false => Some(name),
+                }
+            })
+            .collect();
+
+        return [Encoding::Brotli, Encoding::Gzip]
+            .into_iter()
+            .find(|encoding| accepted.contains(&encoding.name()) || accepted.contains(&"*"));
+    }
+
+    /// Compresses the provided data using the encoding.
+    fn compress(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Encoding::Brotli => {
+                let mut output: Vec<u8> = Vec::new();
+                {
+                    let mut writer = brotli::CompressorWriter::new(&mut output, 4096, 5, 22);
+                    writer.write_all(data)?;
+                }
+                return Ok(output);
+            }
+            Encoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data)?;
+                return encoder.finish();
+            }
+        }
+    }
+}
+
+/// A fairing which compresses JSON responses using brotli or gzip, negotiated using the
+/// ```Accept-Encoding``` request header. Streamed responses are sent uncompressed, as compressing
+/// them would require buffering the whole body.
+pub struct Compression;
+
+#[rocket::async_trait]
+impl Fairing for Compression {
+    fn info(&self) -> Info {
+        return Info {
+            name: "Response compression",
+            kind: Kind::Response,
+        };
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        if response.content_type() != Some(ContentType::JSON)
+            || response.headers().contains("Content-Encoding")
+            || response.body().preset_size().is_none()
+        {
+            return;
+        }
+
+        response.adjoin_header(Header::new("Vary", "Accept-Encoding"));
+
+        let encoding: Encoding = match request
+            .headers()
+            .get_one("Accept-Encoding")
+            .and_then(Encoding::negotiate)
+        {
+            Some(encoding) => encoding,
+            None => return,
+        };
+
+        let body: Vec<u8> = match response.body_mut().to_bytes().await {
+            Ok(body) => body,
+            Err(_) => return,
+        };
+
+        if body.len() < MIN_COMPRESSION_SIZE {
+            response.set_sized_body(body.len(), Cursor::new(body));
+            return;
+        }
+
+        match encoding.compress(&body) {
+            Ok(compressed) => {
+                response.set_header(Header::new("Content-Encoding", encoding.name()));
+                response.set_sized_body(compressed.len(), Cursor::new(compressed));
+            }
+            Err(_) => response.set_sized_body(body.len(), Cursor::new(body)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compression::*;
+
+    #[test]
+    fn negotiate_encoding() {
+        assert_eq!(
+            Encoding::negotiate("gzip, deflate, br"),
+            Some(Encoding::Brotli)
+        );
+        assert_eq!(Encoding::negotiate("gzip"), Some(Encoding::Gzip));
+        assert_eq!(
+            Encoding::negotiate("br;q=0, gzip;q=0.5"),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(Encoding::negotiate("identity"), None);
+    }
+}
@@ -0,0 +1,1132 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::addons::{self, Addon};
+use crate::agents::TravelAgent;
+use crate::features::{Feature, FeatureFlags};
+use crate::pricing::{RatePlan, RoomRate};
+use crate::request_log::RequestLogConfig;
+use crate::screening::ScreeningRuleConfig;
+use crate::stay_rules::StayRule;
+use crate::storage::outbox_event::{EventTopic, OutboxEvent};
+use crate::storage::retention::RetentionAction;
+use crate::tax::TaxRule;
+use crate::tls::CertificatesConfig;
+use chrono::NaiveTime;
+use chrono_tz::Tz;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+/// The name of the configuration section containing the hotel settings.
+static HOTEL_SECTION: &str = "hotel";
+/// The hotel settings currently in use.
+static HOTEL_CONFIG: Lazy<RwLock<HotelConfig>> = Lazy::new(|| RwLock::new(HotelConfig::default()));
+/// The name of the configuration section containing the documentation settings.
+static DOCS_SECTION: &str = "docs";
+/// The documentation settings currently in use.
+static DOCS_CONFIG: Lazy<RwLock<DocsConfig>> = Lazy::new(|| RwLock::new(DocsConfig::default()));
+/// The name of the configuration section containing the feature flags.
+static FEATURES_SECTION: &str = "features";
+/// The feature flags currently in use.
+static FEATURES_CONFIG: Lazy<RwLock<FeatureFlags>> =
+    Lazy::new(|| RwLock::new(FeatureFlags::default()));
+/// The name of the configuration section containing the request log settings.
+static REQUEST_LOG_SECTION: &str = "request_log";
+/// The request log settings currently in use.
+static REQUEST_LOG_CONFIG: Lazy<RwLock<RequestLogConfig>> =
+    Lazy::new(|| RwLock::new(RequestLogConfig::default()));
+/// The name of the configuration section containing the certificate settings.
+static CERTIFICATES_SECTION: &str = "certificates";
+/// The certificate settings currently in use, if the listener serves HTTPS using them.
+static CERTIFICATES_CONFIG: Lazy<RwLock<Option<CertificatesConfig>>> =
+    Lazy::new(|| RwLock::new(None));
+
+/// Describes the number of rooms of a room type available for overnight bookings
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct RoomInventory {
+    /// The id of the room type
+    pub room_type_id: u8,
+    /// The number of rooms of the room type
+    pub rooms: u32,
+    /// The percentage of the number of rooms which may be sold beyond it, rounded down, e.g. 5
+    /// to allow 21 bookings a night for 20 rooms. No rooms are overbooked by default
+    #[serde(default)]
+    pub overbooking_percent: u32,
+}
+
+impl RoomInventory {
+    /// Returns the number of rooms which may be sold each night, including the overbooking
+    /// allowance if overbooking is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let sellable = inventory.sellable();
+    /// ```
+    pub fn sellable(&self) -> u32 {
+        if !features().enabled(Feature::Overbooking) {
+            return self.rooms;
+        }
+
+        return self.rooms + self.rooms * self.overbooking_percent / 100;
+    }
+}
+
+/// Describes the most adults and children who may stay in a room of a room type
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct OccupancyLimit {
+    /// The id of the room type
+    pub room_type_id: u8,
+    /// The most adults who may stay in a room
+    pub max_adults: u32,
+    /// The most children who may stay in a room
+    pub max_children: u32,
+}
+
+/// Maps the code an external system, such as a channel manager, uses for a room type to the room
+/// type
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct RoomTypeCode {
+    /// The external system's code for the room type
+    pub code: String,
+    /// The id of the room type
+    pub room_type_id: u8,
+}
+
+/// Describes the settings used to synchronize availability and reservations with an online
+/// travel agent channel manager
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct ChannelManagerConfig {
+    /// The base URL of the channel manager API. Only http URLs are supported.
+    pub url: String,
+    /// The key sent as a bearer token to authenticate with the channel manager API
+    pub api_key: String,
+    /// How often availability is pushed and reservations are pulled, in seconds
+    pub sync_interval_seconds: u64,
+    /// The number of days from today availability is pushed for
+    pub availability_days: u32,
+    /// The customer id recorded against bookings made through the channel manager
+    pub customer_id: u32,
+    /// The room types synchronized with the channel manager, and their channel manager codes
+    pub room_types: Vec<RoomTypeCode>,
+}
+
+impl Default for ChannelManagerConfig {
+    fn default() -> ChannelManagerConfig {
+        return ChannelManagerConfig {
+            url: String::new(),
+            api_key: String::new(),
+            sync_interval_seconds: 300,
+            availability_days: 365,
+            customer_id: 0,
+            room_types: Vec::new(),
+        };
+    }
+}
+
+impl ChannelManagerConfig {
+    /// Returns the id of the room type mapped to a channel manager room code, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The channel manager's code for the room type
+    pub fn room_type_for(&self, code: &str) -> Option<u8> {
+        return room_type_for_code(&self.room_types, code);
+    }
+}
+
+/// Describes the settings of the circuit breaker each outbound integration, such as the channel
+/// manager, is called through
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// The number of calls which must fail in a row before the circuit is opened
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a probing call is allowed, in seconds
+    pub open_seconds: u64,
+    /// The most calls to an integration which may be in flight at once
+    pub max_concurrent_calls: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> CircuitBreakerConfig {
+        return CircuitBreakerConfig {
+            failure_threshold: 5,
+            open_seconds: 30,
+            max_concurrent_calls: 4,
+        };
+    }
+}
+
+/// Describes the retry and timeout policy used for calls to outbound integrations
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// The most times a call is attempted, including the first attempt
+    pub max_attempts: u32,
+    /// The delay before the first retry, in milliseconds, doubled for each further retry
+    pub base_delay_ms: u64,
+    /// The longest delay before a retry, in milliseconds
+    pub max_delay_ms: u64,
+    /// How long each attempt may take before it fails, in milliseconds
+    pub timeout_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> RetryConfig {
+        return RetryConfig {
+            max_attempts: 3,
+            base_delay_ms: 200,
+            max_delay_ms: 5000,
+            timeout_ms: 10000,
+        };
+    }
+}
+
+/// Describes a webhook subscribed to booking events, and the events delivered to it
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct WebhookSubscription {
+    /// The URL matching events are posted to. Only http URLs are supported.
+    pub url: String,
+    /// The topics delivered to the webhook, e.g. Created or Payment. Every topic is delivered if
+    /// none are listed.
+    #[serde(default)]
+    pub topics: Vec<EventTopic>,
+    /// The room types whose events are delivered to the webhook. Events for every room type are
+    /// delivered if none are listed.
+    #[serde(default)]
+    pub room_type_ids: Vec<u8>,
+}
+
+impl WebhookSubscription {
+    /// Checks whether an event should be delivered to the webhook, matching every filter of the
+    /// subscription.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event to check
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let deliver = subscription.matches(&event);
+    /// ```
+    pub fn matches(&self, event: &OutboxEvent) -> bool {
+        let topic_matches: bool = self.topics.is_empty() || self.topics.contains(&event.topic);
+        let room_type_matches: bool = self.room_type_ids.is_empty()
+            || self
+                .room_type_ids
+                .contains(&event.change.booking.room_type_id);
+
+        return topic_matches && room_type_matches;
+    }
+}
+
+/// Describes the settings used to deliver booking events from the outbox to webhooks
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct EventsConfig {
+    /// The URL every booking event is posted to, if any. Only http URLs are supported.
+    pub webhook_url: String,
+    /// Further webhooks, each receiving only the events matching its topics and filters
+    pub subscriptions: Vec<WebhookSubscription>,
+    /// How often the outbox is checked for events to deliver, in seconds
+    pub dispatch_interval_seconds: u64,
+    /// The most events delivered each time the outbox is checked
+    pub batch_size: usize,
+    /// The number of failed attempts to deliver an event after which it is moved to the
+    /// dead-letter store
+    pub max_delivery_attempts: u32,
+}
+
+impl Default for EventsConfig {
+    fn default() -> EventsConfig {
+        return EventsConfig {
+            webhook_url: String::new(),
+            subscriptions: Vec::new(),
+            dispatch_interval_seconds: 5,
+            batch_size: 100,
+            max_delivery_attempts: 10,
+        };
+    }
+}
+
+impl EventsConfig {
+    /// Returns every webhook events are delivered to, including the webhook receiving every event,
+    /// if one is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let webhooks = config::hotel().events.unwrap().webhooks();
+    /// ```
+    pub fn webhooks(&self) -> Vec<WebhookSubscription> {
+        let mut webhooks: Vec<WebhookSubscription> = Vec::new();
+        if !self.webhook_url.is_empty() {
+            webhooks.push(WebhookSubscription {
+                url: self.webhook_url.clone(),
+                topics: Vec::new(),
+                room_type_ids: Vec::new(),
+            });
+        }
+
+        webhooks.extend(self.subscriptions.iter().cloned());
+        return webhooks;
+    }
+}
+
+/// Defines how the deposit for a booking is charged
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Debug)]
+pub enum DepositCharge {
+    /// The deposit is a percentage of the total price of the booking
+    Percentage,
+    /// The deposit is a fixed amount, or the total price of the booking if it is less
+    Flat,
+}
+
+/// Describes the deposit which must be paid for bookings requiring one
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct DepositPolicy {
+    pub charge: DepositCharge,
+    /// The amount of the deposit. Percentage deposits are given in hundredths of a percent, e.g.
+    /// 2500 for 25%, and flat deposits in the minor unit of the hotel's currency.
+    pub amount: u64,
+}
+
+impl Default for DepositPolicy {
+    fn default() -> DepositPolicy {
+        return DepositPolicy {
+            charge: DepositCharge::Percentage,
+            amount: 10000,
+        };
+    }
+}
+
+impl DepositPolicy {
+    /// Returns the deposit due for a booking with the provided total price.
+    ///
+    /// # Arguments
+    ///
+    /// * `total` - The total price of the booking, in the minor unit of the hotel's currency
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let deposit = config::hotel().deposit.deposit_for(quote.total);
+    /// ```
+    pub fn deposit_for(&self, total: u64) -> u64 {
+        return match self.charge {
+            DepositCharge::Percentage => ((total * self.amount + 5000) / 10000).min(total),
+            DepositCharge::Flat => self.amount.min(total),
+        };
+    }
+}
+
+/// Describes the settings used to request dynamic nightly rates when quoting bookings
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct DynamicPricingConfig {
+    /// The name of the pricing strategy used, e.g. 'http'
+    pub strategy: String,
+    /// The URL bookings are posted to by the 'http' strategy. Only http URLs are supported.
+    pub url: Option<String>,
+}
+
+impl Default for DynamicPricingConfig {
+    fn default() -> DynamicPricingConfig {
+        return DynamicPricingConfig {
+            strategy: "http".to_string(),
+            url: None,
+        };
+    }
+}
+
+/// Describes the settings used to request exchange rates when quoting in another currency
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct ExchangeRatesConfig {
+    /// The name of the exchange rate source used, e.g. 'ecb' or 'openexchangerates'
+    pub source: String,
+    /// The URL rates are requested from, if not the source's default. Must use https for sources
+    /// which require an API key, as the key is sent with each request.
+    pub url: Option<String>,
+    /// The key used to authenticate with the source, if it requires one
+    pub api_key: String,
+    /// How long rates are cached for before they are requested again, in seconds
+    pub cache_seconds: u64,
+}
+
+impl Default for ExchangeRatesConfig {
+    fn default() -> ExchangeRatesConfig {
+        return ExchangeRatesConfig {
+            source: "ecb".to_string(),
+            url: None,
+            api_key: String::new(),
+            cache_seconds: 3600,
+        };
+    }
+}
+
+/// Describes the settings used to receive payment events from the payment provider's webhook
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct PaymentWebhookConfig {
+    /// The secret shared with the payment provider, used to sign the body of each webhook
+    pub secret: String,
+    /// The request header holding the hex encoded HMAC-SHA256 signature of the body
+    pub signature_header: String,
+    /// How often received events are applied to their booking, in seconds
+    pub process_interval_seconds: u64,
+}
+
+impl Default for PaymentWebhookConfig {
+    fn default() -> PaymentWebhookConfig {
+        return PaymentWebhookConfig {
+            secret: String::new(),
+            signature_header: "X-Signature".to_string(),
+            process_interval_seconds: 5,
+        };
+    }
+}
+
+/// Describes the settings used to request refunds from the payment provider
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct PaymentProviderConfig {
+    /// The URL refunds are posted to. Must use https, as the API key is sent with each refund.
+    pub refund_url: String,
+    /// The key sent as a bearer token to authenticate with the payment provider
+    pub api_key: String,
+}
+
+impl Default for PaymentProviderConfig {
+    fn default() -> PaymentProviderConfig {
+        return PaymentProviderConfig {
+            refund_url: String::new(),
+            api_key: String::new(),
+        };
+    }
+}
+
+/// Describes a secret fetched from the secret store, and the setting it is used for
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct SecretRef {
+    /// The setting the secret is used for, e.g. 'payment_webhooks.secret'
+    pub setting: String,
+    /// The path of the secret in Vault, e.g. 'secret/data/booking', or its id in AWS Secrets
+    /// Manager
+    pub path: String,
+    /// The key of the value within the secret, if the secret holds several values
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+/// Describes the settings used to fetch secrets, such as API keys, from a secret store, in place
+/// of the values in the configuration files
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct SecretsConfig {
+    /// The name of the secret store, e.g. 'vault' or 'aws'
+    pub store: String,
+    /// The URL of the secret store. Must use https for Vault, as the token is sent with each
+    /// request. The AWS Secrets Manager Agent only serves http, on the local host.
+    pub url: String,
+    /// The environment variable holding the token used to authenticate with the secret store, if
+    /// not the store's default
+    pub token_env: Option<String>,
+    /// How often secrets are fetched again, in seconds. Secrets are only fetched at startup if
+    /// this is 0
+    pub refresh_seconds: u64,
+    /// The secrets fetched, and the setting each is used for
+    pub secrets: Vec<SecretRef>,
+}
+
+impl Default for SecretsConfig {
+    fn default() -> SecretsConfig {
+        return SecretsConfig {
+            store: "vault".to_string(),
+            url: "https://127.0.0.1:8200".to_string(),
+            token_env: None,
+            refresh_seconds: 300,
+            secrets: Vec::new(),
+        };
+    }
+}
+
+/// Describes the settings of the retention policy, which removes old bookings so personal data is
+/// not kept indefinitely
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// The number of years after check out a booking is kept for
+    pub retention_years: u32,
+    /// Whether bookings are archived or purged once the retention period has passed
+    pub action: RetentionAction,
+    /// Whether the scheduled job only reports the bookings which would be removed
+    pub dry_run: bool,
+    /// How often the retention policy is applied, in hours
+    pub interval_hours: u64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> RetentionConfig {
+        return RetentionConfig {
+            retention_years: 7,
+            action: RetentionAction::Archive,
+            dry_run: false,
+            interval_hours: 24,
+        };
+    }
+}
+
+/// Describes the sandbox, in which integrators may make test bookings against the live service
+/// without affecting real bookings
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct SandboxConfig {
+    /// The number of hours after a sandbox booking is created it is removed
+    pub retention_hours: u64,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> SandboxConfig {
+        return SandboxConfig {
+            retention_hours: 24,
+        };
+    }
+}
+
+/// Describes the business settings of the hotel. Settings are read from the ```hotel``` section
+/// of ```Rocket.toml```, or ```ROCKET_HOTEL``` environment variables, and any setting not provided
+/// uses its default value.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct HotelConfig {
+    /// The IANA timezone of the hotel, e.g. Europe/London, used to determine the current date
+    pub timezone: Tz,
+    /// The time guests may check in from on their check in date
+    pub check_in_time: NaiveTime,
+    /// The time guests must check out by on their check out date
+    pub check_out_time: NaiveTime,
+    /// The latest time a late check out may be requested until
+    pub latest_check_out_time: NaiveTime,
+    /// The number of rooms of each room type. Room types which are not listed are not limited
+    pub inventory: Vec<RoomInventory>,
+    /// The most adults and children who may stay in a room of each room type. Room types which
+    /// are not listed are not limited
+    pub occupancy: Vec<OccupancyLimit>,
+    /// The number of rooms kept for day use bookings. Day use bookings are not accepted if this is 0
+    pub day_use_rooms: u32,
+    /// The longest stay that may be booked, in nights
+    pub max_stay_nights: u32,
+    /// The shortest stay, in nights, which is billed monthly as a long stay
+    pub long_stay_nights: u32,
+    /// The minimum and maximum stays which may be booked in each room type, optionally by season
+    pub stay_rules: Vec<StayRule>,
+    /// The rates charged for each room type
+    pub rates: Vec<RoomRate>,
+    /// The named rate plans charged instead of the nightly rates during their seasons
+    pub rate_plans: Vec<RatePlan>,
+    /// The add-ons which may be attached to a booking, such as breakfast or parking
+    pub addons: Vec<Addon>,
+    /// The taxes charged on bookings, such as VAT or a city tax
+    pub taxes: Vec<TaxRule>,
+    /// The travel agents which may make bookings, and the commission paid to each
+    pub agents: Vec<TravelAgent>,
+    /// The number of nights charged when a guest does not arrive for their booking
+    pub no_show_fee_nights: u32,
+    /// Whether new bookings require a deposit, in which case they are pending payment until the
+    /// payment is confirmed
+    pub deposit_required: bool,
+    /// The deposit which must be paid before a booking pending payment is confirmed
+    pub deposit: DepositPolicy,
+    /// The number of hours a booking may be pending payment for, before it is cancelled
+    pub payment_window_hours: u32,
+    /// The number of hours before check in after which a booking can no longer be cancelled free
+    /// of charge
+    pub cancellation_window_hours: u32,
+    /// The number of minutes after a booking is created during which another booking by the same
+    /// customer, for the same room type and overlapping dates, is rejected as a likely duplicate.
+    /// Duplicates are not detected if this is 0
+    pub duplicate_window_minutes: u32,
+    /// The rules new bookings are screened against for signs of abuse or fraud, and the action
+    /// taken when a booking matches each. Bookings are not screened if this is empty
+    pub screening_rules: Vec<ScreeningRuleConfig>,
+    /// The most items which may be sent in a single batch request, such as the stays checked for
+    /// availability, the bookings given a new status, or the rows of an imported file
+    pub max_batch_size: u32,
+    /// How long computed responses, such as the availability calendar and reports, are cached
+    /// for, in seconds. Cached responses are discarded whenever the stored data changes.
+    /// Responses are not cached if this is 0
+    pub response_cache_seconds: u64,
+    /// The ISO 4217 code of the currency prices are charged in
+    pub currency: String,
+    /// The tag of the language error messages are given in when a request does not ask for an
+    /// available language, e.g. 'fr'
+    pub language: String,
+    /// The ISO 3166 code of the country the hotel is in, e.g. 'GB', whose conventions are used to
+    /// format dates and amounts for guests who do not give a region of their own
+    pub region: String,
+    /// The channel manager to synchronize with, if any
+    pub channel_manager: Option<ChannelManagerConfig>,
+    /// The codes used for each room type by the property management system bookings are imported
+    /// from and exported to
+    pub pms_room_types: Vec<RoomTypeCode>,
+    /// The circuit breaker settings used for calls to outbound integrations
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// The retry and timeout policy used for calls to outbound integrations
+    pub retry: RetryConfig,
+    /// The webhook booking events are delivered to, if any
+    pub events: Option<EventsConfig>,
+    /// The retention policy applied to old bookings, if any
+    pub retention: Option<RetentionConfig>,
+    /// The dynamic pricing strategy used when quoting bookings, if any
+    pub dynamic_pricing: Option<DynamicPricingConfig>,
+    /// The source exchange rates are requested from when quoting in another currency, if any
+    pub exchange_rates: Option<ExchangeRatesConfig>,
+    /// The payment provider webhook settings, if payment events are received
+    pub payment_webhooks: Option<PaymentWebhookConfig>,
+    /// The payment provider refunds are requested from, if any
+    pub payment_provider: Option<PaymentProviderConfig>,
+    /// The secret store API keys and secrets are fetched from, if any
+    pub secrets: Option<SecretsConfig>,
+    /// The sandbox settings, if sandbox bookings are accepted
+    pub sandbox: Option<SandboxConfig>,
+}
+
+impl Default for HotelConfig {
+    fn default() -> HotelConfig {
+        return HotelConfig {
+            timezone: Tz::UTC,
+            check_in_time: NaiveTime::from_hms_opt(15, 0, 0).unwrap_or_default(),
+            check_out_time: NaiveTime::from_hms_opt(11, 0, 0).unwrap_or_default(),
+            latest_check_out_time: NaiveTime::from_hms_opt(14, 0, 0).unwrap_or_default(),
+            inventory: Vec::new(),
+            occupancy: Vec::new(),
+            day_use_rooms: 0,
+            max_stay_nights: 30,
+            long_stay_nights: 28,
+            stay_rules: Vec::new(),
+            rates: Vec::new(),
+            rate_plans: Vec::new(),
+            addons: addons::default_catalog(),
+            taxes: Vec::new(),
+            agents: Vec::new(),
+            no_show_fee_nights: 1,
+            deposit_required: false,
+            deposit: DepositPolicy::default(),
+            payment_window_hours: 48,
+            cancellation_window_hours: 24,
+            duplicate_window_minutes: 10,
+            screening_rules: Vec::new(),
+            max_batch_size: 500,
+            response_cache_seconds: 30,
+            currency: "GBP".to_string(),
+            language: "en".to_string(),
+            region: "GB".to_string(),
+            channel_manager: None,
+            pms_room_types: Vec::new(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            retry: RetryConfig::default(),
+            events: None,
+            retention: None,
+            dynamic_pricing: None,
+            exchange_rates: None,
+            payment_webhooks: None,
+            payment_provider: None,
+            secrets: None,
+            sandbox: None,
+        };
+    }
+}
+
+/// Describes which documentation UIs are served alongside the API. Settings are read from the
+/// ```docs``` section of ```Rocket.toml```, so each UI may be turned off for a profile, e.g. in
+/// production.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct DocsConfig {
+    /// Whether Swagger UI is served at ```/swagger-ui```
+    pub swagger_ui: bool,
+    /// Whether RapiDoc is served at ```/rapidoc```
+    pub rapidoc: bool,
+}
+
+impl Default for DocsConfig {
+    fn default() -> DocsConfig {
+        return DocsConfig {
+            swagger_ui: true,
+            rapidoc: true,
+        };
+    }
+}
+
+/// Loads the hotel, documentation, request log and certificate settings, and the feature flags,
+/// from the Rocket configuration sources.
+///
+/// # Examples
+///
+/// ```
+/// config::load()?;
+/// ```
+pub fn load() -> Result<(), Box<dyn std::error::Error>> {
+    let figment = rocket::Config::figment();
+    let hotel: HotelConfig = match figment.extract_inner(HOTEL_SECTION) {
+        Ok(hotel) => hotel,
+        Err(err) if err.missing() => HotelConfig::default(),
+        Err(err) => return Err(Box::new(err)),
+    };
+    let docs: DocsConfig = match figment.extract_inner(DOCS_SECTION) {
+        Ok(docs) => docs,
+        Err(err) if err.missing() => DocsConfig::default(),
+        Err(err) => return Err(Box::new(err)),
+    };
+    let features: FeatureFlags = match figment.extract_inner(FEATURES_SECTION) {
+        Ok(features) => features,
+        Err(err) if err.missing() => FeatureFlags::default(),
+        Err(err) => return Err(Box::new(err)),
+    };
+    let request_log: RequestLogConfig = match figment.extract_inner(REQUEST_LOG_SECTION) {
+        Ok(request_log) => request_log,
+        Err(err) if err.missing() => RequestLogConfig::default(),
+        Err(err) => return Err(Box::new(err)),
+    };
+    let certificates: Option<CertificatesConfig> = match figment.extract_inner(CERTIFICATES_SECTION)
+    {
+        Ok(certificates) => Some(certificates),
+        Err(err) if err.missing() => None,
+        Err(err) => return Err(Box::new(err)),
+    };
+
+    if let Ok(mut config) = HOTEL_CONFIG.write() {
+        *config = hotel;
+    }
+
+    if let Ok(mut config) = DOCS_CONFIG.write() {
+        *config = docs;
+    }
+
+    if let Ok(mut config) = FEATURES_CONFIG.write() {
+        *config = features;
+    }
+
+    if let Ok(mut config) = REQUEST_LOG_CONFIG.write() {
+        *config = request_log;
+    }
+
+    if let Ok(mut config) = CERTIFICATES_CONFIG.write() {
+        *config = certificates;
+    }
+
+    return Ok(());
+}
+
+/// Returns the room inventory of a room type, or None if the room type is not limited.
+///
+/// # Arguments
+///
+/// * `room_type_id` - The id of the room type
+///
+/// # Examples
+///
+/// ```
+/// let inventory = config::inventory_for(1);
+/// ```
+pub fn inventory_for(room_type_id: u8) -> Option<RoomInventory> {
+    return hotel()
+        .inventory
+        .into_iter()
+        .find(|inventory| inventory.room_type_id == room_type_id);
+}
+
+/// Returns the occupancy limit of a room type, or None if the room type is not limited.
+///
+/// # Arguments
+///
+/// * `room_type_id` - The id of the room type
+///
+/// # Examples
+///
+/// ```
+/// let limit = config::occupancy_for(1);
+/// ```
+pub fn occupancy_for(room_type_id: u8) -> Option<OccupancyLimit> {
+    return hotel()
+        .occupancy
+        .into_iter()
+        .find(|limit| limit.room_type_id == room_type_id);
+}
+
+/// Returns the id of the room type mapped to an external system's room code, if any.
+///
+/// # Arguments
+///
+/// * `room_types` - The room type mapping of the external system
+/// * `code` - The external system's code for the room type
+///
+/// # Examples
+///
+/// ```
+/// let room_type_id = config::room_type_for_code(&config::hotel().pms_room_types, "DBL");
+/// ```
+pub fn room_type_for_code(room_types: &[RoomTypeCode], code: &str) -> Option<u8> {
+    return room_types
+        .iter()
+        .find(|room_type| room_type.code == code)
+        .map(|room_type| room_type.room_type_id);
+}
+
+/// Returns the code an external system uses for a room type, if the room type is mapped.
+///
+/// # Arguments
+///
+/// * `room_types` - The room type mapping of the external system
+/// * `room_type_id` - The id of the room type
+///
+/// # Examples
+///
+/// ```
+/// let code = config::code_for_room_type(&config::hotel().pms_room_types, 1);
+/// ```
+pub fn code_for_room_type(room_types: &[RoomTypeCode], room_type_id: u8) -> Option<String> {
+    return room_types
+        .iter()
+        .find(|room_type| room_type.room_type_id == room_type_id)
+        .map(|room_type| room_type.code.clone());
+}
+
+/// Returns a copy of the hotel settings currently in use.
+///
+/// # Examples
+///
+/// ```
+/// let max_stay = config::hotel().max_stay_nights;
+/// ```
+pub fn hotel() -> HotelConfig {
+    return match HOTEL_CONFIG.read() {
+        Ok(config) => config.clone(),
+        Err(_) => HotelConfig::default(),
+    };
+}
+
+/// Replaces a secret setting, such as an API key, with a value fetched from the secret store.
+/// Returns an error if the setting is not a secret, or belongs to an integration which is not
+/// configured.
+///
+/// # Arguments
+///
+/// * `setting` - The setting replaced, e.g. 'payment_webhooks.secret'
+/// * `value` - The value of the secret
+///
+/// # Examples
+///
+/// ```
+/// config::set_secret("payment_provider.api_key", key)?;
+/// ```
+pub fn set_secret(setting: &str, value: String) -> Result<(), String> {
+    let mut config = HOTEL_CONFIG
+        .write()
+        .map_err(|_| "Hotel settings are unavailable".to_string())?;
+
+    return match secret_field(&mut config, setting)? {
+        Some(field) => {
+            *field = value;
+            Ok(())
+        }
+        None => Err(format!(
+            "{} belongs to an integration which is not configured",
+            setting
+        )),
+    };
+}
+
+/// Returns the field holding a secret setting, or None if the setting belongs to an integration
+/// which is not configured. Returns an error if the setting is not a secret.
+///
+/// # Arguments
+///
+/// * `config` - The hotel settings holding the secret
+/// * `setting` - The secret setting, e.g. 'payment_webhooks.secret'
+fn secret_field<'a>(
+    config: &'a mut HotelConfig,
+    setting: &str,
+) -> Result<Option<&'a mut String>, String> {
+    return match setting {
+        "channel_manager.api_key" => Ok(config
+            .channel_manager
+            .as_mut()
+            .map(|settings| &mut settings.api_key)),
+        "exchange_rates.api_key" => Ok(config
+            .exchange_rates
+            .as_mut()
+            .map(|settings| &mut settings.api_key)),
+        "payment_webhooks.secret" => Ok(config
+            .payment_webhooks
+            .as_mut()
+            .map(|settings| &mut settings.secret)),
+        "payment_provider.api_key" => Ok(config
+            .payment_provider
+            .as_mut()
+            .map(|settings| &mut settings.api_key)),
+        _ => Err(format!("{} is not a secret setting", setting)),
+    };
+}
+
+/// Copies the secrets fetched from the secret store into reloaded hotel settings, in place of the
+/// values read from the configuration files. Secrets of integrations no longer configured, or no
+/// longer fetched from the secret store, are not copied.
+///
+/// # Arguments
+///
+/// * `current` - The hotel settings in use, holding the fetched secrets
+/// * `reloaded` - The hotel settings read from the configuration sources
+fn keep_secrets(current: &mut HotelConfig, reloaded: &mut HotelConfig) {
+    let settings: Vec<String> = match &reloaded.secrets {
+        Some(secrets) => secrets
+            .secrets
+            .iter()
+            .map(|secret| secret.setting.clone())
+            .collect(),
+        None => Vec::new(),
+    };
+
+    for setting in settings {
+        let value: Option<String> = match secret_field(current, &setting) {
+            Ok(field) => field.cloned(),
+            Err(_) => None,
+        };
+
+        if let (Some(value), Ok(Some(field))) = (value, secret_field(reloaded, &setting)) {
+            *field = value;
+        }
+    }
+}
+
+/// Reloads the hotel settings and feature flags from the Rocket configuration sources, so changes
+/// such as rates, stay rules or feature flags apply without a restart. The settings in use are
+/// kept if either section cannot be read. Secrets fetched from the secret store are kept, rather
+/// than replaced by the values in the configuration files, until they are next refreshed.
+///
+/// The documentation, request log and certificate settings are not reloaded, as they are only
+/// applied when the server launches, as are settings read by background tasks when they start,
+/// such as how often they run.
+///
+/// # Examples
+///
+/// ```
+/// config::reload()?;
+/// ```
+pub fn reload() -> Result<(), Box<dyn std::error::Error>> {
+    let figment = rocket::Config::figment();
+    let mut hotel: HotelConfig = match figment.extract_inner(HOTEL_SECTION) {
+        Ok(hotel) => hotel,
+        Err(err) if err.missing() => HotelConfig::default(),
+        Err(err) => return Err(Box::new(err)),
+    };
+    let features: FeatureFlags = match figment.extract_inner(FEATURES_SECTION) {
+        Ok(features) => features,
+        Err(err) if err.missing() => FeatureFlags::default(),
+        Err(err) => return Err(Box::new(err)),
+    };
+
+    let mut config = HOTEL_CONFIG
+        .write()
+        .map_err(|_| "Hotel settings are unavailable".to_string())?;
+
+    keep_secrets(&mut config, &mut hotel);
+    *config = hotel;
+    drop(config);
+
+    if let Ok(mut config) = FEATURES_CONFIG.write() {
+        *config = features;
+    }
+
+    return Ok(());
+}
+
+/// Returns a copy of the documentation settings currently in use.
+///
+/// # Examples
+///
+/// ```
+/// let serve_rapidoc = config::docs().rapidoc;
+/// ```
+pub fn docs() -> DocsConfig {
+    return match DOCS_CONFIG.read() {
+        Ok(config) => config.clone(),
+        Err(_) => DocsConfig::default(),
+    };
+}
+
+/// Returns a copy of the request log settings currently in use.
+///
+/// # Examples
+///
+/// ```
+/// let log_requests = config::request_log().enabled;
+/// ```
+pub fn request_log() -> RequestLogConfig {
+    return match REQUEST_LOG_CONFIG.read() {
+        Ok(config) => config.clone(),
+        Err(_) => RequestLogConfig::default(),
+    };
+}
+
+/// Returns a copy of the feature flags currently in use.
+///
+/// # Examples
+///
+/// ```
+/// let overbooking = config::features().overbooking;
+/// ```
+pub fn features() -> FeatureFlags {
+    return match FEATURES_CONFIG.read() {
+        Ok(config) => config.clone(),
+        Err(_) => FeatureFlags::default(),
+    };
+}
+
+/// Returns a copy of the certificate settings currently in use, or None if the listener is not
+/// configured to serve HTTPS using them.
+///
+/// # Examples
+///
+/// ```
+/// let settings = config::certificates();
+/// ```
+pub fn certificates() -> Option<CertificatesConfig> {
+    return match CERTIFICATES_CONFIG.read() {
+        Ok(config) => config.clone(),
+        Err(_) => None,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::*;
+    use crate::storage::booking_change::{BookingChange, ChangeType};
+    use rocket::figment::providers::{Format, Toml};
+    use rocket::figment::Figment;
+
+    #[test]
+    fn read_hotel_config() {
+        let figment = Figment::new().merge(Toml::string(
+            "[hotel]\ntimezone = \"Europe/Paris\"\ncheck_in_time = \"14:00:00\"\nmax_stay_nights = 14\n",
+        ));
+        let hotel: HotelConfig = figment.extract_inner("hotel").unwrap();
+
+        assert_eq!(
+            hotel.check_in_time,
+            NaiveTime::from_hms_opt(14, 0, 0).unwrap()
+        );
+        assert_eq!(hotel.max_stay_nights, 14);
+        assert_eq!(hotel.timezone, Tz::Europe__Paris);
+        assert_eq!(hotel.currency, HotelConfig::default().currency);
+    }
+
+    #[test]
+    fn match_webhook_subscription() {
+        let mut event = OutboxEvent::new(
+            BookingChange {
+                sequence: 1,
+                recorded_at: String::new(),
+                change_type: ChangeType::Cancelled,
+                booking: rocket::serde::json::from_str(
+                    r#"{"customerId":1,"roomTypeId":2,"checkInDate":"2030-01-01","checkOutDate":"2030-01-02"}"#,
+                )
+                .unwrap(),
+            },
+            EventTopic::Cancelled,
+        );
+        let mut subscription = WebhookSubscription {
+            url: "http://events.example.com".to_string(),
+            topics: Vec::new(),
+            room_type_ids: Vec::new(),
+        };
+        assert!(subscription.matches(&event));
+
+        subscription.topics = vec![EventTopic::Created, EventTopic::Payment];
+        assert!(!subscription.matches(&event));
+
+        event.topic = EventTopic::Payment;
+        assert!(subscription.matches(&event));
+
+        subscription.room_type_ids = vec![1];
+        assert!(!subscription.matches(&event));
+    }
+
+    #[test]
+    fn read_docs_config() {
+        let figment = Figment::new().merge(Toml::string("[docs]\nswagger_ui = false\n"));
+        let docs: DocsConfig = figment.extract_inner("docs").unwrap();
+
+        assert!(!docs.swagger_ui);
+        assert!(docs.rapidoc);
+    }
+
+    #[test]
+    fn sellable_rooms() {
+        let mut inventory = RoomInventory {
+            room_type_id: 1,
+            rooms: 20,
+            overbooking_percent: 0,
+        };
+        assert_eq!(inventory.sellable(), 20);
+
+        inventory.overbooking_percent = 7;
+        assert_eq!(inventory.sellable(), 21);
+    }
+
+    #[test]
+    fn deposit_due() {
+        let mut policy = DepositPolicy {
+            charge: DepositCharge::Percentage,
+            amount: 2500,
+        };
+        assert_eq!(policy.deposit_for(10000), 2500);
+
+        policy.charge = DepositCharge::Flat;
+        assert_eq!(policy.deposit_for(10000), 2500);
+        assert_eq!(policy.deposit_for(1000), 1000);
+    }
+
+    #[test]
+    fn reject_unknown_secret() {
+        assert_eq!(
+            set_secret("currency", "USD".to_string()),
+            Err("currency is not a secret setting".to_string())
+        );
+        assert_eq!(
+            set_secret("payment_webhooks.secret", "secret".to_string()),
+            Err(
+                "payment_webhooks.secret belongs to an integration which is not configured"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn keep_fetched_secrets() {
+        let figment = Figment::new().merge(Toml::string(
+            "[hotel.payment_provider]\napi_key = \"file\"\n\
+             [hotel.exchange_rates]\napi_key = \"file\"\n\
+             [hotel.secrets]\nsecrets = [{ setting = \"payment_provider.api_key\", path = \"booking\" }]\n",
+        ));
+        let mut current: HotelConfig = figment.extract_inner("hotel").unwrap();
+        current.payment_provider.as_mut().unwrap().api_key = "fetched".to_string();
+        current.exchange_rates.as_mut().unwrap().api_key = "fetched".to_string();
+
+        let mut reloaded: HotelConfig = figment.extract_inner("hotel").unwrap();
+        reloaded.max_stay_nights = 14;
+        keep_secrets(&mut current, &mut reloaded);
+
+        assert_eq!(reloaded.max_stay_nights, 14);
+        assert_eq!(reloaded.payment_provider.unwrap().api_key, "fetched");
+        assert_eq!(reloaded.exchange_rates.unwrap().api_key, "file");
+    }
+}
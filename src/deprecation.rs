@@ -0,0 +1,120 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::clock;
+use crate::redaction;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::{Request, Response};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Describes a route which is due to be removed
+pub struct DeprecatedRoute {
+    /// The name of the route handler
+    pub name: &'static str,
+    /// The date the route was deprecated, as an HTTP date
+    pub deprecated_since: &'static str,
+    /// The date the route will be removed, as an HTTP date
+    pub sunset: &'static str,
+    /// The path of the route which replaces the deprecated route
+    pub successor: &'static str,
+}
+
+/// The list of deprecated routes. Routes listed here should also be marked as deprecated in their
+/// OpenAPI attribute, so they are flagged in the documentation.
+pub static DEPRECATED_ROUTES: &[DeprecatedRoute] = &[DeprecatedRoute {
+    name: "get_bookings_starting_on_date",
+    deprecated_since: "Thu, 15 Oct 2026 00:00:00 GMT",
+    sunset: "Thu, 15 Apr 2027 00:00:00 GMT",
+    successor: "/v1/bookings",
+}];
+
+impl DeprecatedRoute {
+    /// Returns the value of the ```Deprecation``` header for the route, as defined in RFC 9745.
+    fn deprecation_header(&self) -> String {
+        let since: u64 = httpdate::parse_http_date(self.deprecated_since)
+            .unwrap_or(UNIX_EPOCH)
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+
+        return format!("@{}", since);
+    }
+
+    /// Checks whether the route has passed its sunset date.
+    fn is_sunset(&self) -> bool {
+        return match httpdate::parse_http_date(self.sunset) {
+            Ok(sunset) => sunset <= SystemTime::from(clock::now()),
+            Err(_) => false,
+        };
+    }
+}
+
+/// A fairing which adds ```Deprecation```, ```Sunset``` and successor ```Link``` headers to
+/// responses from deprecated routes, and logs the client calling them.
+pub struct Deprecations;
+
+#[rocket::async_trait]
+impl Fairing for Deprecations {
+    fn info(&self) -> Info {
+        return Info {
+            name: "Route deprecation",
+            kind: Kind::Response,
+        };
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let name = match request.route().and_then(|route| route.name.as_ref()) {
+            Some(name) => name,
+            None => return,
+        };
+
+        let deprecated: &DeprecatedRoute =
+            match DEPRECATED_ROUTES.iter().find(|route| route.name == name) {
+                Some(deprecated) => deprecated,
+                None => return,
+            };
+
+        response.set_header(Header::new("Deprecation", deprecated.deprecation_header()));
+        response.set_header(Header::new("Sunset", deprecated.sunset));
+        response.adjoin_header(Header::new(
+            "Link",
+            format!("<{}>; rel=\"successor-version\"", deprecated.successor),
+        ));
+
+        redaction::info(&format!(
+            "Deprecated route {} called by {} ({}){}",
+            request.uri(),
+            request
+                .client_ip()
+                .map_or("unknown client".to_string(), |ip| ip.to_string()),
+            request
+                .headers()
+                .get_one("User-Agent")
+                .unwrap_or("no user agent"),
+            match deprecated.is_sunset() {
+                true => ", after its sunset date",
+                false => "",
+            }
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deprecation::*;
+
+    #[test]
+    fn deprecated_route_headers() {
+        let route = DeprecatedRoute {
+            name: "test",
+            deprecated_since: "Thu, 01 Jan 1970 00:01:40 GMT",
+            sunset: "Thu, 01 Jan 1970 00:03:20 GMT",
+            successor: "/v1/test",
+        };
+
+        assert_eq!(route.deprecation_header(), "@100");
+        assert!(route.is_sunset());
+    }
+}
@@ -0,0 +1,113 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use self::http::HttpPricing;
+use crate::circuit_breaker::CallError;
+use crate::config::{self, DynamicPricingConfig};
+use crate::features::{self, Feature};
+use crate::redaction;
+use crate::storage::room_booking::{BookingRules, RoomBooking};
+use std::collections::HashMap;
+pub mod http;
+
+/// Provides the nightly rates charged when quoting a booking, e.g. from a revenue management
+/// system, instead of the configured rates and rate plans. Custom strategies are added to the
+/// list returned by ```strategies```, and selected by name in the dynamic pricing settings.
+#[rocket::async_trait]
+pub trait PricingStrategy {
+    /// Returns the name used to select the strategy, e.g. 'http'.
+    fn name(&self) -> &'static str;
+
+    /// Returns the rate charged for each night of a booking, keyed by date (YYYY-MM-DD), in the
+    /// minor unit of the hotel's currency. Nights which are not included are charged the
+    /// configured rates. Returns an error if no rates are available, in which case every night is
+    /// charged the configured rates.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The dynamic pricing settings
+    /// * `booking` - The booking being quoted
+    async fn nightly_rates(
+        &self,
+        settings: &DynamicPricingConfig,
+        booking: &RoomBooking,
+    ) -> Result<HashMap<String, u64>, CallError>;
+}
+
+/// Returns every available pricing strategy.
+///
+/// # Examples
+///
+/// ```
+/// let strategies = dynamic_pricing::strategies();
+/// ```
+pub fn strategies() -> Vec<Box<dyn PricingStrategy + Send + Sync>> {
+    return vec![Box::new(HttpPricing)];
+}
+
+/// Returns the pricing strategy with the provided name, if any.
+///
+/// # Arguments
+///
+/// * `name` - The name of the strategy
+///
+/// # Examples
+///
+/// ```
+/// let strategy = dynamic_pricing::find("http");
+/// ```
+pub fn find(name: &str) -> Option<Box<dyn PricingStrategy + Send + Sync>> {
+    return strategies()
+        .into_iter()
+        .find(|strategy| strategy.name() == name);
+}
+
+/// Returns the dynamic rates for each night of a booking from the configured pricing strategy,
+/// keyed by date. Returns an empty list if no strategy is configured, dynamic pricing is turned off
+/// by its feature flag, or the strategy is unavailable, so the booking is charged the configured
+/// rates.
+///
+/// # Arguments
+///
+/// * `booking` - The booking being quoted
+///
+/// # Examples
+///
+/// ```
+/// let dynamic_rates = dynamic_pricing::nightly_rates(&booking).await;
+/// ```
+pub async fn nightly_rates(booking: &RoomBooking) -> HashMap<String, u64> {
+    let settings: DynamicPricingConfig = match config::hotel().dynamic_pricing {
+        Some(settings) => settings,
+        None => return HashMap::new(),
+    };
+
+    if !features::enabled(Feature::DynamicPricing) || booking.day_use || booking.nights().is_empty()
+    {
+        return HashMap::new();
+    }
+
+    let strategy = match find(&settings.strategy) {
+        Some(strategy) => strategy,
+        None => {
+            redaction::error(&format!(
+                "Unknown dynamic pricing strategy '{}'",
+                settings.strategy
+            ));
+            return HashMap::new();
+        }
+    };
+
+    return match strategy.nightly_rates(&settings, booking).await {
+        Ok(rates) => rates,
+        Err(error) => {
+            redaction::error(&format!(
+                "Dynamic pricing is unavailable, using the configured rates: {}",
+                error
+            ));
+            HashMap::new()
+        }
+    };
+}
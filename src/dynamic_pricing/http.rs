@@ -0,0 +1,78 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::PricingStrategy;
+use crate::circuit_breaker::CallError;
+use crate::config::DynamicPricingConfig;
+use crate::retry;
+use crate::storage::room_booking::RoomBooking;
+use hyper::body::{self, Body};
+use hyper::{Client, Method, Request};
+use rocket::serde::json;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Describes the rate charged for a night, as returned by a dynamic pricing service
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DynamicRate {
+    /// The night (YYYY-MM-DD)
+    pub date: String,
+    /// The rate charged for the night, in the minor unit of the hotel's currency
+    pub rate: u64,
+}
+
+/// Requests nightly rates from an external dynamic pricing service, by posting the booking being
+/// quoted to the configured URL. The service responds with a JSON array of ```DynamicRate```.
+/// Calls use the outbound retry policy and circuit breaker.
+pub struct HttpPricing;
+
+#[rocket::async_trait]
+impl PricingStrategy for HttpPricing {
+    fn name(&self) -> &'static str {
+        return "http";
+    }
+
+    async fn nightly_rates(
+        &self,
+        settings: &DynamicPricingConfig,
+        booking: &RoomBooking,
+    ) -> Result<HashMap<String, u64>, CallError> {
+        let url: &str = match &settings.url {
+            Some(url) => url,
+            None => return Err("No dynamic pricing URL is configured".into()),
+        };
+        let body: String = json::to_string(booking)?;
+
+        let content: Vec<u8> =
+            retry::call("dynamic_pricing", || request(url, body.clone())).await?;
+        let rates: Vec<DynamicRate> = json::from_slice(&content)?;
+
+        return Ok(rates
+            .into_iter()
+            .map(|rate| (rate.date, rate.rate))
+            .collect());
+    }
+}
+
+/// Posts a booking to the dynamic pricing service, returning the response body. Returns an error
+/// if the request fails, or the response status is not successful.
+async fn request(url: &str, body: String) -> Result<Vec<u8>, CallError> {
+    let request: Request<Body> = Request::builder()
+        .method(Method::POST)
+        .uri(url)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body))?;
+
+    let response = Client::new().request(request).await?;
+    let status = response.status();
+    let content = body::to_bytes(response.into_body()).await?;
+
+    if !status.is_success() {
+        return Err(format!("The dynamic pricing service responded with {}", status).into());
+    }
+
+    return Ok(content.to_vec());
+}
@@ -0,0 +1,285 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use self::ecb::Ecb;
+use self::open_exchange_rates::OpenExchangeRates;
+use crate::circuit_breaker::CallError;
+use crate::config::{self, ExchangeRatesConfig};
+use crate::http_client;
+use crate::redaction;
+use crate::retry;
+use crate::storage;
+use crate::storage::exchange_rate::{ExchangeRate, ExchangeRateOverride};
+use hyper::body::{self, Body};
+use hyper::{Method, Request};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+pub mod ecb;
+pub mod open_exchange_rates;
+
+/// Describes the exchange rates published by a source, against the source's base currency
+#[derive(Clone, PartialEq, Debug)]
+pub struct RateTable {
+    /// The ISO 4217 code of the currency rates are given against, e.g. 'EUR'
+    pub base: String,
+    /// The number of units of each currency equal to one unit of the base currency, keyed by
+    /// ISO 4217 code
+    pub rates: HashMap<String, f64>,
+}
+
+impl RateTable {
+    /// Returns the number of units of one currency equal to one unit of another, crossing the
+    /// rates of both currencies against the base currency. Returns None if either currency has no
+    /// rate.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The ISO 4217 code of the currency converted from
+    /// * `to` - The ISO 4217 code of the currency converted to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let rate = table.cross("GBP", "USD");
+    /// ```
+    pub fn cross(&self, from: &str, to: &str) -> Option<f64> {
+        let rate_for = |currency: &str| -> Option<f64> {
+            return match currency == self.base {
+                true => Some(1.0),
+                false => self.rates.get(currency).copied(),
+            };
+        };
+
+        return Some(rate_for(to)? / rate_for(from)?);
+    }
+}
+
+/// Provides the exchange rates used when quoting in a currency other than the hotel's, e.g. from
+/// a central bank. Custom sources are added to the list returned by ```sources```, and selected by
+/// name in the exchange rate settings.
+#[rocket::async_trait]
+pub trait RateSource {
+    /// Returns the name used to select the source, e.g. 'ecb'.
+    fn name(&self) -> &'static str;
+
+    /// Returns the latest exchange rates published by the source.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The exchange rate settings
+    async fn rates(&self, settings: &ExchangeRatesConfig) -> Result<RateTable, CallError>;
+}
+
+/// The rates last requested from an exchange rate source.
+#[derive(Clone)]
+struct CachedRates {
+    source: String,
+    requested_at: Instant,
+    table: RateTable,
+}
+
+/// A lazily initialised cache of the rates last requested from the exchange rate source.
+static CACHE: Lazy<Mutex<Option<CachedRates>>> = Lazy::new(|| Mutex::new(None));
+
+/// Returns every available exchange rate source.
+///
+/// # Examples
+///
+/// ```
+/// let sources = exchange_rates::sources();
+/// ```
+pub fn sources() -> Vec<Box<dyn RateSource + Send + Sync>> {
+    return vec![Box::new(Ecb), Box::new(OpenExchangeRates)];
+}
+
+/// Returns the exchange rate source with the provided name, if any.
+///
+/// # Arguments
+///
+/// * `name` - The name of the source
+///
+/// # Examples
+///
+/// ```
+/// let source = exchange_rates::find("ecb");
+/// ```
+pub fn find(name: &str) -> Option<Box<dyn RateSource + Send + Sync>> {
+    return sources().into_iter().find(|source| source.name() == name);
+}
+
+/// Returns the rate used to convert prices from the hotel's currency into another currency. A
+/// rate set manually is used if there is one, and otherwise the rate from the configured source.
+/// Returns an error if the currency has no rate, or the source is unavailable and has no cached
+/// rates.
+///
+/// # Arguments
+///
+/// * `currency` - The ISO 4217 code of the currency
+///
+/// # Examples
+///
+/// ```
+/// let rate = exchange_rates::rate("EUR").await?;
+/// ```
+pub async fn rate(currency: &str) -> Result<ExchangeRate, String> {
+    let base: String = config::hotel().currency;
+    let no_rate: String = format!("No exchange rate is available for {}", currency);
+
+    if let Some(rate) = storage::fetch_exchange_rate(currency) {
+        return Ok(from_override(base, rate));
+    }
+
+    let settings: ExchangeRatesConfig = match config::hotel().exchange_rates {
+        Some(settings) => settings,
+        None => return Err(no_rate),
+    };
+
+    let table: RateTable = table(&settings).await?;
+    return match table.cross(&base, currency) {
+        Some(rate) => Ok(ExchangeRate {
+            base,
+            currency: currency.to_string(),
+            rate,
+            source: settings.source,
+        }),
+        None => Err(no_rate),
+    };
+}
+
+/// Returns the number of digits after the decimal point in a currency's minor unit, e.g. 2 for
+/// pence and 0 for yen.
+///
+/// # Arguments
+///
+/// * `currency` - The ISO 4217 code of the currency
+///
+/// # Examples
+///
+/// ```
+/// let digits = exchange_rates::minor_unit_digits("JPY");
+/// ```
+pub fn minor_unit_digits(currency: &str) -> i32 {
+    return match currency {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF" | "UGX"
+        | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        _ => 2,
+    };
+}
+
+/// Requests a URL from an exchange rate source using the outbound retry policy, returning the
+/// response body.
+///
+/// # Arguments
+///
+/// * `url` - The URL to request
+///
+/// # Examples
+///
+/// ```
+/// let content = exchange_rates::get(&url).await?;
+/// ```
+pub async fn get(url: &str) -> Result<Vec<u8>, CallError> {
+    return retry::call("exchange_rates", || request(url)).await;
+}
+
+/// Requests a URL from an exchange rate source, returning the response body. Returns an error if
+/// the request fails, or the response status is not successful.
+async fn request(url: &str) -> Result<Vec<u8>, CallError> {
+    let request: Request<Body> = Request::builder()
+        .method(Method::GET)
+        .uri(url)
+        .body(Body::empty())?;
+
+    let response = http_client::client().request(request).await?;
+    let status = response.status();
+    let content = body::to_bytes(response.into_body()).await?;
+
+    if !status.is_success() {
+        return Err(format!("The exchange rate source responded with {}", status).into());
+    }
+
+    return Ok(content.to_vec());
+}
+
+/// Returns the rates from the configured source, using the cached rates if they were requested
+/// within the cache period. If the source is unavailable, the cached rates are used however old
+/// they are.
+async fn table(settings: &ExchangeRatesConfig) -> Result<RateTable, String> {
+    let cached: Option<CachedRates> = CACHE
+        .lock()
+        .ok()
+        .and_then(|cache| cache.clone())
+        .filter(|cached| cached.source == settings.source);
+
+    if let Some(cached) = &cached {
+        if cached.requested_at.elapsed() < Duration::from_secs(settings.cache_seconds) {
+            return Ok(cached.table.clone());
+        }
+    }
+
+    let source = match find(&settings.source) {
+        Some(source) => source,
+        None => {
+            return Err(format!(
+                "Unknown exchange rate source '{}'",
+                settings.source
+            ))
+        }
+    };
+
+    return match source.rates(settings).await {
+        Ok(table) => {
+            if let Ok(mut cache) = CACHE.lock() {
+                *cache = Some(CachedRates {
+                    source: settings.source.clone(),
+                    requested_at: Instant::now(),
+                    table: table.clone(),
+                });
+            }
+            Ok(table)
+        }
+        Err(error) => match cached {
+            Some(cached) => {
+                redaction::error(&format!(
+                    "Exchange rates are unavailable, using the cached rates: {}",
+                    error
+                ));
+                Ok(cached.table)
+            }
+            None => Err(format!("Exchange rates are unavailable: {}", error)),
+        },
+    };
+}
+
+/// Creates the exchange rate for a rate set manually.
+fn from_override(base: String, rate: ExchangeRateOverride) -> ExchangeRate {
+    return ExchangeRate {
+        base,
+        currency: rate.currency,
+        rate: rate.rate,
+        source: "override".to_string(),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::exchange_rates::*;
+
+    #[test]
+    fn cross_rates() {
+        let table = RateTable {
+            base: "EUR".to_string(),
+            rates: HashMap::from([("GBP".to_string(), 0.8), ("USD".to_string(), 1.2)]),
+        };
+
+        assert_eq!(table.cross("EUR", "USD"), Some(1.2));
+        assert_eq!(table.cross("GBP", "EUR"), Some(1.25));
+        assert!((table.cross("GBP", "USD").unwrap() - 1.5).abs() < 1e-9);
+        assert_eq!(table.cross("GBP", "JPY"), None);
+    }
+}
@@ -0,0 +1,98 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::{RateSource, RateTable};
+use crate::circuit_breaker::CallError;
+use crate::config::ExchangeRatesConfig;
+use std::collections::HashMap;
+
+/// The URL the European Central Bank publishes its daily reference rates at.
+static DEFAULT_URL: &str = "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-daily.xml";
+
+/// Requests the daily euro foreign exchange reference rates published by the European Central
+/// Bank. Rates are given against the euro, and do not require an API key.
+pub struct Ecb;
+
+#[rocket::async_trait]
+impl RateSource for Ecb {
+    fn name(&self) -> &'static str {
+        return "ecb";
+    }
+
+    async fn rates(&self, settings: &ExchangeRatesConfig) -> Result<RateTable, CallError> {
+        let url: &str = settings.url.as_deref().unwrap_or(DEFAULT_URL);
+        let content: Vec<u8> = super::get(url).await?;
+
+        return parse(&String::from_utf8_lossy(&content));
+    }
+}
+
+/// Parses the reference rates from the XML published by the European Central Bank, where each
+/// rate is a ```Cube``` element with ```currency``` and ```rate``` attributes. Returns an error if
+/// no rates are found.
+///
+/// # Arguments
+///
+/// * `content` - The XML published by the European Central Bank
+///
+/// # Examples
+///
+/// ```
+/// let table = ecb::parse(&content)?;
+/// ```
+pub fn parse(content: &str) -> Result<RateTable, CallError> {
+    let rates: HashMap<String, f64> = content
+        .split("<Cube")
+        .filter_map(|element| {
+            let currency: &str = attribute(element, "currency")?;
+            let rate: f64 = attribute(element, "rate")?.parse().ok()?;
+            return Some((currency.to_string(), rate));
+        })
+        .collect();
+
+    if rates.is_empty() {
+        return Err("The European Central Bank published no exchange rates".into());
+    }
+
+    return Ok(RateTable {
+        base: "EUR".to_string(),
+        rates,
+    });
+}
+
+/// Returns the value of an attribute of an XML element, quoted with single or double quotes.
+fn attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let element: &str = element.split('>').next()?;
+
+    return ['\'', '"'].iter().find_map(|quote| {
+        let start: usize = element.find(&format!(" {}={}", name, quote))? + name.len() + 3;
+        let length: usize = element[start..].find(*quote)?;
+        return Some(&element[start..start + length]);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::exchange_rates::ecb::*;
+
+    #[test]
+    fn parse_reference_rates() {
+        let content = r#"<gesmes:Envelope>
+            <Cube>
+                <Cube time='2024-01-02'>
+                    <Cube currency='USD' rate='1.0956'/>
+                    <Cube currency="GBP" rate="0.86518"/>
+                </Cube>
+            </Cube>
+        </gesmes:Envelope>"#;
+
+        let table: RateTable = parse(content).unwrap();
+        assert_eq!(table.base, "EUR");
+        assert_eq!(table.rates.len(), 2);
+        assert_eq!(table.rates.get("USD"), Some(&1.0956));
+        assert_eq!(table.rates.get("GBP"), Some(&0.86518));
+        assert!(parse("<Cube time='2024-01-02'/>").is_err());
+    }
+}
@@ -0,0 +1,53 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::{RateSource, RateTable};
+use crate::circuit_breaker::CallError;
+use crate::config::ExchangeRatesConfig;
+use crate::http_client;
+use rocket::serde::json;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The URL Open Exchange Rates publishes its latest rates at.
+static DEFAULT_URL: &str = "https://openexchangerates.org/api/latest.json";
+
+/// Describes the latest rates, as returned by Open Exchange Rates
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct LatestRates {
+    /// The ISO 4217 code of the currency rates are given against
+    pub base: String,
+    /// The number of units of each currency equal to one unit of the base currency
+    pub rates: HashMap<String, f64>,
+}
+
+/// Requests the latest rates from Open Exchange Rates, authenticating with the configured API key
+/// as the app id. Rates are given against the account's base currency, usually US dollars.
+pub struct OpenExchangeRates;
+
+#[rocket::async_trait]
+impl RateSource for OpenExchangeRates {
+    fn name(&self) -> &'static str {
+        return "openexchangerates";
+    }
+
+    async fn rates(&self, settings: &ExchangeRatesConfig) -> Result<RateTable, CallError> {
+        if settings.api_key.is_empty() {
+            return Err("No Open Exchange Rates API key is configured".into());
+        }
+
+        let base_url: &str = settings.url.as_deref().unwrap_or(DEFAULT_URL);
+        http_client::require_https(base_url)?;
+
+        let url: String = format!("{}?app_id={}", base_url, settings.api_key);
+        let content: Vec<u8> = super::get(&url).await?;
+        let latest: LatestRates = json::from_slice(&content)?;
+
+        return Ok(RateTable {
+            base: latest.base,
+            rates: latest.rates,
+        });
+    }
+}
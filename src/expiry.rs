@@ -0,0 +1,50 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::clock;
+use crate::redaction;
+use crate::storage;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::tokio::time::{interval, Duration};
+use rocket::Rocket;
+
+/// How often bookings pending payment are checked for expiry.
+static SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A fairing which starts a background task when the server launches, cancelling bookings pending
+/// payment once their deposit is overdue.
+pub struct PaymentExpiry;
+
+#[rocket::async_trait]
+impl Fairing for PaymentExpiry {
+    fn info(&self) -> Info {
+        return Info {
+            name: "Payment expiry",
+            kind: Kind::Liftoff,
+        };
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<rocket::Orbit>) {
+        let shutdown = rocket.shutdown();
+        rocket::tokio::spawn(async move {
+            let mut sweep = interval(SWEEP_INTERVAL);
+
+            loop {
+                rocket::tokio::select! {
+                    _ = sweep.tick() => {}
+                    _ = shutdown.clone() => return,
+                }
+
+                let expired: usize = storage::expire_unpaid(clock::now());
+                if expired > 0 {
+                    redaction::info(&format!(
+                        "Cancelled {} bookings with overdue payments",
+                        expired
+                    ));
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,99 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::config;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Defines the behaviours which may be turned off with a feature flag, e.g. while a behaviour is
+/// rolled out to one property at a time
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    /// Selling rooms beyond a room type's inventory, up to its overbooking allowance
+    Overbooking,
+    /// Requesting nightly rates from the dynamic pricing strategy when quoting
+    DynamicPricing,
+    /// Screening new bookings against the abuse and fraud rules
+    Screening,
+}
+
+/// Describes which features are enabled. Settings are read from the ```features``` section of
+/// ```Rocket.toml```, or ```ROCKET_FEATURES``` environment variables. Every feature is enabled
+/// by default, though each also needs its own settings, e.g. an overbooking allowance, to have an
+/// effect.
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(default)]
+pub struct FeatureFlags {
+    pub overbooking: bool,
+    pub dynamic_pricing: bool,
+    pub screening: bool,
+}
+
+impl Default for FeatureFlags {
+    fn default() -> FeatureFlags {
+        return FeatureFlags {
+            overbooking: true,
+            dynamic_pricing: true,
+            screening: true,
+        };
+    }
+}
+
+impl FeatureFlags {
+    /// Checks whether a feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `feature` - The feature to check
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let overbooking = flags.enabled(Feature::Overbooking);
+    /// ```
+    pub fn enabled(&self, feature: Feature) -> bool {
+        return match feature {
+            Feature::Overbooking => self.overbooking,
+            Feature::DynamicPricing => self.dynamic_pricing,
+            Feature::Screening => self.screening,
+        };
+    }
+}
+
+/// Checks whether a feature is enabled by the feature flags currently in use.
+///
+/// # Arguments
+///
+/// * `feature` - The feature to check
+///
+/// # Examples
+///
+/// ```
+/// if features::enabled(Feature::DynamicPricing) {
+///     ...
+/// }
+/// ```
+pub fn enabled(feature: Feature) -> bool {
+    return config::features().enabled(feature);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::features::*;
+    use rocket::figment::providers::{Format, Toml};
+    use rocket::figment::Figment;
+
+    #[test]
+    fn read_feature_flags() {
+        let figment = Figment::new().merge(Toml::string("[features]\noverbooking = false\n"));
+        let flags: FeatureFlags = figment.extract_inner("features").unwrap();
+
+        assert!(!flags.enabled(Feature::Overbooking));
+        assert!(flags.enabled(Feature::DynamicPricing));
+        assert!(flags.enabled(Feature::Screening));
+    }
+}
@@ -0,0 +1,135 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use rocket::serde::json::{serde_json, to_value, Value};
+use rocket_okapi::okapi::schemars::gen::SchemaGenerator;
+use rocket_okapi::okapi::schemars::schema::Schema;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::ser::{Error, SerializeSeq};
+use serde::{Serialize, Serializer};
+
+/// A list of items which, when serialized, only includes the requested fields of each item.
+pub struct SparseList<T> {
+    items: Vec<T>,
+    fields: Option<Vec<String>>,
+}
+
+impl<T> SparseList<T> {
+    /// Creates a new sparse list.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The items to include in the list
+    /// * `fields` - A comma separated list of the (serialized) field names to include. All
+    ///   fields are included if not provided.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let list = SparseList::new(bookings, Some("bookingId,status"));
+    /// ```
+    pub fn new(items: Vec<T>, fields: Option<&str>) -> SparseList<T> {
+        return SparseList {
+            items,
+            fields: parse(fields),
+        };
+    }
+}
+
+/// Parses a comma separated list of (serialized) field names, ignoring empty names. Returns None
+/// if no list is provided, meaning all fields are included.
+///
+/// # Arguments
+///
+/// * `fields` - The comma separated list of field names
+///
+/// # Examples
+///
+/// ```
+/// let fields = fields::parse(Some("bookingId,status"));
+/// ```
+pub fn parse(fields: Option<&str>) -> Option<Vec<String>> {
+    return fields.map(|fields| {
+        fields
+            .split(',')
+            .map(|field| field.trim().to_string())
+            .filter(|field| !field.is_empty())
+            .collect()
+    });
+}
+
+/// Converts an item to JSON, only including the provided fields. Items which do not serialize to
+/// a JSON object are returned in full.
+///
+/// # Arguments
+///
+/// * `item` - The item to convert
+/// * `fields` - The (serialized) field names to include
+///
+/// # Examples
+///
+/// ```
+/// let value = fields::select(&booking, &fields)?;
+/// ```
+pub fn select<T: Serialize>(item: &T, fields: &[String]) -> Result<Value, serde_json::Error> {
+    let mut value: Value = to_value(item)?;
+    if let Value::Object(map) = &mut value {
+        map.retain(|key, _| fields.contains(key));
+    }
+
+    return Ok(value);
+}
+
+impl<T: Serialize> Serialize for SparseList<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let fields: &Vec<String> = match &self.fields {
+            Some(fields) => fields,
+            None => return self.items.serialize(serializer),
+        };
+
+        let mut seq = serializer.serialize_seq(Some(self.items.len()))?;
+        for item in &self.items {
+            let value: Value = select(item, fields).map_err(S::Error::custom)?;
+            seq.serialize_element(&value)?;
+        }
+
+        return seq.end();
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for SparseList<T> {
+    fn is_referenceable() -> bool {
+        return Vec::<T>::is_referenceable();
+    }
+
+    fn schema_name() -> String {
+        return Vec::<T>::schema_name();
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        return Vec::<T>::json_schema(gen);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fields::*;
+    use rocket::serde::json::json;
+
+    #[test]
+    fn select_fields() {
+        let items: Vec<Value> =
+            vec![json!({"bookingId": 1, "status": "Confirmed", "roomTypeId": 3})];
+
+        let list = SparseList::new(items.clone(), Some("bookingId, status"));
+        assert_eq!(
+            to_value(&list).unwrap(),
+            json!([{"bookingId": 1, "status": "Confirmed"}])
+        );
+
+        let list = SparseList::new(items.clone(), None);
+        assert_eq!(to_value(&list).unwrap(), Value::Array(items));
+    }
+}
@@ -0,0 +1,66 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+//! The HTTP client used for calls to other services, connecting to https URLs over TLS.
+
+use crate::circuit_breaker::CallError;
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Uri};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+
+/// Returns a client which connects to https URLs over TLS, verifying the server's certificate
+/// against the Mozilla root certificates, and to http URLs without encryption.
+///
+/// # Examples
+///
+/// ```
+/// let response = http_client::client().request(request).await?;
+/// ```
+pub fn client() -> Client<HttpsConnector<HttpConnector>, Body> {
+    let connector: HttpsConnector<HttpConnector> = HttpsConnectorBuilder::new()
+        .with_webpki_roots()
+        .https_or_http()
+        .enable_http1()
+        .build();
+    return Client::builder().build(connector);
+}
+
+/// Checks a URL uses https, so credentials sent with requests to it, such as API keys, are
+/// encrypted. Returns an error if the URL is not valid, or uses any other scheme.
+///
+/// # Arguments
+///
+/// * `url` - The URL credentials are sent to
+///
+/// # Examples
+///
+/// ```
+/// http_client::require_https(&settings.refund_url)?;
+/// ```
+pub fn require_https(url: &str) -> Result<(), CallError> {
+    let uri: Uri = url.parse()?;
+    if uri.scheme_str() != Some("https") {
+        return Err(format!(
+            "Credentials are only sent to https URLs, but {} is not",
+            uri.host().unwrap_or(url)
+        )
+        .into());
+    }
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http_client::*;
+
+    #[test]
+    fn require_https_urls() {
+        assert!(require_https("https://payments.example.com/refunds").is_ok());
+        assert!(require_https("http://payments.example.com/refunds").is_err());
+        assert!(require_https("payments.example.com/refunds").is_err());
+        assert!(require_https("").is_err());
+    }
+}
@@ -0,0 +1,186 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::storage;
+use crate::storage::room_booking::RoomBooking;
+use rocket::http::ContentType;
+use rocket::serde::json::{from_str, from_value, Value};
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes the outcome of importing a single row
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportRowResult {
+    /// The position of the row in the imported file, starting from 1
+    pub row: usize,
+    /// The id assigned to the imported booking, if the row was imported
+    pub booking_id: Option<u32>,
+    /// A description of each problem found with the row, if it was not imported
+    pub errors: Vec<String>,
+}
+
+/// Describes the outcome of an import
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    /// The number of rows imported
+    pub imported: usize,
+    /// The number of rows rejected
+    pub rejected: usize,
+    pub rows: Vec<ImportRowResult>,
+}
+
+impl ImportReport {
+    /// Creates an import report from the outcome of each row.
+    ///
+    /// # Arguments
+    ///
+    /// * `results` - The imported booking or list of errors for each row, in file order
+    pub fn new(results: Vec<Result<RoomBooking, Vec<String>>>) -> ImportReport {
+        let rows: Vec<ImportRowResult> = results
+            .into_iter()
+            .enumerate()
+            .map(|(index, result)| match result {
+                Ok(booking) => ImportRowResult {
+                    row: index + 1,
+                    booking_id: booking.booking_id,
+                    errors: Vec::new(),
+                },
+                Err(errors) => ImportRowResult {
+                    row: index + 1,
+                    booking_id: None,
+                    errors,
+                },
+            })
+            .collect();
+
+        let imported: usize = rows.iter().filter(|row| row.errors.is_empty()).count();
+
+        return ImportReport {
+            imported,
+            rejected: rows.len() - imported,
+            rows,
+        };
+    }
+}
+
+/// Parses each row of a CSV or JSON file into a booking. Returns an error if the file itself could
+/// not be read, or a list containing the booking or parse error for each row.
+///
+/// CSV files must include a header row, using the same field names as the JSON representation
+/// of a booking (e.g. ```customerId,roomTypeId,checkInDate,checkOutDate```). JSON files must
+/// contain an array of bookings.
+///
+/// # Arguments
+///
+/// * `content_type` - The content type of the file, either text/csv or application/json
+/// * `content` - The content of the file
+pub fn parse(
+    content_type: &ContentType,
+    content: &str,
+) -> Result<Vec<Result<RoomBooking, String>>, String> {
+    if *content_type == ContentType::CSV {
+        return Ok(parse_csv(content));
+    }
+
+    if *content_type == ContentType::JSON {
+        return parse_json(content);
+    }
+
+    return Err("Unsupported file type, expected text/csv or application/json".to_string());
+}
+
+/// Parses each record of a CSV file into a booking.
+fn parse_csv(content: &str) -> Vec<Result<RoomBooking, String>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    return reader
+        .deserialize::<RoomBooking>()
+        .map(|record| record.map_err(|error| error.to_string()))
+        .collect();
+}
+
+/// Parses each element of a JSON array into a booking.
+fn parse_json(content: &str) -> Result<Vec<Result<RoomBooking, String>>, String> {
+    let rows: Vec<Value> = from_str(content).map_err(|error| error.to_string())?;
+
+    return Ok(rows
+        .into_iter()
+        .map(|row| from_value::<RoomBooking>(row).map_err(|error| error.to_string()))
+        .collect());
+}
+
+/// Imports each parsed row, returning the imported booking or list of errors for each row, in
+/// the original order.
+///
+/// # Arguments
+///
+/// * `rows` - The booking or parse error for each row, as returned by ```parse```
+pub fn import_rows(
+    rows: Vec<Result<RoomBooking, String>>,
+) -> Vec<Result<RoomBooking, Vec<String>>> {
+    let bookings: Vec<RoomBooking> = rows.iter().flatten().cloned().collect();
+    return merge(rows, storage::import(bookings));
+}
+
+/// Checks whether each parsed row would be imported, without importing any of them. Returns the
+/// booking as it would be imported, without a booking id, or the list of errors for each row, in
+/// the original order.
+///
+/// # Arguments
+///
+/// * `rows` - The booking or parse error for each row, as returned by ```parse```
+pub fn validate_rows(
+    rows: Vec<Result<RoomBooking, String>>,
+) -> Vec<Result<RoomBooking, Vec<String>>> {
+    let bookings: Vec<RoomBooking> = rows.iter().flatten().cloned().collect();
+    return merge(rows, storage::check_import(bookings));
+}
+
+/// Merges the outcome of each parsed booking back into the rows it was parsed from, keeping the
+/// parse error of any row which could not be parsed.
+fn merge(
+    rows: Vec<Result<RoomBooking, String>>,
+    results: Vec<Result<RoomBooking, Vec<String>>>,
+) -> Vec<Result<RoomBooking, Vec<String>>> {
+    let mut imported = results.into_iter();
+
+    return rows
+        .into_iter()
+        .map(|row| match row {
+            Ok(_) => imported.next().unwrap_or(Err(Vec::new())),
+            Err(error) => Err(vec![error]),
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::import::*;
+
+    #[test]
+    fn parse_rows() {
+        let csv: &str = "customerId,roomTypeId,checkInDate,checkOutDate\n\
+            1,3,2020-01-01,2020-01-08\n\
+            x,3,2020-01-01,2020-01-08\n";
+        let rows = parse(&ContentType::CSV, csv).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].as_ref().unwrap().customer_id, 1);
+        assert!(rows[1].is_err());
+
+        let json: &str = r#"[{"customerId":1,"roomTypeId":3,"checkInDate":"2020-01-01",
+            "checkOutDate":"2020-01-08"},{"customerId":1}]"#;
+        let rows = parse(&ContentType::JSON, json).unwrap();
+        assert!(rows[0].is_ok());
+        assert!(rows[1].is_err());
+
+        assert!(parse(&ContentType::JSON, "{").is_err());
+        assert!(parse(&ContentType::XML, "").is_err());
+    }
+}
@@ -0,0 +1,58 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+//! The modules behind the room booking service. The HTTP routes and entry point live in the
+//! binary; the modules are exposed as a library so benchmarks can exercise storage directly.
+
+#![allow(clippy::needless_return)]
+// Storage functions report failures the routes map to a status code as unit errors.
+#![allow(clippy::result_unit_err)]
+// Required by the FromForm derive in the current Rocket release candidate.
+#![allow(renamed_and_removed_lints)]
+
+pub mod addons;
+pub mod agents;
+pub mod channel_manager;
+pub mod circuit_breaker;
+pub mod cli;
+pub mod clock;
+pub mod compression;
+pub mod config;
+pub mod deprecation;
+pub mod dynamic_pricing;
+pub mod exchange_rates;
+pub mod expiry;
+pub mod features;
+pub mod fields;
+pub mod http_client;
+pub mod import;
+pub mod links;
+pub mod loadgen;
+pub mod local_time;
+pub mod locale;
+pub mod outbox;
+pub mod pagination;
+pub mod payment_provider;
+pub mod payment_webhooks;
+pub mod pms;
+pub mod pricing;
+pub mod redaction;
+pub mod reload;
+pub mod request_log;
+pub mod responders;
+pub mod response_cache;
+pub mod retention;
+pub mod retry;
+pub mod sandbox;
+pub mod screening;
+pub mod secrets;
+pub mod seed;
+pub mod state_machine;
+pub mod stay_rules;
+pub mod storage;
+pub mod streaming;
+pub mod tax;
+pub mod tls;
+pub mod versioning;
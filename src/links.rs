@@ -0,0 +1,137 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::state_machine;
+use crate::storage::room_booking::{BookingStatus, RoomBooking};
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The path prefix of the API version the links point to.
+static BASE_PATH: &str = "/v1";
+
+/// Describes a link to a related resource or action
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct Link {
+    /// The path of the linked resource
+    pub href: String,
+    /// The HTTP method used to follow the link
+    pub method: String,
+}
+
+impl Link {
+    fn new(method: &str, href: String) -> Link {
+        return Link {
+            href,
+            method: method.to_string(),
+        };
+    }
+}
+
+/// Describes a room booking, along with links to the actions currently available for it
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct BookingResource {
+    #[serde(flatten)]
+    pub booking: RoomBooking,
+    #[serde(rename = "_links")]
+    pub links: BTreeMap<String, Link>,
+}
+
+impl BookingResource {
+    /// Creates a booking resource, generating the links allowed by the booking's current status.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking` - The booking to generate links for
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let resource = BookingResource::new(booking);
+    /// ```
+    pub fn new(booking: RoomBooking) -> BookingResource {
+        let mut links: BTreeMap<String, Link> = BTreeMap::new();
+
+        if let Some(booking_id) = booking.booking_id {
+            let path: String = format!("{}/booking/{}", BASE_PATH, booking_id);
+            links.insert("self".to_string(), Link::new("GET", path.clone()));
+            links.insert(
+                "history".to_string(),
+                Link::new("GET", format!("{}/history", path)),
+            );
+            links.insert(
+                "balance".to_string(),
+                Link::new("GET", format!("{}/balance", path)),
+            );
+
+            if state_machine::check(&booking.status, &BookingStatus::Cancelled).is_ok() {
+                links.insert("cancel".to_string(), Link::new("DELETE", path.clone()));
+            }
+
+            if state_machine::check(&booking.status, &BookingStatus::CheckedIn).is_ok() {
+                links.insert(
+                    "checkIn".to_string(),
+                    Link::new("PUT", format!("{}/check-in", path)),
+                );
+            }
+
+            if state_machine::check(&booking.status, &BookingStatus::Complete).is_ok() {
+                links.insert(
+                    "complete".to_string(),
+                    Link::new("PUT", format!("{}/complete", path)),
+                );
+            }
+
+            if state_machine::check(&booking.status, &BookingStatus::NoShow).is_ok() {
+                links.insert(
+                    "noShow".to_string(),
+                    Link::new("PUT", format!("{}/no-show", path)),
+                );
+            }
+
+            if booking.status == Some(BookingStatus::Cancelled) {
+                links.insert(
+                    "reinstate".to_string(),
+                    Link::new("PUT", format!("{}/reinstate", path)),
+                );
+            }
+
+            if booking.status == Some(BookingStatus::PendingReview) {
+                links.insert(
+                    "approve".to_string(),
+                    Link::new("PUT", format!("{}/approve", path)),
+                );
+            }
+
+            if booking.status == Some(BookingStatus::PendingPayment) {
+                links.insert(
+                    "confirmPayment".to_string(),
+                    Link::new("PUT", format!("{}/confirm-payment", path)),
+                );
+            }
+        }
+
+        if let Some(series_id) = booking.series_id {
+            links.insert(
+                "series".to_string(),
+                Link::new(
+                    "GET",
+                    format!("{}/bookings/series/{}", BASE_PATH, series_id),
+                ),
+            );
+        }
+
+        links.insert(
+            "customerBookings".to_string(),
+            Link::new(
+                "GET",
+                format!("{}/bookings/customer/{}", BASE_PATH, booking.customer_id),
+            ),
+        );
+
+        return BookingResource { booking, links };
+    }
+}
@@ -0,0 +1,415 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::circuit_breaker::CallError;
+use crate::seed::{self, SeedOptions};
+use crate::storage::room_booking::RoomBooking;
+use hyper::body::{self, Body};
+use hyper::client::HttpConnector;
+use hyper::{Client, Method, Request};
+use rand::Rng;
+use rocket::serde::json::{self, Value};
+use rocket::tokio;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Defines the operations sent by the load generator
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Operation {
+    /// Create a new booking
+    Create,
+    /// Fetch a booking created earlier in the run
+    Read,
+    /// Cancel a booking created earlier in the run
+    Cancel,
+}
+
+impl Operation {
+    /// Returns the name of the operation, as used in the mix and the report
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Operation::Create => "create",
+            Operation::Read => "read",
+            Operation::Cancel => "cancel",
+        };
+    }
+}
+
+/// Describes the relative weight of each operation in the generated traffic, e.g. a mix of
+/// 'create=2,read=7,cancel=1' sends 20% creates, 70% reads and 10% cancellations.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Mix {
+    pub create: u32,
+    pub read: u32,
+    pub cancel: u32,
+}
+
+impl Mix {
+    /// Picks an operation at random, in proportion to its weight.
+    pub fn pick(&self) -> Operation {
+        let total: u32 = self.create + self.read + self.cancel;
+        return self.operation_at(rand::thread_rng().gen_range(0..total.max(1)));
+    }
+
+    /// Returns the operation a roll between 0 and the total weight falls on.
+    fn operation_at(&self, roll: u32) -> Operation {
+        if roll < self.create {
+            return Operation::Create;
+        }
+
+        return match roll < self.create + self.read {
+            true => Operation::Read,
+            false => Operation::Cancel,
+        };
+    }
+}
+
+impl FromStr for Mix {
+    type Err = String;
+
+    fn from_str(mix: &str) -> Result<Mix, String> {
+        let mut parsed = Mix {
+            create: 0,
+            read: 0,
+            cancel: 0,
+        };
+
+        for part in mix
+            .split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+        {
+            let (name, weight) = part
+                .split_once('=')
+                .ok_or_else(|| format!("'{}' must be in the form operation=weight", part))?;
+            let weight: u32 = weight
+                .trim()
+                .parse()
+                .map_err(|_| format!("The weight of '{}' must be a whole number", name))?;
+
+            match name.trim() {
+                "create" => parsed.create = weight,
+                "read" => parsed.read = weight,
+                "cancel" => parsed.cancel = weight,
+                other => return Err(format!("Unknown operation '{}'", other)),
+            }
+        }
+
+        if parsed.create == 0 {
+            return Err(
+                "The mix must include creates, to provide bookings to read and cancel".to_string(),
+            );
+        }
+
+        return Ok(parsed);
+    }
+}
+
+/// Describes a load generation run
+#[derive(Clone, PartialEq, Debug)]
+pub struct LoadgenOptions {
+    /// The base URL of the running instance, including the API version, e.g.
+    /// 'http://127.0.0.1:8000/v1'
+    pub url: String,
+    /// The total number of requests to send
+    pub requests: usize,
+    /// The number of requests sent at the same time
+    pub concurrency: usize,
+    /// The relative weight of each operation
+    pub mix: Mix,
+}
+
+/// Describes the latency of the requests sent for a single operation
+#[derive(Clone, PartialEq, Debug)]
+pub struct OperationReport {
+    pub operation: Operation,
+    /// The number of requests sent
+    pub requests: usize,
+    /// The number of requests which failed, or returned an unsuccessful status
+    pub errors: usize,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl OperationReport {
+    /// Summarises the latencies of the requests sent for an operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - The operation the requests were sent for
+    /// * `results` - The latency of each request, and whether it succeeded
+    fn new(operation: Operation, results: &[(Duration, bool)]) -> OperationReport {
+        let mut latencies: Vec<Duration> = results.iter().map(|(latency, _)| *latency).collect();
+        latencies.sort_unstable();
+
+        return OperationReport {
+            operation,
+            requests: results.len(),
+            errors: results.iter().filter(|(_, success)| !success).count(),
+            p50: percentile(&latencies, 50.0),
+            p90: percentile(&latencies, 90.0),
+            p95: percentile(&latencies, 95.0),
+            p99: percentile(&latencies, 99.0),
+            max: latencies.last().copied().unwrap_or_default(),
+        };
+    }
+}
+
+/// Describes the outcome of a load generation run
+#[derive(Clone, PartialEq, Debug)]
+pub struct LoadgenReport {
+    /// The report for each operation sent at least once
+    pub operations: Vec<OperationReport>,
+    /// How long the run took
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for LoadgenReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let requests: usize = self.operations.iter().map(|report| report.requests).sum();
+        let seconds: f64 = self.elapsed.as_secs_f64().max(f64::EPSILON);
+
+        writeln!(
+            f,
+            "{} requests in {:.2}s ({:.1} requests/s)",
+            requests,
+            seconds,
+            requests as f64 / seconds
+        )?;
+        writeln!(
+            f,
+            "{:<8} {:>9} {:>7} {:>10} {:>10} {:>10} {:>10} {:>10}",
+            "", "requests", "errors", "p50", "p90", "p95", "p99", "max"
+        )?;
+
+        for report in &self.operations {
+            writeln!(
+                f,
+                "{:<8} {:>9} {:>7} {:>10} {:>10} {:>10} {:>10} {:>10}",
+                report.operation.name(),
+                report.requests,
+                report.errors,
+                millis(report.p50),
+                millis(report.p90),
+                millis(report.p95),
+                millis(report.p99),
+                millis(report.max)
+            )?;
+        }
+
+        return Ok(());
+    }
+}
+
+/// Sends a mix of create, read and cancel requests to a running instance, and reports the
+/// latency of each operation. Reads and cancellations are sent for bookings created earlier in
+/// the run, and a create is sent instead if there are none. Each booking is cancelled at most
+/// once. Requests are sent directly, without retries, so failures are reported as errors.
+///
+/// # Arguments
+///
+/// * `options` - The options for the run
+///
+/// # Examples
+///
+/// ```
+/// let report = loadgen::run(&options).await;
+/// ```
+pub async fn run(options: &LoadgenOptions) -> LoadgenReport {
+    let client: Client<HttpConnector> = Client::new();
+    let sent = Arc::new(AtomicUsize::new(0));
+    let booking_ids: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(Vec::new()));
+    let started: Instant = Instant::now();
+
+    let workers: Vec<_> = (0..options.concurrency.max(1))
+        .map(|_| {
+            let client: Client<HttpConnector> = client.clone();
+            let sent: Arc<AtomicUsize> = sent.clone();
+            let booking_ids: Arc<Mutex<Vec<u32>>> = booking_ids.clone();
+            let options: LoadgenOptions = options.clone();
+
+            return tokio::spawn(async move {
+                let mut results: Vec<(Operation, Duration, bool)> = Vec::new();
+
+                while sent.fetch_add(1, Ordering::Relaxed) < options.requests {
+                    let (operation, booking_id) = next_operation(&options.mix, &booking_ids);
+                    let request_started: Instant = Instant::now();
+                    let outcome = send(&client, &options.url, operation, booking_id).await;
+                    let latency: Duration = request_started.elapsed();
+
+                    if let (Ok(Some(booking_id)), Ok(mut booking_ids)) =
+                        (&outcome, booking_ids.lock())
+                    {
+                        booking_ids.push(*booking_id);
+                    }
+
+                    results.push((operation, latency, outcome.is_ok()));
+                }
+
+                return results;
+            });
+        })
+        .collect();
+
+    let mut results: Vec<(Operation, Duration, bool)> = Vec::new();
+    for worker in workers {
+        if let Ok(worker_results) = worker.await {
+            results.extend(worker_results);
+        }
+    }
+
+    let operations: Vec<OperationReport> = [Operation::Create, Operation::Read, Operation::Cancel]
+        .into_iter()
+        .filter_map(|operation| {
+            let operation_results: Vec<(Duration, bool)> = results
+                .iter()
+                .filter(|(sent, _, _)| *sent == operation)
+                .map(|(_, latency, success)| (*latency, *success))
+                .collect();
+
+            return match operation_results.is_empty() {
+                true => None,
+                false => Some(OperationReport::new(operation, &operation_results)),
+            };
+        })
+        .collect();
+
+    return LoadgenReport {
+        operations,
+        elapsed: started.elapsed(),
+    };
+}
+
+/// Picks the next operation to send, and the booking it is sent for. A create is picked if there
+/// are no bookings to read or cancel. Bookings picked for cancellation are removed from the list.
+fn next_operation(mix: &Mix, booking_ids: &Mutex<Vec<u32>>) -> (Operation, Option<u32>) {
+    let mut booking_ids = match booking_ids.lock() {
+        Ok(guard) => guard,
+        Err(_) => return (Operation::Create, None),
+    };
+
+    if booking_ids.is_empty() {
+        return (Operation::Create, None);
+    }
+
+    let index: usize = rand::thread_rng().gen_range(0..booking_ids.len());
+    return match mix.pick() {
+        Operation::Create => (Operation::Create, None),
+        Operation::Read => (Operation::Read, Some(booking_ids[index])),
+        Operation::Cancel => (Operation::Cancel, Some(booking_ids.swap_remove(index))),
+    };
+}
+
+/// Sends a single request for an operation. Returns the booking id of the created booking for
+/// creates, or an error if the request fails or the response status is not successful.
+async fn send(
+    client: &Client<HttpConnector>,
+    url: &str,
+    operation: Operation,
+    booking_id: Option<u32>,
+) -> Result<Option<u32>, CallError> {
+    let url: &str = url.trim_end_matches('/');
+    let request: Request<Body> = match (operation, booking_id) {
+        (Operation::Read, Some(booking_id)) => Request::builder()
+            .method(Method::GET)
+            .uri(format!("{}/booking/{}", url, booking_id))
+            .body(Body::empty())?,
+        (Operation::Cancel, Some(booking_id)) => Request::builder()
+            .method(Method::DELETE)
+            .uri(format!("{}/booking/{}", url, booking_id))
+            .body(Body::empty())?,
+        _ => Request::builder()
+            .method(Method::POST)
+            .uri(format!("{}/booking", url))
+            .header("Content-Type", "application/json")
+            .body(Body::from(json::to_string(&new_booking())?))?,
+    };
+
+    let response = client.request(request).await?;
+    let status = response.status();
+    let content = body::to_bytes(response.into_body()).await?;
+
+    if !status.is_success() {
+        return Err(format!("The instance responded with {}", status).into());
+    }
+
+    if operation != Operation::Create {
+        return Ok(None);
+    }
+
+    let created: Value = json::from_slice(&content)?;
+    return Ok(created["bookingId"]
+        .as_u64()
+        .map(|booking_id| booking_id as u32));
+}
+
+/// Returns a random booking which may be created, with no id or status.
+fn new_booking() -> RoomBooking {
+    let mut booking: RoomBooking = seed::generate(&SeedOptions::new(1)).remove(0);
+    booking.status = None;
+    return booking;
+}
+
+/// Returns the latency below which the provided percentage of requests completed, using the
+/// nearest rank method.
+///
+/// # Arguments
+///
+/// * `sorted` - The latency of each request, in ascending order
+/// * `percent` - The percentage of requests, e.g. 99.0
+fn percentile(sorted: &[Duration], percent: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+
+    let rank: usize = ((percent / 100.0) * sorted.len() as f64).ceil() as usize;
+    return sorted[rank.clamp(1, sorted.len()) - 1];
+}
+
+/// Formats a duration in milliseconds, for the report.
+fn millis(duration: Duration) -> String {
+    return format!("{:.2}ms", duration.as_secs_f64() * 1000.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::loadgen::*;
+
+    #[test]
+    fn parse_mix() {
+        let mix: Mix = "create=1, read=8,cancel=1".parse().unwrap();
+        assert_eq!(
+            mix,
+            Mix {
+                create: 1,
+                read: 8,
+                cancel: 1
+            }
+        );
+        assert_eq!(mix.operation_at(0), Operation::Create);
+        assert_eq!(mix.operation_at(1), Operation::Read);
+        assert_eq!(mix.operation_at(9), Operation::Cancel);
+
+        assert!("read=1".parse::<Mix>().is_err());
+        assert!("create=1,update=1".parse::<Mix>().is_err());
+        assert!("create".parse::<Mix>().is_err());
+    }
+
+    #[test]
+    fn latency_percentiles() {
+        let sorted: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+
+        assert_eq!(percentile(&sorted, 50.0), Duration::from_millis(50));
+        assert_eq!(percentile(&sorted, 99.0), Duration::from_millis(99));
+        assert_eq!(percentile(&sorted, 100.0), Duration::from_millis(100));
+        assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+    }
+}
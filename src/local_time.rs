@@ -0,0 +1,32 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::clock;
+use crate::config;
+use chrono::{DateTime, NaiveDate};
+use chrono_tz::Tz;
+
+/// Returns the current time in the hotel's timezone, rather than the server's local timezone. The
+/// time is read from ```clock::now```, so it can be controlled in tests.
+///
+/// # Examples
+///
+/// ```
+/// let now = local_time::now();
+/// ```
+pub fn now() -> DateTime<Tz> {
+    return clock::now().with_timezone(&config::hotel().timezone);
+}
+
+/// Returns the current date in the hotel's timezone, rather than the server's local timezone.
+///
+/// # Examples
+///
+/// ```
+/// let today = local_time::today();
+/// ```
+pub fn today() -> NaiveDate {
+    return now().date_naive();
+}
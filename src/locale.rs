@@ -0,0 +1,524 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::config;
+use crate::exchange_rates;
+use chrono::NaiveDate;
+use once_cell::sync::Lazy;
+use rocket::figment::providers::{Format, Toml};
+use rocket::figment::Figment;
+use rocket::request::{FromRequest, Outcome};
+use rocket::Request;
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::request::{OpenApiFromRequest, RequestHeaderInput};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// The language messages are written in, used when no translation is available.
+static SOURCE_LANGUAGE: &str = "en";
+
+/// The translations of the service's messages into each supported language, built into the
+/// service from the ```locales``` directory.
+static CATALOGS: Lazy<Vec<Catalog>> = Lazy::new(|| {
+    return [
+        include_str!("../locales/de.toml"),
+        include_str!("../locales/es.toml"),
+        include_str!("../locales/fr.toml"),
+    ]
+    .into_iter()
+    .filter_map(|content| Catalog::parse(content).ok())
+    .collect();
+});
+
+/// Holds the translations of the service's messages into a single language
+#[derive(Clone, Deserialize, PartialEq, Debug)]
+pub struct Catalog {
+    /// The language tag of the translations, e.g. 'fr'
+    pub language: String,
+    /// The translation of each message, keyed by the English message, with {} marking each value
+    /// inserted into the message
+    pub messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Reads a catalog from its TOML representation. Returns an error if the catalog cannot be
+    /// read.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The content of the catalog file
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let catalog = Catalog::parse(include_str!("../locales/fr.toml"))?;
+    /// ```
+    pub fn parse(content: &str) -> Result<Catalog, String> {
+        return Figment::from(Toml::string(content))
+            .extract()
+            .map_err(|error| error.to_string());
+    }
+
+    /// Translates a single message. Returns None if the catalog has no translation for it. Where
+    /// several messages match, the one with the most fixed text is used, so a message written for
+    /// a particular field is preferred over a message for any field.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The English message
+    pub fn translate(&self, message: &str) -> Option<String> {
+        let mut templates: Vec<(&String, &String)> = self.messages.iter().collect();
+        templates.sort_by_key(|(template, _)| std::cmp::Reverse(template.replace("{}", "").len()));
+
+        return templates.into_iter().find_map(|(template, translation)| {
+            return values(template, message).map(|values| fill(translation, &values));
+        });
+    }
+}
+
+/// Returns the values inserted into a message, if the message was written from the template.
+///
+/// # Arguments
+///
+/// * `template` - The English template, with {} marking each value
+/// * `message` - The message to match against the template
+fn values(template: &str, message: &str) -> Option<Vec<String>> {
+    let mut parts = template.split("{}");
+    let mut rest: &str = message.strip_prefix(parts.next()?)?;
+    let mut values: Vec<String> = Vec::new();
+
+    let parts: Vec<&str> = parts.collect();
+    for (index, part) in parts.iter().enumerate() {
+        let end: usize = match index == parts.len() - 1 {
+            true if part.is_empty() => rest.len(),
+            true => rest.strip_suffix(part)?.len(),
+            false => rest.find(part)?,
+        };
+
+        if end == 0 {
+            return None;
+        }
+
+        values.push(rest[..end].to_string());
+        rest = &rest[end + part.len()..];
+    }
+
+    return match rest.is_empty() {
+        true => Some(values),
+        false => None,
+    };
+}
+
+/// Inserts values into a translation, in order for each {}, or by position for {0}, {1} and so
+/// on.
+///
+/// # Arguments
+///
+/// * `translation` - The translated template
+/// * `values` - The values to insert
+fn fill(translation: &str, values: &[String]) -> String {
+    let mut filled: String = String::new();
+    let mut next: usize = 0;
+    let mut rest: &str = translation;
+
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end: usize = match rest.find('}') {
+            Some(end) => end,
+            None => break,
+        };
+        let position: Option<usize> = match &rest[1..end] {
+            "" => {
+                next += 1;
+                Some(next - 1)
+            }
+            index => index.parse::<usize>().ok(),
+        };
+
+        match position.and_then(|position| values.get(position)) {
+            Some(value) => filled.push_str(value),
+            None => filled.push_str(&rest[..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+
+    filled.push_str(rest);
+    return filled;
+}
+
+/// Returns the tags of the languages messages are available in, e.g. 'fr'.
+///
+/// # Examples
+///
+/// ```
+/// let languages = locale::languages();
+/// ```
+pub fn languages() -> Vec<String> {
+    let mut languages: Vec<String> = vec![SOURCE_LANGUAGE.to_string()];
+    languages.extend(CATALOGS.iter().map(|catalog| catalog.language.clone()));
+    return languages;
+}
+
+/// Chooses the language to respond in from an ```Accept-Language``` header. Languages are tried
+/// in order of preference, matching a regional language, e.g. 'fr-CA', by its primary language if
+/// it is not available itself. The hotel's configured language is used if none of the languages
+/// asked for are available, or the header is missing, and English if that is not available.
+///
+/// # Arguments
+///
+/// * `accept_language` - The value of the ```Accept-Language``` header, if sent
+///
+/// # Examples
+///
+/// ```
+/// let language = locale::negotiate(request.headers().get_one("Accept-Language"));
+/// ```
+pub fn negotiate(accept_language: Option<&str>) -> String {
+    let available: Vec<String> = languages();
+    let fallback: String = match available.contains(&config::hotel().language) {
+        true => config::hotel().language,
+        false => SOURCE_LANGUAGE.to_string(),
+    };
+
+    for tag in preferences(accept_language) {
+        if tag == "*" {
+            return fallback;
+        }
+
+        let primary: &str = tag.split('-').next().unwrap_or_default();
+        if let Some(language) = available
+            .iter()
+            .find(|language| **language == tag || *language == primary)
+        {
+            return language.clone();
+        }
+    }
+
+    return fallback;
+}
+
+/// Returns the language tags of an ```Accept-Language``` header, in lower case, in order of
+/// preference. Languages the client refuses, with a quality of 0, are left out.
+///
+/// # Arguments
+///
+/// * `accept_language` - The value of the ```Accept-Language``` header, if sent
+fn preferences(accept_language: Option<&str>) -> Vec<String> {
+    let mut preferences: Vec<(String, f32)> = accept_language
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|entry| {
+            let mut parameters = entry.split(';');
+            let tag: String = parameters.next()?.trim().to_lowercase();
+            let quality: f32 = parameters
+                .filter_map(|parameter| parameter.trim().strip_prefix("q="))
+                .find_map(|quality| quality.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            return match tag.is_empty() || quality <= 0.0 {
+                true => None,
+                false => Some((tag, quality)),
+            };
+        })
+        .collect();
+    preferences.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    return preferences.into_iter().map(|(tag, _)| tag).collect();
+}
+
+/// Translates a message into a language. Messages listing several problems, separated by
+/// commas, have each problem translated. Any message or problem without a translation is
+/// returned in English.
+///
+/// # Arguments
+///
+/// * `language` - The language tag, as returned by ```negotiate```
+/// * `message` - The English message
+///
+/// # Examples
+///
+/// ```
+/// let error = locale::translate(&language, "Booking not found");
+/// ```
+pub fn translate(language: &str, message: &str) -> String {
+    let catalog: &Catalog = match CATALOGS.iter().find(|catalog| catalog.language == language) {
+        Some(catalog) => catalog,
+        None => return message.to_string(),
+    };
+
+    if let Some(translation) = catalog.translate(message) {
+        return translation;
+    }
+
+    return message
+        .split(", ")
+        .map(|problem| {
+            catalog
+                .translate(problem)
+                .unwrap_or_else(|| problem.to_string())
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+}
+
+/// Describes the conventions dates and amounts are formatted with for a guest
+#[derive(Clone, PartialEq, Debug)]
+pub struct Locale {
+    /// The tag of the language, e.g. 'fr'
+    pub language: String,
+    /// The ISO 3166 code of the region, e.g. 'CA'
+    pub region: String,
+}
+
+impl Locale {
+    /// Chooses the locale to format a response for from an ```Accept-Language``` header. The
+    /// language is chosen as for error messages, and the region is taken from the most preferred
+    /// tag for that language which gives one, e.g. 'fr-CA', or the hotel's region if none do.
+    ///
+    /// # Arguments
+    ///
+    /// * `accept_language` - The value of the ```Accept-Language``` header, if sent
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let locale = Locale::negotiate(request.headers().get_one("Accept-Language"));
+    /// ```
+    pub fn negotiate(accept_language: Option<&str>) -> Locale {
+        let language: String = negotiate(accept_language);
+        let region: String = preferences(accept_language)
+            .iter()
+            .find_map(|tag| {
+                let (primary, region) = tag.split_once('-')?;
+                return match primary == language && region.len() == 2 {
+                    true => Some(region.to_uppercase()),
+                    false => None,
+                };
+            })
+            .unwrap_or_else(|| config::hotel().region.to_uppercase());
+
+        return Locale { language, region };
+    }
+
+    /// Returns the language tag of the locale, e.g. 'fr-CA'.
+    pub fn tag(&self) -> String {
+        return format!("{}-{}", self.language, self.region);
+    }
+
+    /// Formats a date in the locale's numeric date format, e.g. 15/10/2026 in Britain, 10/15/2026
+    /// in the United States and 15.10.2026 in Germany.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - The date to format
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let check_in_date = locale.format_date(booking.check_in_date);
+    /// ```
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        let format: &str = match (self.language.as_str(), self.region.as_str()) {
+            (_, "CA") => "%Y-%m-%d",
+            ("en", "US") => "%m/%d/%Y",
+            ("de", _) => "%d.%m.%Y",
+            _ => "%d/%m/%Y",
+        };
+
+        return date.format(format).to_string();
+    }
+
+    /// Formats an amount of money with the locale's separators, and the currency's symbol placed
+    /// where the locale expects it, e.g. £1,234.50 in English and 1.234,50 € in German. Currencies
+    /// without a well known symbol are shown by their code.
+    ///
+    /// # Arguments
+    ///
+    /// * `currency` - The ISO 4217 code of the currency
+    /// * `amount` - The amount, in the minor unit of the currency
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let total = locale.format_amount("GBP", 123450);
+    /// ```
+    pub fn format_amount(&self, currency: &str, amount: u64) -> String {
+        let (group, decimal, min_grouped): (&str, &str, usize) = match self.language.as_str() {
+            "fr" => ("\u{202f}", ",", 4),
+            "de" => (".", ",", 4),
+            // Spanish only groups numbers of five or more digits
+            "es" => (".", ",", 5),
+            _ => (",", ".", 4),
+        };
+
+        let digits: u32 = exchange_rates::minor_unit_digits(currency) as u32;
+        let units: String = (amount / 10u64.pow(digits)).to_string();
+        let mut number: String = match units.len() < min_grouped {
+            true => units,
+            false => units
+                .as_bytes()
+                .rchunks(3)
+                .rev()
+                .map(|chunk| String::from_utf8_lossy(chunk).to_string())
+                .collect::<Vec<String>>()
+                .join(group),
+        };
+        if digits > 0 {
+            number = format!(
+                "{}{}{:0width$}",
+                number,
+                decimal,
+                amount % 10u64.pow(digits),
+                width = digits as usize
+            );
+        }
+
+        let symbol: Option<&str> = match currency {
+            "GBP" => Some("£"),
+            "EUR" => Some("€"),
+            "USD" if self.region == "US" => Some("$"),
+            "USD" => Some("US$"),
+            "JPY" => Some("¥"),
+            _ => None,
+        };
+
+        return match (self.language.as_str(), symbol) {
+            ("en", Some(symbol)) => format!("{}{}", symbol, number),
+            ("en", None) => format!("{}\u{a0}{}", currency, number),
+            (_, symbol) => format!("{}\u{a0}{}", number, symbol.unwrap_or(currency)),
+        };
+    }
+}
+
+/// The language negotiated for a request from its ```Accept-Language``` header.
+pub struct Language(pub String);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Language {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Language, ()> {
+        return Outcome::Success(Language(negotiate(
+            request.headers().get_one("Accept-Language"),
+        )));
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Locale {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Locale, ()> {
+        return Outcome::Success(Locale::negotiate(
+            request.headers().get_one("Accept-Language"),
+        ));
+    }
+}
+
+impl<'r> OpenApiFromRequest<'r> for Language {
+    fn from_request_input(
+        _: &mut OpenApiGenerator,
+        _: String,
+        _: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        return Ok(RequestHeaderInput::None);
+    }
+}
+
+impl<'r> OpenApiFromRequest<'r> for Locale {
+    fn from_request_input(
+        _: &mut OpenApiGenerator,
+        _: String,
+        _: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        return Ok(RequestHeaderInput::None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::locale::*;
+
+    #[test]
+    fn negotiate_language() {
+        assert_eq!(negotiate(None), "en");
+        assert_eq!(negotiate(Some("fr-CA, en;q=0.8")), "fr");
+        assert_eq!(negotiate(Some("en;q=0.5, de;q=0.9")), "de");
+        assert_eq!(negotiate(Some("ja, es;q=0.1")), "es");
+        assert_eq!(negotiate(Some("fr;q=0, ja")), "en");
+        assert_eq!(negotiate(Some("*")), "en");
+    }
+
+    #[test]
+    fn translate_messages() {
+        assert_eq!(languages().len(), 4);
+        assert_eq!(
+            translate("fr", "Booking not found"),
+            "Réservation introuvable"
+        );
+        assert_eq!(
+            translate("de", "Stays must not be longer than 30 nights"),
+            "Aufenthalte dürfen nicht länger als 30 Nächte sein"
+        );
+        assert_eq!(
+            translate(
+                "es",
+                "status must not be set, checkOutDate must be after checkInDate"
+            ),
+            "status no debe indicarse, checkOutDate debe ser posterior a checkInDate"
+        );
+        assert_eq!(
+            translate("fr", "A Cancelled booking cannot be changed to Complete, allowed statuses are: Confirmed, PendingPayment"),
+            "Une réservation Cancelled ne peut pas passer à Complete, les statuts autorisés sont : Confirmed, PendingPayment"
+        );
+        assert_eq!(translate("fr", "An unknown message"), "An unknown message");
+        assert_eq!(translate("en", "Booking not found"), "Booking not found");
+
+        let values: Vec<String> = vec!["3".to_string(), "2".to_string()];
+        assert_eq!(fill("{1} of {0}, {}", &values), "2 of 3, 3");
+    }
+
+    #[test]
+    fn format_for_locale() {
+        let locale: Locale = Locale::negotiate(Some("en-US, fr;q=0.5"));
+        assert_eq!(locale.tag(), "en-US");
+        assert_eq!(Locale::negotiate(Some("fr, fr-CA;q=0.5")).tag(), "fr-CA");
+        assert_eq!(Locale::negotiate(Some("de")).tag(), "de-GB");
+        assert_eq!(Locale::negotiate(None).tag(), "en-GB");
+
+        let date: NaiveDate = NaiveDate::from_ymd_opt(2026, 10, 5).unwrap();
+        assert_eq!(locale.format_date(date), "10/05/2026");
+        assert_eq!(Locale::negotiate(None).format_date(date), "05/10/2026");
+        assert_eq!(
+            Locale::negotiate(Some("de")).format_date(date),
+            "05.10.2026"
+        );
+        assert_eq!(
+            Locale::negotiate(Some("fr-CA")).format_date(date),
+            "2026-10-05"
+        );
+
+        assert_eq!(locale.format_amount("USD", 123456789), "$1,234,567.89");
+        assert_eq!(Locale::negotiate(None).format_amount("GBP", 5), "£0.05");
+        assert_eq!(
+            Locale::negotiate(None).format_amount("CHF", 123450),
+            "CHF\u{a0}1,234.50"
+        );
+        assert_eq!(
+            Locale::negotiate(Some("fr")).format_amount("EUR", 123450),
+            "1\u{202f}234,50\u{a0}€"
+        );
+        assert_eq!(
+            Locale::negotiate(Some("es")).format_amount("EUR", 123450),
+            "1234,50\u{a0}€"
+        );
+        assert_eq!(
+            Locale::negotiate(Some("es")).format_amount("JPY", 1234567),
+            "1.234.567\u{a0}¥"
+        );
+    }
+}
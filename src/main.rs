@@ -3,138 +3,2722 @@
     SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
 */
 
-use rocket::http::Status;
-use rocket::{delete, get, post, put, serde::json::Json};
-use rocket_okapi::{openapi, openapi_get_routes, swagger_ui::*};
+#![allow(clippy::needless_return)]
+// Required by the FromForm derive in the current Rocket release candidate.
+#![allow(renamed_and_removed_lints)]
 
-mod storage;
+use chrono::NaiveDate;
+use rocket::data::Capped;
+use rocket::figment::Figment;
+use rocket::http::uri::Origin;
+use rocket::http::{ContentType, Status};
+use rocket::serde::json::serde_json::error::Category;
+use rocket::serde::json::{self, Json, Value};
+use rocket::{delete, get, head, patch, post, put};
+use rocket_okapi::okapi::openapi3::{OpenApi, Server};
+use rocket_okapi::rapidoc::{make_rapidoc, GeneralConfig, RapiDocConfig};
+use rocket_okapi::settings::{OpenApiSettings, UrlObject};
+use rocket_okapi::{get_openapi_route, openapi, openapi_get_routes_spec, swagger_ui::*};
+use room_booking_service::{
+    addons, agents, channel_manager, circuit_breaker, cli, compression, config, deprecation,
+    dynamic_pricing, exchange_rates, expiry, features, fields, import, links, local_time, locale,
+    outbox, pagination, payment_provider, payment_webhooks, pms, pricing, redaction, reload,
+    request_log, responders, retention, sandbox, secrets, state_machine, storage, streaming, tls,
+    versioning,
+};
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::time::SystemTime;
+
+use addons::Addon;
+use agents::AgentCommissions;
+use channel_manager::ChannelSync;
+use circuit_breaker::CircuitMetrics;
+use clap::Parser;
+use cli::{Cli, Command, ServeArgs};
+use compression::Compression;
+use config::{DocsConfig, WebhookSubscription};
+use deprecation::Deprecations;
+use expiry::PaymentExpiry;
+use features::FeatureFlags;
+use fields::SparseList;
+use import::ImportReport;
+use links::BookingResource;
+use locale::{Language, Locale};
+use outbox::{OutboxDispatcher, ReplayReport};
+use pagination::Pagination;
+use payment_provider::RefundRequest;
+use payment_webhooks::{PaymentEventProcessor, Signature};
+use pricing::{Invoice, PriceBreakdown};
+use reload::ConfigReload;
+use request_log::{RequestLog, RequestLogConfig};
+use responders::{
+    error_response, etag_for, ApiError, Attachment, FieldError, Paginated, Tagged,
+    TransitionFailure,
+};
+use retention::RetentionJob;
+use sandbox::SandboxWipe;
+use secrets::SecretRefresh;
+use state_machine::TransitionError;
+use storage::anonymization::Anonymization;
+use storage::availability::{AvailabilityCalendar, AvailabilityQuery, AvailabilityResult};
+use storage::booking_addon::BookingAddon;
+use storage::booking_change::ChangeSet;
+use storage::booking_cursor::BookingCursor;
+use storage::booking_delta::BookingDelta;
+use storage::booking_filter::BookingFilter;
+use storage::booking_history::BookingHistoryEntry;
+use storage::booking_page::BookingPage;
+use storage::booking_summary::{BookingView, View};
+use storage::booking_validation::BookingValidation;
+use storage::bulk_status::{BulkStatusResult, BulkStatusUpdate};
+use storage::cancellation::{Cancellation, CancellationReport};
+use storage::cdc_record::CdcPage;
+use storage::corporate_account::{AccountReport, CorporateAccount};
+use storage::create_error::CreateError;
+use storage::customer_export::CustomerExport;
+use storage::exchange_rate::{ExchangeRate, ExchangeRateOverride};
+use storage::inventory_adjustment::InventoryAdjustment;
+use storage::ledger::{self, LedgerEntry};
+use storage::metrics::StorageMetrics;
+use storage::outbox_event::OutboxEvent;
+use storage::payment::{Balance, Payment, Refund, RefundReservation};
+use storage::payment_event::PaymentEvent;
+use storage::pricing_record::PricingRecord;
+use storage::promo_code::PromoCode;
+use storage::recurring_booking::{RecurringBooking, SeriesUpdate};
+use storage::refund_error::RefundError;
+use storage::retention::RetentionReport;
 use storage::room_booking::*;
+use storage::screening_decision::ScreeningDecision;
+use storage::sell_restriction::SellRestriction;
+use streaming::{StreamFormat, Streamed};
+use tls::{CertificateReload, Certificates, CertificatesConfig};
+use versioning::Versioning;
 
 #[doc(hidden)]
 /// # Create a room booking with the provided data
 ///
-/// Creates the room booking with the provided booking data. Returns the booking.
+/// Creates the room booking with the provided booking data. The booking is confirmed, or pending
+/// payment if the hotel requires a deposit. If a promo code is provided, it is redeemed and its
+/// discount applied to the booking. Returns the booking, along with links to the actions
+/// available for it. Bookings which fail validation, or contain a field which is not part of a
+/// booking, e.g. a misspelt field, are rejected with a description of each problem found, listing
+/// the field affected where known. The whitespace surrounding text fields is removed. Bookings
+/// for which no room is available, or whose promo code may not be redeemed, are rejected as a
+/// conflict.
+///
+/// Bookings made by the same customer, for the same room type and overlapping dates, as a booking
+/// created within the last few minutes are rejected as a likely duplicate, with the path of the
+/// existing booking given in conflictsWith, unless allow_duplicate is true.
+///
+/// New bookings are screened against the hotel's abuse and fraud rules. A booking matching a rule
+/// may be held pending review, in which case it must be approved before it is confirmed, or
+/// rejected. The rules a booking matched are recorded for audit, but not returned.
+///
+/// If the sandbox is enabled, bookings with sandbox set to true are created as sandbox bookings,
+/// which do not hold a real room, are left out of reports and events, and are removed
+/// periodically.
 #[openapi(tag = "Room Booking")]
-#[post("/booking", format = "json", data = "<booking_details>")]
+#[post(
+    "/booking?<allow_duplicate>",
+    format = "json",
+    data = "<booking_details>"
+)]
 pub fn create_room_booking(
-    booking_details: Json<RoomBooking>,
-) -> Result<Json<RoomBooking>, Status> {
-    let result: Result<RoomBooking, ()> = storage::create(booking_details.into_inner());
-    match result {
-        Ok(booking) => Ok(Json(booking)),
-        Err(_) => Err(Status::BadRequest),
-    }
+    booking_details: Result<Json<RoomBooking>, json::Error<'_>>,
+    allow_duplicate: Option<bool>,
+) -> Result<Json<BookingResource>, ApiError<400, 409, 413, 422, 503>> {
+    let booking: RoomBooking = read_booking(booking_details)?;
+    let result: Result<RoomBooking, CreateError> =
+        storage::create(booking, allow_duplicate.unwrap_or(false));
+    return match result {
+        Ok(booking) => Ok(Json(BookingResource::new(booking))),
+        Err(CreateError::Duplicate(booking_id)) => Err(error_response(
+            Status::Conflict,
+            &CreateError::Duplicate(booking_id).to_string(),
+        )
+        .conflicting_with(format!("/v1/booking/{}", booking_id))),
+        Err(error) => {
+            let status: Status = match error {
+                CreateError::Invalid(_) | CreateError::Rejected => Status::UnprocessableEntity,
+                CreateError::NoRoomAvailable
+                | CreateError::PromoCode(_)
+                | CreateError::Duplicate(_) => Status::Conflict,
+                CreateError::Unavailable => Status::ServiceUnavailable,
+            };
+            let fields: Vec<FieldError> = match &error {
+                CreateError::Invalid(errors) => field_errors(errors),
+                _ => Vec::new(),
+            };
+            Err(error_response(status, &error.to_string()).with_fields(fields))
+        }
+    };
+}
+
+#[doc(hidden)]
+/// # Check whether a room booking would be created
+///
+/// Runs the same validation, availability and screening checks as creating the booking, without
+/// storing it, redeeming its promo code or recording its screening. Returns whether the booking
+/// would be created, along with the status it would be created with, its price, the deposit
+/// required and the time the deposit would be due by. Bookings which would be rejected are
+/// returned with valid set to false, and a description of the problem, listing each field
+/// affected where known, or the path of the booking it would duplicate in conflictsWith.
+///
+/// As nothing is reserved, a booking which would be created may still be rejected if another
+/// booking is created for the same room type first.
+#[openapi(tag = "Room Booking")]
+#[post(
+    "/booking/validate?<allow_duplicate>",
+    format = "json",
+    data = "<booking_details>"
+)]
+pub fn validate_room_booking(
+    booking_details: Result<Json<RoomBooking>, json::Error<'_>>,
+    allow_duplicate: Option<bool>,
+    language: Language,
+) -> Result<Json<BookingValidation>, ApiError<400, 413, 422, 503>> {
+    let booking: RoomBooking = read_booking(booking_details)?;
+    let result: Result<RoomBooking, CreateError> =
+        storage::check_create(booking, allow_duplicate.unwrap_or(false));
+    return match result {
+        Ok(booking) => Ok(Json(BookingValidation {
+            valid: true,
+            error: None,
+            fields: Vec::new(),
+            conflicts_with: None,
+            status: booking.status.clone(),
+            price: pricing::quote(&booking).ok(),
+            deposit: match config::hotel().deposit_required {
+                true => Balance::new(&booking).deposit,
+                false => 0,
+            },
+            payment_due_by: booking.payment_due_by,
+        })),
+        Err(CreateError::Unavailable) => Err(error_response(
+            Status::ServiceUnavailable,
+            &CreateError::Unavailable.to_string(),
+        )),
+        Err(error) => {
+            let mut validation: BookingValidation =
+                BookingValidation::rejected(locale::translate(&language.0, &error.to_string()));
+            match error {
+                CreateError::Invalid(errors) => {
+                    validation.fields = field_errors(&errors)
+                        .into_iter()
+                        .map(|mut field| {
+                            field.error = locale::translate(&language.0, &field.error);
+                            return field;
+                        })
+                        .collect()
+                }
+                CreateError::Duplicate(booking_id) => {
+                    validation.conflicts_with = Some(format!("/v1/booking/{}", booking_id))
+                }
+                _ => {}
+            }
+            Ok(Json(validation))
+        }
+    };
+}
+
+/// Reads a booking from a request body, removing the whitespace surrounding each text field.
+/// Returns an error describing why the body could not be read, including the field affected if
+/// the error names one, e.g. a misspelt field.
+///
+/// # Arguments
+///
+/// * `body` - The booking read from the request body, or the error returned while reading it
+fn read_booking<const A: u16, const B: u16, const C: u16, const D: u16, const E: u16>(
+    body: Result<Json<RoomBooking>, json::Error<'_>>,
+) -> Result<RoomBooking, ApiError<A, B, C, D, E>> {
+    let mut booking: RoomBooking = match body {
+        Ok(booking) => booking.into_inner(),
+        Err(json::Error::Io(error)) if error.kind() == ErrorKind::UnexpectedEof => {
+            return Err(error_response(
+                Status::PayloadTooLarge,
+                "The request body is too large",
+            ))
+        }
+        Err(json::Error::Io(error)) => {
+            return Err(error_response(Status::BadRequest, &error.to_string()))
+        }
+        Err(json::Error::Parse(_, error)) => {
+            let status: Status = match error.classify() {
+                Category::Data => Status::UnprocessableEntity,
+                _ => Status::BadRequest,
+            };
+            let fields: Vec<FieldError> =
+                FieldError::from_parse_error(&error).into_iter().collect();
+            return Err(error_response(status, &error.to_string()).with_fields(fields));
+        }
+    };
+
+    booking.trim();
+    return Ok(booking);
+}
+
+/// Returns the field errors described by a list of validation messages, leaving out any message
+/// which does not name a field.
+///
+/// # Arguments
+///
+/// * `errors` - The validation messages
+fn field_errors(errors: &[String]) -> Vec<FieldError> {
+    return errors
+        .iter()
+        .filter_map(|error| FieldError::from_message(error))
+        .collect();
 }
 
 #[doc(hidden)]
 /// # Get room booking for the specified id
 ///
-/// Returns booking details.
+/// Returns booking details and links to the actions available for the booking's current status,
+/// with ETag and Last-Modified headers.
 #[openapi(tag = "Room Booking")]
 #[get("/booking/<booking_id>")]
-pub fn get_room_booking(booking_id: u32) -> Result<Json<RoomBooking>, Status> {
+pub fn get_room_booking(booking_id: u32) -> Result<Tagged<Json<BookingResource>>, ApiError<404>> {
+    let result: Option<RoomBooking> = storage::fetch_by_id(booking_id);
+    match result {
+        Some(booking) => Ok(Tagged::new(
+            Json(BookingResource::new(booking.clone())),
+            etag_for(&booking),
+            storage::last_modified(booking_id),
+        )),
+        None => Err(error_response(Status::NotFound, "Booking not found")),
+    }
+}
+
+#[doc(hidden)]
+/// # Check a room booking exists for the specified id
+///
+/// Returns the booking's ETag and Last-Modified headers, without a body.
+#[openapi(tag = "Room Booking")]
+#[head("/booking/<booking_id>")]
+pub fn head_room_booking(booking_id: u32) -> Result<Tagged<()>, ApiError<404>> {
     let result: Option<RoomBooking> = storage::fetch_by_id(booking_id);
     match result {
-        Some(booking) => Ok(Json(booking)),
-        None => Err(Status::NotFound),
+        Some(booking) => Ok(Tagged::new(
+            (),
+            etag_for(&booking),
+            storage::last_modified(booking_id),
+        )),
+        None => Err(error_response(Status::NotFound, "Booking not found")),
+    }
+}
+
+#[doc(hidden)]
+/// # Get the invoice for the booking with the provided booking id
+///
+/// Returns the price of the booking, itemizing the room charges and each add-on. The booking's
+/// dates and each amount are also given formatted for the locale asked for by the
+/// ```Accept-Language``` header, following the hotel's region where the header gives none.
+#[openapi(tag = "Room Booking")]
+#[get("/booking/<booking_id>/invoice")]
+pub fn get_room_booking_invoice(
+    booking_id: u32,
+    locale: Locale,
+) -> Result<Json<Invoice>, ApiError<404, 409>> {
+    let booking: RoomBooking = match storage::fetch_by_id(booking_id) {
+        Some(booking) => booking,
+        None => return Err(error_response(Status::NotFound, "Booking not found")),
+    };
+
+    return match pricing::quote(&booking) {
+        Ok(price) => Ok(Json(Invoice::new(&booking, price, &locale))),
+        Err(error) => Err(error_response(Status::Conflict, &error)),
+    };
+}
+
+#[doc(hidden)]
+/// # Get the pricing record for the booking with the provided booking id
+///
+/// Returns the rate plan and rate charged for each night, the room charges and the taxes in effect
+/// when the booking was priced. The booking's invoice is charged from this record, so later
+/// changes to rates and taxes do not change it.
+#[openapi(tag = "Room Booking")]
+#[get("/booking/<booking_id>/pricing")]
+pub fn get_room_booking_pricing(booking_id: u32) -> Result<Json<PricingRecord>, ApiError<404>> {
+    return match storage::fetch_by_id(booking_id).and_then(|booking| booking.pricing) {
+        Some(record) => Ok(Json(record)),
+        None => Err(error_response(
+            Status::NotFound,
+            "Booking not found, or it has not been priced",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// # Get the balance of the booking with the provided booking id
+///
+/// Returns the total price of the booking, the deposit required, the total of the payments
+/// received, and the amount still to pay. The booking must be paid in full before the guest
+/// checks in.
+#[openapi(tag = "Room Booking")]
+#[get("/booking/<booking_id>/balance")]
+pub fn get_room_booking_balance(booking_id: u32) -> Result<Json<Balance>, ApiError<404>> {
+    return match storage::fetch_by_id(booking_id) {
+        Some(booking) => Ok(Json(Balance::new(&booking))),
+        None => Err(error_response(Status::NotFound, "Booking not found")),
+    };
+}
+
+#[doc(hidden)]
+/// # Record a payment for the booking with the provided booking id
+///
+/// Records a payment received for the booking, which may be a deposit or a partial payment. A
+/// booking pending payment is confirmed once its deposit has been paid. Returns the booking's
+/// balance after the payment.
+#[openapi(tag = "Room Booking")]
+#[post("/booking/<booking_id>/payments", format = "json", data = "<payment>")]
+pub fn record_room_booking_payment(
+    booking_id: u32,
+    payment: Json<Payment>,
+) -> Result<Json<Balance>, ApiError<400, 404>> {
+    if storage::fetch_by_id(booking_id).is_none() {
+        return Err(error_response(Status::NotFound, "Booking not found"));
     }
+
+    return match storage::record_payment(booking_id, payment.into_inner()) {
+        Ok(balance) => Ok(Json(balance)),
+        Err(error) => Err(error_response(Status::BadRequest, &error)),
+    };
+}
+
+#[doc(hidden)]
+/// # Refund the booking with the provided booking id
+///
+/// Refunds part or all of the amount paid for the booking. The refund must not be more than the
+/// amount paid, less any earlier refunds and the amount kept under the cancellation policy. A
+/// booking cancelled within the cancellation window keeps its deposit, and a booking where the
+/// guest did not arrive keeps its no show fee. If a payment provider is configured, the refund is
+/// requested from the provider, and recorded with the provider's reference, unless the booking is
+/// a sandbox booking. Returns the refund as recorded.
+#[openapi(tag = "Room Booking")]
+#[post("/booking/<booking_id>/refund", format = "json", data = "<refund>")]
+pub async fn refund_room_booking(
+    booking_id: u32,
+    refund: Json<Refund>,
+) -> Result<Json<Refund>, ApiError<400, 404, 409, 502, 503>> {
+    let mut refund: Refund = refund.into_inner();
+
+    // The refund is reserved before the provider is called, so refunds made at the same time
+    // cannot together refund more than the refundable amount.
+    let reservation: RefundReservation = match storage::reserve_refund(booking_id, refund.amount) {
+        Ok(reservation) => reservation,
+        Err(error) => return Err(refund_error_response(&error)),
+    };
+
+    if let (Some(settings), false) = (config::hotel().payment_provider, reservation.sandbox) {
+        let request = RefundRequest::new(booking_id, &refund, &config::hotel().currency);
+        refund.reference =
+            match payment_provider::refund(&settings, &request, &reservation.idempotency_key).await
+            {
+                Ok(reference) => Some(reference),
+                Err(error) => {
+                    storage::release_refund(&reservation);
+                    return Err(error_response(
+                        Status::BadGateway,
+                        &format!("The payment provider could not make the refund: {}", error),
+                    ));
+                }
+            };
+    }
+
+    return match storage::complete_refund(&reservation, refund) {
+        Ok(refund) => Ok(Json(refund)),
+        Err(error) => Err(refund_error_response(&error)),
+    };
+}
+
+/// Returns the error response for a refund which could not be made.
+///
+/// # Arguments
+///
+/// * `error` - Why the refund could not be made
+fn refund_error_response(error: &RefundError) -> ApiError<400, 404, 409, 502, 503> {
+    let status: Status = match error {
+        RefundError::InvalidAmount => Status::BadRequest,
+        RefundError::ExceedsRefundable(_) => Status::Conflict,
+        RefundError::NotFound => Status::NotFound,
+        RefundError::Unavailable => Status::ServiceUnavailable,
+    };
+
+    return error_response(status, &error.to_string());
+}
+
+#[doc(hidden)]
+/// # Receive a payment event from the payment provider
+///
+/// Receives a payment success, failure or refund event from the payment provider. The body must
+/// be signed with the hex encoded HMAC-SHA256 of the body, using the shared secret, sent in the
+/// configured signature header. Events are applied to their booking in the background, so the
+/// event is accepted once stored. Events which have already been received are ignored.
+#[openapi(tag = "Webhooks")]
+#[post("/webhooks/payments", data = "<body>")]
+fn receive_payment_webhook(
+    signature: Signature,
+    body: Capped<&str>,
+) -> Result<Status, ApiError<400, 401, 404, 413, 503>> {
+    let settings = match config::hotel().payment_webhooks {
+        Some(settings) => settings,
+        None => {
+            return Err(error_response(
+                Status::NotFound,
+                "Payment webhook not enabled",
+            ))
+        }
+    };
+
+    if !body.is_complete() {
+        return Err(error_response(Status::PayloadTooLarge, "Body too large"));
+    }
+
+    let valid: bool = signature
+        .0
+        .is_some_and(|signature| payment_webhooks::verify(&settings.secret, &body, &signature));
+    if !valid {
+        return Err(error_response(Status::Unauthorized, "Invalid signature"));
+    }
+
+    let event: PaymentEvent = match rocket::serde::json::from_str(&body) {
+        Ok(event) => event,
+        Err(error) => return Err(error_response(Status::BadRequest, &error.to_string())),
+    };
+
+    return match storage::receive_payment_event(event) {
+        Some(true) => Ok(Status::Accepted),
+        Some(false) => Ok(Status::Ok),
+        None => Err(error_response(
+            Status::ServiceUnavailable,
+            "Storage unavailable",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// # Attach an add-on to the booking with the provided booking id
+///
+/// Attaches an add-on from the catalog, such as breakfast or parking, to the booking. The add-on
+/// is priced from the catalog. Returns the updated booking.
+#[openapi(tag = "Room Booking")]
+#[post("/booking/<booking_id>/addons", format = "json", data = "<addon>")]
+pub fn add_room_booking_addon(
+    booking_id: u32,
+    addon: Json<BookingAddon>,
+) -> Result<Json<BookingResource>, ApiError<400, 404>> {
+    if storage::fetch_by_id(booking_id).is_none() {
+        return Err(error_response(Status::NotFound, "Booking not found"));
+    }
+
+    return match storage::add_addon(booking_id, addon.into_inner()) {
+        Ok(booking) => Ok(Json(BookingResource::new(booking))),
+        Err(errors) => Err(error_response(Status::BadRequest, &errors.join(", "))),
+    };
+}
+
+#[doc(hidden)]
+/// # Remove an add-on from the booking with the provided booking id
+///
+/// Removes the add-on with the provided code from the booking. Returns the updated booking.
+#[openapi(tag = "Room Booking")]
+#[delete("/booking/<booking_id>/addons/<code>")]
+pub fn remove_room_booking_addon(
+    booking_id: u32,
+    code: &str,
+) -> Result<Json<BookingResource>, ApiError<404>> {
+    return match storage::remove_addon(booking_id, code) {
+        Some(booking) => Ok(Json(BookingResource::new(booking))),
+        None => Err(error_response(
+            Status::NotFound,
+            "Booking or add-on not found",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// # Get the history of the booking with the provided booking id
+///
+/// Returns every change made to the booking, in the order it was made, with the time of the
+/// change and each field changed, including status transitions.
+#[openapi(tag = "Room Booking")]
+#[get("/booking/<booking_id>/history")]
+pub fn get_room_booking_history(
+    booking_id: u32,
+) -> Result<Json<Vec<BookingHistoryEntry>>, ApiError<404>> {
+    return match storage::fetch_history(booking_id) {
+        Some(history) => Ok(Json(history)),
+        None => Err(error_response(Status::NotFound, "Booking not found")),
+    };
+}
+
+#[doc(hidden)]
+/// # Check in the guest of the booking with the provided booking id
+///
+/// Sets the status of a confirmed booking to 'CheckedIn', when the guest arrives. The booking must
+/// be paid in full. Returns the updated booking, or an error describing why the guest could not be
+/// checked in.
+#[openapi(tag = "Room Booking")]
+#[put("/booking/<booking_id>/check-in")]
+pub fn check_in_room_booking(booking_id: u32) -> Result<Json<BookingResource>, TransitionFailure> {
+    storage::status(booking_id, BookingStatus::CheckedIn)
+        .map_err(|error| failure(booking_id, error))?;
+    return transitioned(booking_id);
 }
 
 #[doc(hidden)]
 /// # Complete the booking with the provided booking id
 ///
-/// Sets the status of the room booking specified to 'Complete'. Returns details of the booking.
+/// Sets the status of a checked in booking to 'Complete', when the guest checks out. Returns the
+/// updated booking, or an error describing why the booking could not be completed.
 #[openapi(tag = "Room Booking")]
 #[put("/booking/<booking_id>/complete")]
-pub fn complete_room_booking(booking_id: u32) -> Json<bool> {
-    Json(storage::status(booking_id, BookingStatus::Complete))
+pub fn complete_room_booking(booking_id: u32) -> Result<Json<BookingResource>, TransitionFailure> {
+    storage::status(booking_id, BookingStatus::Complete)
+        .map_err(|error| failure(booking_id, error))?;
+    return transitioned(booking_id);
 }
 
 #[doc(hidden)]
 /// # Cancel the booking with the provided booking id
 ///
-/// Sets the booking status to 'Cancelled' for the booking with the provided id. Returns true on success, false on failure.
+/// Sets the booking status to 'Cancelled' for the booking with the provided id. A reason code and
+/// comment may be provided in the request body, which are stored on the booking. Returns the
+/// updated booking, or an error describing why the booking could not be cancelled.
 #[openapi(tag = "Room Booking")]
-#[delete("/booking/<booking_id>")]
-pub fn cancel_room_booking(booking_id: u32) -> Json<bool> {
-    Json(storage::status(booking_id, BookingStatus::Cancelled))
+#[delete("/booking/<booking_id>", data = "<cancellation>")]
+pub fn cancel_room_booking(
+    booking_id: u32,
+    cancellation: Option<Json<Cancellation>>,
+) -> Result<Json<BookingResource>, TransitionFailure> {
+    storage::cancel(booking_id, cancellation.map(|json| json.into_inner()))
+        .map_err(|error| failure(booking_id, error))?;
+    return transitioned(booking_id);
 }
 
 #[doc(hidden)]
-/// # Get all room bookings
+/// # Mark the booking with the provided booking id as a no-show
 ///
-/// Returns a list containing all room bookings in the system
-#[openapi(tag = "Room Bookings")]
-#[get("/bookings")]
-fn get_room_bookings() -> Json<Vec<RoomBooking>> {
-    return Json(storage::fetch_all());
+/// Sets the status of the booking to 'NoShow', when the guest has not arrived, and applies the
+/// no-show fee set by the hotel's policy. This is recorded separately from a cancellation. Returns
+/// the updated booking, or an error describing why the booking could not be marked as a no-show.
+#[openapi(tag = "Room Booking")]
+#[put("/booking/<booking_id>/no-show")]
+pub fn no_show_room_booking(booking_id: u32) -> Result<Json<BookingResource>, TransitionFailure> {
+    storage::status(booking_id, BookingStatus::NoShow)
+        .map_err(|error| failure(booking_id, error))?;
+    return transitioned(booking_id);
 }
 
 #[doc(hidden)]
-/// # Get room bookings for the specified customer id
+/// # Reinstate the cancelled booking with the provided booking id
 ///
-/// Returns a list of bookings.
-#[openapi(tag = "Room Bookings")]
-#[get("/bookings/customer/<customer_id>")]
-fn get_customer_room_bookings(customer_id: u32) -> Json<Vec<RoomBooking>> {
-    return Json(storage::fetch_by_customer_id(customer_id));
+/// Sets the status of a cancelled booking back to 'Confirmed', if a room is still available for
+/// its dates. A booking cancelled while held for review moves back to 'PendingReview', and a
+/// booking whose deposit is still owed moves to 'PendingPayment', with a new payment due date.
+/// Returns the updated booking, or an error describing why the booking could not be reinstated.
+#[openapi(tag = "Room Booking")]
+#[put("/booking/<booking_id>/reinstate")]
+pub fn reinstate_room_booking(booking_id: u32) -> Result<Json<BookingResource>, TransitionFailure> {
+    storage::reinstate(booking_id).map_err(|error| failure(booking_id, error))?;
+    return transitioned(booking_id);
 }
 
 #[doc(hidden)]
-/// # Get room bookings starting on the provided date
+/// # Confirm payment for the booking with the provided booking id
 ///
-/// Returns a list of bookings.
+/// Sets the status of a booking which is pending payment to 'Confirmed', once its deposit has been
+/// paid. Returns the updated booking, or an error describing why the payment could not be
+/// confirmed, including the amount still owed if the deposit has not been paid in full.
+#[openapi(tag = "Room Booking")]
+#[put("/booking/<booking_id>/confirm-payment")]
+pub fn confirm_room_booking_payment(
+    booking_id: u32,
+) -> Result<Json<BookingResource>, TransitionFailure> {
+    storage::status_from(
+        booking_id,
+        BookingStatus::PendingPayment,
+        BookingStatus::Confirmed,
+    )
+    .map_err(|error| failure(booking_id, error))?;
+    return transitioned(booking_id);
+}
+
+#[doc(hidden)]
+/// # Approve the booking with the provided booking id
+///
+/// Approves a booking held pending review by a screening rule. The booking moves to 'PendingPayment'
+/// if the hotel requires a deposit, and to 'Confirmed' otherwise. Returns the updated booking, or
+/// an error describing why the booking could not be approved.
+#[openapi(tag = "Room Booking")]
+#[put("/booking/<booking_id>/approve")]
+pub fn approve_room_booking(booking_id: u32) -> Result<Json<BookingResource>, TransitionFailure> {
+    storage::approve(booking_id).map_err(|error| failure(booking_id, error))?;
+    return transitioned(booking_id);
+}
+
+#[doc(hidden)]
+/// Returns the booking with the provided id after its status has been changed, along with links to
+/// the actions available for its new status.
+fn transitioned(booking_id: u32) -> Result<Json<BookingResource>, TransitionFailure> {
+    return match storage::fetch_by_id(booking_id) {
+        Some(booking) => Ok(Json(BookingResource::new(booking))),
+        None => Err(TransitionError::NotFound.into()),
+    };
+}
+
+/// Describes a failed status change, including the current state of the booking if the change
+/// conflicts with it.
+///
+/// # Arguments
+///
+/// * `booking_id` - The id of the booking
+/// * `error` - The error returned by the status change
+fn failure(booking_id: u32, error: TransitionError) -> TransitionFailure {
+    return TransitionFailure::new(error, storage::fetch_by_id(booking_id));
+}
+
+/// Checks a batch request contains no more items than the configured maximum batch size.
+///
+/// # Arguments
+///
+/// * `size` - The number of items in the batch
+fn check_batch_size<const A: u16, const B: u16, const C: u16, const D: u16, const E: u16>(
+    size: usize,
+) -> Result<(), ApiError<A, B, C, D, E>> {
+    let max_batch_size: u32 = config::hotel().max_batch_size;
+    if size > max_batch_size as usize {
+        return Err(error_response(
+            Status::PayloadTooLarge,
+            &format!(
+                "The batch must not contain more than {} items",
+                max_batch_size
+            ),
+        ));
+    }
+
+    return Ok(());
+}
+
+#[doc(hidden)]
+/// # Update the status of several bookings
+///
+/// Applies the provided status to each booking in the list whose current status may move to it,
+/// e.g. 'Complete' or 'Cancelled'. Returns the outcome of the update for each booking, including
+/// why any update failed. The list may not contain more bookings than the maximum batch size.
 #[openapi(tag = "Room Bookings")]
-#[get("/bookings/date/<date>")]
-fn get_bookings_starting_on_date(date: &str) -> Json<Vec<RoomBooking>> {
-    return Json(storage::fetch_by_check_in_date(date));
+#[post("/bookings/status", format = "json", data = "<update>")]
+fn update_room_booking_statuses(
+    update: Json<BulkStatusUpdate>,
+) -> Result<Json<Vec<BulkStatusResult>>, ApiError<400, 413>> {
+    let update: BulkStatusUpdate = update.into_inner();
+    check_batch_size(update.booking_ids.len())?;
+    if update.status == BookingStatus::Confirmed {
+        return Err(error_response(
+            Status::BadRequest,
+            "status must not be Confirmed",
+        ));
+    }
+
+    return Ok(Json(storage::bulk_status(
+        &update.booking_ids,
+        update.status,
+    )));
 }
 
 #[doc(hidden)]
-/// # Get room bookings for the specified room type
+/// # Get a quote for a room booking
 ///
-/// Returns a list of bookings.
+/// Returns the price of the provided booking details, itemizing the room charges, each add-on and
+/// the discount given by any promo code, without creating the booking or redeeming the code.
+/// Nightly rates are requested from the dynamic pricing strategy, if one is configured, and the
+/// configured rates are charged if it is unavailable. If a currency (ISO 4217) other than the
+/// hotel's is requested, prices are converted at the current exchange rate.
 #[openapi(tag = "Room Bookings")]
-#[get("/bookings/room-type/<room_type_id>")]
-fn get_room_type_bookings(room_type_id: u8) -> Json<Vec<RoomBooking>> {
-    return Json(storage::fetch_by_room_type_id(room_type_id));
+#[post(
+    "/bookings/quote?<currency>",
+    format = "json",
+    data = "<booking_details>"
+)]
+async fn quote_room_booking(
+    booking_details: Result<Json<RoomBooking>, json::Error<'_>>,
+    currency: Option<&str>,
+) -> Result<Json<PriceBreakdown>, ApiError<400, 413, 422>> {
+    let mut booking: RoomBooking = read_booking(booking_details)?;
+    booking.status = None;
+    booking.pricing = None;
+
+    let errors: Vec<String> = booking.validate();
+    if !errors.is_empty() {
+        return Err(error_response(Status::BadRequest, &errors.join(", "))
+            .with_fields(field_errors(&errors)));
+    }
+
+    booking.promo_code = booking.promo_code.map(|code| code.to_uppercase());
+    booking.discount = match storage::check_promo_code(&booking) {
+        Ok(discount) => discount,
+        Err(error) => return Err(error_response(Status::BadRequest, &error)),
+    };
+    booking.billing_milestones = pricing::billing_milestones(&booking);
+    addons::reprice(&mut booking.addons);
+
+    let dynamic_rates: HashMap<String, u64> = dynamic_pricing::nightly_rates(&booking).await;
+
+    let quote: PriceBreakdown = match pricing::quote_with_rates(&booking, &dynamic_rates) {
+        Ok(quote) => quote,
+        Err(error) => return Err(error_response(Status::BadRequest, &error)),
+    };
+
+    let currency: String = match currency {
+        Some(currency) if !currency.eq_ignore_ascii_case(&quote.currency) => {
+            currency.to_uppercase()
+        }
+        _ => return Ok(Json(quote)),
+    };
+
+    return match exchange_rates::rate(&currency).await {
+        Ok(rate) => Ok(Json(quote.convert(&rate))),
+        Err(error) => Err(error_response(Status::UnprocessableEntity, &error)),
+    };
 }
 
 #[doc(hidden)]
-#[rocket::main]
-async fn main() {
-    if storage::snapshot_exists() {
-        match storage::load_snapshot() {
-            Ok(_) => println!("Loaded snapshot..."),
-            Err(err) => println!("An error occurred loading snapshot: {}", err),
-        }
-    }
-
-    let launch_result = rocket::build()
-        .mount(
-            "/",
-            openapi_get_routes![
-                get_room_booking,
-                create_room_booking,
-                complete_room_booking,
-                cancel_room_booking,
-                get_room_bookings,
-                get_customer_room_bookings,
-                get_bookings_starting_on_date,
-                get_room_type_bookings
-            ],
-        )
-        .mount(
-            "/swagger-ui/",
-            make_swagger_ui(&SwaggerUIConfig {
-                url: "../openapi.json".to_owned(),
-                ..Default::default()
-            }),
-        )
-        .launch()
-        .await;
-    match launch_result {
-        Ok(_) => println!("Shutdown complete."),
-        Err(err) => println!("An error occurred during shutdown: {}", err),
+/// # Get the add-on catalog
+///
+/// Returns every add-on which may be attached to a booking, with its price.
+#[openapi(tag = "Add-ons")]
+#[get("/addons")]
+fn get_addon_catalog() -> Json<Vec<Addon>> {
+    return Json(addons::catalog());
+}
+
+#[doc(hidden)]
+/// # Get every promo code
+///
+/// Returns every promo code, with the number of times each has been redeemed.
+#[openapi(tag = "Promo Codes")]
+#[get("/promo-codes")]
+fn get_promo_codes() -> Json<Vec<PromoCode>> {
+    return Json(storage::fetch_promo_codes());
+}
+
+#[doc(hidden)]
+/// # Create a promo code
+///
+/// Creates a promo code giving a percentage or fixed amount discount, optionally limited to a
+/// validity window, a number of redemptions and a list of room types. Codes are matched regardless
+/// of case. Returns the promo code.
+#[openapi(tag = "Promo Codes")]
+#[post("/promo-codes", format = "json", data = "<promo_code>")]
+fn create_promo_code(promo_code: Json<PromoCode>) -> Result<Json<PromoCode>, ApiError<400>> {
+    return match storage::create_promo_code(promo_code.into_inner()) {
+        Ok(promo_code) => Ok(Json(promo_code)),
+        Err(errors) => Err(error_response(Status::BadRequest, &errors.join(", "))),
+    };
+}
+
+#[doc(hidden)]
+/// # Get the promo code with the provided code
+#[openapi(tag = "Promo Codes")]
+#[get("/promo-codes/<code>")]
+fn get_promo_code(code: &str) -> Result<Json<PromoCode>, ApiError<404>> {
+    return match storage::fetch_promo_code(code) {
+        Some(promo_code) => Ok(Json(promo_code)),
+        None => Err(error_response(Status::NotFound, "Promo code not found")),
+    };
+}
+
+#[doc(hidden)]
+/// # Delete the promo code with the provided code
+///
+/// Deletes the promo code, so it can no longer be redeemed. Bookings which have already redeemed
+/// the code keep their discount. Returns the deleted promo code.
+#[openapi(tag = "Promo Codes")]
+#[delete("/promo-codes/<code>")]
+fn delete_promo_code(code: &str) -> Result<Json<PromoCode>, ApiError<404>> {
+    return match storage::delete_promo_code(code) {
+        Some(promo_code) => Ok(Json(promo_code)),
+        None => Err(error_response(Status::NotFound, "Promo code not found")),
+    };
+}
+
+#[doc(hidden)]
+/// # Get every sell restriction
+///
+/// Returns every blackout period and closed to arrival or departure restriction, ordered by start
+/// date.
+#[openapi(tag = "Sell Restrictions")]
+#[get("/restrictions")]
+fn get_sell_restrictions() -> Json<Vec<SellRestriction>> {
+    return Json(storage::fetch_restrictions());
+}
+
+#[doc(hidden)]
+/// # Create a sell restriction with the provided data
+///
+/// Creates a blackout period, during which no bookings may be made, or a period during which
+/// bookings may not check in (closed to arrival) or check out (closed to departure). A
+/// restriction applies to a single room type, or every room type if no room type is provided.
+/// Bookings which are already made are unaffected.
+#[openapi(tag = "Sell Restrictions")]
+#[post("/restrictions", format = "json", data = "<restriction>")]
+fn create_sell_restriction(
+    restriction: Json<SellRestriction>,
+) -> Result<Json<SellRestriction>, ApiError<400>> {
+    return match storage::create_restriction(restriction.into_inner()) {
+        Ok(restriction) => Ok(Json(restriction)),
+        Err(errors) => Err(error_response(Status::BadRequest, &errors.join(", "))),
+    };
+}
+
+#[doc(hidden)]
+/// # Get the sell restriction with the provided id
+#[openapi(tag = "Sell Restrictions")]
+#[get("/restrictions/<restriction_id>")]
+fn get_sell_restriction(restriction_id: u32) -> Result<Json<SellRestriction>, ApiError<404>> {
+    return match storage::fetch_restriction(restriction_id) {
+        Some(restriction) => Ok(Json(restriction)),
+        None => Err(error_response(
+            Status::NotFound,
+            "Sell restriction not found",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// # Delete the sell restriction with the provided id
+///
+/// Returns the deleted restriction.
+#[openapi(tag = "Sell Restrictions")]
+#[delete("/restrictions/<restriction_id>")]
+fn delete_sell_restriction(restriction_id: u32) -> Result<Json<SellRestriction>, ApiError<404>> {
+    return match storage::delete_restriction(restriction_id) {
+        Some(restriction) => Ok(Json(restriction)),
+        None => Err(error_response(
+            Status::NotFound,
+            "Sell restriction not found",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// # Get the availability of a room type on each day of a month
+///
+/// Returns the number of overnight bookings holding a room of the room type on each night of the
+/// provided month (YYYY-MM), or the current month in the hotel's timezone if no month is given,
+/// with the number of rooms which may still be sold. Remaining capacity includes any inventory
+/// adjustments and overbooking allowance, and is null for room types which are not limited by the
+/// hotel's room inventory.
+#[openapi(tag = "Availability")]
+#[get("/availability/calendar?<room_type_id>&<month>")]
+fn get_availability_calendar(
+    room_type_id: u8,
+    month: Option<&str>,
+) -> Result<Json<AvailabilityCalendar>, ApiError<400>> {
+    let month: String = parse_month(month)?;
+    let key: String = format!("calendar:{}:{}", room_type_id, month);
+
+    return match storage::cached(&key, || {
+        return AvailabilityCalendar::new(
+            room_type_id,
+            &month,
+            &storage::occupied_rooms(room_type_id),
+            config::inventory_for(room_type_id),
+            &storage::fetch_adjustments(),
+        );
+    }) {
+        Some(calendar) => Ok(Json(calendar)),
+        None => Err(error_response(
+            Status::BadRequest,
+            "month must be in the format YYYY-MM",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// # Check the availability and price of several stays
+///
+/// Returns whether a room is available for each of the provided stays, and its indicative price,
+/// in the order given. Each stay is checked independently, so stays which are not valid are
+/// reported with the reasons they may not be booked, without affecting the others. The list may
+/// not contain more stays than the maximum batch size.
+#[openapi(tag = "Availability")]
+#[post("/availability/check", format = "json", data = "<queries>")]
+fn check_availability(
+    queries: Json<Vec<AvailabilityQuery>>,
+) -> Result<Json<Vec<AvailabilityResult>>, ApiError<413, 500>> {
+    check_batch_size(queries.len())?;
+
+    return match storage::check_availability(&queries) {
+        Some(results) => Ok(Json(results)),
+        None => Err(error_response(
+            Status::InternalServerError,
+            "Booking storage is unavailable",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// # Get every inventory adjustment
+///
+/// Returns every change made to the number of rooms of a room type which may be sold during a
+/// period, ordered by start date.
+#[openapi(tag = "Admin")]
+#[get("/admin/inventory/adjustments")]
+fn get_inventory_adjustments() -> Json<Vec<InventoryAdjustment>> {
+    return Json(storage::fetch_adjustments());
+}
+
+#[doc(hidden)]
+/// # Adjust the inventory of a room type during a period
+///
+/// Adds rooms to, or takes rooms out of, the inventory of a room type for each night from the
+/// start date to the end date, e.g. to take rooms out of service for maintenance. Adjustments to
+/// the same night are combined, and apply before the overbooking allowance. Room types which are
+/// not limited by the hotel's room inventory are unaffected. Bookings which are already made are
+/// unaffected.
+#[openapi(tag = "Admin")]
+#[post("/admin/inventory/adjustments", format = "json", data = "<adjustment>")]
+fn create_inventory_adjustment(
+    adjustment: Json<InventoryAdjustment>,
+) -> Result<Json<InventoryAdjustment>, ApiError<400>> {
+    return match storage::create_adjustment(adjustment.into_inner()) {
+        Ok(adjustment) => Ok(Json(adjustment)),
+        Err(errors) => Err(error_response(Status::BadRequest, &errors.join(", "))),
+    };
+}
+
+#[doc(hidden)]
+/// # Get the inventory adjustment with the provided id
+#[openapi(tag = "Admin")]
+#[get("/admin/inventory/adjustments/<adjustment_id>")]
+fn get_inventory_adjustment(
+    adjustment_id: u32,
+) -> Result<Json<InventoryAdjustment>, ApiError<404>> {
+    return match storage::fetch_adjustment(adjustment_id) {
+        Some(adjustment) => Ok(Json(adjustment)),
+        None => Err(error_response(
+            Status::NotFound,
+            "Inventory adjustment not found",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// # Delete the inventory adjustment with the provided id
+///
+/// Returns the deleted adjustment.
+#[openapi(tag = "Admin")]
+#[delete("/admin/inventory/adjustments/<adjustment_id>")]
+fn delete_inventory_adjustment(
+    adjustment_id: u32,
+) -> Result<Json<InventoryAdjustment>, ApiError<404>> {
+    return match storage::delete_adjustment(adjustment_id) {
+        Some(adjustment) => Ok(Json(adjustment)),
+        None => Err(error_response(
+            Status::NotFound,
+            "Inventory adjustment not found",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// # Get the exchange rate for a currency
+///
+/// Returns the rate prices are converted from the hotel's currency into the provided currency
+/// (ISO 4217) at. A rate set manually is used if there is one, and otherwise the rate from the
+/// configured exchange rate source, which is cached.
+#[openapi(tag = "Exchange Rates")]
+#[get("/exchange-rates/<currency>")]
+async fn get_exchange_rate(currency: &str) -> Result<Json<ExchangeRate>, ApiError<404>> {
+    return match exchange_rates::rate(&currency.to_uppercase()).await {
+        Ok(rate) => Ok(Json(rate)),
+        Err(error) => Err(error_response(Status::NotFound, &error)),
     };
 }
+
+#[doc(hidden)]
+/// # Get every exchange rate override
+///
+/// Returns every exchange rate set manually, ordered by currency.
+#[openapi(tag = "Admin")]
+#[get("/admin/exchange-rates")]
+fn get_exchange_rate_overrides() -> Json<Vec<ExchangeRateOverride>> {
+    return Json(storage::fetch_exchange_rates());
+}
+
+#[doc(hidden)]
+/// # Set the exchange rate for a currency
+///
+/// Sets the rate prices are converted from the hotel's currency into the provided currency
+/// (ISO 4217) at, as the number of units of the currency equal to one unit of the hotel's
+/// currency. The rate is used instead of the rate from the exchange rate source until it is
+/// deleted.
+#[openapi(tag = "Admin")]
+#[put("/admin/exchange-rates/<currency>", format = "json", data = "<rate>")]
+fn set_exchange_rate_override(
+    currency: &str,
+    rate: Json<ExchangeRateOverride>,
+) -> Result<Json<ExchangeRateOverride>, ApiError<400>> {
+    let mut rate: ExchangeRateOverride = rate.into_inner();
+    rate.currency = currency.to_uppercase();
+
+    return match storage::set_exchange_rate(rate) {
+        Ok(rate) => Ok(Json(rate)),
+        Err(errors) => Err(error_response(Status::BadRequest, &errors.join(", "))),
+    };
+}
+
+#[doc(hidden)]
+/// # Delete the exchange rate override for a currency
+///
+/// Deletes the rate set manually for the provided currency, so the rate from the exchange rate
+/// source is used. Returns the deleted override.
+#[openapi(tag = "Admin")]
+#[delete("/admin/exchange-rates/<currency>")]
+fn delete_exchange_rate_override(
+    currency: &str,
+) -> Result<Json<ExchangeRateOverride>, ApiError<404>> {
+    return match storage::delete_exchange_rate(&currency.to_uppercase()) {
+        Some(rate) => Ok(Json(rate)),
+        None => Err(error_response(
+            Status::NotFound,
+            "Exchange rate override not found",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// # Get every corporate account
+#[openapi(tag = "Corporate Accounts")]
+#[get("/accounts")]
+fn get_corporate_accounts() -> Json<Vec<CorporateAccount>> {
+    return Json(storage::fetch_accounts());
+}
+
+#[doc(hidden)]
+/// # Create a corporate account
+///
+/// Creates a corporate account with a billing reference and a rate plan, listing the negotiated
+/// rates for each room type the account may book. Returns the account, with its account id.
+#[openapi(tag = "Corporate Accounts")]
+#[post("/accounts", format = "json", data = "<account>")]
+fn create_corporate_account(
+    account: Json<CorporateAccount>,
+) -> Result<Json<CorporateAccount>, ApiError<400>> {
+    return match storage::create_account(account.into_inner()) {
+        Ok(account) => Ok(Json(account)),
+        Err(errors) => Err(error_response(Status::BadRequest, &errors.join(", "))),
+    };
+}
+
+#[doc(hidden)]
+/// # Report bookings made against each corporate account
+///
+/// Lists the bookings made against each corporate account which check out in the provided month
+/// (YYYY-MM), or the current month in the hotel's timezone if no month is given, with the price of
+/// each booking and the total to invoice. Cancelled bookings are not included.
+#[openapi(tag = "Corporate Accounts")]
+#[get("/accounts/report?<month>")]
+fn get_corporate_account_report(
+    month: Option<&str>,
+) -> Result<Json<Vec<AccountReport>>, ApiError<400>> {
+    let month: String = parse_month(month)?;
+
+    let reports: Vec<AccountReport> = storage::cached(&format!("accounts:{}", month), || {
+        return storage::fetch_accounts()
+            .into_iter()
+            .filter_map(|account| {
+                let bookings: Vec<RoomBooking> = storage::fetch_by_account_id(account.account_id?)
+                    .into_iter()
+                    .filter(|booking| booking.check_out_date.starts_with(&month))
+                    .filter(|booking| booking.status != Some(BookingStatus::Cancelled))
+                    .collect();
+
+                return Some(AccountReport::new(account, &bookings));
+            })
+            .collect();
+    });
+
+    return Ok(Json(reports));
+}
+
+#[doc(hidden)]
+/// # Report the commission due to each travel agent
+///
+/// Lists the bookings made by each travel agent which check out in the provided month (YYYY-MM),
+/// or the current month in the hotel's timezone if no month is given, with the price of each
+/// booking before tax and the commission due on it. Cancelled bookings are not included.
+#[openapi(tag = "Travel Agents")]
+#[get("/agents/commissions?<month>")]
+fn get_agent_commissions(
+    month: Option<&str>,
+) -> Result<Json<Vec<AgentCommissions>>, ApiError<400>> {
+    let month: String = parse_month(month)?;
+
+    let reports: Vec<AgentCommissions> = storage::cached(&format!("commissions:{}", month), || {
+        let filter = BookingFilter::default();
+        let bookings: Vec<RoomBooking> = storage::iter_filtered(&filter)
+            .filter(|booking| booking.check_out_date.starts_with(&month))
+            .filter(|booking| booking.status != Some(BookingStatus::Cancelled))
+            .collect();
+
+        return agents::agents()
+            .into_iter()
+            .map(|agent| {
+                let agent_bookings: Vec<RoomBooking> = bookings
+                    .iter()
+                    .filter(|booking| booking.agent_id == Some(agent.agent_id))
+                    .cloned()
+                    .collect();
+
+                return AgentCommissions::new(agent, &agent_bookings);
+            })
+            .collect();
+    });
+
+    return Ok(Json(reports));
+}
+
+#[doc(hidden)]
+/// # Export the accounting ledger for a day
+///
+/// Returns a CSV file summarizing the charges, taxes, payments and refunds of each booking on the
+/// provided day (YYYY-MM-DD), or today in the hotel's timezone if no day is given. Bookings are
+/// charged on their check out date, unless they were cancelled, and payments and refunds are
+/// included on the day they were recorded. The columns are date, bookingId, customerId,
+/// accountId, currency, charges, taxes, payments and refunds, with amounts in the minor unit of
+/// the hotel's currency.
+#[openapi(tag = "Admin")]
+#[get("/admin/ledger?<date>")]
+fn export_ledger(
+    date: Option<&str>,
+) -> Result<Attachment<(ContentType, Vec<u8>)>, ApiError<400, 500>> {
+    let date: NaiveDate = match ledger::parse_date(date) {
+        Ok(date) => date,
+        Err(error) => return Err(error_response(Status::BadRequest, &error)),
+    };
+    let key: String = format!("ledger:{}", date.format(DATE_FORMAT));
+    let entries: Vec<LedgerEntry> = storage::cached(&key, || {
+        return LedgerEntry::for_date(storage::iter_filtered(&BookingFilter::default()), date);
+    });
+
+    return match ledger::to_csv(&entries) {
+        Ok(content) => Ok(Attachment::new(
+            (ContentType::CSV, content),
+            format!("ledger-{}.csv", date.format(DATE_FORMAT)),
+        )),
+        Err(_) => Err(error_response(
+            Status::InternalServerError,
+            "The ledger could not be written",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// Parses a month (YYYY-MM) provided to a report, returning the current month in the hotel's
+/// timezone if no month is provided.
+fn parse_month(month: Option<&str>) -> Result<String, ApiError<400>> {
+    return match month {
+        Some(month) => match NaiveDate::parse_from_str(&format!("{}-01", month), DATE_FORMAT) {
+            Ok(_) => Ok(month.to_string()),
+            Err(_) => Err(error_response(
+                Status::BadRequest,
+                "month must be in the format YYYY-MM",
+            )),
+        },
+        None => Ok(local_time::today().format("%Y-%m").to_string()),
+    };
+}
+
+#[doc(hidden)]
+/// Parses the view requested from a booking list, returning the full view if no view is provided.
+fn parse_view(view: Option<&str>) -> Result<View, ApiError<400>> {
+    return match View::parse(view) {
+        Some(view) => Ok(view),
+        None => Err(error_response(
+            Status::BadRequest,
+            "view must be full or summary",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// # Get the corporate account with the provided account id
+#[openapi(tag = "Corporate Accounts")]
+#[get("/accounts/<account_id>")]
+fn get_corporate_account(account_id: u32) -> Result<Json<CorporateAccount>, ApiError<404>> {
+    return match storage::fetch_account(account_id) {
+        Some(account) => Ok(Json(account)),
+        None => Err(error_response(Status::NotFound, "Account not found")),
+    };
+}
+
+#[doc(hidden)]
+/// # Update the corporate account with the provided account id
+///
+/// Replaces the account's details and rate plan. Existing bookings made against the account are
+/// charged the updated rates. Returns the updated account.
+#[openapi(tag = "Corporate Accounts")]
+#[put("/accounts/<account_id>", format = "json", data = "<account>")]
+fn update_corporate_account(
+    account_id: u32,
+    account: Json<CorporateAccount>,
+) -> Result<Json<CorporateAccount>, ApiError<400, 404>> {
+    return match storage::update_account(account_id, account.into_inner()) {
+        Some(Ok(account)) => Ok(Json(account)),
+        Some(Err(errors)) => Err(error_response(Status::BadRequest, &errors.join(", "))),
+        None => Err(error_response(Status::NotFound, "Account not found")),
+    };
+}
+
+#[doc(hidden)]
+/// # Create a series of recurring room bookings
+///
+/// Creates a booking for each occurrence of the recurrence rule provided, e.g. every week for 12
+/// weeks, starting from the dates of the booking provided. Each booking is linked by a newly
+/// assigned series id. Either every booking is created, or none are if any booking is invalid, is
+/// likely a duplicate of a recently created booking, or is rejected by screening.
+#[openapi(tag = "Recurring Bookings")]
+#[post("/bookings/recurring", format = "json", data = "<recurring>")]
+fn create_recurring_room_bookings(
+    recurring: Json<RecurringBooking>,
+) -> Result<Json<Vec<BookingResource>>, ApiError<400, 409, 422, 503>> {
+    let bookings: Vec<RoomBooking> = match recurring.occurrences() {
+        Some(bookings) => bookings,
+        None => {
+            return Err(error_response(
+                Status::BadRequest,
+                "The recurrence rule, or the dates of the booking, are not valid",
+            ))
+        }
+    };
+
+    return match storage::create_series(bookings) {
+        Ok(bookings) => Ok(Json(
+            bookings.into_iter().map(BookingResource::new).collect(),
+        )),
+        Err(CreateError::Duplicate(booking_id)) => Err(error_response(
+            Status::Conflict,
+            &CreateError::Duplicate(booking_id).to_string(),
+        )
+        .conflicting_with(format!("/v1/booking/{}", booking_id))),
+        Err(error) => {
+            let status: Status = match error {
+                CreateError::Invalid(_) => Status::BadRequest,
+                CreateError::Rejected => Status::UnprocessableEntity,
+                CreateError::NoRoomAvailable
+                | CreateError::PromoCode(_)
+                | CreateError::Duplicate(_) => Status::Conflict,
+                CreateError::Unavailable => Status::ServiceUnavailable,
+            };
+            Err(error_response(status, &error.to_string()))
+        }
+    };
+}
+
+#[doc(hidden)]
+/// # Get the room bookings in a series
+///
+/// Returns every booking in the series of recurring bookings with the provided id, ordered by
+/// booking id.
+#[openapi(tag = "Recurring Bookings")]
+#[get("/bookings/series/<series_id>")]
+fn get_series_room_bookings(series_id: u32) -> Result<Json<Vec<RoomBooking>>, ApiError<404>> {
+    let bookings: Vec<RoomBooking> = storage::fetch_by_series_id(series_id);
+    if bookings.is_empty() {
+        return Err(error_response(Status::NotFound, "Series not found"));
+    }
+
+    return Ok(Json(bookings));
+}
+
+#[doc(hidden)]
+/// # Update the room bookings in a series
+///
+/// Applies the provided changes to every confirmed booking in the series. Either every confirmed
+/// booking is updated, or none are if the changes would make any booking invalid. Returns the
+/// updated bookings.
+#[openapi(tag = "Recurring Bookings")]
+#[patch("/bookings/series/<series_id>", format = "json", data = "<update>")]
+fn update_series_room_bookings(
+    series_id: u32,
+    update: Json<SeriesUpdate>,
+) -> Result<Json<Vec<RoomBooking>>, ApiError<400, 404>> {
+    if storage::fetch_by_series_id(series_id).is_empty() {
+        return Err(error_response(Status::NotFound, "Series not found"));
+    }
+
+    return match storage::update_series(series_id, &update) {
+        Ok(bookings) => Ok(Json(bookings)),
+        Err(_) => Err(error_response(
+            Status::BadRequest,
+            "The changes would make a booking in the series invalid",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// # Cancel the room bookings in a series
+///
+/// Cancels every confirmed booking in the series. Returns the outcome of the cancellation for each
+/// booking.
+#[openapi(tag = "Recurring Bookings")]
+#[delete("/bookings/series/<series_id>")]
+fn cancel_series_room_bookings(
+    series_id: u32,
+) -> Result<Json<Vec<BulkStatusResult>>, ApiError<404>> {
+    if storage::fetch_by_series_id(series_id).is_empty() {
+        return Err(error_response(Status::NotFound, "Series not found"));
+    }
+
+    return Ok(Json(storage::cancel_series(series_id)));
+}
+
+#[doc(hidden)]
+/// # Get all room bookings, optionally matching a set of filters
+///
+/// Returns a list containing all room bookings in the system, ordered by booking id. Any
+/// combination of customer id, room type id, status and check in date range (from/to) may be
+/// provided, in which case only bookings matching every filter are returned.
+///
+/// If a limit is provided, only that many bookings are returned starting from the offset, and a
+/// Link header pointing to the first, previous, next and last pages is included. The total
+/// number of matching bookings is returned in the X-Total-Count header.
+///
+/// Alternatively, a booking id may be provided as the cursor (after), in which case only bookings
+/// with a higher booking id are returned, and the Link header points to the first and next
+/// pages. Cursors remain stable as bookings are added and removed.
+///
+/// A comma separated list of fields (e.g. bookingId,checkInDate,status) may be provided, in which
+/// case only those fields are included for each booking. If view is summary, only the id, dates,
+/// status and room type of each booking are returned.
+///
+/// The list is streamed as it is written, so large listings are not held in memory. Bookings are
+/// returned as newline delimited JSON, one booking per line, if the Accept header includes
+/// application/x-ndjson.
+#[openapi(tag = "Room Bookings")]
+#[get("/bookings?<limit>&<offset>&<after>&<fields>&<view>&<filter..>")]
+#[allow(clippy::too_many_arguments)]
+fn get_room_bookings(
+    origin: &Origin,
+    format: StreamFormat,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    after: Option<u32>,
+    fields: Option<&str>,
+    view: Option<&str>,
+    filter: BookingFilter,
+) -> Result<Tagged<Paginated<Streamed<BookingView>>>, ApiError<400>> {
+    if !filter.is_valid() {
+        return Err(error_response(
+            Status::BadRequest,
+            "status must be a valid booking status",
+        ));
+    }
+
+    let view: View = parse_view(view)?;
+
+    let pagination = Pagination {
+        after,
+        ..Pagination::new(limit, offset)
+    };
+    let page: BookingPage = storage::fetch_page(&filter, &pagination);
+    let links: Vec<(String, String)> = match after {
+        Some(_) => pagination.cursor_links(origin, page.next_cursor),
+        None => pagination.links(origin, page.total),
+    };
+    let last_modified: Option<SystemTime> = storage::last_modified_any();
+    let etag: String = etag_for(&(
+        page.total,
+        &page.booking_ids,
+        fields,
+        view,
+        format,
+        last_modified,
+    ));
+    let bookings = BookingCursor::new(page.booking_ids).map(move |booking| view.show(booking));
+
+    return Ok(Tagged::new(
+        Paginated::new(Streamed::new(bookings, format, fields), page.total, links),
+        etag,
+        last_modified,
+    ));
+}
+
+#[doc(hidden)]
+/// # Count room bookings, optionally matching a set of filters
+///
+/// Accepts the same filters as the booking list. Returns only the number of matching bookings.
+#[openapi(tag = "Room Bookings")]
+#[get("/bookings/count?<filter..>")]
+fn count_room_bookings(filter: BookingFilter) -> Result<Json<usize>, ApiError<400>> {
+    if !filter.is_valid() {
+        return Err(error_response(
+            Status::BadRequest,
+            "status must be a valid booking status",
+        ));
+    }
+
+    return Ok(Json(storage::count_filtered(&filter)));
+}
+
+#[doc(hidden)]
+/// # Report cancelled room bookings by cancellation reason
+///
+/// Accepts the same filters as the booking list, other than status. Returns the number of
+/// cancelled bookings, and the number of bookings and nights cancelled for each reason.
+#[openapi(tag = "Room Bookings")]
+#[get("/bookings/cancellations?<filter..>")]
+fn get_cancellation_report(mut filter: BookingFilter) -> Json<CancellationReport> {
+    filter.status = Some("Cancelled".to_string());
+    return Json(storage::cached(
+        &format!("cancellations:{:?}", filter),
+        || {
+            return CancellationReport::new(storage::iter_filtered(&filter));
+        },
+    ));
+}
+
+#[doc(hidden)]
+/// # Get changes made to room bookings since the provided cursor
+///
+/// Returns every booking creation, update and cancellation made after the provided sequence
+/// number, in order, along with the cursor to use for the next request. If no cursor is provided,
+/// all changes are returned.
+#[openapi(tag = "Room Bookings")]
+#[get("/bookings/changes?<since>&<limit>")]
+fn get_room_booking_changes(since: Option<u64>, limit: Option<usize>) -> Json<ChangeSet> {
+    return Json(storage::fetch_changes(since.unwrap_or(0), limit));
+}
+
+#[doc(hidden)]
+/// # Sync room bookings since the provided sync token
+///
+/// Returns the bookings created and updated since the provided sync token, in their current
+/// state, along with the ids of bookings deleted since the token, and the token to use for the
+/// next sync. Lets an offline client, such as a housekeeping tablet, reconcile its copy of the
+/// bookings once it reconnects. If no token is provided, every booking is returned as created.
+#[openapi(tag = "Room Bookings")]
+#[get("/bookings/sync?<token>")]
+fn sync_room_bookings(token: Option<u64>) -> Result<Json<BookingDelta>, ApiError<503>> {
+    return match storage::fetch_delta(token.unwrap_or(0)) {
+        Some(delta) => Ok(Json(delta)),
+        None => Err(error_response(
+            Status::ServiceUnavailable,
+            "Booking storage is unavailable",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// # Get changes made to every record since the provided cursor
+///
+/// Returns every change made to bookings, promo codes, corporate accounts, sell restrictions,
+/// inventory adjustments and exchange rate overrides after the provided sequence number, in the
+/// order they were made, along with the cursor to use for the next request. Sequence numbers are
+/// shared by every kind of record, so a consumer storing the cursor sees each change exactly once.
+/// If no cursor is provided, all changes are returned.
+#[openapi(tag = "Admin")]
+#[get("/cdc?<after>&<limit>")]
+fn get_cdc_records(after: Option<u64>, limit: Option<usize>) -> Json<CdcPage> {
+    return Json(storage::fetch_cdc(after.unwrap_or(0), limit));
+}
+
+#[doc(hidden)]
+/// # Get room bookings arriving on the provided date
+///
+/// Returns a list of confirmed bookings checking in on the provided date (YYYY-MM-DD), ordered by
+/// expected arrival time, with bookings without an expected arrival time last. If no date is
+/// provided, today's date in the hotel's timezone is used. Sandbox bookings are left out.
+#[openapi(tag = "Room Bookings")]
+#[get("/bookings/arrivals?<date>")]
+fn get_room_booking_arrivals(date: Option<&str>) -> Json<Vec<RoomBooking>> {
+    let date: String = date.map_or_else(
+        || local_time::today().format(DATE_FORMAT).to_string(),
+        |date| date.to_string(),
+    );
+    let filter = BookingFilter {
+        status: Some("Confirmed".to_string()),
+        from: Some(date.clone()),
+        to: Some(date),
+        sandbox: Some(false),
+        ..Default::default()
+    };
+    let mut bookings: Vec<RoomBooking> = storage::fetch_filtered(&filter);
+    bookings.sort_by_key(|booking| {
+        (
+            booking.expected_arrival_time.is_none(),
+            booking.expected_arrival_time.clone(),
+        )
+    });
+
+    return Json(bookings);
+}
+
+#[doc(hidden)]
+/// # Get room bookings departing on the provided date
+///
+/// Returns a list of bookings, excluding cancelled, no-show and sandbox bookings, checking out on
+/// the provided date (YYYY-MM-DD). If no date is provided, today's date in the hotel's timezone is
+/// used.
+#[openapi(tag = "Room Bookings")]
+#[get("/bookings/departures?<date>")]
+fn get_room_booking_departures(date: Option<&str>) -> Json<Vec<RoomBooking>> {
+    let date: String = date.map_or_else(
+        || local_time::today().format(DATE_FORMAT).to_string(),
+        |date| date.to_string(),
+    );
+    let mut bookings: Vec<RoomBooking> = storage::fetch_by_check_out_date(&date);
+    bookings.retain(|booking| {
+        booking.status != Some(BookingStatus::Cancelled)
+            && booking.status != Some(BookingStatus::NoShow)
+            && !booking.sandbox
+    });
+    bookings.sort_by_key(|booking| booking.booking_id);
+
+    return Json(bookings);
+}
+
+#[doc(hidden)]
+/// # Get room bookings for the specified customer id
+///
+/// Returns a list of bookings. If a comma separated list of fields is provided, only those fields
+/// are included for each booking. If view is summary, only the id, dates, status and room type
+/// of each booking are returned.
+#[openapi(tag = "Room Bookings")]
+#[get("/bookings/customer/<customer_id>?<fields>&<view>")]
+fn get_customer_room_bookings(
+    customer_id: u32,
+    fields: Option<&str>,
+    view: Option<&str>,
+) -> Result<Tagged<Json<SparseList<BookingView>>>, ApiError<400>> {
+    let view: View = parse_view(view)?;
+    let bookings: Vec<BookingView> = storage::fetch_by_customer_id(customer_id)
+        .into_iter()
+        .map(|booking| view.show(booking))
+        .collect();
+
+    return Ok(Tagged::json(
+        SparseList::new(bookings, fields),
+        storage::last_modified_any(),
+    ));
+}
+
+#[doc(hidden)]
+/// # Export the data held for the specified customer id
+///
+/// Returns every stored record referencing the customer, as a JSON file, to respond to a subject
+/// access request. The export includes each of the customer's bookings, and every change recorded
+/// for those bookings.
+#[openapi(tag = "Customers")]
+#[get("/customers/<customer_id>/export")]
+fn export_customer_data(
+    customer_id: u32,
+) -> Result<Attachment<Json<CustomerExport>>, ApiError<500>> {
+    return match storage::export_customer(customer_id) {
+        Some(export) => Ok(Attachment::new(
+            Json(export),
+            format!("customer-{}-export.json", customer_id),
+        )),
+        None => Err(error_response(
+            Status::InternalServerError,
+            "Booking storage is unavailable",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// # Erase the personal data held for the specified customer id
+///
+/// Anonymizes each of the customer's bookings, along with the recorded changes to those bookings,
+/// removing the customer id and any other personal data. The dates, room types, statuses and
+/// prices of the bookings are kept, so reports and statistics are unaffected. The erasure is
+/// recorded in the change log of each booking. This cannot be undone.
+#[openapi(tag = "Customers")]
+#[post("/customers/<customer_id>/anonymize")]
+fn anonymize_customer(customer_id: u32) -> Result<Json<Anonymization>, ApiError<404>> {
+    return match storage::anonymize_customer(customer_id) {
+        Some(anonymization) => Ok(Json(anonymization)),
+        None => Err(error_response(
+            Status::NotFound,
+            "No bookings found for the customer",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// # Get room bookings starting on the provided date
+///
+/// Deprecated: use the from and to filters of the booking list instead. This route will be removed
+/// on the date given in the Sunset response header.
+///
+/// Returns a list of bookings. If a comma separated list of fields is provided, only those fields
+/// are included for each booking. If view is summary, only the id, dates, status and room type
+/// of each booking are returned.
+#[openapi(tag = "Room Bookings", deprecated = true)]
+#[get("/bookings/date/<date>?<fields>&<view>")]
+fn get_bookings_starting_on_date(
+    date: &str,
+    fields: Option<&str>,
+    view: Option<&str>,
+) -> Result<Tagged<Json<SparseList<BookingView>>>, ApiError<400>> {
+    let view: View = parse_view(view)?;
+    let bookings: Vec<BookingView> = storage::fetch_by_check_in_date(date)
+        .into_iter()
+        .map(|booking| view.show(booking))
+        .collect();
+
+    return Ok(Tagged::json(
+        SparseList::new(bookings, fields),
+        storage::last_modified_any(),
+    ));
+}
+
+#[doc(hidden)]
+/// # Get room bookings for the specified room type
+///
+/// Returns a list of bookings. If a comma separated list of fields is provided, only those fields
+/// are included for each booking. If view is summary, only the id, dates, status and room type
+/// of each booking are returned.
+#[openapi(tag = "Room Bookings")]
+#[get("/bookings/room-type/<room_type_id>?<fields>&<view>")]
+fn get_room_type_bookings(
+    room_type_id: u8,
+    fields: Option<&str>,
+    view: Option<&str>,
+) -> Result<Tagged<Json<SparseList<BookingView>>>, ApiError<400>> {
+    let view: View = parse_view(view)?;
+    let bookings: Vec<BookingView> = storage::fetch_by_room_type_id(room_type_id)
+        .into_iter()
+        .map(|booking| view.show(booking))
+        .collect();
+
+    return Ok(Tagged::json(
+        SparseList::new(bookings, fields),
+        storage::last_modified_any(),
+    ));
+}
+
+#[doc(hidden)]
+/// # Import room bookings from a CSV or JSON file
+///
+/// Accepts a CSV file (text/csv) with a header row naming each field, or a JSON file
+/// (application/json) containing an array of bookings. Every row is validated, and valid rows
+/// are imported with a newly assigned booking id. Returns the outcome of each row, including a
+/// description of any errors.
+#[openapi(tag = "Admin")]
+#[post("/admin/import", data = "<file>")]
+fn import_room_bookings(
+    content_type: &ContentType,
+    file: Capped<&str>,
+) -> Result<Json<ImportReport>, ApiError<400, 413>> {
+    if !file.is_complete() {
+        return Err(error_response(Status::PayloadTooLarge, "File is too large"));
+    }
+
+    let rows: Vec<Result<RoomBooking, String>> = match import::parse(content_type, &file) {
+        Ok(rows) => rows,
+        Err(error) => return Err(error_response(Status::BadRequest, &error)),
+    };
+    check_batch_size(rows.len())?;
+
+    return Ok(Json(ImportReport::new(import::import_rows(rows))));
+}
+
+#[doc(hidden)]
+/// # Check whether room bookings would be imported from a CSV or JSON file
+///
+/// Accepts the same files as the import, and runs the same checks on every row, without
+/// importing any of them. Each row is checked against the stored bookings, and the rows before it
+/// which would be imported. Returns the outcome each row would have, with imported counting the
+/// rows which would be imported. No booking id is given for rows which would be imported, as ids
+/// are assigned when the file is imported.
+#[openapi(tag = "Admin")]
+#[post("/bookings/validate-batch", data = "<file>")]
+fn validate_room_booking_batch(
+    content_type: &ContentType,
+    file: Capped<&str>,
+) -> Result<Json<ImportReport>, ApiError<400, 413>> {
+    if !file.is_complete() {
+        return Err(error_response(Status::PayloadTooLarge, "File is too large"));
+    }
+
+    let rows: Vec<Result<RoomBooking, String>> = match import::parse(content_type, &file) {
+        Ok(rows) => rows,
+        Err(error) => return Err(error_response(Status::BadRequest, &error)),
+    };
+    check_batch_size(rows.len())?;
+
+    return Ok(Json(ImportReport::new(import::validate_rows(rows))));
+}
+
+#[doc(hidden)]
+/// # Import room bookings from a property management system
+///
+/// Accepts a JSON array of reservations exported from a property management system (PMS), in the
+/// format of the named PMS adapter, e.g. 'generic'. Each reservation is mapped to a booking and
+/// validated, and valid bookings are imported with a newly assigned booking id. Returns the
+/// outcome of each reservation, including a description of any errors.
+#[openapi(tag = "Admin")]
+#[post("/admin/pms/<adapter>/import", data = "<file>")]
+fn import_pms_reservations(
+    adapter: &str,
+    file: Capped<&str>,
+) -> Result<Json<ImportReport>, ApiError<400, 404, 413>> {
+    let adapter = match pms::find(adapter) {
+        Some(adapter) => adapter,
+        None => return Err(error_response(Status::NotFound, "PMS adapter not found")),
+    };
+
+    if !file.is_complete() {
+        return Err(error_response(Status::PayloadTooLarge, "File is too large"));
+    }
+
+    let rows: Vec<Result<RoomBooking, String>> = match pms::parse(adapter.as_ref(), &file) {
+        Ok(rows) => rows,
+        Err(error) => return Err(error_response(Status::BadRequest, &error)),
+    };
+    check_batch_size(rows.len())?;
+
+    return Ok(Json(ImportReport::new(import::import_rows(rows))));
+}
+
+#[doc(hidden)]
+/// # Export room bookings to a property management system
+///
+/// Returns every booking as a reservation in the format of the named PMS adapter, e.g. 'generic'.
+/// The reservations are streamed as they are written, as newline delimited JSON if the Accept
+/// header includes application/x-ndjson.
+#[openapi(tag = "Admin")]
+#[get("/admin/pms/<adapter>/export")]
+fn export_pms_reservations(
+    adapter: &str,
+    format: StreamFormat,
+) -> Result<Streamed<Value>, ApiError<404>> {
+    let adapter = match pms::find(adapter) {
+        Some(adapter) => adapter,
+        None => return Err(error_response(Status::NotFound, "PMS adapter not found")),
+    };
+
+    let bookings = storage::iter_filtered(&BookingFilter::default());
+    return Ok(Streamed::new(pms::export(adapter, bookings), format, None));
+}
+
+#[doc(hidden)]
+/// # Get the state of outbound integrations
+///
+/// Returns the circuit breaker state, and the call, retry and timeout counts, of each outbound
+/// integration, such as the channel manager, which has been called since the server started.
+#[openapi(tag = "Admin")]
+#[get("/admin/integrations")]
+fn get_integration_metrics() -> Json<Vec<CircuitMetrics>> {
+    return Json(circuit_breaker::metrics());
+}
+
+#[doc(hidden)]
+/// # Get the work done by the storage layer
+///
+/// Returns the number of bookings held, and since the server started, the time taken to acquire
+/// locks on the stored lists, the number of scans of every booking and the bookings they covered,
+/// and the bytes written to and time taken to write snapshot files. Durations are given in
+/// microseconds, counted into latency buckets.
+#[openapi(tag = "Admin")]
+#[get("/admin/storage/metrics")]
+fn get_storage_metrics() -> Json<StorageMetrics> {
+    return Json(storage::metrics());
+}
+
+#[doc(hidden)]
+/// # Get the events waiting to be delivered
+///
+/// Returns the booking events in the outbox which have not yet been delivered to the events
+/// webhook, in the order they were recorded, along with the number of failed delivery attempts.
+#[openapi(tag = "Admin")]
+#[get("/admin/outbox")]
+fn get_outbox_events() -> Json<Vec<OutboxEvent>> {
+    return Json(storage::fetch_outbox(None));
+}
+
+#[doc(hidden)]
+/// # Get the events which could not be delivered
+///
+/// Returns the booking events in the dead-letter store, which failed every attempt to deliver
+/// them to the events webhook, along with the last delivery error.
+#[openapi(tag = "Admin")]
+#[get("/admin/dead-letters")]
+fn get_dead_letters() -> Json<Vec<OutboxEvent>> {
+    return Json(storage::fetch_dead_letters());
+}
+
+#[doc(hidden)]
+/// # Get the feature flags
+///
+/// Returns whether each feature which may be turned off with a feature flag, such as overbooking
+/// or dynamic pricing, is enabled.
+#[openapi(tag = "Admin")]
+#[get("/admin/features")]
+fn get_feature_flags() -> Json<FeatureFlags> {
+    return Json(config::features());
+}
+
+#[doc(hidden)]
+/// # Get the screening decisions made for new bookings
+///
+/// Returns each screening rule a new booking matched, and the action taken, in the order the
+/// bookings were screened. Rejected bookings have no booking id.
+#[openapi(tag = "Admin")]
+#[get("/admin/screening")]
+fn get_screening_decisions() -> Json<Vec<ScreeningDecision>> {
+    return Json(storage::fetch_screening_decisions());
+}
+
+#[doc(hidden)]
+/// # Get the undelivered event with the provided sequence number
+#[openapi(tag = "Admin")]
+#[get("/admin/dead-letters/<sequence>")]
+fn get_dead_letter(sequence: u64) -> Result<Json<OutboxEvent>, ApiError<404>> {
+    return match storage::fetch_dead_letter(sequence) {
+        Some(event) => Ok(Json(event)),
+        None => Err(error_response(Status::NotFound, "Event not found")),
+    };
+}
+
+#[doc(hidden)]
+/// # Redeliver the undelivered event with the provided sequence number
+///
+/// Moves the event from the dead-letter store back to the outbox, where it is delivered to the
+/// events webhook again, in order with the other events waiting to be delivered.
+#[openapi(tag = "Admin")]
+#[post("/admin/dead-letters/<sequence>/redeliver")]
+fn redeliver_dead_letter(sequence: u64) -> Result<Json<OutboxEvent>, ApiError<404>> {
+    return match storage::redeliver_dead_letter(sequence) {
+        Some(event) => Ok(Json(event)),
+        None => Err(error_response(Status::NotFound, "Event not found")),
+    };
+}
+
+#[doc(hidden)]
+/// # Replay historical booking events to a webhook
+///
+/// Delivers the booking changes from from_seq to to_seq, inclusive, from the change log to the
+/// configured webhook with the target URL, in the order they were recorded, e.g. to rebuild a
+/// downstream read model after an outage. Changes to sandbox bookings, or not matching the
+/// webhook's topics and filters, are skipped, and replay stops at the first change which cannot
+/// be delivered, which is given in the report so replay can be resumed. Events waiting in the
+/// outbox are not affected.
+#[openapi(tag = "Admin")]
+#[post("/admin/events/replay?<from_seq>&<to_seq>&<target>")]
+async fn replay_events(
+    from_seq: u64,
+    to_seq: u64,
+    target: &str,
+) -> Result<Json<ReplayReport>, ApiError<400, 404>> {
+    if from_seq == 0 || to_seq < from_seq {
+        return Err(error_response(
+            Status::BadRequest,
+            "from_seq must be at least 1, and not after to_seq",
+        ));
+    }
+
+    let settings = match config::hotel().events {
+        Some(settings) => settings,
+        None => {
+            return Err(error_response(
+                Status::BadRequest,
+                "No events webhooks are configured",
+            ))
+        }
+    };
+
+    let webhook: WebhookSubscription = match settings
+        .webhooks()
+        .into_iter()
+        .find(|webhook| webhook.url == target)
+    {
+        Some(webhook) => webhook,
+        None => return Err(error_response(Status::NotFound, "Webhook not found")),
+    };
+
+    return Ok(Json(outbox::replay(&webhook, from_seq, to_seq).await));
+}
+
+#[doc(hidden)]
+/// # Reload the configuration
+///
+/// Reloads the hotel settings, such as rates, stay rules and room inventory, and the feature
+/// flags from the configuration files and environment, without a restart, and returns the feature
+/// flags now in use. Bookings and requests in flight are unaffected. The settings in use are kept
+/// if the new settings cannot be read. Sending SIGHUP to the server also reloads them.
+#[openapi(tag = "Admin")]
+#[post("/admin/config/reload")]
+fn reload_config() -> Result<Json<FeatureFlags>, ApiError<500>> {
+    return match reload::apply() {
+        Ok(_) => Ok(Json(config::features())),
+        Err(error) => Err(error_response(
+            Status::InternalServerError,
+            &format!("Unable to reload the configuration: {}", error),
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// # Apply the data retention policy
+///
+/// Archives or purges the bookings which checked out more than the configured number of years
+/// ago, along with their change log entries, and returns a report of the bookings removed. If
+/// dry_run is true, the bookings which would be removed are reported, but nothing is removed.
+/// Uses the configured dry run setting if dry_run is not provided.
+#[openapi(tag = "Admin")]
+#[post("/admin/retention?<dry_run>")]
+fn apply_retention_policy(
+    dry_run: Option<bool>,
+) -> Result<Json<RetentionReport>, ApiError<400, 500>> {
+    let settings = match config::hotel().retention {
+        Some(settings) => settings,
+        None => {
+            return Err(error_response(
+                Status::BadRequest,
+                "No retention policy is configured",
+            ))
+        }
+    };
+
+    return match retention::apply(&settings, dry_run.unwrap_or(settings.dry_run)) {
+        Some(report) => Ok(Json(report)),
+        None => Err(error_response(
+            Status::InternalServerError,
+            "Booking storage is unavailable",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// # Remove every sandbox booking
+///
+/// Removes every sandbox booking, however recently it was created, along with its change log
+/// entries, and returns the ids of the bookings removed. Sandbox bookings are otherwise removed
+/// once they are older than the configured retention period.
+#[openapi(tag = "Admin")]
+#[delete("/admin/sandbox")]
+fn wipe_sandbox() -> Result<Json<Vec<u32>>, ApiError<400, 500>> {
+    if config::hotel().sandbox.is_none() {
+        return Err(error_response(
+            Status::BadRequest,
+            "The sandbox is not enabled",
+        ));
+    }
+
+    return match sandbox::wipe_all() {
+        Some(removed) => Ok(Json(removed)),
+        None => Err(error_response(
+            Status::InternalServerError,
+            "Booking storage is unavailable",
+        )),
+    };
+}
+
+#[doc(hidden)]
+/// Builds the Rocket instance, mounting the routes for each API version and the documentation UIs
+/// enabled in the documentation settings.
+fn rocket(figment: Figment) -> rocket::Rocket<rocket::Build> {
+    let settings = OpenApiSettings::new();
+    let (v1_routes, mut v1_spec): (Vec<rocket::Route>, OpenApi) = openapi_get_routes_spec![
+        settings: get_room_booking,
+        head_room_booking,
+        get_room_booking_history,
+        get_room_booking_invoice,
+        get_room_booking_pricing,
+        get_room_booking_balance,
+        record_room_booking_payment,
+        refund_room_booking,
+        receive_payment_webhook,
+        add_room_booking_addon,
+        remove_room_booking_addon,
+        create_room_booking,
+        validate_room_booking,
+        check_in_room_booking,
+        complete_room_booking,
+        cancel_room_booking,
+        no_show_room_booking,
+        reinstate_room_booking,
+        confirm_room_booking_payment,
+        approve_room_booking,
+        update_room_booking_statuses,
+        quote_room_booking,
+        get_addon_catalog,
+        get_promo_codes,
+        create_promo_code,
+        get_promo_code,
+        delete_promo_code,
+        get_sell_restrictions,
+        create_sell_restriction,
+        get_sell_restriction,
+        delete_sell_restriction,
+        get_availability_calendar,
+        check_availability,
+        get_inventory_adjustments,
+        create_inventory_adjustment,
+        get_inventory_adjustment,
+        delete_inventory_adjustment,
+        get_exchange_rate,
+        get_exchange_rate_overrides,
+        set_exchange_rate_override,
+        delete_exchange_rate_override,
+        get_corporate_accounts,
+        create_corporate_account,
+        get_corporate_account_report,
+        get_corporate_account,
+        update_corporate_account,
+        get_agent_commissions,
+        export_ledger,
+        create_recurring_room_bookings,
+        get_series_room_bookings,
+        update_series_room_bookings,
+        cancel_series_room_bookings,
+        get_room_bookings,
+        count_room_bookings,
+        get_cancellation_report,
+        get_room_booking_changes,
+        get_cdc_records,
+        sync_room_bookings,
+        get_room_booking_arrivals,
+        get_room_booking_departures,
+        get_customer_room_bookings,
+        export_customer_data,
+        anonymize_customer,
+        get_bookings_starting_on_date,
+        get_room_type_bookings,
+        import_room_bookings,
+        validate_room_booking_batch,
+        import_pms_reservations,
+        export_pms_reservations,
+        get_integration_metrics,
+        get_storage_metrics,
+        get_outbox_events,
+        get_dead_letters,
+        get_dead_letter,
+        get_screening_decisions,
+        get_feature_flags,
+        reload_config,
+        redeliver_dead_letter,
+        replay_events,
+        apply_retention_policy,
+        wipe_sandbox
+    ];
+    v1_spec.servers = vec![Server {
+        url: "/v1".to_owned(),
+        ..Default::default()
+    }];
+
+    let docs: DocsConfig = config::docs();
+    let spec_urls: Vec<UrlObject> = vec![UrlObject::new("v1", "../v1/openapi.json")];
+
+    let mut rocket = rocket::custom(figment)
+        .attach(Versioning)
+        .attach(Compression)
+        .attach(Deprecations)
+        .attach(PaymentExpiry)
+        .attach(ChannelSync)
+        .attach(OutboxDispatcher)
+        .attach(PaymentEventProcessor)
+        .attach(RetentionJob)
+        .attach(SandboxWipe)
+        .attach(SecretRefresh)
+        .attach(ConfigReload)
+        .mount("/v1", v1_routes)
+        .mount("/v1", vec![get_openapi_route(v1_spec, &settings)]);
+
+    let request_log: RequestLogConfig = config::request_log();
+    if request_log.enabled {
+        rocket = rocket.attach(RequestLog {
+            settings: request_log,
+        });
+    }
+
+    if docs.swagger_ui {
+        rocket = rocket.mount(
+            "/swagger-ui/",
+            make_swagger_ui(&SwaggerUIConfig {
+                urls: spec_urls.clone(),
+                ..Default::default()
+            }),
+        );
+    }
+
+    if docs.rapidoc {
+        rocket = rocket.mount(
+            "/rapidoc/",
+            make_rapidoc(&RapiDocConfig {
+                general: GeneralConfig {
+                    spec_urls,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        );
+    }
+
+    return rocket;
+}
+
+#[doc(hidden)]
+/// Launches the server, after fetching any secrets from the secret store. If certificates are
+/// configured, the server is relaunched with the new certificates each time they are rotated, or
+/// with the previous certificates if it cannot be launched with the new ones.
+async fn serve() -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(settings) = config::hotel().secrets {
+        let fetched: usize = secrets::load(&settings).await?;
+        redaction::notice(&format!("Fetched {} secrets...", fetched));
+    }
+
+    let settings: Option<CertificatesConfig> = config::certificates();
+    let mut certificates: Option<Certificates> = match &settings {
+        Some(settings) => Some(Certificates::read(settings)?),
+        None => None,
+    };
+    let mut previous: Option<Certificates> = None;
+
+    loop {
+        let mut server = rocket(tls::figment(settings.as_ref().zip(certificates.as_ref())));
+        if let (Some(settings), Some(loaded)) = (&settings, &certificates) {
+            server = server.attach(CertificateReload {
+                settings: settings.clone(),
+                loaded: loaded.clone(),
+            });
+        }
+
+        if let Err(err) = server.launch().await {
+            match previous.take() {
+                Some(restored) => {
+                    redaction::error(&format!(
+                        "Unable to launch with the rotated certificates: {}",
+                        err
+                    ));
+                    certificates = Some(restored);
+                    continue;
+                }
+                None => return Err(format!("An error occurred during shutdown: {}", err).into()),
+            }
+        }
+
+        match tls::take_rotated() {
+            Some(rotated) => previous = certificates.replace(rotated),
+            None => break,
+        }
+    }
+
+    redaction::info("Shutdown complete.");
+    return Ok(());
+}
+
+#[doc(hidden)]
+#[rocket::main]
+async fn main() {
+    let cli: Cli = Cli::parse();
+
+    if let Err(err) = config::load() {
+        redaction::error(&format!(
+            "An error occurred loading hotel configuration: {}",
+            err
+        ));
+        std::process::exit(1);
+    }
+
+    if storage::snapshot_exists() {
+        match storage::load_snapshot() {
+            Ok(_) => redaction::notice("Loaded snapshot..."),
+            Err(err) => {
+                // Serving an empty store would replace the snapshot on the next change, losing
+                // every booking, so the snapshot must be fixed or restored first.
+                redaction::error(&format!("An error occurred loading snapshot: {}", err));
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let result: Result<(), Box<dyn std::error::Error>> =
+        match cli.command.unwrap_or(Command::Serve(ServeArgs::default())) {
+            Command::Serve(args) => {
+                args.seed();
+                serve().await
+            }
+            Command::Export { format, output } => cli::export(format, output),
+            Command::Ledger { date, output } => cli::ledger(date, output),
+            Command::Import { file, format } => cli::import(file, format),
+            Command::CompactSnapshot => cli::compact_snapshot(),
+            Command::Verify => cli::verify(),
+            Command::Loadgen(args) => cli::loadgen(args).await,
+        };
+
+    if let Err(err) = result {
+        redaction::error(&err.to_string());
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rocket;
+    use rocket::http::{ContentType, Header, Status};
+    use rocket::local::blocking::{Client, LocalResponse};
+    use rocket::serde::json::Value;
+    use room_booking_service::request_log::{RequestLog, RequestLogConfig};
+    use room_booking_service::storage::isolated::IsolatedStorage;
+    use std::ops::Deref;
+
+    /// A local client for the service, with storage isolated from any other test.
+    struct TestClient {
+        client: Client,
+        _storage: IsolatedStorage,
+    }
+
+    impl TestClient {
+        fn new() -> TestClient {
+            let storage = IsolatedStorage::new();
+            let client =
+                Client::tracked(rocket(rocket::Config::figment())).expect("valid rocket instance");
+
+            return TestClient {
+                client,
+                _storage: storage,
+            };
+        }
+
+        fn create_booking(&self, customer_id: u32) -> LocalResponse<'_> {
+            return self.post_booking("/v1/booking", customer_id);
+        }
+
+        fn post_booking(&self, path: &str, customer_id: u32) -> LocalResponse<'_> {
+            let body: String = format!(
+                r#"{{"customerId":{},"roomTypeId":3,"checkInDate":"2030-01-01","checkOutDate":"2030-01-08"}}"#,
+                customer_id
+            );
+
+            return self
+                .client
+                .post(path.to_string())
+                .header(ContentType::JSON)
+                .body(body)
+                .dispatch();
+        }
+    }
+
+    impl Deref for TestClient {
+        type Target = Client;
+
+        fn deref(&self) -> &Client {
+            return &self.client;
+        }
+    }
+
+    #[test]
+    fn create_and_fetch_booking() {
+        let client = TestClient::new();
+        assert_eq!(client.create_booking(1).status(), Status::Ok);
+
+        let response = client.get("/v1/booking/1").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains(r#""customerId":1"#));
+    }
+
+    #[test]
+    fn fetch_missing_booking() {
+        let client = TestClient::new();
+        assert_eq!(
+            client.get("/v1/booking/1").dispatch().status(),
+            Status::NotFound
+        );
+
+        let response = client
+            .get("/v1/booking/1")
+            .header(Header::new("Accept-Language", "fr-FR, en;q=0.5"))
+            .dispatch();
+        assert_eq!(response.headers().get_one("Content-Language"), Some("fr"));
+        assert_eq!(
+            response.into_string(),
+            Some(r#"{"error":"Réservation introuvable"}"#.to_string())
+        );
+    }
+
+    #[test]
+    fn count_bookings() {
+        let client = TestClient::new();
+        for (path, customer_id) in [
+            ("/v1/booking", 1),
+            ("/v1/booking", 2),
+            ("/v1/booking?allow_duplicate=true", 2),
+        ] {
+            assert_eq!(client.post_booking(path, customer_id).status(), Status::Ok);
+        }
+
+        let response = client.get("/v1/bookings/count").dispatch();
+        assert_eq!(response.into_string(), Some("3".to_string()));
+
+        let response = client.get("/v1/bookings/count?customer_id=2").dispatch();
+        assert_eq!(response.into_string(), Some("2".to_string()));
+    }
+
+    #[test]
+    fn reject_duplicate_booking() {
+        let client = TestClient::new();
+        assert_eq!(client.create_booking(1).status(), Status::Ok);
+
+        let response = client.create_booking(1);
+        assert_eq!(response.status(), Status::Conflict);
+        let body: Value = rocket::serde::json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["conflictsWith"], "/v1/booking/1");
+
+        assert_eq!(client.create_booking(2).status(), Status::Ok);
+        let response = client.post_booking("/v1/booking?allow_duplicate=true", 1);
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[test]
+    fn validate_booking_without_storing() {
+        let client = TestClient::new();
+        let response = client.post_booking("/v1/booking/validate", 1);
+        assert_eq!(response.status(), Status::Ok);
+        let body: Value = rocket::serde::json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["valid"], true);
+        assert_eq!(body["status"], "Confirmed");
+        assert_eq!(body["deposit"], 0);
+
+        let response = client.get("/v1/bookings/count").dispatch();
+        assert_eq!(response.into_string(), Some("0".to_string()));
+
+        assert_eq!(client.create_booking(1).status(), Status::Ok);
+        let response = client.post_booking("/v1/booking/validate", 1);
+        assert_eq!(response.status(), Status::Ok);
+        let body: Value = rocket::serde::json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["valid"], false);
+        assert_eq!(body["conflictsWith"], "/v1/booking/1");
+
+        let response = client.post_booking("/v1/booking/validate?allow_duplicate=true", 1);
+        let body: Value = rocket::serde::json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["valid"], true);
+
+        let response = client.get("/v1/bookings/count").dispatch();
+        assert_eq!(response.into_string(), Some("1".to_string()));
+    }
+
+    #[test]
+    fn invalidate_cached_reports() {
+        let client = TestClient::new();
+        assert_eq!(client.create_booking(1).status(), Status::Ok);
+
+        let report = |client: &TestClient| -> Value {
+            let response = client.get("/v1/bookings/cancellations").dispatch();
+            return rocket::serde::json::from_str(&response.into_string().unwrap()).unwrap();
+        };
+        assert_eq!(report(&client)["bookings"], 0);
+
+        let response = client.delete("/v1/booking/1").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(report(&client)["bookings"], 1);
+
+        let calendar = |client: &TestClient| -> Value {
+            let response = client
+                .get("/v1/availability/calendar?room_type_id=3&month=2030-01")
+                .dispatch();
+            return rocket::serde::json::from_str(&response.into_string().unwrap()).unwrap();
+        };
+        assert_eq!(calendar(&client)["days"][0]["booked"], 0);
+        assert_eq!(client.create_booking(2).status(), Status::Ok);
+        assert_eq!(calendar(&client)["days"][0]["booked"], 1);
+    }
+
+    #[test]
+    fn reject_refunds_beyond_policy() {
+        let client = TestClient::new();
+        assert_eq!(client.create_booking(1).status(), Status::Ok);
+
+        let refund = |booking_id: u32, amount: u64| -> Status {
+            return client
+                .client
+                .post(format!("/v1/booking/{}/refund", booking_id))
+                .header(ContentType::JSON)
+                .body(format!(r#"{{"amount":{}}}"#, amount))
+                .dispatch()
+                .status();
+        };
+
+        assert_eq!(refund(1, 100), Status::Conflict);
+        assert_eq!(refund(1, 0), Status::BadRequest);
+        assert_eq!(refund(99, 100), Status::NotFound);
+    }
+
+    #[test]
+    fn report_storage_metrics() {
+        let client = TestClient::new();
+        assert_eq!(client.create_booking(1).status(), Status::Ok);
+        let response = client.get("/v1/bookings/count").dispatch();
+        assert_eq!(response.into_string(), Some("1".to_string()));
+
+        let response = client.get("/v1/admin/storage/metrics").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body: Value = rocket::serde::json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["bookings"], 1);
+        assert!(body["scans"].as_u64().unwrap() > 0);
+        assert!(body["itemsScanned"].as_u64().unwrap() > 0);
+        assert!(body["lockWaits"]["count"].as_u64().unwrap() > 0);
+        assert!(body["snapshotWrites"]["count"].as_u64().unwrap() > 0);
+        assert!(body["snapshotBytes"].as_u64().unwrap() > 0);
+        assert_eq!(body["snapshotFailures"], 0);
+    }
+
+    #[test]
+    fn log_requests_without_consuming_bodies() {
+        let _storage = IsolatedStorage::new();
+        let rocket = rocket(rocket::Config::figment()).attach(RequestLog {
+            settings: RequestLogConfig {
+                enabled: true,
+                max_body_bytes: 16,
+            },
+        });
+        let client = Client::tracked(rocket).expect("valid rocket instance");
+
+        let response = client
+            .post("/v1/booking")
+            .header(ContentType::JSON)
+            .body(r#"{"customerId":1,"roomTypeId":3,"checkInDate":"2030-01-01","checkOutDate":"2030-01-08"}"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains(r#""customerId":1"#));
+    }
+
+    #[test]
+    fn redact_personal_data_from_errors() {
+        let client = TestClient::new();
+        let response = client
+            .post("/v1/booking")
+            .header(ContentType::JSON)
+            .body(r#"{"customerId":"jane@example.com","roomTypeId":3,"checkInDate":"2030-01-01","checkOutDate":"2030-01-08"}"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        let body: String = response.into_string().unwrap();
+        assert!(body.contains(r#"invalid type: string \"[redacted]\""#));
+        assert!(!body.contains("jane"));
+    }
+
+    #[test]
+    fn validate_batch_without_importing() {
+        let client = TestClient::new();
+        let response = client
+            .post("/v1/bookings/validate-batch")
+            .header(ContentType::CSV)
+            .body(
+                "customerId,roomTypeId,checkInDate,checkOutDate\n\
+                1,3,2030-01-01,2030-01-08\n\
+                2,3,2030-01-08,2030-01-01\n\
+                x,3,2030-01-01,2030-01-08\n",
+            )
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body: Value = rocket::serde::json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["imported"], 1);
+        assert_eq!(body["rejected"], 2);
+        assert_eq!(body["rows"][0]["bookingId"], Value::Null);
+        assert_eq!(
+            body["rows"][1]["errors"][0],
+            "checkOutDate must be after checkInDate"
+        );
+
+        let response = client.get("/v1/bookings/count").dispatch();
+        assert_eq!(response.into_string(), Some("0".to_string()));
+    }
+
+    #[test]
+    fn reject_invalid_booking() {
+        let client = TestClient::new();
+        let response = client
+            .post("/v1/booking")
+            .header(ContentType::JSON)
+            .body(r#"{"customerId":1,"roomTypeId":3,"checkInDate":"2030-01-08","checkOutDate":"2030-01-01"}"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("checkOutDate must be after checkInDate"));
+    }
+
+    #[test]
+    fn reject_unknown_booking_fields() {
+        let client = TestClient::new();
+        let response = client
+            .post("/v1/booking")
+            .header(ContentType::JSON)
+            .body(r#"{"customerId":1,"roomTypeId":3,"checkinDate":"2030-01-01","checkInDate":"2030-01-01","checkOutDate":"2030-01-08"}"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        let body: Value = rocket::serde::json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["fields"][0]["field"], "checkinDate");
+        assert_eq!(body["fields"][0]["error"], "checkinDate unknown field");
+
+        let response = client
+            .post("/v1/booking")
+            .header(ContentType::JSON)
+            .body(format!(
+                r#"{{"customerId":1,"roomTypeId":3,"checkInDate":" 2030-01-01 ","checkOutDate":"2030-01-08","promoCode":"{}"}}"#,
+                "A".repeat(65)
+            ))
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        let body: Value = rocket::serde::json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["fields"].as_array().unwrap().len(), 1);
+        assert_eq!(body["fields"][0]["field"], "promoCode");
+    }
+
+    #[test]
+    fn cancel_returns_booking() {
+        let client = TestClient::new();
+        assert_eq!(client.create_booking(1).status(), Status::Ok);
+
+        let response = client.delete("/v1/booking/1").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains(r#""status":"Cancelled""#));
+
+        let response = client.delete("/v1/booking/2").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(
+            response.into_string(),
+            Some(r#"{"error":"Booking not found"}"#.to_string())
+        );
+    }
+
+    #[test]
+    fn describe_transition_conflict() {
+        let client = TestClient::new();
+        assert_eq!(client.create_booking(1).status(), Status::Ok);
+        assert_eq!(
+            client.delete("/v1/booking/1").dispatch().status(),
+            Status::Ok
+        );
+
+        let response = client.put("/v1/booking/1/complete").dispatch();
+        assert_eq!(response.status(), Status::Conflict);
+        let body: Value = rocket::serde::json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["conflict"]["reason"], "IllegalTransition");
+        assert_eq!(body["conflict"]["requestedStatus"], "Complete");
+        assert_eq!(body["conflict"]["allowedStatuses"][0], "Confirmed");
+        assert_eq!(body["conflict"]["booking"]["status"], "Cancelled");
+
+        let response = client.put("/v1/booking/1/confirm-payment").dispatch();
+        let body: Value = rocket::serde::json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(body["conflict"]["reason"], "WrongStatus");
+        assert_eq!(body["conflict"]["requiredStatus"], "PendingPayment");
+    }
+
+    #[test]
+    fn reject_oversized_batch() {
+        let client = TestClient::new();
+        let booking_ids: Vec<String> = (1..=501).map(|id| id.to_string()).collect();
+        let response = client
+            .post("/v1/bookings/status")
+            .header(ContentType::JSON)
+            .body(format!(
+                r#"{{"bookingIds":[{}],"status":"Complete"}}"#,
+                booking_ids.join(",")
+            ))
+            .dispatch();
+        assert_eq!(response.status(), Status::PayloadTooLarge);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("more than 500 items"));
+
+        let response = client
+            .post("/v1/availability/check")
+            .header(ContentType::JSON)
+            .body(format!("[{}]", " ".repeat(2 * 1024 * 1024)))
+            .dispatch();
+        assert_eq!(response.status(), Status::PayloadTooLarge);
+    }
+
+    #[test]
+    fn serve_documentation_uis() {
+        let client = TestClient::new();
+        for path in ["/swagger-ui/index.html", "/rapidoc/index.html"] {
+            let response = client.get(path).dispatch();
+            assert_eq!(response.status(), Status::Ok, "{} not served", path);
+        }
+    }
+
+    #[test]
+    fn list_booking_summaries() {
+        let client = TestClient::new();
+        assert_eq!(client.create_booking(1).status(), Status::Ok);
+
+        let response = client.get("/v1/bookings?view=summary").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let bookings: Value =
+            rocket::serde::json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(bookings[0]["checkInDate"], "2030-01-01");
+        assert!(bookings[0].get("customerId").is_none());
+
+        let response = client
+            .get("/v1/bookings/customer/1?view=summary")
+            .dispatch();
+        assert!(!response.into_string().unwrap().contains("customerId"));
+
+        let response = client
+            .get("/v1/bookings/room-type/3?view=compact")
+            .dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn reject_invalid_replay() {
+        let client = TestClient::new();
+        let target: &str = "http%3A%2F%2Flocalhost%2Fevents";
+
+        let response = client
+            .post(format!(
+                "/v1/admin/events/replay?from_seq=5&to_seq=2&target={}",
+                target
+            ))
+            .dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client
+            .post(format!(
+                "/v1/admin/events/replay?from_seq=1&to_seq=2&target={}",
+                target
+            ))
+            .dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("No events webhooks are configured"));
+    }
+
+    #[test]
+    fn document_error_responses() {
+        let client = TestClient::new();
+        let spec: String = client
+            .get("/v1/openapi.json")
+            .dispatch()
+            .into_string()
+            .unwrap();
+        let spec: Value = rocket::serde::json::from_str(&spec).unwrap();
+        let responses: &Value = &spec["paths"]["/booking"]["post"]["responses"];
+
+        for status in ["200", "409", "422", "503"] {
+            assert!(responses.get(status).is_some(), "{} not documented", status);
+        }
+        assert!(responses.get("default").is_none());
+    }
+}
@@ -0,0 +1,220 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::circuit_breaker::CallError;
+use crate::config::{self, EventsConfig, WebhookSubscription};
+use crate::redaction;
+use crate::retry;
+use crate::storage;
+use crate::storage::booking_change::BookingChange;
+use crate::storage::outbox_event::{EventTopic, OutboxEvent};
+use hyper::body::Body;
+use hyper::{Client, Method, Request};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::serde::json;
+use rocket::tokio::time::{interval, Duration};
+use rocket::Rocket;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes the result of replaying a range of the change log to a webhook
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayReport {
+    /// The URL of the webhook the events were replayed to
+    pub target: String,
+    /// The sequence number of the first change replayed
+    pub from_seq: u64,
+    /// The sequence number of the last change replayed
+    pub to_seq: u64,
+    /// The number of events delivered to the webhook
+    pub delivered: usize,
+    /// The number of events not delivered, as they do not match the webhook's topics and filters
+    pub skipped: usize,
+    /// The sequence number of the event which could not be delivered, if replay stopped early.
+    /// Replay can be resumed from this sequence number.
+    pub failed_sequence: Option<u64>,
+    /// A description of why the event could not be delivered, if replay stopped early
+    pub error: Option<String>,
+}
+
+/// Posts an event to a webhook using the outbound retry policy. The sequence number of the event's
+/// booking change is sent as the idempotency key, so the receiver can ignore events delivered more
+/// than once, along with the event's topic.
+async fn deliver(url: &str, event: &OutboxEvent) -> Result<(), CallError> {
+    let body: String = json::to_string(&event.change)?;
+
+    return retry::call("webhooks", || post(url, event, body.clone())).await;
+}
+
+/// Posts a body to a webhook. Returns an error if the request fails, or the response status is
+/// not successful.
+async fn post(url: &str, event: &OutboxEvent, body: String) -> Result<(), CallError> {
+    let request: Request<Body> = Request::builder()
+        .method(Method::POST)
+        .uri(url)
+        .header("Content-Type", "application/json")
+        .header("Idempotency-Key", event.change.sequence.to_string())
+        .header("Event-Topic", format!("{:?}", event.topic))
+        .body(Body::from(body))?;
+
+    let status = Client::new().request(request).await?.status();
+
+    if !status.is_success() {
+        return Err(format!("The webhook responded with {}", status).into());
+    }
+
+    return Ok(());
+}
+
+/// Delivers a batch of events from the outbox, in the order they were recorded, to each webhook
+/// subscribed to the event's topic and matching its filters. Each event is removed from the outbox
+/// once delivered to every matching webhook, or straight away if no webhook matches. Delivery stops
+/// at the first event which cannot be delivered, so events are delivered in order, and the event
+/// is moved to the dead-letter store once it has failed the configured number of attempts.
+/// Webhooks which have already received an event are skipped when it is retried. Returns the
+/// number of events removed from the outbox.
+async fn dispatch(settings: &EventsConfig) -> usize {
+    let webhooks: Vec<WebhookSubscription> = settings.webhooks();
+    let mut delivered: usize = 0;
+
+    for event in storage::fetch_outbox(Some(settings.batch_size)) {
+        let pending = webhooks.iter().filter(|webhook| {
+            webhook.matches(&event) && !event.delivered_to.contains(&webhook.url)
+        });
+
+        for webhook in pending {
+            if let Err(error) = deliver(&webhook.url, &event).await {
+                let error: String = format!("{}: {}", webhook.url, error);
+                redaction::error(&format!(
+                    "Unable to deliver event {} to {}",
+                    event.change.sequence, error
+                ));
+
+                if storage::fail_event(
+                    event.change.sequence,
+                    &error,
+                    settings.max_delivery_attempts,
+                ) {
+                    redaction::error(&format!(
+                        "Moved event {} to the dead-letter store",
+                        event.change.sequence
+                    ));
+                }
+                return delivered;
+            }
+
+            storage::mark_delivered(event.change.sequence, &webhook.url);
+        }
+
+        storage::complete_event(event.change.sequence);
+        delivered += 1;
+    }
+
+    return delivered;
+}
+
+/// Re-delivers historical booking changes from the change log to a webhook, in the order they
+/// were recorded, e.g. to rebuild a downstream read model after an outage. Each change is
+/// delivered under the topic of its change type, and changes to sandbox bookings, or not matching
+/// the webhook's topics and filters, are skipped. The outbox is not affected. Replay stops at the first change which
+/// cannot be delivered. As each change's sequence number is sent as the idempotency key, receivers
+/// can safely ignore changes they have already seen.
+///
+/// # Arguments
+///
+/// * `webhook` - The webhook to deliver the changes to
+/// * `from_seq` - The sequence number of the first change to replay
+/// * `to_seq` - The sequence number of the last change to replay
+///
+/// # Examples
+///
+/// ```
+/// let report = outbox::replay(&webhook, 1, 100).await;
+/// ```
+pub async fn replay(webhook: &WebhookSubscription, from_seq: u64, to_seq: u64) -> ReplayReport {
+    let mut report: ReplayReport = ReplayReport {
+        target: webhook.url.clone(),
+        from_seq,
+        to_seq,
+        delivered: 0,
+        skipped: 0,
+        failed_sequence: None,
+        error: None,
+    };
+
+    let changes = storage::fetch_changes(from_seq.saturating_sub(1), None)
+        .changes
+        .into_iter()
+        .take_while(|change: &BookingChange| change.sequence <= to_seq);
+
+    for change in changes {
+        if change.booking.sandbox {
+            report.skipped += 1;
+            continue;
+        }
+
+        let topic: EventTopic = EventTopic::of(&change.change_type);
+        let event: OutboxEvent = OutboxEvent::new(change, topic);
+
+        if !webhook.matches(&event) {
+            report.skipped += 1;
+            continue;
+        }
+
+        if let Err(error) = deliver(&webhook.url, &event).await {
+            report.failed_sequence = Some(event.change.sequence);
+            report.error = Some(error.to_string());
+            return report;
+        }
+
+        report.delivered += 1;
+    }
+
+    return report;
+}
+
+/// A fairing which starts a background task when the server launches, if events are configured.
+/// The task delivers the events waiting in the outbox, including any left undelivered when the
+/// server last stopped, on the interval set by the events settings. Events are delivered at least
+/// once.
+pub struct OutboxDispatcher;
+
+#[rocket::async_trait]
+impl Fairing for OutboxDispatcher {
+    fn info(&self) -> Info {
+        return Info {
+            name: "Outbox dispatcher",
+            kind: Kind::Liftoff,
+        };
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<rocket::Orbit>) {
+        let settings: EventsConfig = match config::hotel().events {
+            Some(settings) => settings,
+            None => return,
+        };
+
+        let shutdown = rocket.shutdown();
+        rocket::tokio::spawn(async move {
+            let mut tick = interval(Duration::from_secs(
+                settings.dispatch_interval_seconds.max(1),
+            ));
+
+            loop {
+                rocket::tokio::select! {
+                    _ = tick.tick() => {}
+                    _ = shutdown.clone() => return,
+                }
+
+                let delivered: usize = dispatch(&settings).await;
+                if delivered > 0 {
+                    redaction::info(&format!("Delivered {} events", delivered));
+                }
+            }
+        });
+    }
+}
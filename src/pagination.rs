@@ -0,0 +1,230 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use rocket::http::uri::Origin;
+
+/// Describes the window of a collection requested by a client.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Pagination {
+    /// The maximum number of items to return. All items are returned if not provided.
+    pub limit: Option<usize>,
+    /// The number of items to skip before the first returned item
+    pub offset: Option<usize>,
+    /// The cursor of the last item already seen. Only items after it are returned, and the
+    /// offset is counted from the first of them.
+    pub after: Option<u32>,
+}
+
+impl Pagination {
+    /// Creates a new pagination window.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of items to return
+    /// * `offset` - The number of items to skip
+    pub fn new(limit: Option<usize>, offset: Option<usize>) -> Pagination {
+        return Pagination {
+            limit,
+            offset,
+            after: None,
+        };
+    }
+
+    /// Returns the number of items from the start of the collection (or the cursor, if provided)
+    /// to the end of the window, or None if no limit was requested.
+    pub fn window(&self) -> Option<usize> {
+        return self
+            .limit
+            .map(|limit| limit.saturating_add(self.offset.unwrap_or(0)));
+    }
+
+    /// Returns the items that fall inside the window.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The full, ordered collection
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let page = Pagination::new(Some(10), Some(20)).apply(bookings);
+    /// ```
+    pub fn apply<T>(&self, items: Vec<T>) -> Vec<T> {
+        let offset: usize = self.offset.unwrap_or(0);
+
+        return match self.limit {
+            Some(limit) => items.into_iter().skip(offset).take(limit).collect(),
+            None => items.into_iter().skip(offset).collect(),
+        };
+    }
+
+    /// Generates RFC 8288 links to the first, previous, next and last pages of a collection,
+    /// preserving any other query parameters present in the request.
+    ///
+    /// Returns no links if a limit was not requested.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The URI of the current request
+    /// * `total` - The total number of items in the collection
+    pub fn links(&self, origin: &Origin, total: usize) -> Vec<(String, String)> {
+        let limit: usize = match self.limit {
+            Some(limit) if limit > 0 => limit,
+            _ => return Vec::new(),
+        };
+        let offset: usize = self.offset.unwrap_or(0);
+
+        let link = |offset: usize| -> String {
+            return link_to(
+                origin,
+                &[format!("limit={}", limit), format!("offset={}", offset)],
+            );
+        };
+
+        let last_offset: usize = match total {
+            0 => 0,
+            total => ((total - 1) / limit) * limit,
+        };
+
+        let mut links: Vec<(String, String)> = vec![("first".to_string(), link(0))];
+
+        if offset > 0 {
+            links.push(("prev".to_string(), link(offset.saturating_sub(limit))));
+        }
+
+        if offset + limit < total {
+            links.push(("next".to_string(), link(offset + limit)));
+        }
+
+        links.push(("last".to_string(), link(last_offset)));
+        return links;
+    }
+
+    /// Generates RFC 8288 links to the first and next pages of a collection paged using a
+    /// cursor, preserving any other query parameters present in the request.
+    ///
+    /// Returns no links if a limit was not requested.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The URI of the current request
+    /// * `next_cursor` - The cursor of the last item on the page, if there are more items
+    pub fn cursor_links(&self, origin: &Origin, next_cursor: Option<u32>) -> Vec<(String, String)> {
+        let limit: usize = match self.limit {
+            Some(limit) if limit > 0 => limit,
+            _ => return Vec::new(),
+        };
+
+        let mut links: Vec<(String, String)> = vec![(
+            "first".to_string(),
+            link_to(origin, &[format!("limit={}", limit)]),
+        )];
+
+        if let Some(cursor) = next_cursor {
+            links.push((
+                "next".to_string(),
+                link_to(
+                    origin,
+                    &[format!("limit={}", limit), format!("after={}", cursor)],
+                ),
+            ));
+        }
+
+        return links;
+    }
+}
+
+/// Builds a link to the current request path with the provided pagination parameters, keeping
+/// every other query parameter of the request.
+fn link_to(origin: &Origin, pagination: &[String]) -> String {
+    let mut parameters: Vec<String> = Vec::new();
+    if let Some(original) = origin.query() {
+        for segment in original.raw_segments() {
+            let segment: &str = segment.as_str();
+            if !["limit=", "offset=", "after="]
+                .iter()
+                .any(|name| segment.starts_with(name))
+            {
+                parameters.push(segment.to_string());
+            }
+        }
+    }
+
+    parameters.extend(pagination.iter().cloned());
+    return format!("{}?{}", origin.path(), parameters.join("&"));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pagination::*;
+
+    #[test]
+    fn paginate_items() {
+        let items: Vec<u32> = (1..=5).collect();
+        assert_eq!(
+            Pagination::new(Some(2), Some(2)).apply(items.clone()),
+            vec![3, 4]
+        );
+        assert_eq!(
+            Pagination::new(None, Some(3)).apply(items.clone()),
+            vec![4, 5]
+        );
+        assert_eq!(Pagination::default().apply(items), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn pagination_links() {
+        let origin = Origin::parse("/bookings?customer_id=1&limit=2&offset=2").unwrap();
+        let links = Pagination::new(Some(2), Some(2)).links(&origin, 5);
+
+        assert_eq!(
+            links,
+            vec![
+                (
+                    "first".to_string(),
+                    "/bookings?customer_id=1&limit=2&offset=0".to_string()
+                ),
+                (
+                    "prev".to_string(),
+                    "/bookings?customer_id=1&limit=2&offset=0".to_string()
+                ),
+                (
+                    "next".to_string(),
+                    "/bookings?customer_id=1&limit=2&offset=4".to_string()
+                ),
+                (
+                    "last".to_string(),
+                    "/bookings?customer_id=1&limit=2&offset=4".to_string()
+                ),
+            ]
+        );
+
+        assert!(Pagination::default().links(&origin, 5).is_empty());
+    }
+
+    #[test]
+    fn cursor_links() {
+        let origin = Origin::parse("/bookings?status=Confirmed&limit=2&after=4").unwrap();
+        let pagination = Pagination {
+            after: Some(4),
+            ..Pagination::new(Some(2), None)
+        };
+
+        assert_eq!(
+            pagination.cursor_links(&origin, Some(9)),
+            vec![
+                (
+                    "first".to_string(),
+                    "/bookings?status=Confirmed&limit=2".to_string()
+                ),
+                (
+                    "next".to_string(),
+                    "/bookings?status=Confirmed&limit=2&after=9".to_string()
+                ),
+            ]
+        );
+        assert_eq!(pagination.cursor_links(&origin, None).len(), 1);
+    }
+}
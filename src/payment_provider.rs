@@ -0,0 +1,119 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::circuit_breaker::CallError;
+use crate::config::PaymentProviderConfig;
+use crate::http_client;
+use crate::retry;
+use crate::storage::payment::Refund;
+use hyper::body::{self, Body};
+use hyper::{Method, Request};
+use rocket::serde::json;
+use serde::{Deserialize, Serialize};
+
+/// Describes a refund requested from the payment provider
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RefundRequest {
+    pub booking_id: u32,
+    /// The amount to refund, in the minor unit of the hotel's currency
+    pub amount: u64,
+    /// The ISO 4217 code of the currency the amount is given in
+    pub currency: String,
+    /// Why the amount is being refunded, if known
+    pub reason: Option<String>,
+}
+
+impl RefundRequest {
+    /// Creates a refund request for a refund of a booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The booking id of the booking
+    /// * `refund` - The refund to request
+    /// * `currency` - The ISO 4217 code of the hotel's currency
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let request = RefundRequest::new(1, &refund, &config::hotel().currency);
+    /// ```
+    pub fn new(booking_id: u32, refund: &Refund, currency: &str) -> RefundRequest {
+        return RefundRequest {
+            booking_id,
+            amount: refund.amount,
+            currency: currency.to_string(),
+            reason: refund.reason.clone(),
+        };
+    }
+}
+
+/// Describes the payment provider's response to a refund request
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RefundResponse {
+    /// The payment provider's reference for the refund
+    pub reference: String,
+}
+
+/// Requests a refund from the payment provider, by posting a ```RefundRequest``` to the
+/// configured refund URL. Returns the payment provider's reference for the refund. Calls use the
+/// outbound retry policy and circuit breaker. Every attempt sends the same idempotency key, so a
+/// refund the provider made before an attempt failed, e.g. by timing out, is not made again. The
+/// refund URL must use https, as the API key is sent with each attempt.
+///
+/// # Arguments
+///
+/// * `settings` - The payment provider settings
+/// * `request` - The refund to request
+/// * `idempotency_key` - The key identifying the refund to the provider
+///
+/// # Examples
+///
+/// ```
+/// let reference = payment_provider::refund(&settings, &request, &key).await?;
+/// ```
+pub async fn refund(
+    settings: &PaymentProviderConfig,
+    request: &RefundRequest,
+    idempotency_key: &str,
+) -> Result<String, CallError> {
+    http_client::require_https(&settings.refund_url)?;
+    let body: String = json::to_string(request)?;
+
+    let content: Vec<u8> = retry::call("payment_provider", || {
+        send(settings, body.clone(), idempotency_key)
+    })
+    .await?;
+    let response: RefundResponse = json::from_slice(&content)?;
+
+    return Ok(response.reference);
+}
+
+/// Posts a refund request to the payment provider, with its idempotency key, returning the
+/// response body. Returns an error if the request fails, or the response status is not successful.
+async fn send(
+    settings: &PaymentProviderConfig,
+    body: String,
+    idempotency_key: &str,
+) -> Result<Vec<u8>, CallError> {
+    let request: Request<Body> = Request::builder()
+        .method(Method::POST)
+        .uri(&settings.refund_url)
+        .header("Authorization", format!("Bearer {}", settings.api_key))
+        .header("Content-Type", "application/json")
+        .header("Idempotency-Key", idempotency_key)
+        .body(Body::from(body))?;
+
+    let response = http_client::client().request(request).await?;
+    let status = response.status();
+    let content = body::to_bytes(response.into_body()).await?;
+
+    if !status.is_success() {
+        return Err(format!("The payment provider responded with {}", status).into());
+    }
+
+    return Ok(content.to_vec());
+}
@@ -0,0 +1,199 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::config::{self, PaymentWebhookConfig};
+use crate::redaction;
+use crate::storage;
+use crate::storage::payment::{Payment, Refund};
+use crate::storage::payment_event::{PaymentEvent, PaymentEventType};
+use hmac::{Hmac, Mac};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::request::{FromRequest, Outcome};
+use rocket::tokio::time::{interval, Duration};
+use rocket::{Request, Rocket};
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::request::{OpenApiFromRequest, RequestHeaderInput};
+use sha2::Sha256;
+
+/// The signature sent with a payment webhook, read from the header named in the payment webhook
+/// settings. The signature is None if the header is missing, or no webhook is configured.
+pub struct Signature(pub Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Signature {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Signature, ()> {
+        let signature: Option<String> = config::hotel().payment_webhooks.and_then(|settings| {
+            return request
+                .headers()
+                .get_one(&settings.signature_header)
+                .map(|signature| signature.to_string());
+        });
+
+        return Outcome::Success(Signature(signature));
+    }
+}
+
+impl<'r> OpenApiFromRequest<'r> for Signature {
+    fn from_request_input(
+        _: &mut OpenApiGenerator,
+        _: String,
+        _: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        return Ok(RequestHeaderInput::None);
+    }
+}
+
+/// Checks a webhook body was signed with the shared secret. The signature is the hex encoded
+/// HMAC-SHA256 of the body, optionally prefixed with 'sha256='. Signatures are compared in
+/// constant time.
+///
+/// # Arguments
+///
+/// * `secret` - The secret shared with the payment provider
+/// * `body` - The raw body of the webhook
+/// * `signature` - The signature sent with the webhook
+///
+/// # Examples
+///
+/// ```
+/// let valid = payment_webhooks::verify(&settings.secret, &body, &signature);
+/// ```
+pub fn verify(secret: &str, body: &str, signature: &str) -> bool {
+    if secret.is_empty() {
+        return false;
+    }
+
+    let signature: Vec<u8> = match hex::decode(signature.trim().trim_start_matches("sha256=")) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+
+    let mut mac = match Hmac::<Sha256>::new_from_slice(secret.as_bytes()) {
+        Ok(mac) => mac,
+        Err(_) => return false,
+    };
+    mac.update(body.as_bytes());
+
+    return mac.verify_slice(&signature).is_ok();
+}
+
+/// Applies a payment event to its booking. Successful payments are recorded against the booking,
+/// confirming it once its deposit is paid, failed payments remove the payment with the same
+/// reference, if one was recorded, and refunds are recorded against the booking.
+fn apply(event: &PaymentEvent) -> Result<(), String> {
+    return match event.event_type {
+        PaymentEventType::PaymentSucceeded => storage::record_payment(
+            event.booking_id,
+            Payment {
+                amount: event.amount,
+                reference: event.reference.clone(),
+                received_at: None,
+            },
+        )
+        .map(|_| ()),
+        PaymentEventType::PaymentFailed => match &event.reference {
+            Some(reference) => storage::reverse_payment(event.booking_id, reference).map(|_| ()),
+            None => Ok(()),
+        },
+        PaymentEventType::Refunded => storage::record_refund(
+            event.booking_id,
+            Refund {
+                amount: event.amount,
+                reason: None,
+                reference: event.reference.clone(),
+                refunded_at: None,
+            },
+        )
+        .map(|_| ()),
+    };
+}
+
+/// Applies each payment event waiting to be applied, in the order they were received. Events
+/// which cannot be applied are marked as processed with a description of the error, as the
+/// provider will not resend them. Returns the number of events applied.
+fn process() -> usize {
+    let mut applied: usize = 0;
+
+    for event in storage::fetch_pending_payment_events() {
+        let error: Option<String> = apply(&event).err();
+
+        if let Some(error) = &error {
+            redaction::error(&format!(
+                "Unable to apply payment event {} to booking {}: {}",
+                event.event_id, event.booking_id, error
+            ));
+        } else {
+            applied += 1;
+        }
+
+        storage::complete_payment_event(&event.event_id, error);
+    }
+
+    return applied;
+}
+
+/// A fairing which starts a background task when the server launches, if the payment webhook is
+/// configured. The task applies the payment events received from the payment provider, including
+/// any left unapplied when the server last stopped, on the interval set by the payment webhook
+/// settings.
+pub struct PaymentEventProcessor;
+
+#[rocket::async_trait]
+impl Fairing for PaymentEventProcessor {
+    fn info(&self) -> Info {
+        return Info {
+            name: "Payment event processor",
+            kind: Kind::Liftoff,
+        };
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<rocket::Orbit>) {
+        let settings: PaymentWebhookConfig = match config::hotel().payment_webhooks {
+            Some(settings) => settings,
+            None => return,
+        };
+
+        let shutdown = rocket.shutdown();
+        rocket::tokio::spawn(async move {
+            let mut tick = interval(Duration::from_secs(
+                settings.process_interval_seconds.max(1),
+            ));
+
+            loop {
+                rocket::tokio::select! {
+                    _ = tick.tick() => {}
+                    _ = shutdown.clone() => return,
+                }
+
+                let applied: usize = process();
+                if applied > 0 {
+                    redaction::info(&format!("Applied {} payment events", applied));
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::payment_webhooks::*;
+
+    #[test]
+    fn verify_signature() {
+        let body = r#"{"eventId":"evt_1"}"#;
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(body.as_bytes());
+        let signature = hex::encode(mac.finalize().into_bytes());
+
+        assert!(verify("secret", body, &signature));
+        assert!(verify("secret", body, &format!("sha256={}", signature)));
+        assert!(!verify("other", body, &signature));
+        assert!(!verify("secret", r#"{"eventId":"evt_2"}"#, &signature));
+        assert!(!verify("", body, &signature));
+        assert!(!verify("secret", body, "not hex"));
+    }
+}
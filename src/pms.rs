@@ -0,0 +1,103 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use self::generic::GenericPms;
+use crate::storage::room_booking::RoomBooking;
+use rocket::serde::json::{from_str, Value};
+pub mod generic;
+
+/// Maps the reservations of an external property management system (PMS) to bookings, and
+/// bookings back to reservations, so bookings can be imported from and exported to the PMS.
+pub trait PmsAdapter {
+    /// Returns the name used to select the adapter, e.g. 'generic'.
+    fn name(&self) -> &'static str;
+
+    /// Maps a reservation payload from the PMS into a booking, without a booking id. Returns a
+    /// description of the problem if the payload cannot be mapped.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The reservation, in the format used by the PMS
+    fn to_booking(&self, payload: Value) -> Result<RoomBooking, String>;
+
+    /// Maps a booking into a reservation payload for the PMS.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking` - The booking to map
+    fn to_reservation(&self, booking: &RoomBooking) -> Value;
+}
+
+/// Returns every available PMS adapter.
+///
+/// # Examples
+///
+/// ```
+/// let adapters = pms::adapters();
+/// ```
+pub fn adapters() -> Vec<Box<dyn PmsAdapter + Send + Sync>> {
+    return vec![Box::new(GenericPms)];
+}
+
+/// Returns the PMS adapter with the provided name, if any.
+///
+/// # Arguments
+///
+/// * `name` - The name of the adapter
+///
+/// # Examples
+///
+/// ```
+/// let adapter = pms::find("generic");
+/// ```
+pub fn find(name: &str) -> Option<Box<dyn PmsAdapter + Send + Sync>> {
+    return adapters()
+        .into_iter()
+        .find(|adapter| adapter.name() == name);
+}
+
+/// Parses a JSON array of PMS reservations into bookings. Returns an error if the content is not
+/// a JSON array, or a list containing the booking or mapping error for each reservation.
+///
+/// # Arguments
+///
+/// * `adapter` - The adapter for the PMS the reservations were exported from
+/// * `content` - The JSON array of reservations
+///
+/// # Examples
+///
+/// ```
+/// let rows = pms::parse(adapter.as_ref(), &content)?;
+/// ```
+pub fn parse(
+    adapter: &dyn PmsAdapter,
+    content: &str,
+) -> Result<Vec<Result<RoomBooking, String>>, String> {
+    let reservations: Vec<Value> = from_str(content).map_err(|error| error.to_string())?;
+
+    return Ok(reservations
+        .into_iter()
+        .map(|reservation| adapter.to_booking(reservation))
+        .collect());
+}
+
+/// Maps bookings into PMS reservations, one at a time as they are reached.
+///
+/// # Arguments
+///
+/// * `adapter` - The adapter for the PMS the reservations are exported to
+/// * `bookings` - An iterator over the bookings to export, e.g. a ```BookingCursor```
+///
+/// # Examples
+///
+/// ```
+/// let reservations = pms::export(adapter, cursor);
+/// ```
+pub fn export<I: Iterator<Item = RoomBooking>>(
+    adapter: Box<dyn PmsAdapter + Send + Sync>,
+    bookings: I,
+) -> impl Iterator<Item = Value> {
+    return bookings.map(move |booking| adapter.to_reservation(&booking));
+}
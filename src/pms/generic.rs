@@ -0,0 +1,155 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::PmsAdapter;
+use crate::config;
+use crate::storage::room_booking::{BookingStatus, RoomBooking};
+use rocket::serde::json::{from_value, json, to_value, Value};
+use serde::{Deserialize, Serialize};
+
+/// Describes a reservation in the generic PMS format, a flat JSON record supported by many
+/// property management systems' import and export tools
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GenericReservation {
+    /// The booking id, set when a booking is exported and ignored when it is imported
+    #[serde(default)]
+    pub confirmation_number: Option<u32>,
+    pub guest_id: u32,
+    /// The PMS code for the room type, mapped using the ```pms_room_types``` setting
+    pub room_type: String,
+    /// The arrival date (YYYY-MM-DD)
+    pub arrival: String,
+    /// The departure date (YYYY-MM-DD)
+    pub departure: String,
+    /// One of RESERVED, PENDING, IN_HOUSE, CHECKED_OUT, CANCELLED or NO_SHOW
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub adults: u32,
+    #[serde(default)]
+    pub children: u32,
+    /// The expected arrival time (HH:MM)
+    #[serde(default)]
+    pub eta: Option<String>,
+}
+
+/// The status codes of the generic PMS format, and the booking status each maps to.
+static STATUS_CODES: &[(&str, BookingStatus)] = &[
+    ("RESERVED", BookingStatus::Confirmed),
+    ("PENDING", BookingStatus::PendingPayment),
+    ("IN_HOUSE", BookingStatus::CheckedIn),
+    ("CHECKED_OUT", BookingStatus::Complete),
+    ("CANCELLED", BookingStatus::Cancelled),
+    ("NO_SHOW", BookingStatus::NoShow),
+];
+
+/// The reference PMS adapter, mapping reservations in the generic PMS format.
+pub struct GenericPms;
+
+impl PmsAdapter for GenericPms {
+    fn name(&self) -> &'static str {
+        return "generic";
+    }
+
+    fn to_booking(&self, payload: Value) -> Result<RoomBooking, String> {
+        let reservation: GenericReservation =
+            from_value(payload).map_err(|error| error.to_string())?;
+
+        let room_type_id: u8 = match config::room_type_for_code(
+            &config::hotel().pms_room_types,
+            &reservation.room_type,
+        ) {
+            Some(room_type_id) => room_type_id,
+            None => return Err(format!("Room type {} is not mapped", reservation.room_type)),
+        };
+
+        let status: Option<BookingStatus> = match &reservation.status {
+            Some(code) => match STATUS_CODES.iter().find(|(name, _)| name == code) {
+                Some((_, status)) => Some(status.clone()),
+                None => return Err(format!("{} is not a known status", code)),
+            },
+            None => None,
+        };
+
+        let booking = json!({
+            "customerId": reservation.guest_id,
+            "roomTypeId": room_type_id,
+            "checkInDate": reservation.arrival,
+            "checkOutDate": reservation.departure,
+            "status": status,
+            "expectedArrivalTime": reservation.eta,
+            "guests": (reservation.adults + reservation.children).max(1),
+            "children": reservation.children,
+        });
+
+        return from_value(booking).map_err(|error| error.to_string());
+    }
+
+    fn to_reservation(&self, booking: &RoomBooking) -> Value {
+        let status: Option<String> = booking.status.as_ref().and_then(|status| {
+            STATUS_CODES
+                .iter()
+                .find(|(_, other)| other == status)
+                .map(|(code, _)| code.to_string())
+        });
+
+        let reservation = GenericReservation {
+            confirmation_number: booking.booking_id,
+            guest_id: booking.customer_id,
+            room_type: config::code_for_room_type(
+                &config::hotel().pms_room_types,
+                booking.room_type_id,
+            )
+            .unwrap_or(booking.room_type_id.to_string()),
+            arrival: booking.check_in_date.clone(),
+            departure: booking.check_out_date.clone(),
+            status,
+            adults: booking.guests.saturating_sub(booking.children),
+            children: booking.children,
+            eta: booking.expected_arrival_time.clone(),
+        };
+
+        return to_value(reservation).unwrap_or(Value::Null);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pms::generic::*;
+
+    #[test]
+    fn map_generic_reservation() {
+        let payload: Value = json!({
+            "guestId": 12,
+            "roomType": "1",
+            "arrival": "2020-01-01",
+            "departure": "2020-01-03",
+            "status": "IN_HOUSE",
+            "adults": 2,
+            "children": 1,
+        });
+
+        // Room type codes are only mapped through the pms_room_types setting.
+        assert!(GenericPms.to_booking(payload).is_err());
+
+        let booking: RoomBooking = from_value(json!({
+            "bookingId": 4,
+            "customerId": 12,
+            "roomTypeId": 1,
+            "checkInDate": "2020-01-01",
+            "checkOutDate": "2020-01-03",
+            "status": "CheckedIn",
+            "guests": 3,
+        }))
+        .unwrap();
+        let exported: Value = GenericPms.to_reservation(&booking);
+
+        assert_eq!(exported["confirmationNumber"], 4);
+        assert_eq!(exported["roomType"], "1");
+        assert_eq!(exported["status"], "IN_HOUSE");
+        assert_eq!(exported["adults"], 3);
+    }
+}
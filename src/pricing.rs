@@ -0,0 +1,868 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::addons::{self, AddonCharge};
+use crate::clock;
+use crate::config;
+use crate::exchange_rates;
+use crate::locale::Locale;
+use crate::storage;
+use crate::storage::billing_milestone::BillingMilestone;
+use crate::storage::exchange_rate::ExchangeRate;
+use crate::storage::pricing_record::{NightlyPrice, PricingRecord};
+use crate::storage::promo_code::Discount;
+use crate::storage::room_booking::{BookingRules, BookingStatus, RoomBooking, DATE_FORMAT};
+use crate::tax;
+pub use booking_types::pricing_record::PriceLine;
+use chrono::{Datelike, Months, NaiveDate, SecondsFormat, Weekday};
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Describes the rates charged for a room type. Rates are given in the minor unit of the hotel's
+/// currency, e.g. pence.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct RoomRate {
+    /// The id of the room type the rates apply to
+    pub room_type_id: u8,
+    /// The rate charged per night
+    pub nightly_rate: u64,
+    /// The rate charged per month for long stays. Partial months are prorated.
+    pub monthly_rate: u64,
+}
+
+/// The name shown against nights charged at a rate given by the dynamic pricing strategy.
+pub const DYNAMIC_RATE: &str = "Dynamic rate";
+
+/// Describes a named rate plan for a room type, charged instead of the room type's nightly rate
+/// for the nights it applies to. Rates are given in the minor unit of the hotel's currency.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct RatePlan {
+    /// The name of the plan, e.g. Summer, shown on quotes and recorded on bookings
+    pub name: String,
+    /// The id of the room type the plan applies to
+    pub room_type_id: u8,
+    /// The rate charged per night, before any day of week modifier
+    pub nightly_rate: u64,
+    /// The first night the plan applies to (YYYY-MM-DD). The plan applies from any date if not
+    /// given.
+    #[serde(default)]
+    pub start_date: Option<String>,
+    /// The last night the plan applies to, inclusive (YYYY-MM-DD). The plan applies until any
+    /// date if not given.
+    #[serde(default)]
+    pub end_date: Option<String>,
+    /// The plan's priority when several plans apply to the same night. The plan with the highest
+    /// priority is charged, and the plan listed first if several share it.
+    #[serde(default)]
+    pub priority: u32,
+    /// Changes to the nightly rate on particular days of the week
+    #[serde(default)]
+    pub day_of_week_modifiers: Vec<DayOfWeekModifier>,
+}
+
+/// Describes a change to a rate plan's nightly rate on particular days of the week
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct DayOfWeekModifier {
+    /// The days the modifier applies to, e.g. Fri and Sat
+    pub days: Vec<Weekday>,
+    /// The percentage added to the nightly rate, or taken off it if negative
+    pub percent: i32,
+}
+
+impl RatePlan {
+    /// Checks whether the plan applies to a night.
+    fn applies_on(&self, night: &str) -> bool {
+        return self
+            .start_date
+            .as_ref()
+            .is_none_or(|start_date| night >= start_date.as_str())
+            && self
+                .end_date
+                .as_ref()
+                .is_none_or(|end_date| night <= end_date.as_str());
+    }
+
+    /// Returns the rate charged for a night, after the first day of week modifier applying to it.
+    fn rate_on(&self, night: NaiveDate) -> u64 {
+        let percent: i64 = self
+            .day_of_week_modifiers
+            .iter()
+            .find(|modifier| modifier.days.contains(&night.weekday()))
+            .map_or(0, |modifier| modifier.percent as i64);
+
+        return (self.nightly_rate as i64 * (100 + percent) / 100).max(0) as u64;
+    }
+}
+
+/// Describes the price of a booking, itemized into lines, used for quotes and invoices
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceBreakdown {
+    /// The ISO 4217 code of the currency prices are given in
+    pub currency: String,
+    pub lines: Vec<PriceLine>,
+    /// The discounts taken off the price of the lines, such as from a promo code
+    pub discounts: Vec<PriceLine>,
+    /// The taxes charged on the price, after any discount
+    pub taxes: Vec<PriceLine>,
+    /// The total price, in the minor unit of the currency
+    pub total: u64,
+    /// The rate prices were converted from the hotel's currency at, or null if they are in the
+    /// hotel's currency
+    #[serde(default)]
+    pub exchange_rate: Option<f64>,
+}
+
+impl PriceBreakdown {
+    /// Creates a price breakdown from a list of lines, in the hotel's currency.
+    ///
+    /// # Arguments
+    ///
+    /// * `lines` - The lines of the quote or invoice
+    pub fn new(lines: Vec<PriceLine>) -> PriceBreakdown {
+        return PriceBreakdown {
+            currency: config::hotel().currency,
+            total: lines.iter().map(|line| line.amount).sum(),
+            lines,
+            discounts: Vec::new(),
+            taxes: Vec::new(),
+            exchange_rate: None,
+        };
+    }
+
+    /// Converts the prices from the hotel's currency into another currency, rounding each amount
+    /// to the nearest minor unit of the currency. Amounts are rounded separately, so the converted
+    /// lines may not add up exactly to the converted total.
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - The exchange rate to convert at
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let quote = quote.convert(&exchange_rates::rate("EUR").await?);
+    /// ```
+    pub fn convert(&self, rate: &ExchangeRate) -> PriceBreakdown {
+        let scale: f64 = rate.rate
+            * 10f64.powi(
+                exchange_rates::minor_unit_digits(&rate.currency)
+                    - exchange_rates::minor_unit_digits(&self.currency),
+            );
+        let amount = |amount: u64| -> u64 { (amount as f64 * scale).round() as u64 };
+        let lines = |lines: &[PriceLine]| -> Vec<PriceLine> {
+            return lines
+                .iter()
+                .map(|line| PriceLine {
+                    description: line.description.clone(),
+                    quantity: line.quantity,
+                    unit_price: amount(line.unit_price),
+                    amount: amount(line.amount),
+                })
+                .collect();
+        };
+
+        return PriceBreakdown {
+            currency: rate.currency.clone(),
+            lines: lines(&self.lines),
+            discounts: lines(&self.discounts),
+            taxes: lines(&self.taxes),
+            total: amount(self.total),
+            exchange_rate: Some(rate.rate),
+        };
+    }
+
+    /// Takes a discount off the total price.
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - A description of the discount
+    /// * `discount` - The discount to take off
+    pub fn apply_discount(&mut self, description: String, discount: &Discount) {
+        let amount: u64 = discount.amount(self.total);
+        self.discounts.push(PriceLine::new(description, 1, amount));
+        self.total -= amount;
+    }
+
+    /// Adds each tax charged on a booking to the total price, charging percentage taxes on the
+    /// price after any discount.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking` - The booking being priced
+    /// * `nights` - The number of nights charged for, which is 0 if the guest did not stay
+    pub fn apply_taxes(&mut self, booking: &RoomBooking, nights: u64) {
+        self.taxes = tax::lines(booking, self.total, nights);
+        self.total += self.taxes.iter().map(|line| line.amount).sum::<u64>();
+    }
+}
+
+/// Describes the invoice of a booking: its itemized price, with its dates and amounts formatted
+/// for the guest's locale
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Invoice {
+    #[serde(flatten)]
+    pub price: PriceBreakdown,
+    /// The language tag of the locale the invoice is formatted for, e.g. 'fr-FR'
+    pub locale: String,
+    pub formatted: FormattedInvoice,
+}
+
+/// Describes the dates and amounts of an invoice, formatted for display to the guest
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FormattedInvoice {
+    pub check_in_date: String,
+    pub check_out_date: String,
+    pub lines: Vec<FormattedLine>,
+    pub discounts: Vec<FormattedLine>,
+    pub taxes: Vec<FormattedLine>,
+    pub total: String,
+}
+
+/// Describes a single line of an invoice, with its prices formatted for display to the guest
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FormattedLine {
+    pub description: String,
+    pub quantity: u64,
+    pub unit_price: String,
+    pub amount: String,
+}
+
+impl Invoice {
+    /// Creates the invoice of a booking from its price, formatting its dates and amounts for a
+    /// locale. Dates which cannot be read are given as stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking` - The booking being invoiced
+    /// * `price` - The price of the booking
+    /// * `locale` - The locale to format the invoice for
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let invoice = Invoice::new(&booking, pricing::quote(&booking)?, &locale);
+    /// ```
+    pub fn new(booking: &RoomBooking, price: PriceBreakdown, locale: &Locale) -> Invoice {
+        let date = |date: &str| -> String {
+            return match NaiveDate::parse_from_str(date, DATE_FORMAT) {
+                Ok(date) => locale.format_date(date),
+                Err(_) => date.to_string(),
+            };
+        };
+        let lines = |lines: &[PriceLine]| -> Vec<FormattedLine> {
+            return lines
+                .iter()
+                .map(|line| FormattedLine {
+                    description: line.description.clone(),
+                    quantity: line.quantity,
+                    unit_price: locale.format_amount(&price.currency, line.unit_price),
+                    amount: locale.format_amount(&price.currency, line.amount),
+                })
+                .collect();
+        };
+
+        let formatted: FormattedInvoice = FormattedInvoice {
+            check_in_date: date(&booking.check_in_date),
+            check_out_date: date(&booking.check_out_date),
+            lines: lines(&price.lines),
+            discounts: lines(&price.discounts),
+            taxes: lines(&price.taxes),
+            total: locale.format_amount(&price.currency, price.total),
+        };
+
+        return Invoice {
+            price,
+            locale: locale.tag(),
+            formatted,
+        };
+    }
+}
+
+/// Returns the rates configured for a room type, if any.
+///
+/// # Arguments
+///
+/// * `room_type_id` - The id of the room type
+///
+/// # Examples
+///
+/// ```
+/// let rate = pricing::rate_for(1);
+/// ```
+pub fn rate_for(room_type_id: u8) -> Option<RoomRate> {
+    return config::hotel()
+        .rates
+        .into_iter()
+        .find(|rate| rate.room_type_id == room_type_id);
+}
+
+/// Returns the rates charged for a booking. Bookings made against a corporate account are charged
+/// the account's negotiated rates, and other bookings the rates configured for their room type.
+///
+/// # Arguments
+///
+/// * `booking` - The booking to find the rates for
+///
+/// # Examples
+///
+/// ```
+/// let rate = pricing::rate_for_booking(&booking);
+/// ```
+pub fn rate_for_booking(booking: &RoomBooking) -> Option<RoomRate> {
+    return negotiated_rate(booking).or_else(|| rate_for(booking.room_type_id));
+}
+
+/// Returns the rates negotiated by the corporate account a booking was made against, if any.
+fn negotiated_rate(booking: &RoomBooking) -> Option<RoomRate> {
+    return booking
+        .account_id
+        .and_then(storage::fetch_account)
+        .and_then(|account| account.rate_for(booking.room_type_id));
+}
+
+/// Returns the rate plan charged for a night of a room type, if any plan applies to it.
+///
+/// # Arguments
+///
+/// * `plans` - The rate plans, in the order they are configured
+/// * `room_type_id` - The id of the room type
+/// * `night` - The night (YYYY-MM-DD)
+fn resolve_plan<'a>(plans: &'a [RatePlan], room_type_id: u8, night: &str) -> Option<&'a RatePlan> {
+    return plans
+        .iter()
+        .rev()
+        .filter(|plan| plan.room_type_id == room_type_id && plan.applies_on(night))
+        .max_by_key(|plan| plan.priority);
+}
+
+/// Returns the rate plan and rate charged for each night of a booking, in order. Nights with a
+/// dynamic rate are charged it, and other nights are charged the rate plan applying to them, or
+/// the provided nightly rate, with no plan, if none does. Bookings made against a corporate
+/// account with negotiated rates, day use bookings and long stays are not charged by the night,
+/// so an empty list is returned.
+///
+/// # Arguments
+///
+/// * `booking` - The booking to price
+/// * `nightly_rate` - The nightly rate charged for nights without a rate plan
+/// * `dynamic_rates` - The rates given by the dynamic pricing strategy, keyed by date
+fn nightly_rates(
+    booking: &RoomBooking,
+    nightly_rate: u64,
+    dynamic_rates: &HashMap<String, u64>,
+) -> Vec<(Option<String>, u64)> {
+    if booking.day_use
+        || !billing_milestones(booking).is_empty()
+        || negotiated_rate(booking).is_some()
+    {
+        return Vec::new();
+    }
+
+    let plans: Vec<RatePlan> = config::hotel().rate_plans;
+
+    return booking
+        .nights()
+        .iter()
+        .map(|night| {
+            if let Some(rate) = dynamic_rates.get(night) {
+                return (Some(DYNAMIC_RATE.to_string()), *rate);
+            }
+
+            let plan: Option<&RatePlan> = resolve_plan(&plans, booking.room_type_id, night);
+            let date: Option<NaiveDate> = NaiveDate::parse_from_str(night, DATE_FORMAT).ok();
+
+            return match (plan, date) {
+                (Some(plan), Some(date)) => (Some(plan.name.clone()), plan.rate_on(date)),
+                _ => (None, nightly_rate),
+            };
+        })
+        .collect();
+}
+
+/// Returns the names of the rate plans charged for a booking, in the order its nights are charged
+/// at them, to be recorded on the booking. Returns an empty list if no rate plan applies.
+///
+/// # Arguments
+///
+/// * `booking` - The booking to price
+///
+/// # Examples
+///
+/// ```
+/// booking.rate_plans = pricing::rate_plans(&booking);
+/// ```
+pub fn rate_plans(booking: &RoomBooking) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (plan, _) in nightly_rates(booking, 0, &HashMap::new()) {
+        if let Some(plan) = plan.filter(|plan| !names.contains(plan)) {
+            names.push(plan);
+        }
+    }
+
+    return names;
+}
+
+/// Splits a long stay booking into monthly billing periods, starting from the check in date. Each
+/// full month is charged at the room type's monthly rate, and a final partial month is prorated by
+/// the number of nights it contains. Returns an empty list if the booking is not a long stay, its
+/// dates are invalid, or no rate is configured for its room type.
+///
+/// # Arguments
+///
+/// * `booking` - The booking to bill
+///
+/// # Examples
+///
+/// ```
+/// booking.billing_milestones = pricing::billing_milestones(&booking);
+/// ```
+pub fn billing_milestones(booking: &RoomBooking) -> Vec<BillingMilestone> {
+    let check_in_date = NaiveDate::parse_from_str(&booking.check_in_date, DATE_FORMAT);
+    let check_out_date = NaiveDate::parse_from_str(&booking.check_out_date, DATE_FORMAT);
+
+    let (check_in_date, check_out_date) = match (check_in_date, check_out_date) {
+        (Ok(check_in_date), Ok(check_out_date)) => (check_in_date, check_out_date),
+        _ => return Vec::new(),
+    };
+
+    let nights: i64 = (check_out_date - check_in_date).num_days();
+    if booking.day_use || nights < config::hotel().long_stay_nights as i64 {
+        return Vec::new();
+    }
+
+    let monthly_rate: u64 = match rate_for_booking(booking) {
+        Some(rate) => rate.monthly_rate,
+        None => return Vec::new(),
+    };
+
+    let mut milestones: Vec<BillingMilestone> = Vec::new();
+    let mut period_start: NaiveDate = check_in_date;
+    let mut period: u32 = 1;
+
+    // Periods are measured from the check in date, so a stay starting on the 31st does not drift
+    // to an earlier day after a shorter month.
+    while period_start < check_out_date {
+        let full_period_end: NaiveDate = match check_in_date.checked_add_months(Months::new(period))
+        {
+            Some(date) => date,
+            None => break,
+        };
+        let period_end: NaiveDate = full_period_end.min(check_out_date);
+        let period_nights: u64 = (period_end - period_start).num_days() as u64;
+        let full_period_nights: u64 = (full_period_end - period_start).num_days() as u64;
+
+        milestones.push(BillingMilestone {
+            period_start: period_start.format(DATE_FORMAT).to_string(),
+            period_end: period_end.format(DATE_FORMAT).to_string(),
+            nights: period_nights as u32,
+            amount: prorate(monthly_rate, period_nights, full_period_nights),
+        });
+
+        period_start = period_end;
+        period += 1;
+    }
+
+    return milestones;
+}
+
+/// Prices a booking, itemizing the room charges, each add-on, any discount and each tax. Long
+/// stays are charged for each monthly billing period, and other stays for each night. Bookings
+/// which were priced when they were made are charged the room charges and taxes recorded then.
+/// Bookings which were cancelled are not charged, and bookings where the guest did not arrive are
+/// charged the no-show fee. Returns an error if the booking has not been priced, and no rate is
+/// configured for its room type.
+///
+/// # Arguments
+///
+/// * `booking` - The booking to price
+///
+/// # Examples
+///
+/// ```
+/// let quote = pricing::quote(&booking)?;
+/// ```
+pub fn quote(booking: &RoomBooking) -> Result<PriceBreakdown, String> {
+    return quote_with_rates(booking, &HashMap::new());
+}
+
+/// Prices a booking in the same way as ```quote```, charging the rates given by a dynamic pricing
+/// strategy for the nights they are given for, instead of the configured rates. Dynamic rates
+/// only apply to nights charged by the night.
+///
+/// # Arguments
+///
+/// * `booking` - The booking to price
+/// * `dynamic_rates` - The rates given by the dynamic pricing strategy, keyed by date
+///
+/// # Examples
+///
+/// ```
+/// let quote = pricing::quote_with_rates(&booking, &dynamic_rates)?;
+/// ```
+pub fn quote_with_rates(
+    booking: &RoomBooking,
+    dynamic_rates: &HashMap<String, u64>,
+) -> Result<PriceBreakdown, String> {
+    match booking.status {
+        Some(BookingStatus::Cancelled) => return Ok(PriceBreakdown::new(Vec::new())),
+        Some(BookingStatus::NoShow) => {
+            let fee: u64 = booking.no_show_fee.unwrap_or_default();
+            let mut breakdown: PriceBreakdown =
+                PriceBreakdown::new(vec![PriceLine::new("No-show fee".to_string(), 1, fee)]);
+            breakdown.apply_taxes(booking, 0);
+            return Ok(breakdown);
+        }
+        _ => {}
+    }
+
+    let mut lines: Vec<PriceLine> = match (&booking.pricing, rate_for_booking(booking)) {
+        (Some(record), _) => record.room_lines.clone(),
+        (None, Some(rate)) => room_lines(booking, &rate, dynamic_rates),
+        (None, None) => {
+            return Err(format!(
+                "No rate is configured for room type {}",
+                booking.room_type_id
+            ))
+        }
+    };
+    let nights: u64 = (booking.nights().len() as u64).max(1);
+
+    let mut booking_addons = booking.addons.clone();
+    addons::price(&mut booking_addons);
+    for addon in booking_addons {
+        let name: String = addons::find(&addon.code).map_or(addon.code.clone(), |item| item.name);
+        let quantity: u64 = match addon.charge {
+            Some(AddonCharge::PerNight) => addon.quantity as u64 * nights,
+            _ => addon.quantity as u64,
+        };
+
+        lines.push(PriceLine::new(
+            name,
+            quantity,
+            addon.unit_price.unwrap_or_default(),
+        ));
+    }
+
+    let mut breakdown: PriceBreakdown = PriceBreakdown::new(lines);
+    if let Some(record) = &booking.pricing {
+        breakdown.currency = record.currency.clone();
+    }
+
+    if let Some(discount) = &booking.discount {
+        let description: String = match &booking.promo_code {
+            Some(code) => format!("Promo code {}", code),
+            None => "Discount".to_string(),
+        };
+        breakdown.apply_discount(description, discount);
+    }
+    breakdown.apply_taxes(booking, booking.nights().len() as u64);
+
+    return Ok(breakdown);
+}
+
+/// Records the rates and taxes in effect for a booking, so it is charged them however the
+/// configured rates and taxes later change. Returns None if no rate is configured for the
+/// booking's room type.
+///
+/// # Arguments
+///
+/// * `booking` - The booking to price
+///
+/// # Examples
+///
+/// ```
+/// booking.pricing = pricing::record(&booking);
+/// ```
+pub fn record(booking: &RoomBooking) -> Option<PricingRecord> {
+    let rate: RoomRate = rate_for_booking(booking)?;
+    let nightly_prices: Vec<NightlyPrice> = booking
+        .nights()
+        .into_iter()
+        .zip(nightly_rates(booking, rate.nightly_rate, &HashMap::new()))
+        .map(|(date, (rate_plan, rate))| NightlyPrice {
+            date,
+            rate_plan,
+            rate,
+        })
+        .collect();
+
+    return Some(PricingRecord {
+        priced_at: clock::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        currency: config::hotel().currency,
+        nightly_prices,
+        room_lines: room_lines(booking, &rate, &HashMap::new()),
+        taxes: tax::applied(booking),
+    });
+}
+
+/// Returns the room charges for a booking. Consecutive nights charged at the same rate plan and
+/// rate share a line.
+fn room_lines(
+    booking: &RoomBooking,
+    rate: &RoomRate,
+    dynamic_rates: &HashMap<String, u64>,
+) -> Vec<PriceLine> {
+    if booking.day_use {
+        return vec![PriceLine::new(
+            "Day use room".to_string(),
+            1,
+            rate.nightly_rate,
+        )];
+    }
+
+    let milestones: Vec<BillingMilestone> = match booking.billing_milestones.is_empty() {
+        true => billing_milestones(booking),
+        false => booking.billing_milestones.clone(),
+    };
+
+    if milestones.is_empty() {
+        let mut lines: Vec<PriceLine> = Vec::new();
+        let mut previous: Option<(Option<String>, u64)> = None;
+
+        for (plan, nightly_rate) in nightly_rates(booking, rate.nightly_rate, dynamic_rates) {
+            if previous == Some((plan.clone(), nightly_rate)) {
+                if let Some(line) = lines.last_mut() {
+                    *line =
+                        PriceLine::new(line.description.clone(), line.quantity + 1, nightly_rate);
+                    continue;
+                }
+            }
+
+            let description: String = match &plan {
+                Some(plan) => format!("Room, {}, per night", plan),
+                None => "Room, per night".to_string(),
+            };
+            lines.push(PriceLine::new(description, 1, nightly_rate));
+            previous = Some((plan, nightly_rate));
+        }
+
+        if lines.is_empty() {
+            lines.push(PriceLine::new(
+                "Room, per night".to_string(),
+                booking.nights().len() as u64,
+                rate.nightly_rate,
+            ));
+        }
+
+        return lines;
+    }
+
+    return milestones
+        .iter()
+        .map(|milestone| {
+            PriceLine::new(
+                format!(
+                    "Room, {} to {}",
+                    milestone.period_start, milestone.period_end
+                ),
+                1,
+                milestone.amount,
+            )
+        })
+        .collect();
+}
+
+/// Returns the fee charged when the guest does not arrive for a booking, which is the nightly rate
+/// of the room type for the number of nights set by the hotel's no-show policy. Returns 0 if no
+/// rate is configured for the room type.
+///
+/// # Arguments
+///
+/// * `booking` - The booking the guest did not arrive for
+///
+/// # Examples
+///
+/// ```
+/// booking.no_show_fee = Some(pricing::no_show_fee(&booking));
+/// ```
+pub fn no_show_fee(booking: &RoomBooking) -> u64 {
+    let nightly_rate: u64 = match rate_for_booking(booking) {
+        Some(rate) => rate.nightly_rate,
+        None => return 0,
+    };
+    let nights: u64 = (booking.nights().len() as u64).max(1);
+
+    return nightly_rate * nights.min(config::hotel().no_show_fee_nights as u64);
+}
+
+/// Prorates an amount by the fraction of a period used, rounding to the nearest minor unit.
+fn prorate(amount: u64, used: u64, period: u64) -> u64 {
+    if period == 0 {
+        return 0;
+    }
+
+    return (amount * used + period / 2) / period;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pricing::*;
+
+    #[test]
+    fn prorate_long_stay() {
+        assert_eq!(prorate(3100, 31, 31), 3100);
+        assert_eq!(prorate(3000, 10, 30), 1000);
+        assert_eq!(prorate(100, 1, 3), 33);
+
+        let booking = RoomBooking {
+            booking_id: None,
+            customer_id: 1,
+            room_type_id: 1,
+            check_in_date: "2020-01-15".to_string(),
+            check_out_date: "2020-01-20".to_string(),
+            status: None,
+            expected_arrival_time: None,
+            late_check_out_time: None,
+            day_use: false,
+            start_time: None,
+            end_time: None,
+            billing_milestones: Vec::new(),
+            series_id: None,
+            cancellation: None,
+            no_show_fee: None,
+            payment_due_by: None,
+            addons: Vec::new(),
+            promo_code: None,
+            discount: None,
+            guests: 1,
+            tax_exemptions: Vec::new(),
+            account_id: None,
+            agent_id: None,
+            channel_reservation_id: None,
+            children: 0,
+            rate_plans: Vec::new(),
+            pricing: None,
+            payments: Vec::new(),
+            refunds: Vec::new(),
+            created_at: None,
+            updated_at: None,
+            cancelled_at: None,
+            sandbox: false,
+        };
+        assert!(billing_milestones(&booking).is_empty());
+    }
+
+    #[test]
+    fn resolve_rate_plans() {
+        let plans = vec![
+            RatePlan {
+                name: "Standard".to_string(),
+                room_type_id: 1,
+                nightly_rate: 10000,
+                start_date: None,
+                end_date: None,
+                priority: 0,
+                day_of_week_modifiers: vec![DayOfWeekModifier {
+                    days: vec![Weekday::Fri, Weekday::Sat],
+                    percent: 20,
+                }],
+            },
+            RatePlan {
+                name: "Summer".to_string(),
+                room_type_id: 1,
+                nightly_rate: 15000,
+                start_date: Some("2020-06-01".to_string()),
+                end_date: Some("2020-08-31".to_string()),
+                priority: 1,
+                day_of_week_modifiers: Vec::new(),
+            },
+        ];
+
+        let plan = resolve_plan(&plans, 1, "2020-05-29").unwrap();
+        assert_eq!(plan.name, "Standard");
+        assert_eq!(
+            plan.rate_on(NaiveDate::from_ymd_opt(2020, 5, 29).unwrap()),
+            12000
+        );
+        assert_eq!(
+            resolve_plan(&plans, 1, "2020-06-01").unwrap().name,
+            "Summer"
+        );
+        assert!(resolve_plan(&plans, 2, "2020-06-01").is_none());
+    }
+
+    #[test]
+    fn quote_recorded_pricing() {
+        let mut booking: RoomBooking = rocket::serde::json::from_str(
+            r#"{"customerId":1,"roomTypeId":200,"checkInDate":"2020-01-01",
+            "checkOutDate":"2020-01-03","status":null,"guests":2}"#,
+        )
+        .unwrap();
+        assert!(quote(&booking).is_err());
+
+        booking.pricing = Some(PricingRecord {
+            priced_at: "2020-01-01T00:00:00Z".to_string(),
+            currency: "EUR".to_string(),
+            nightly_prices: Vec::new(),
+            room_lines: vec![PriceLine::new("Room, per night".to_string(), 2, 9000)],
+            taxes: vec![crate::storage::pricing_record::AppliedTax {
+                name: "City tax".to_string(),
+                charge: crate::tax::TaxCharge::PerPersonPerNight,
+                rate: 250,
+            }],
+        });
+
+        let quote = quote(&booking).unwrap();
+        assert_eq!(quote.currency, "EUR");
+        assert_eq!(quote.total, 19000);
+    }
+
+    #[test]
+    fn convert_quote() {
+        let mut quote = PriceBreakdown::new(vec![PriceLine::new("Room".to_string(), 2, 10000)]);
+        quote.currency = "GBP".to_string();
+
+        let rate = ExchangeRate {
+            base: "GBP".to_string(),
+            currency: "EUR".to_string(),
+            rate: 1.15,
+            source: "override".to_string(),
+        };
+        let converted: PriceBreakdown = quote.convert(&rate);
+        assert_eq!(converted.currency, "EUR");
+        assert_eq!(converted.lines[0].unit_price, 11500);
+        assert_eq!(converted.total, 23000);
+        assert_eq!(converted.exchange_rate, Some(1.15));
+
+        let rate = ExchangeRate {
+            currency: "JPY".to_string(),
+            rate: 190.0,
+            ..rate
+        };
+        assert_eq!(quote.convert(&rate).total, 38000);
+    }
+
+    #[test]
+    fn format_invoice() {
+        let booking: RoomBooking = rocket::serde::json::from_str(
+            r#"{"customerId":1,"roomTypeId":1,"checkInDate":"2020-01-01",
+            "checkOutDate":"2020-01-03","status":null}"#,
+        )
+        .unwrap();
+        let mut price = PriceBreakdown::new(vec![PriceLine::new("Room".to_string(), 2, 123450)]);
+        price.currency = "EUR".to_string();
+
+        let locale = Locale {
+            language: "de".to_string(),
+            region: "DE".to_string(),
+        };
+        let invoice: Invoice = Invoice::new(&booking, price.clone(), &locale);
+        assert_eq!(invoice.locale, "de-DE");
+        assert_eq!(invoice.formatted.check_in_date, "01.01.2020");
+        assert_eq!(invoice.formatted.lines[0].unit_price, "1.234,50\u{a0}€");
+        assert_eq!(invoice.formatted.total, "2.469,00\u{a0}€");
+        assert_eq!(invoice.price, price);
+
+        let json = rocket::serde::json::to_value(&invoice).unwrap();
+        assert_eq!(json["total"], 246900);
+        assert_eq!(json["formatted"]["checkOutDate"], "03.01.2020");
+    }
+}
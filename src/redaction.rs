@@ -0,0 +1,344 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::storage::room_booking::RoomBooking;
+
+/// The text personal data is replaced with.
+pub static REDACTED: &str = "[redacted]";
+
+/// The fields of JSON bodies which hold personal data or payment references, in lower case with
+/// any underscores removed, so both camelCase and snake_case names match, e.g. 'firstName' and
+/// 'first_name'.
+static SENSITIVE_FIELDS: [&str; 11] = [
+    "address",
+    "channelreservationid",
+    "comment",
+    "email",
+    "firstname",
+    "fullname",
+    "guestname",
+    "lastname",
+    "name",
+    "phone",
+    "reference",
+];
+
+/// Returns whether a JSON field holds personal data or a payment reference.
+///
+/// # Arguments
+///
+/// * `field` - The name of the field
+fn is_sensitive(field: &str) -> bool {
+    let normalized: String = field.replace('_', "").to_lowercase();
+    return SENSITIVE_FIELDS.contains(&normalized.as_str());
+}
+
+/// Returns the position after the closing quote of the quoted string starting at a position, or
+/// None if the string is not closed, e.g. because the text was truncated.
+///
+/// # Arguments
+///
+/// * `text` - The text holding the string
+/// * `start` - The position of the string's opening quote
+fn string_end(text: &str, start: usize) -> Option<usize> {
+    let bytes: &[u8] = text.as_bytes();
+    let mut index: usize = start + 1;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' => index += 2,
+            b'"' => return Some(index + 1),
+            _ => index += 1,
+        }
+    }
+
+    return None;
+}
+
+/// Replaces the value of each sensitive JSON field. A value left unclosed, because the text was
+/// truncated, is redacted to the end of the text.
+///
+/// # Arguments
+///
+/// * `text` - The text to redact
+fn redact_fields(text: &str) -> String {
+    let mut redacted: String = String::with_capacity(text.len());
+    let mut copied: usize = 0;
+    let mut index: usize = 0;
+
+    while let Some(offset) = text[index..].find('"') {
+        let key_start: usize = index + offset;
+        let key_end: usize = match string_end(text, key_start) {
+            Some(key_end) => key_end,
+            None => break,
+        };
+        index = key_end;
+
+        let value: &str = match text[key_end..].trim_start().strip_prefix(':') {
+            Some(value) => value.trim_start(),
+            None => continue,
+        };
+        if !value.starts_with('"') || !is_sensitive(&text[key_start + 1..key_end - 1]) {
+            continue;
+        }
+
+        let value_start: usize = text.len() - value.len();
+        redacted.push_str(&text[copied..=value_start]);
+        redacted.push_str(REDACTED);
+
+        match string_end(text, value_start) {
+            Some(value_end) => {
+                redacted.push('"');
+                copied = value_end;
+                index = value_end;
+            }
+            None => {
+                copied = text.len();
+                break;
+            }
+        }
+    }
+
+    redacted.push_str(&text[copied..]);
+    return redacted;
+}
+
+/// Replaces the strings echoed from a request body in an error returned while reading it, e.g.
+/// 'invalid type: string "jane@example.com", expected u32'.
+///
+/// # Arguments
+///
+/// * `text` - The text to redact
+fn redact_echoed_strings(text: &str) -> String {
+    let mut redacted: String = String::with_capacity(text.len());
+    let mut rest: &str = text;
+
+    while let Some(offset) = rest.find("string \"") {
+        let start: usize = offset + "string ".len();
+        redacted.push_str(&rest[..=start]);
+        redacted.push_str(REDACTED);
+
+        match string_end(rest, start) {
+            Some(end) => {
+                redacted.push('"');
+                rest = &rest[end..];
+            }
+            None => {
+                rest = "";
+            }
+        }
+    }
+
+    redacted.push_str(rest);
+    return redacted;
+}
+
+/// Replaces each email address.
+///
+/// # Arguments
+///
+/// * `text` - The text to redact
+fn redact_emails(text: &str) -> String {
+    let is_local = |byte: u8| -> bool {
+        return byte.is_ascii_alphanumeric() || b"._%+-".contains(&byte);
+    };
+    let is_domain = |byte: u8| -> bool {
+        return byte.is_ascii_alphanumeric() || b".-".contains(&byte);
+    };
+
+    let bytes: &[u8] = text.as_bytes();
+    let mut redacted: String = String::with_capacity(text.len());
+    let mut copied: usize = 0;
+
+    for (at, _) in text.match_indices('@') {
+        if at < copied {
+            continue;
+        }
+
+        let start: usize = (copied..at)
+            .rev()
+            .take_while(|index| is_local(bytes[*index]))
+            .last()
+            .unwrap_or(at);
+        let end: usize = (at + 1..bytes.len())
+            .take_while(|index| is_domain(bytes[*index]))
+            .last()
+            .map_or(at, |index| index + 1);
+        let domain: &str = text[at + 1..end].trim_end_matches('.');
+
+        if start == at || !domain.contains('.') {
+            continue;
+        }
+
+        redacted.push_str(&text[copied..start]);
+        redacted.push_str(REDACTED);
+        copied = at + 1 + domain.len();
+    }
+
+    redacted.push_str(&text[copied..]);
+    return redacted;
+}
+
+/// Removes personal data and payment references from text before it is logged or returned in an
+/// error response. The values of sensitive JSON fields, such as 'email' or 'reference', strings
+/// echoed from a request body in parsing errors, and email addresses are replaced with
+/// ```REDACTED```.
+///
+/// # Arguments
+///
+/// * `text` - The text to redact
+///
+/// # Examples
+///
+/// ```
+/// let message = redaction::redact(&error.to_string());
+/// ```
+pub fn redact(text: &str) -> String {
+    return redact_emails(&redact_fields(&redact_echoed_strings(text)));
+}
+
+/// Removes the payment references and free text which may hold personal data from a booking
+/// returned in an error response, e.g. the booking a status change conflicts with. Unlike
+/// anonymization, the customer id is kept, so the client can still identify the booking.
+///
+/// # Arguments
+///
+/// * `booking` - The booking to redact
+///
+/// # Examples
+///
+/// ```
+/// redaction::redact_booking(&mut conflict.booking);
+/// ```
+pub fn redact_booking(booking: &mut RoomBooking) {
+    let redact = |value: &mut Option<String>| {
+        if value.is_some() {
+            *value = Some(REDACTED.to_string());
+        }
+    };
+
+    booking
+        .payments
+        .iter_mut()
+        .for_each(|payment| redact(&mut payment.reference));
+    booking
+        .refunds
+        .iter_mut()
+        .for_each(|refund| redact(&mut refund.reference));
+    redact(&mut booking.channel_reservation_id);
+
+    if let Some(cancellation) = booking.cancellation.as_mut() {
+        redact(&mut cancellation.comment);
+    }
+}
+
+/// Writes a message to the server's log, on standard output, with any personal data redacted.
+/// Every message logged by the server is written through this function, ```notice``` or
+/// ```error```, so redaction does not rely on each call site.
+///
+/// # Arguments
+///
+/// * `message` - The message to log
+///
+/// # Examples
+///
+/// ```
+/// redaction::info(&format!("Delivered {} events", delivered));
+/// ```
+pub fn info(message: &str) {
+    println!("{}", redact(message));
+}
+
+/// Writes a message to the server's log, on standard error, with any personal data redacted. Used
+/// for progress logged before a command runs, such as loading the snapshot, so it is not mixed
+/// into output the command writes to standard output, e.g. an export.
+///
+/// # Arguments
+///
+/// * `message` - The message to log
+///
+/// # Examples
+///
+/// ```
+/// redaction::notice("Loaded snapshot...");
+/// ```
+pub fn notice(message: &str) {
+    eprintln!("{}", redact(message));
+}
+
+/// Writes an error to the server's log, on standard error, with any personal data redacted.
+///
+/// # Arguments
+///
+/// * `message` - The error to log
+///
+/// # Examples
+///
+/// ```
+/// redaction::error(&format!("Unable to refresh secrets: {}", error));
+/// ```
+pub fn error(message: &str) {
+    eprintln!("{}", redact(message));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::redaction::*;
+    use crate::storage::payment::Payment;
+    use rocket::serde::json::{from_value, json};
+
+    #[test]
+    fn redact_text() {
+        assert_eq!(
+            redact(
+                r#"{"customerId":1,"email": "jane@example.com","payments":[{"reference":"pi_3NkQ"}]}"#
+            ),
+            r#"{"customerId":1,"email": "[redacted]","payments":[{"reference":"[redacted]"}]}"#
+        );
+        assert_eq!(
+            redact(r#"{"guest_name":"Jane \"JD\" Doe","roomTypeId":1}"#),
+            r#"{"guest_name":"[redacted]","roomTypeId":1}"#
+        );
+        assert_eq!(
+            redact(r#"{"comment":"Arriving la"#),
+            r#"{"comment":"[redacted]"#
+        );
+        assert_eq!(
+            redact(r#"invalid type: string "Jane Doe", expected u32 at line 1 column 24"#),
+            r#"invalid type: string "[redacted]", expected u32 at line 1 column 24"#
+        );
+        assert_eq!(
+            redact("Delivery to ops@hotel.example.com failed, contact a@b."),
+            "Delivery to [redacted] failed, contact a@b."
+        );
+        assert_eq!(redact("Booking not found"), "Booking not found");
+    }
+
+    #[test]
+    fn redact_booking_references() {
+        let mut booking: RoomBooking = from_value(json!({
+            "customerId": 12,
+            "roomTypeId": 1,
+            "checkInDate": "2030-01-01",
+            "checkOutDate": "2030-01-03",
+            "cancellation": { "reason": "ChangeOfPlans", "comment": "Call Jane on 0123" },
+        }))
+        .unwrap();
+        booking.payments.push(Payment {
+            amount: 100,
+            reference: Some("pi_3NkQ".to_string()),
+            received_at: None,
+        });
+
+        redact_booking(&mut booking);
+        assert_eq!(booking.customer_id, 12);
+        assert_eq!(booking.payments[0].reference, Some(REDACTED.to_string()));
+        assert_eq!(booking.channel_reservation_id, None);
+        assert_eq!(
+            booking.cancellation.unwrap().comment,
+            Some(REDACTED.to_string())
+        );
+    }
+}
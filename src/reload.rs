@@ -0,0 +1,73 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::config;
+use crate::redaction;
+use crate::storage;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::tokio::signal::unix::{signal, Signal, SignalKind};
+use rocket::Rocket;
+
+/// Reloads the hotel settings and feature flags, discarding any responses cached from the
+/// previous settings. Bookings, and requests in flight, are unaffected; a request already being
+/// handled may finish using the previous settings. The settings in use are kept if the new
+/// settings cannot be read.
+///
+/// # Examples
+///
+/// ```
+/// reload::apply()?;
+/// ```
+pub fn apply() -> Result<(), String> {
+    if let Err(error) = config::reload() {
+        redaction::error(&format!("Unable to reload configuration: {}", error));
+        return Err(error.to_string());
+    }
+
+    storage::discard_cached();
+    redaction::info("Reloaded hotel settings and feature flags...");
+    return Ok(());
+}
+
+/// A fairing which starts a background task when the server launches, reloading the hotel
+/// settings and feature flags each time the process receives SIGHUP, without a restart.
+pub struct ConfigReload;
+
+#[rocket::async_trait]
+impl Fairing for ConfigReload {
+    fn info(&self) -> Info {
+        return Info {
+            name: "Configuration reload",
+            kind: Kind::Liftoff,
+        };
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<rocket::Orbit>) {
+        let mut hangup: Signal = match signal(SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(error) => {
+                redaction::error(&format!("Unable to listen for SIGHUP: {}", error));
+                return;
+            }
+        };
+
+        let shutdown = rocket.shutdown();
+        rocket::tokio::spawn(async move {
+            loop {
+                rocket::tokio::select! {
+                    received = hangup.recv() => {
+                        if received.is_none() {
+                            return;
+                        }
+                    }
+                    _ = shutdown.clone() => return,
+                }
+
+                // Errors are logged by apply, and the settings in use are kept.
+                let _ = apply();
+            }
+        });
+    }
+}
@@ -0,0 +1,158 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::redaction;
+use rocket::data::Data;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Method;
+use rocket::{Request, Response};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// The most bytes of a request body Rocket buffers before the body is read, which limits the
+/// bytes of a body which can be logged.
+static PEEK_BYTES: usize = 512;
+
+/// Describes how requests are logged. Settings are read from the ```request_log``` section of
+/// ```Rocket.toml```, so logging may be turned on for a single profile, e.g. while debugging an
+/// integration with a partner.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct RequestLogConfig {
+    /// Whether each request is logged
+    pub enabled: bool,
+    /// The most bytes of each request body logged, for requests which change data. Bodies are
+    /// captured before they are read, so at most 512 bytes can be logged. Bodies are not logged if
+    /// this is 0
+    pub max_body_bytes: usize,
+}
+
+impl Default for RequestLogConfig {
+    fn default() -> RequestLogConfig {
+        return RequestLogConfig {
+            enabled: false,
+            max_body_bytes: PEEK_BYTES,
+        };
+    }
+}
+
+/// The time a request was received, cached against the request.
+struct Received(Option<Instant>);
+
+/// The start of a request's body, cached against the request, and whether the body was longer.
+struct CapturedBody(Option<(String, bool)>);
+
+/// Returns whether requests using a method change data, so have their bodies logged.
+///
+/// # Arguments
+///
+/// * `method` - The method of the request
+fn is_mutating(method: Method) -> bool {
+    return matches!(
+        method,
+        Method::Post | Method::Put | Method::Patch | Method::Delete
+    );
+}
+
+/// Formats the log line written for a request.
+///
+/// # Arguments
+///
+/// * `method` - The method of the request
+/// * `uri` - The path and query of the request
+/// * `status` - The status code of the response
+/// * `latency` - The time taken to respond, if known
+/// * `body` - The start of the request body, and whether the body was longer, if captured
+fn line(
+    method: Method,
+    uri: &str,
+    status: u16,
+    latency: Option<Duration>,
+    body: Option<&(String, bool)>,
+) -> String {
+    let mut line: String = format!("{} {} {}", method, uri, status);
+
+    if let Some(latency) = latency {
+        line.push_str(&format!(" {:.3}ms", latency.as_secs_f64() * 1000.0));
+    }
+
+    if let Some((body, truncated)) = body {
+        line.push_str(&format!(" body: {}", body));
+        if *truncated {
+            line.push_str(" (truncated)");
+        }
+    }
+
+    return line;
+}
+
+/// A fairing which logs the method, path, response status and latency of each request, along
+/// with the start of the body of each request which changes data, such as a POST. Personal data
+/// and payment references are redacted from each line logged.
+pub struct RequestLog {
+    pub settings: RequestLogConfig,
+}
+
+#[rocket::async_trait]
+impl Fairing for RequestLog {
+    fn info(&self) -> Info {
+        return Info {
+            name: "Request log",
+            kind: Kind::Request | Kind::Response,
+        };
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, data: &mut Data<'_>) {
+        request.local_cache(|| Received(Some(Instant::now())));
+
+        let limit: usize = self.settings.max_body_bytes.min(PEEK_BYTES);
+        if limit == 0 || !is_mutating(request.method()) {
+            return;
+        }
+
+        let body: String = String::from_utf8_lossy(data.peek(limit).await).to_string();
+        let truncated: bool = !data.peek_complete() || data.peek(PEEK_BYTES).await.len() > limit;
+        request.local_cache(|| CapturedBody(Some((body, truncated))));
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let received: &Received = request.local_cache(|| Received(None));
+        let body: &CapturedBody = request.local_cache(|| CapturedBody(None));
+
+        redaction::info(&line(
+            request.method(),
+            &request.uri().to_string(),
+            response.status().code,
+            received.0.map(|received| received.elapsed()),
+            body.0.as_ref(),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::request_log::*;
+
+    #[test]
+    fn format_log_line() {
+        assert!(is_mutating(Method::Post));
+        assert!(!is_mutating(Method::Get));
+
+        assert_eq!(
+            line(Method::Get, "/v1/booking/1", 404, None, None),
+            "GET /v1/booking/1 404"
+        );
+        assert_eq!(
+            line(
+                Method::Post,
+                "/v1/booking",
+                200,
+                Some(Duration::from_micros(1500)),
+                Some(&(r#"{"customerId":1"#.to_string(), true)),
+            ),
+            r#"POST /v1/booking 200 1.500ms body: {"customerId":1 (truncated)"#
+        );
+    }
+}
@@ -0,0 +1,600 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::locale;
+use crate::redaction;
+use crate::state_machine::TransitionError;
+use crate::storage::booking_conflict::BookingConflict;
+use crate::storage::room_booking::RoomBooking;
+use rocket::http::{Header, Method, Status};
+use rocket::response::{self, Responder};
+use rocket::serde::json::{serde_json, Json};
+use rocket::{Request, Response};
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::okapi::openapi3::{RefOr, Responses};
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use rocket_okapi::response::OpenApiResponderInner;
+use rocket_okapi::util::{add_schema_response, ensure_status_code_exists};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::SystemTime;
+
+/// Generates a strong entity tag for a serializable value, derived from its JSON representation.
+///
+/// # Arguments
+///
+/// * `value` - The value to generate the entity tag for
+///
+/// # Examples
+///
+/// ```
+/// let etag = etag_for(&booking);
+/// ```
+pub fn etag_for<T: Serialize>(value: &T) -> String {
+    let mut hasher = DefaultHasher::new();
+    rocket::serde::json::to_string(value)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+
+    return format!("\"{:x}\"", hasher.finish());
+}
+
+/// Wraps a responder, adding ```ETag``` and ```Last-Modified``` headers to the response.
+///
+/// If the request contains an ```If-None-Match``` header matching the entity tag, or an
+/// ```If-Modified-Since``` header no earlier than the last modification time, an empty
+/// ```304 Not Modified``` response is returned instead.
+pub struct Tagged<R> {
+    pub inner: R,
+    pub etag: String,
+    pub last_modified: Option<SystemTime>,
+}
+
+impl<R> Tagged<R> {
+    /// Creates a new tagged response.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The responder used to generate the response body
+    /// * `etag` - The entity tag of the resource, as returned by ```etag_for```
+    /// * `last_modified` - The time the resource was last modified, if known
+    pub fn new(inner: R, etag: String, last_modified: Option<SystemTime>) -> Tagged<R> {
+        return Tagged {
+            inner,
+            etag,
+            last_modified,
+        };
+    }
+
+    /// Checks whether the client already holds the current version of the resource, using the
+    /// conditional headers in the request. ```If-None-Match``` takes precedence over
+    /// ```If-Modified-Since``` when both are present.
+    fn is_not_modified(&self, request: &Request<'_>) -> bool {
+        if request.method() != Method::Get && request.method() != Method::Head {
+            return false;
+        }
+
+        if let Some(if_none_match) = request.headers().get_one("If-None-Match") {
+            return if_none_match.split(',').any(|tag| {
+                let tag: &str = tag.trim();
+                tag == "*" || tag.trim_start_matches("W/") == self.etag
+            });
+        }
+
+        let if_modified_since: Option<SystemTime> = request
+            .headers()
+            .get_one("If-Modified-Since")
+            .and_then(|date| httpdate::parse_http_date(date).ok());
+
+        return match (if_modified_since, self.last_modified) {
+            // HTTP dates only have second precision, so compare using the formatted value.
+            (Some(since), Some(last_modified)) => {
+                let last_modified: SystemTime =
+                    httpdate::parse_http_date(&httpdate::fmt_http_date(last_modified))
+                        .unwrap_or(last_modified);
+                last_modified <= since
+            }
+            _ => false,
+        };
+    }
+}
+
+impl<T: Serialize> Tagged<Json<T>> {
+    /// Creates a new tagged JSON response, generating the entity tag from the value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to return
+    /// * `last_modified` - The time the value was last modified, if known
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let response = Tagged::json(bookings, storage::last_modified_any());
+    /// ```
+    pub fn json(value: T, last_modified: Option<SystemTime>) -> Tagged<Json<T>> {
+        let etag: String = etag_for(&value);
+        return Tagged::new(Json(value), etag, last_modified);
+    }
+}
+
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for Tagged<R> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'o> {
+        let mut response = match self.is_not_modified(request) {
+            true => Response::build().status(Status::NotModified).finalize(),
+            false => self.inner.respond_to(request)?,
+        };
+        response.set_header(Header::new("ETag", self.etag));
+
+        if let Some(last_modified) = self.last_modified {
+            response.set_header(Header::new(
+                "Last-Modified",
+                httpdate::fmt_http_date(last_modified),
+            ));
+        }
+
+        return Ok(response);
+    }
+}
+
+impl<R: OpenApiResponderInner> OpenApiResponderInner for Tagged<R> {
+    fn responses(gen: &mut OpenApiGenerator) -> rocket_okapi::Result<Responses> {
+        let mut responses: Responses = R::responses(gen)?;
+        if let RefOr::Object(response) = ensure_status_code_exists(&mut responses, 304) {
+            response.description =
+                "The resource has not been modified since the version held by the client."
+                    .to_string();
+        }
+
+        return Ok(responses);
+    }
+}
+
+/// Wraps a responder for a page of a collection, adding ```Link``` and ```X-Total-Count```
+/// headers to the response.
+pub struct Paginated<R> {
+    pub inner: R,
+    pub total: usize,
+    pub links: Vec<(String, String)>,
+}
+
+impl<R> Paginated<R> {
+    /// Creates a new paginated response.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The responder used to generate the response body
+    /// * `total` - The total number of items in the collection
+    /// * `links` - A list of (relation, href) pairs, as returned by ```Pagination::links```
+    pub fn new(inner: R, total: usize, links: Vec<(String, String)>) -> Paginated<R> {
+        return Paginated {
+            inner,
+            total,
+            links,
+        };
+    }
+}
+
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for Paginated<R> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'o> {
+        let mut response = self.inner.respond_to(request)?;
+        response.set_header(Header::new("X-Total-Count", self.total.to_string()));
+
+        if !self.links.is_empty() {
+            let links: Vec<String> = self
+                .links
+                .iter()
+                .map(|(rel, href)| format!("<{}>; rel=\"{}\"", href, rel))
+                .collect();
+            response.set_header(Header::new("Link", links.join(", ")));
+        }
+
+        return Ok(response);
+    }
+}
+
+impl<R: OpenApiResponderInner> OpenApiResponderInner for Paginated<R> {
+    fn responses(gen: &mut OpenApiGenerator) -> rocket_okapi::Result<Responses> {
+        return R::responses(gen);
+    }
+}
+
+/// Wraps a responder, adding a ```Content-Disposition``` header so the response is downloaded as
+/// a file.
+pub struct Attachment<R> {
+    pub inner: R,
+    pub filename: String,
+}
+
+impl<R> Attachment<R> {
+    /// Creates a new attachment response.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The responder used to generate the response body
+    /// * `filename` - The name the file is saved as
+    pub fn new(inner: R, filename: String) -> Attachment<R> {
+        return Attachment { inner, filename };
+    }
+}
+
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for Attachment<R> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'o> {
+        let mut response = self.inner.respond_to(request)?;
+        response.set_header(Header::new(
+            "Content-Disposition",
+            format!("attachment; filename=\"{}\"", self.filename),
+        ));
+
+        return Ok(response);
+    }
+}
+
+impl<R: OpenApiResponderInner> OpenApiResponderInner for Attachment<R> {
+    fn responses(gen: &mut OpenApiGenerator) -> rocket_okapi::Result<Responses> {
+        return R::responses(gen);
+    }
+}
+
+/// Describes a problem with a single field of a request body
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldError {
+    /// The name of the field, e.g. checkInDate
+    pub field: String,
+    /// A description of the problem
+    pub error: String,
+}
+
+impl FieldError {
+    /// Creates the error for a field named at the start of a validation message, e.g.
+    /// 'checkOutDate must be after checkInDate'. Returns None if the message does not start with
+    /// the name of a field.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The validation message
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let error = FieldError::from_message("guests must be at least 1");
+    /// ```
+    pub fn from_message(message: &str) -> Option<FieldError> {
+        let field: &str = message.split_whitespace().next()?;
+        if !field.starts_with(|c: char| c.is_ascii_lowercase())
+            || !field.chars().all(|c| c.is_ascii_alphanumeric())
+        {
+            return None;
+        }
+
+        return Some(FieldError {
+            field: field.to_string(),
+            error: message.to_string(),
+        });
+    }
+
+    /// Creates the error for a field named in an error returned while reading a JSON body, such
+    /// as an unknown or missing field. Returns None if the error does not name a field.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The error returned while reading the body
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let error = FieldError::from_parse_error(&error);
+    /// ```
+    pub fn from_parse_error(error: &serde_json::Error) -> Option<FieldError> {
+        let message: String = error.to_string();
+        let kind: &str = ["unknown field", "missing field", "duplicate field"]
+            .into_iter()
+            .find(|kind| message.starts_with(kind))?;
+        let field: &str = message.split('`').nth(1)?;
+
+        return Some(FieldError {
+            field: field.to_string(),
+            error: format!("{} {}", field, kind),
+        });
+    }
+}
+
+/// Describes an error returned in a response body
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorResponse {
+    /// A description of the error
+    pub error: String,
+    /// The path of the existing resource the request conflicts with, if any, e.g. the booking a
+    /// new booking duplicates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conflicts_with: Option<String>,
+    /// The current state of the booking the request conflicts with, if the request changes a
+    /// booking
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conflict: Option<Box<BookingConflict>>,
+    /// The problems found with each field of the request body, if the body is not valid
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+}
+
+impl ErrorResponse {
+    /// Translates the description of the error, and of the problem found with each field, into a
+    /// language.
+    ///
+    /// # Arguments
+    ///
+    /// * `language` - The language tag, as returned by ```locale::negotiate```
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let body = body.translate(&language);
+    /// ```
+    pub fn translate(mut self, language: &str) -> ErrorResponse {
+        self.error = locale::translate(language, &self.error);
+        for field in self.fields.iter_mut() {
+            field.error = locale::translate(language, &field.error);
+        }
+
+        return self;
+    }
+
+    /// Removes any personal data and payment references from the error, such as a value echoed
+    /// from the request body, or a payment reference on the booking the request conflicts with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let body = body.redact();
+    /// ```
+    pub fn redact(mut self) -> ErrorResponse {
+        self.error = redaction::redact(&self.error);
+        for field in self.fields.iter_mut() {
+            field.error = redaction::redact(&field.error);
+        }
+
+        if let Some(conflict) = self.conflict.as_mut() {
+            redaction::redact_booking(&mut conflict.booking);
+        }
+
+        return self;
+    }
+
+    /// Responds with the error, with any personal data redacted, translated into the language
+    /// negotiated from the request's ```Accept-Language``` header, naming the language in the
+    /// ```Content-Language``` header.
+    fn respond_localized(self, status: Status, request: &Request<'_>) -> response::Result<'static> {
+        let language: String = locale::negotiate(request.headers().get_one("Accept-Language"));
+        let mut response =
+            Response::build_from(Json(self.redact().translate(&language)).respond_to(request)?)
+                .status(status)
+                .finalize();
+        response.set_header(Header::new("Content-Language", language));
+        response.adjoin_header(Header::new("Vary", "Accept-Language"));
+
+        return Ok(response);
+    }
+}
+
+/// An error response, with a body describing the error. The const parameters list each status
+/// the route may respond with, so the errors are documented in the OpenAPI specification. Unused
+/// parameters are left as 0.
+#[derive(Debug)]
+pub struct ApiError<
+    const A: u16,
+    const B: u16 = 0,
+    const C: u16 = 0,
+    const D: u16 = 0,
+    const E: u16 = 0,
+> {
+    pub status: Status,
+    pub body: ErrorResponse,
+}
+
+impl<const A: u16, const B: u16, const C: u16, const D: u16, const E: u16> ApiError<A, B, C, D, E> {
+    /// The statuses the error may be returned with.
+    const STATUSES: [u16; 5] = [A, B, C, D, E];
+
+    /// Adds the path of the existing resource the request conflicts with to the error.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the existing resource
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// return Err(error_response(Status::Conflict, "Duplicate booking").conflicting_with(path));
+    /// ```
+    pub fn conflicting_with(mut self, path: String) -> ApiError<A, B, C, D, E> {
+        self.body.conflicts_with = Some(path);
+        return self;
+    }
+
+    /// Adds the problems found with each field of the request body to the error.
+    ///
+    /// # Arguments
+    ///
+    /// * `fields` - The problem found with each field
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// return Err(error_response(Status::UnprocessableEntity, &error).with_fields(fields));
+    /// ```
+    pub fn with_fields(mut self, fields: Vec<FieldError>) -> ApiError<A, B, C, D, E> {
+        self.body.fields = fields;
+        return self;
+    }
+}
+
+impl<'r, const A: u16, const B: u16, const C: u16, const D: u16, const E: u16>
+    Responder<'r, 'static> for ApiError<A, B, C, D, E>
+{
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        return self.body.respond_localized(self.status, request);
+    }
+}
+
+impl<const A: u16, const B: u16, const C: u16, const D: u16, const E: u16> OpenApiResponderInner
+    for ApiError<A, B, C, D, E>
+{
+    fn responses(gen: &mut OpenApiGenerator) -> rocket_okapi::Result<Responses> {
+        let mut responses = Responses::default();
+        let schema = gen.json_schema::<ErrorResponse>();
+
+        for status in Self::STATUSES.into_iter().filter(|status| *status != 0) {
+            add_schema_response(&mut responses, status, "application/json", schema.clone())?;
+            if let RefOr::Object(response) = ensure_status_code_exists(&mut responses, status) {
+                response.description = error_description(status).to_string();
+            }
+        }
+
+        return Ok(responses);
+    }
+}
+
+/// Returns the description used to document an error status in the OpenAPI specification.
+///
+/// # Arguments
+///
+/// * `status` - The status code of the error
+fn error_description(status: u16) -> &'static str {
+    return match status {
+        400 => "The request is not valid, e.g. a parameter is in the wrong format.",
+        401 => "The request is not authorised.",
+        404 => "The resource was not found.",
+        409 => "The request conflicts with the current state of the resource.",
+        413 => "The request body is too large.",
+        422 => "The request body was understood, but failed validation.",
+        500 => "The request could not be completed due to an error in the service.",
+        502 => "A service the request depends on returned an error.",
+        503 => "The service is temporarily unavailable.",
+        _ => "The request failed.",
+    };
+}
+
+/// Creates an error response with the provided status, and a body describing the error. The
+/// statuses documented for the error are taken from the route's return type.
+///
+/// # Arguments
+///
+/// * `status` - The status of the response, which should be one of the statuses documented
+/// * `error` - A description of the error
+///
+/// # Examples
+///
+/// ```
+/// return Err(error_response(Status::NotFound, "Booking not found"));
+/// ```
+pub fn error_response<const A: u16, const B: u16, const C: u16, const D: u16, const E: u16>(
+    status: Status,
+    error: &str,
+) -> ApiError<A, B, C, D, E> {
+    debug_assert!(
+        ApiError::<A, B, C, D, E>::STATUSES.contains(&status.code),
+        "{} is not documented for the route",
+        status.code
+    );
+
+    return ApiError {
+        status,
+        body: ErrorResponse {
+            error: error.to_string(),
+            conflicts_with: None,
+            conflict: None,
+            fields: Vec::new(),
+        },
+    };
+}
+
+impl TransitionError {
+    /// Returns the HTTP status used to report the error.
+    fn status(&self) -> Status {
+        return match self {
+            TransitionError::NotFound => Status::NotFound,
+            TransitionError::Illegal { .. }
+            | TransitionError::WrongStatus { .. }
+            | TransitionError::BeforeCheckIn
+            | TransitionError::NoRoomAvailable
+            | TransitionError::OutstandingBalance(_)
+            | TransitionError::DepositOutstanding(_)
+            | TransitionError::AwaitingReview => Status::Conflict,
+            TransitionError::Unavailable => Status::ServiceUnavailable,
+        };
+    }
+}
+
+/// A status change which could not be made, along with the current state of the booking if the
+/// change conflicts with it
+#[derive(Debug)]
+pub struct TransitionFailure {
+    pub error: TransitionError,
+    pub conflict: Option<Box<BookingConflict>>,
+}
+
+impl TransitionFailure {
+    /// Creates a failed status change, describing the conflict with the booking's current state
+    /// if the error is a conflict.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The error returned by the status change
+    /// * `booking` - The booking in its current state, if it exists
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let failure = TransitionFailure::new(error, storage::fetch_by_id(booking_id));
+    /// ```
+    pub fn new(error: TransitionError, booking: Option<RoomBooking>) -> TransitionFailure {
+        let conflict: Option<Box<BookingConflict>> = booking
+            .and_then(|booking| BookingConflict::new(&error, booking))
+            .map(Box::new);
+
+        return TransitionFailure { error, conflict };
+    }
+}
+
+impl From<TransitionError> for TransitionFailure {
+    fn from(error: TransitionError) -> TransitionFailure {
+        return TransitionFailure::new(error, None);
+    }
+}
+
+impl<'r> Responder<'r, 'static> for TransitionFailure {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let body = ErrorResponse {
+            error: self.error.to_string(),
+            conflicts_with: None,
+            conflict: self.conflict,
+            fields: Vec::new(),
+        };
+
+        return body.respond_localized(self.error.status(), request);
+    }
+}
+
+impl OpenApiResponderInner for TransitionFailure {
+    fn responses(gen: &mut OpenApiGenerator) -> rocket_okapi::Result<Responses> {
+        let mut responses = Responses::default();
+        let schema = gen.json_schema::<ErrorResponse>();
+
+        for status in [404, 409, 503] {
+            add_schema_response(&mut responses, status, "application/json", schema.clone())?;
+        }
+
+        if let RefOr::Object(response) = ensure_status_code_exists(&mut responses, 409) {
+            response.description =
+                "The booking's current status may not be changed to the requested status, no room is available for the booking's dates, or the booking has not been paid in full before check in. The booking's current state is included.".to_string();
+        }
+
+        return Ok(responses);
+    }
+}
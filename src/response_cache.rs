@@ -0,0 +1,135 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A response held by the cache, and when it was computed.
+struct CachedResponse {
+    computed_at: Instant,
+    value: Box<dyn Any + Send + Sync>,
+}
+
+/// Holds responses computed from the stored data, such as reports, keyed by the endpoint and its
+/// parameters, so they are not recomputed from a full scan of the bookings on every request.
+/// Responses are held until they expire, or the cache is invalidated because the stored data
+/// changed.
+pub struct ResponseCache {
+    /// The number of times the cache has been invalidated, used so a response computed while the
+    /// stored data changed is not cached
+    generation: AtomicU64,
+    responses: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl ResponseCache {
+    /// Creates an empty cache.
+    pub fn new() -> ResponseCache {
+        return ResponseCache {
+            generation: AtomicU64::new(0),
+            responses: Mutex::new(HashMap::new()),
+        };
+    }
+
+    /// Returns the response cached under a key, if it has not expired, or computes it and caches
+    /// it. Responses are not cached if the time to live is 0, or the cache is invalidated while
+    /// the response is computed.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key the response is cached under, naming the endpoint and its parameters
+    /// * `ttl` - How long the response is cached for
+    /// * `compute` - Computes the response if it is not cached
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let report = cache.get_or_compute("cancellations", Duration::from_secs(30), || report());
+    /// ```
+    pub fn get_or_compute<T: Clone + Send + Sync + 'static>(
+        &self,
+        key: &str,
+        ttl: Duration,
+        compute: impl FnOnce() -> T,
+    ) -> T {
+        if ttl.is_zero() {
+            return compute();
+        }
+
+        if let Ok(responses) = self.responses.lock() {
+            let cached: Option<&T> = responses
+                .get(key)
+                .filter(|cached| cached.computed_at.elapsed() < ttl)
+                .and_then(|cached| cached.value.downcast_ref::<T>());
+
+            if let Some(value) = cached {
+                return value.clone();
+            }
+        }
+
+        let generation: u64 = self.generation.load(Ordering::SeqCst);
+        let value: T = compute();
+
+        if let Ok(mut responses) = self.responses.lock() {
+            if self.generation.load(Ordering::SeqCst) == generation {
+                responses.retain(|_, cached| cached.computed_at.elapsed() < ttl);
+                responses.insert(
+                    key.to_string(),
+                    CachedResponse {
+                        computed_at: Instant::now(),
+                        value: Box::new(value.clone()),
+                    },
+                );
+            }
+        }
+
+        return value;
+    }
+
+    /// Removes every cached response, so each is computed again from the stored data when next
+    /// requested.
+    pub fn invalidate(&self) {
+        if let Ok(mut responses) = self.responses.lock() {
+            self.generation.fetch_add(1, Ordering::SeqCst);
+            responses.clear();
+        }
+    }
+}
+
+impl Default for ResponseCache {
+    fn default() -> ResponseCache {
+        return ResponseCache::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::response_cache::*;
+
+    #[test]
+    fn cache_responses() {
+        let cache: ResponseCache = ResponseCache::new();
+        let ttl: Duration = Duration::from_secs(60);
+
+        assert_eq!(cache.get_or_compute("report", ttl, || 1), 1);
+        assert_eq!(cache.get_or_compute("report", ttl, || 2), 1);
+        assert_eq!(cache.get_or_compute("other", ttl, || 3), 3);
+
+        cache.invalidate();
+        assert_eq!(cache.get_or_compute("report", ttl, || 4), 4);
+
+        let computed: u32 = cache.get_or_compute("report", ttl, || {
+            cache.invalidate();
+            return 5;
+        });
+        assert_eq!(computed, 5);
+        assert_eq!(cache.get_or_compute("report", ttl, || 6), 6);
+
+        assert_eq!(cache.get_or_compute("uncached", Duration::ZERO, || 7), 7);
+        assert_eq!(cache.get_or_compute("uncached", Duration::ZERO, || 8), 8);
+    }
+}
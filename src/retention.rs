@@ -0,0 +1,123 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::config::{self, RetentionConfig};
+use crate::local_time;
+use crate::redaction;
+use crate::storage;
+use crate::storage::retention::RetentionReport;
+use chrono::{Months, NaiveDate};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::tokio::time::{interval, Duration};
+use rocket::Rocket;
+
+/// Returns the date bookings must have checked out before to be removed by the retention policy.
+///
+/// # Arguments
+///
+/// * `today` - The current date at the hotel
+/// * `retention_years` - The number of years after check out a booking is kept for
+fn cutoff(today: NaiveDate, retention_years: u32) -> NaiveDate {
+    return today
+        .checked_sub_months(Months::new(retention_years.saturating_mul(12)))
+        .unwrap_or(NaiveDate::MIN);
+}
+
+/// Applies the retention policy, removing the bookings which checked out more than the configured
+/// number of years ago. Returns None if storage is unavailable.
+///
+/// # Arguments
+///
+/// * `settings` - The retention settings
+/// * `dry_run` - Whether to only report the bookings which would be removed
+///
+/// # Examples
+///
+/// ```
+/// let report = retention::apply(&settings, true);
+/// ```
+pub fn apply(settings: &RetentionConfig, dry_run: bool) -> Option<RetentionReport> {
+    return storage::apply_retention(
+        cutoff(local_time::today(), settings.retention_years),
+        settings.action,
+        dry_run,
+    );
+}
+
+/// A fairing which starts a background task when the server launches, if a retention policy is
+/// configured. The task applies the policy on the interval set by the retention settings.
+pub struct RetentionJob;
+
+#[rocket::async_trait]
+impl Fairing for RetentionJob {
+    fn info(&self) -> Info {
+        return Info {
+            name: "Data retention",
+            kind: Kind::Liftoff,
+        };
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<rocket::Orbit>) {
+        let settings: RetentionConfig = match config::hotel().retention {
+            Some(settings) => settings,
+            None => return,
+        };
+
+        let shutdown = rocket.shutdown();
+        rocket::tokio::spawn(async move {
+            let mut sweep = interval(Duration::from_secs(settings.interval_hours.max(1) * 3600));
+
+            loop {
+                rocket::tokio::select! {
+                    _ = sweep.tick() => {}
+                    _ = shutdown.clone() => return,
+                }
+
+                let report: RetentionReport = match apply(&settings, settings.dry_run) {
+                    Some(report) => report,
+                    None => continue,
+                };
+
+                if report.bookings.is_empty() {
+                    continue;
+                }
+
+                let booking_ids: Vec<String> = report
+                    .bookings
+                    .iter()
+                    .filter_map(|booking| booking.booking_id)
+                    .map(|booking_id| booking_id.to_string())
+                    .collect();
+
+                redaction::info(&format!(
+                    "{} {} bookings which checked out before {}: {}",
+                    if report.dry_run {
+                        "Would remove"
+                    } else {
+                        "Removed"
+                    },
+                    report.bookings.len(),
+                    report.cutoff_date,
+                    booking_ids.join(", ")
+                ));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::retention::*;
+
+    #[test]
+    fn retention_cutoff() {
+        let today = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        assert_eq!(
+            cutoff(today, 7),
+            NaiveDate::from_ymd_opt(2017, 2, 28).unwrap()
+        );
+        assert_eq!(cutoff(today, 0), today);
+    }
+}
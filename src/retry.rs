@@ -0,0 +1,108 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::circuit_breaker::{self, CallError, CircuitError};
+use crate::config::{self, RetryConfig};
+use rand::Rng;
+use rocket::tokio::time::{sleep, timeout, Duration};
+use std::fmt;
+use std::future::Future;
+
+/// The error returned when an attempt to call an outbound integration takes longer than the
+/// configured timeout.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TimeoutError(&'static str);
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "The call to {} timed out", self.0);
+    }
+}
+
+impl std::error::Error for TimeoutError {}
+
+/// Makes a call to an outbound integration using the configured retry and timeout policy. Each
+/// attempt is made through the integration's circuit breaker, and fails if it takes longer than
+/// the timeout. Failed attempts are retried after an exponential delay with full jitter, until
+/// the maximum number of attempts is reached. Calls rejected by the circuit breaker are not
+/// retried.
+///
+/// # Arguments
+///
+/// * `name` - The name of the integration, e.g. 'channel_manager'
+/// * `request` - A function making a single attempt of the call
+///
+/// # Examples
+///
+/// ```
+/// let response = retry::call("channel_manager", || send(&request)).await?;
+/// ```
+pub async fn call<T, F, R>(name: &'static str, request: F) -> Result<T, CallError>
+where
+    F: Fn() -> R,
+    R: Future<Output = Result<T, CallError>>,
+{
+    let settings: RetryConfig = config::hotel().retry;
+    let limit: Duration = Duration::from_millis(settings.timeout_ms);
+    let mut attempt: u32 = 1;
+
+    loop {
+        let result: Result<T, CallError> = circuit_breaker::call(name, async {
+            return match timeout(limit, request()).await {
+                Ok(result) => result,
+                Err(_) => {
+                    circuit_breaker::record_timeout(name);
+                    Err(TimeoutError(name).into())
+                }
+            };
+        })
+        .await;
+
+        match result {
+            Err(error) if attempt < settings.max_attempts && !error.is::<CircuitError>() => {
+                circuit_breaker::record_retry(name);
+                sleep(delay(&settings, attempt)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Returns a random delay before retrying a call, between 0 and the base delay doubled for each
+/// attempt already made, capped at the maximum delay.
+///
+/// # Arguments
+///
+/// * `settings` - The retry settings
+/// * `attempt` - The number of attempts already made
+fn delay(settings: &RetryConfig, attempt: u32) -> Duration {
+    let ceiling: u64 = settings
+        .base_delay_ms
+        .saturating_mul(1 << attempt.saturating_sub(1).min(32))
+        .min(settings.max_delay_ms);
+
+    return Duration::from_millis(rand::thread_rng().gen_range(0..=ceiling));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::retry::*;
+
+    #[test]
+    fn jittered_delay() {
+        let settings = RetryConfig {
+            base_delay_ms: 100,
+            max_delay_ms: 1000,
+            ..Default::default()
+        };
+
+        for _ in 0..50 {
+            assert!(delay(&settings, 1) <= Duration::from_millis(100));
+            assert!(delay(&settings, 3) <= Duration::from_millis(400));
+            assert!(delay(&settings, 40) <= Duration::from_millis(1000));
+        }
+    }
+}
@@ -0,0 +1,110 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::clock;
+use crate::config::{self, SandboxConfig};
+use crate::redaction;
+use crate::storage;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::tokio::time::interval;
+use rocket::Rocket;
+
+/// Returns the time sandbox bookings must have been created before to be removed (RFC 3339).
+///
+/// # Arguments
+///
+/// * `now` - The current time
+/// * `retention_hours` - The number of hours after a sandbox booking is created it is removed
+fn cutoff(now: DateTime<Utc>, retention_hours: u64) -> String {
+    let retention: Duration = Duration::hours(retention_hours.min(i64::MAX as u64 / 3600) as i64);
+    return (now - retention).to_rfc3339_opts(SecondsFormat::Secs, true);
+}
+
+/// Removes the sandbox bookings older than the configured retention period. Returns the ids of
+/// the bookings removed, or None if storage is unavailable.
+///
+/// # Arguments
+///
+/// * `settings` - The sandbox settings
+///
+/// # Examples
+///
+/// ```
+/// let removed = sandbox::wipe(&settings);
+/// ```
+pub fn wipe(settings: &SandboxConfig) -> Option<Vec<u32>> {
+    return storage::wipe_sandbox(&cutoff(clock::now(), settings.retention_hours));
+}
+
+/// Removes every sandbox booking, however recently it was created. Returns the ids of the
+/// bookings removed, or None if storage is unavailable.
+///
+/// # Examples
+///
+/// ```
+/// let removed = sandbox::wipe_all();
+/// ```
+pub fn wipe_all() -> Option<Vec<u32>> {
+    // Creation times are recorded to the second, so bookings created this second are included.
+    return storage::wipe_sandbox(&cutoff(clock::now() + Duration::seconds(1), 0));
+}
+
+/// A fairing which starts a background task when the server launches, if the sandbox is enabled.
+/// The task removes sandbox bookings once they are older than the configured retention period,
+/// checking every hour.
+pub struct SandboxWipe;
+
+#[rocket::async_trait]
+impl Fairing for SandboxWipe {
+    fn info(&self) -> Info {
+        return Info {
+            name: "Sandbox wipe",
+            kind: Kind::Liftoff,
+        };
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<rocket::Orbit>) {
+        let settings: SandboxConfig = match config::hotel().sandbox {
+            Some(settings) => settings,
+            None => return,
+        };
+
+        let shutdown = rocket.shutdown();
+        rocket::tokio::spawn(async move {
+            let mut sweep = interval(std::time::Duration::from_secs(3600));
+
+            loop {
+                rocket::tokio::select! {
+                    _ = sweep.tick() => {}
+                    _ = shutdown.clone() => return,
+                }
+
+                let removed: Vec<u32> = match wipe(&settings) {
+                    Some(removed) => removed,
+                    None => continue,
+                };
+
+                if !removed.is_empty() {
+                    redaction::info(&format!("Removed {} sandbox bookings", removed.len()));
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sandbox::*;
+
+    #[test]
+    fn sandbox_cutoff() {
+        let now: DateTime<Utc> = DateTime::parse_from_rfc3339("2024-03-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(cutoff(now, 24), "2024-02-29T12:00:00Z");
+        assert_eq!(cutoff(now, 0), "2024-03-01T12:00:00Z");
+    }
+}
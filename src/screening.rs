@@ -0,0 +1,184 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use self::overlapping_stays::OverlappingStays;
+use self::velocity::Velocity;
+use crate::config;
+use crate::features::{self, Feature};
+use crate::storage::room_booking::RoomBooking;
+use crate::storage::screening_decision::ScreeningAction;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+pub mod overlapping_stays;
+pub mod velocity;
+
+/// Describes a screening rule applied to new bookings, and the action taken when a booking matches
+/// it
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct ScreeningRuleConfig {
+    /// The name of the rule, e.g. 'velocity'
+    pub rule: String,
+    /// The action taken when a booking matches the rule
+    pub action: ScreeningAction,
+    /// The most bookings a customer may make within the window, used by the 'velocity' rule
+    #[serde(default)]
+    pub max_bookings: Option<u32>,
+    /// The number of minutes of bookings counted, used by the 'velocity' rule
+    #[serde(default)]
+    pub window_minutes: Option<u32>,
+}
+
+/// Checks new bookings for signs of abuse or fraud, such as a customer making many bookings in a
+/// short time.
+pub trait ScreeningRule {
+    /// Returns the name used to configure the rule, e.g. 'velocity'.
+    fn name(&self) -> &'static str;
+
+    /// Checks a new booking against the rule. Returns a description of why the booking matches
+    /// the rule, or None if it does not.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking` - The new booking, without a booking id
+    /// * `bookings` - The stored bookings, keyed by booking id
+    fn evaluate(
+        &self,
+        booking: &RoomBooking,
+        bookings: &HashMap<u32, RoomBooking>,
+    ) -> Option<String>;
+}
+
+/// Describes a screening rule a new booking matched, and the action to take
+#[derive(Clone, PartialEq, Debug)]
+pub struct Finding {
+    /// The name of the rule the booking matched
+    pub rule: String,
+    pub action: ScreeningAction,
+    /// Why the booking matched the rule
+    pub reason: String,
+}
+
+/// Returns the rule with the settings provided, or None if there is no rule with the configured
+/// name.
+///
+/// # Arguments
+///
+/// * `settings` - The settings of the rule
+///
+/// # Examples
+///
+/// ```
+/// let rule = screening::rule(&settings);
+/// ```
+pub fn rule(settings: &ScreeningRuleConfig) -> Option<Box<dyn ScreeningRule + Send + Sync>> {
+    return match settings.rule.as_str() {
+        "velocity" => Some(Box::new(Velocity {
+            max_bookings: settings.max_bookings.unwrap_or(5),
+            window_minutes: settings.window_minutes.unwrap_or(60),
+        })),
+        "overlapping_stays" => Some(Box::new(OverlappingStays)),
+        _ => None,
+    };
+}
+
+/// Screens a new booking against each configured rule, in the order configured. Returns the rules
+/// the booking matched, and the action to take for each. Rules with an unknown name are skipped,
+/// and no rules are checked if screening is turned off by its feature flag.
+///
+/// # Arguments
+///
+/// * `booking` - The new booking, without a booking id
+/// * `bookings` - The stored bookings, keyed by booking id
+///
+/// # Examples
+///
+/// ```
+/// let findings = screening::screen(&booking, &booking_list);
+/// ```
+pub fn screen(booking: &RoomBooking, bookings: &HashMap<u32, RoomBooking>) -> Vec<Finding> {
+    if !features::enabled(Feature::Screening) {
+        return Vec::new();
+    }
+
+    return config::hotel()
+        .screening_rules
+        .iter()
+        .filter_map(|settings| {
+            let reason: String = rule(settings)?.evaluate(booking, bookings)?;
+            return Some(Finding {
+                rule: settings.rule.clone(),
+                action: settings.action,
+                reason,
+            });
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock;
+    use crate::screening::*;
+    use crate::storage::room_booking::BookingStatus;
+    use chrono::SecondsFormat;
+    use rocket::serde::json::{from_value, json};
+
+    fn booking(customer_id: u32, check_in_date: &str, check_out_date: &str) -> RoomBooking {
+        return from_value(json!({
+            "customerId": customer_id,
+            "roomTypeId": 1,
+            "checkInDate": check_in_date,
+            "checkOutDate": check_out_date,
+        }))
+        .unwrap();
+    }
+
+    #[test]
+    fn evaluate_rules() {
+        let mut existing: RoomBooking = booking(1, "2024-01-01", "2024-01-05");
+        existing.set_booking_id(1);
+        existing.set_status(BookingStatus::Confirmed);
+        existing.created_at = Some(clock::now().to_rfc3339_opts(SecondsFormat::Secs, true));
+        let bookings: HashMap<u32, RoomBooking> = HashMap::from([(1, existing)]);
+
+        let settings = ScreeningRuleConfig {
+            rule: "overlapping_stays".to_string(),
+            action: ScreeningAction::Hold,
+            max_bookings: None,
+            window_minutes: None,
+        };
+        let overlapping = rule(&settings).unwrap();
+        assert_eq!(overlapping.name(), "overlapping_stays");
+        assert!(overlapping
+            .evaluate(&booking(1, "2024-01-04", "2024-01-06"), &bookings)
+            .is_some());
+        assert!(overlapping
+            .evaluate(&booking(1, "2024-01-05", "2024-01-06"), &bookings)
+            .is_none());
+        assert!(overlapping
+            .evaluate(&booking(2, "2024-01-04", "2024-01-06"), &bookings)
+            .is_none());
+
+        let settings = ScreeningRuleConfig {
+            rule: "velocity".to_string(),
+            action: ScreeningAction::Reject,
+            max_bookings: Some(1),
+            window_minutes: Some(10),
+        };
+        let velocity = rule(&settings).unwrap();
+        assert_eq!(
+            velocity.evaluate(&booking(1, "2024-02-01", "2024-02-02"), &bookings),
+            Some("Customer 1 made 1 bookings in the last 10 minutes".to_string())
+        );
+        assert!(velocity
+            .evaluate(&booking(2, "2024-02-01", "2024-02-02"), &bookings)
+            .is_none());
+
+        let settings = ScreeningRuleConfig {
+            rule: "disposable_email".to_string(),
+            ..settings
+        };
+        assert!(rule(&settings).is_none());
+    }
+}
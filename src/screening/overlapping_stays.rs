@@ -0,0 +1,39 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::ScreeningRule;
+use crate::storage::room_booking::RoomBooking;
+use std::collections::HashMap;
+
+/// Matches a booking if the customer already holds a room for overlapping dates. A guest can only
+/// stay in one place at a time, so overlapping stays may indicate resale or inventory hoarding.
+pub struct OverlappingStays;
+
+impl ScreeningRule for OverlappingStays {
+    fn name(&self) -> &'static str {
+        return "overlapping_stays";
+    }
+
+    fn evaluate(
+        &self,
+        booking: &RoomBooking,
+        bookings: &HashMap<u32, RoomBooking>,
+    ) -> Option<String> {
+        let existing: &RoomBooking = bookings.values().find(|existing| {
+            existing.holds_room()
+                && existing.customer_id == booking.customer_id
+                && existing.check_in_date < booking.check_out_date
+                && booking.check_in_date < existing.check_out_date
+        })?;
+
+        return Some(format!(
+            "Customer {} already holds booking {} from {} to {}",
+            booking.customer_id,
+            existing.booking_id.unwrap_or_default(),
+            existing.check_in_date,
+            existing.check_out_date
+        ));
+    }
+}
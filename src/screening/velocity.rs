@@ -0,0 +1,52 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::ScreeningRule;
+use crate::clock;
+use crate::storage::room_booking::RoomBooking;
+use chrono::{Duration, SecondsFormat};
+use std::collections::HashMap;
+
+/// Matches a booking if the customer has already made the maximum number of bookings within the
+/// window, e.g. a script making many speculative bookings.
+pub struct Velocity {
+    pub max_bookings: u32,
+    pub window_minutes: u32,
+}
+
+impl ScreeningRule for Velocity {
+    fn name(&self) -> &'static str {
+        return "velocity";
+    }
+
+    fn evaluate(
+        &self,
+        booking: &RoomBooking,
+        bookings: &HashMap<u32, RoomBooking>,
+    ) -> Option<String> {
+        let since: String = (clock::now() - Duration::minutes(self.window_minutes as i64))
+            .to_rfc3339_opts(SecondsFormat::Secs, true);
+
+        let recent: usize = bookings
+            .values()
+            .filter(|existing| existing.customer_id == booking.customer_id)
+            .filter(|existing| {
+                existing
+                    .created_at
+                    .as_ref()
+                    .is_some_and(|created_at| *created_at >= since)
+            })
+            .count();
+
+        if recent < self.max_bookings as usize {
+            return None;
+        }
+
+        return Some(format!(
+            "Customer {} made {} bookings in the last {} minutes",
+            booking.customer_id, recent, self.window_minutes
+        ));
+    }
+}
@@ -0,0 +1,187 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use self::aws::AwsSecretsManager;
+use self::vault::Vault;
+use crate::circuit_breaker::CallError;
+use crate::config::{self, SecretRef, SecretsConfig};
+use crate::http_client;
+use crate::redaction;
+use crate::retry;
+use hyper::body::{self, Body};
+use hyper::{Method, Request};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::tokio::time::{interval, Duration};
+use rocket::Rocket;
+pub mod aws;
+pub mod vault;
+
+/// Provides secrets, such as API keys, from a secret store. Custom stores are added to the list
+/// returned by ```stores```, and selected by name in the secret settings.
+#[rocket::async_trait]
+pub trait SecretStore {
+    /// Returns the name used to select the store, e.g. 'vault'.
+    fn name(&self) -> &'static str;
+
+    /// Returns the name of the environment variable holding the token used to authenticate with
+    /// the store, unless another is configured.
+    fn token_env(&self) -> &'static str;
+
+    /// Fetches the value of a secret from the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The secret settings
+    /// * `token` - The token used to authenticate with the store
+    /// * `secret` - The secret to fetch
+    async fn fetch(
+        &self,
+        settings: &SecretsConfig,
+        token: &str,
+        secret: &SecretRef,
+    ) -> Result<String, CallError>;
+}
+
+/// Returns every available secret store.
+///
+/// # Examples
+///
+/// ```
+/// let stores = secrets::stores();
+/// ```
+pub fn stores() -> Vec<Box<dyn SecretStore + Send + Sync>> {
+    return vec![Box::new(Vault), Box::new(AwsSecretsManager)];
+}
+
+/// Returns the secret store with the provided name, if any.
+///
+/// # Arguments
+///
+/// * `name` - The name of the store
+///
+/// # Examples
+///
+/// ```
+/// let store = secrets::find("vault");
+/// ```
+pub fn find(name: &str) -> Option<Box<dyn SecretStore + Send + Sync>> {
+    return stores().into_iter().find(|store| store.name() == name);
+}
+
+/// Fetches each configured secret from the secret store, replacing the setting it is used for.
+/// Returns the number of secrets fetched, or an error describing the first secret which could not
+/// be fetched or applied. Secrets fetched before the error are still applied.
+///
+/// # Arguments
+///
+/// * `settings` - The secret settings
+///
+/// # Examples
+///
+/// ```
+/// let fetched = secrets::load(&settings).await?;
+/// ```
+pub async fn load(settings: &SecretsConfig) -> Result<usize, String> {
+    let store = match find(&settings.store) {
+        Some(store) => store,
+        None => return Err(format!("Unknown secret store '{}'", settings.store)),
+    };
+
+    let token_env: &str = settings.token_env.as_deref().unwrap_or(store.token_env());
+    let token: String = std::env::var(token_env).map_err(|error| {
+        format!(
+            "Unable to read the secret store token {}: {}",
+            token_env, error
+        )
+    })?;
+
+    for secret in &settings.secrets {
+        let value: String = store
+            .fetch(settings, &token, secret)
+            .await
+            .map_err(|error| format!("Unable to fetch {}: {}", secret.setting, error))?;
+        config::set_secret(&secret.setting, value)?;
+    }
+
+    return Ok(settings.secrets.len());
+}
+
+/// Requests a secret from the secret store using the outbound retry policy, returning the
+/// response body.
+///
+/// # Arguments
+///
+/// * `url` - The URL to request
+/// * `header` - The name of the header the token is sent in
+/// * `token` - The token used to authenticate with the store
+///
+/// # Examples
+///
+/// ```
+/// let content = secrets::get(&url, "X-Vault-Token", token).await?;
+/// ```
+pub async fn get(url: &str, header: &str, token: &str) -> Result<Vec<u8>, CallError> {
+    return retry::call("secrets", || request(url, header, token)).await;
+}
+
+/// Requests a secret from the secret store, returning the response body. Returns an error if the
+/// request fails, or the response status is not successful.
+async fn request(url: &str, header: &str, token: &str) -> Result<Vec<u8>, CallError> {
+    let request: Request<Body> = Request::builder()
+        .method(Method::GET)
+        .uri(url)
+        .header(header, token)
+        .body(Body::empty())?;
+
+    let response = http_client::client().request(request).await?;
+    let status = response.status();
+    let content = body::to_bytes(response.into_body()).await?;
+
+    if !status.is_success() {
+        return Err(format!("The secret store responded with {}", status).into());
+    }
+
+    return Ok(content.to_vec());
+}
+
+/// A fairing which starts a background task when the server launches, fetching the configured
+/// secrets again on the refresh interval, so rotated secrets are used without a restart. The
+/// previous values are kept if the secrets cannot be fetched.
+pub struct SecretRefresh;
+
+#[rocket::async_trait]
+impl Fairing for SecretRefresh {
+    fn info(&self) -> Info {
+        return Info {
+            name: "Secret refresh",
+            kind: Kind::Liftoff,
+        };
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<rocket::Orbit>) {
+        let settings: SecretsConfig = match config::hotel().secrets {
+            Some(settings) if settings.refresh_seconds > 0 => settings,
+            _ => return,
+        };
+
+        let shutdown = rocket.shutdown();
+        rocket::tokio::spawn(async move {
+            let mut refresh = interval(Duration::from_secs(settings.refresh_seconds));
+            // The secrets were fetched at startup, so the first, immediate, tick is skipped.
+            refresh.tick().await;
+
+            loop {
+                rocket::tokio::select! {
+                    _ = refresh.tick() => {}
+                    _ = shutdown.clone() => return,
+                }
+
+                if let Err(error) = load(&settings).await {
+                    redaction::error(&format!("Unable to refresh secrets: {}", error));
+                }
+            }
+        });
+    }
+}
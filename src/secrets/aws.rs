@@ -0,0 +1,60 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::SecretStore;
+use crate::circuit_breaker::CallError;
+use crate::config::{SecretRef, SecretsConfig};
+use rocket::serde::json::{self, Value};
+use serde::{Deserialize, Serialize};
+
+/// Describes a secret, as returned by the AWS Secrets Manager Agent
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct SecretValue {
+    pub secret_string: String,
+}
+
+/// Reads secrets from AWS Secrets Manager, through the AWS Secrets Manager Agent running alongside
+/// the service, which caches secrets and signs requests to AWS. The agent authenticates requests
+/// with its SSRF token, and only serves http on the local host, so its URL is not required to use
+/// https. Secrets holding JSON are read using the key, and other secrets are read whole.
+pub struct AwsSecretsManager;
+
+#[rocket::async_trait]
+impl SecretStore for AwsSecretsManager {
+    fn name(&self) -> &'static str {
+        return "aws";
+    }
+
+    fn token_env(&self) -> &'static str {
+        return "AWS_TOKEN";
+    }
+
+    async fn fetch(
+        &self,
+        settings: &SecretsConfig,
+        token: &str,
+        secret: &SecretRef,
+    ) -> Result<String, CallError> {
+        let url: String = format!(
+            "{}/secretsmanager/get?secretId={}",
+            settings.url.trim_end_matches('/'),
+            secret.path
+        );
+        let content: Vec<u8> = super::get(&url, "X-Aws-Parameters-Secrets-Token", token).await?;
+        let response: SecretValue = json::from_slice(&content)?;
+
+        let key: &str = match &secret.key {
+            Some(key) => key,
+            None => return Ok(response.secret_string),
+        };
+
+        let values: Value = json::from_str(&response.secret_string)?;
+        return values[key]
+            .as_str()
+            .map(|value| value.to_string())
+            .ok_or_else(|| format!("{} has no value for {}", secret.path, key).into());
+    }
+}
@@ -0,0 +1,78 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::SecretStore;
+use crate::circuit_breaker::CallError;
+use crate::config::{SecretRef, SecretsConfig};
+use crate::http_client;
+use rocket::serde::json::{self, Value};
+
+/// Reads secrets from HashiCorp Vault's key/value secrets engine, authenticating with a Vault
+/// token. Both versions of the engine are supported. The key defaults to 'value'.
+pub struct Vault;
+
+#[rocket::async_trait]
+impl SecretStore for Vault {
+    fn name(&self) -> &'static str {
+        return "vault";
+    }
+
+    fn token_env(&self) -> &'static str {
+        return "VAULT_TOKEN";
+    }
+
+    async fn fetch(
+        &self,
+        settings: &SecretsConfig,
+        token: &str,
+        secret: &SecretRef,
+    ) -> Result<String, CallError> {
+        http_client::require_https(&settings.url)?;
+
+        let url: String = format!(
+            "{}/v1/{}",
+            settings.url.trim_end_matches('/'),
+            secret.path.trim_start_matches('/')
+        );
+        let content: Vec<u8> = super::get(&url, "X-Vault-Token", token).await?;
+        let response: Value = json::from_slice(&content)?;
+
+        return value(&response, secret.key.as_deref().unwrap_or("value"))
+            .ok_or_else(|| format!("{} has no value for {}", secret.path, secret.setting).into());
+    }
+}
+
+/// Returns a value from a Vault secret, nested in ```data.data``` by version 2 of the key/value
+/// engine, and in ```data``` by version 1.
+///
+/// # Arguments
+///
+/// * `response` - The response to a secret read
+/// * `key` - The key of the value within the secret
+pub fn value(response: &Value, key: &str) -> Option<String> {
+    let data: &Value = &response["data"];
+    let secret: &Value = match data.get("data") {
+        Some(nested) if nested.is_object() => nested,
+        _ => data,
+    };
+
+    return secret[key].as_str().map(|value| value.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::secrets::vault::*;
+    use rocket::serde::json::json;
+
+    #[test]
+    fn read_secret_value() {
+        let kv2 = json!({"data": {"data": {"apiKey": "abc"}, "metadata": {"version": 2}}});
+        assert_eq!(value(&kv2, "apiKey"), Some("abc".to_string()));
+
+        let kv1 = json!({"data": {"value": "def"}});
+        assert_eq!(value(&kv1, "value"), Some("def".to_string()));
+        assert_eq!(value(&kv1, "missing"), None);
+    }
+}
@@ -0,0 +1,135 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::config;
+use crate::local_time;
+use crate::storage::room_booking::{BookingStatus, RoomBooking, DATE_FORMAT};
+use chrono::{Duration, NaiveDate};
+use rand::Rng;
+
+/// Describes the range of values used when generating synthetic bookings
+#[derive(Clone, PartialEq, Debug)]
+pub struct SeedOptions {
+    /// The number of bookings to generate
+    pub count: usize,
+    /// The earliest check in date to generate
+    pub from: NaiveDate,
+    /// The latest check in date to generate
+    pub to: NaiveDate,
+    /// Room type ids are generated between 1 and this value
+    pub room_types: u8,
+    /// Customer ids are generated between 1 and this value
+    pub customers: u32,
+    /// The longest stay to generate, in nights
+    pub max_nights: i64,
+}
+
+impl SeedOptions {
+    /// Creates seed options generating the provided number of bookings, with check in dates over
+    /// the next year and stays of up to 14 nights, or the maximum stay length if shorter.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The number of bookings to generate
+    pub fn new(count: usize) -> SeedOptions {
+        let today: NaiveDate = local_time::today();
+
+        return SeedOptions {
+            count,
+            from: today,
+            to: today + Duration::days(365),
+            room_types: 5,
+            customers: 1000,
+            max_nights: 14.min(config::hotel().max_stay_nights as i64),
+        };
+    }
+}
+
+/// Generates a list of random, valid bookings, without a booking id.
+///
+/// # Arguments
+///
+/// * `options` - The range of values to generate bookings within
+///
+/// # Examples
+///
+/// ```
+/// let bookings = generate(&SeedOptions::new(1000));
+/// ```
+pub fn generate(options: &SeedOptions) -> Vec<RoomBooking> {
+    let mut rng = rand::thread_rng();
+    let days: i64 = (options.to - options.from).num_days().max(0);
+
+    return (0..options.count)
+        .map(|_| {
+            let check_in_date: NaiveDate = options.from + Duration::days(rng.gen_range(0..=days));
+            let nights: i64 = rng.gen_range(1..=options.max_nights.max(1));
+            let status: BookingStatus = match rng.gen_range(0..10) {
+                0 => BookingStatus::Cancelled,
+                1 => BookingStatus::Complete,
+                _ => BookingStatus::Confirmed,
+            };
+
+            return RoomBooking {
+                booking_id: None,
+                customer_id: rng.gen_range(1..=options.customers.max(1)),
+                room_type_id: rng.gen_range(1..=options.room_types.max(1)),
+                check_in_date: check_in_date.format(DATE_FORMAT).to_string(),
+                check_out_date: (check_in_date + Duration::days(nights))
+                    .format(DATE_FORMAT)
+                    .to_string(),
+                status: Some(status),
+                expected_arrival_time: None,
+                late_check_out_time: None,
+                day_use: false,
+                start_time: None,
+                end_time: None,
+                billing_milestones: Vec::new(),
+                series_id: None,
+                cancellation: None,
+                no_show_fee: None,
+                payment_due_by: None,
+                addons: Vec::new(),
+                promo_code: None,
+                discount: None,
+                guests: 1,
+                tax_exemptions: Vec::new(),
+                account_id: None,
+                agent_id: None,
+                channel_reservation_id: None,
+                children: 0,
+                rate_plans: Vec::new(),
+                pricing: None,
+                payments: Vec::new(),
+                refunds: Vec::new(),
+                created_at: None,
+                updated_at: None,
+                cancelled_at: None,
+                sandbox: false,
+            };
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::seed::*;
+    use crate::storage::room_booking::BookingRules;
+
+    #[test]
+    fn generate_bookings() {
+        let mut options = SeedOptions::new(50);
+        options.from = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+        options.to = NaiveDate::from_ymd_opt(2020, 1, 31).unwrap();
+        let bookings: Vec<RoomBooking> = generate(&options);
+
+        assert_eq!(bookings.len(), 50);
+        for booking in bookings {
+            assert!(booking.validate().is_empty());
+            assert!(booking.check_in_date.as_str() >= "2020-01-01");
+            assert!(booking.check_in_date.as_str() <= "2020-01-31");
+        }
+    }
+}
@@ -0,0 +1,200 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::storage::room_booking::BookingStatus;
+use std::fmt;
+
+/// The statuses each booking status may move to. A status which is not listed may not be changed.
+static TRANSITIONS: &[(BookingStatus, &[BookingStatus])] = &[
+    (
+        BookingStatus::Confirmed,
+        &[
+            BookingStatus::CheckedIn,
+            BookingStatus::Cancelled,
+            BookingStatus::NoShow,
+        ],
+    ),
+    (BookingStatus::CheckedIn, &[BookingStatus::Complete]),
+    (
+        BookingStatus::Cancelled,
+        &[
+            BookingStatus::Confirmed,
+            BookingStatus::PendingPayment,
+            BookingStatus::PendingReview,
+        ],
+    ),
+    (
+        BookingStatus::PendingPayment,
+        &[BookingStatus::Confirmed, BookingStatus::Cancelled],
+    ),
+    (
+        BookingStatus::PendingReview,
+        &[
+            BookingStatus::Confirmed,
+            BookingStatus::PendingPayment,
+            BookingStatus::Cancelled,
+        ],
+    ),
+];
+
+/// Describes why the status of a booking could not be changed
+#[derive(Clone, PartialEq, Debug)]
+pub enum TransitionError {
+    /// The booking does not exist
+    NotFound,
+    /// The booking's current status may not move to the requested status
+    Illegal {
+        from: Option<BookingStatus>,
+        to: BookingStatus,
+    },
+    /// The action only applies to bookings with another status, e.g. reinstating a booking which
+    /// is not cancelled
+    WrongStatus {
+        expected: BookingStatus,
+        actual: Option<BookingStatus>,
+    },
+    /// The booking cannot be marked as a no-show before its check in date
+    BeforeCheckIn,
+    /// No room is available for the booking's dates, so it cannot hold a room again
+    NoRoomAvailable,
+    /// The booking has not been paid in full, so the guest cannot check in
+    OutstandingBalance(u64),
+    /// The booking's deposit has not been paid in full, so its payment cannot be confirmed
+    DepositOutstanding(u64),
+    /// The booking was held for review by screening, and must be approved before it is confirmed
+    AwaitingReview,
+    /// The booking storage could not be accessed
+    Unavailable,
+}
+
+impl fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            TransitionError::NotFound => write!(f, "Booking not found"),
+            TransitionError::Illegal {
+                from: Some(from),
+                to,
+            } => {
+                let allowed: Vec<String> = allowed_transitions(from)
+                    .iter()
+                    .map(|status| status.to_string())
+                    .collect();
+                write!(
+                    f,
+                    "A {} booking cannot be changed to {}, allowed statuses are: {}",
+                    from,
+                    to,
+                    match allowed.is_empty() {
+                        true => "none".to_string(),
+                        false => allowed.join(", "),
+                    }
+                )
+            }
+            TransitionError::Illegal { from: None, to } => {
+                write!(f, "A booking without a status cannot be changed to {}", to)
+            }
+            TransitionError::WrongStatus { expected, actual } => write!(
+                f,
+                "This action only applies to {} bookings, but the booking is {}",
+                expected,
+                actual
+                    .as_ref()
+                    .map_or("without a status".to_string(), |actual| actual.to_string())
+            ),
+            TransitionError::BeforeCheckIn => write!(
+                f,
+                "A booking cannot be marked as a no-show before its check in date"
+            ),
+            TransitionError::NoRoomAvailable => {
+                write!(f, "No rooms are available for the booking's dates")
+            }
+            TransitionError::OutstandingBalance(outstanding) => write!(
+                f,
+                "The booking must be paid in full before check in, {} is outstanding",
+                outstanding
+            ),
+            TransitionError::DepositOutstanding(outstanding) => write!(
+                f,
+                "The deposit must be paid before the booking is confirmed, {} is outstanding",
+                outstanding
+            ),
+            TransitionError::AwaitingReview => write!(
+                f,
+                "The booking is held for review, and must be approved before it is confirmed"
+            ),
+            TransitionError::Unavailable => write!(f, "Booking storage is unavailable"),
+        };
+    }
+}
+
+/// Returns the statuses a booking with the provided status may move to.
+///
+/// # Arguments
+///
+/// * `from` - The current status of the booking
+///
+/// # Examples
+///
+/// ```
+/// let allowed = state_machine::allowed_transitions(&BookingStatus::Confirmed);
+/// ```
+pub fn allowed_transitions(from: &BookingStatus) -> &'static [BookingStatus] {
+    return TRANSITIONS
+        .iter()
+        .find(|(status, _)| status == from)
+        .map_or(&[], |(_, allowed)| *allowed);
+}
+
+/// Checks whether a booking with the provided status may move to another status.
+///
+/// # Arguments
+///
+/// * `from` - The current status of the booking
+/// * `to` - The requested status
+///
+/// # Examples
+///
+/// ```
+/// state_machine::check(&booking.status, &BookingStatus::Cancelled)?;
+/// ```
+pub fn check(from: &Option<BookingStatus>, to: &BookingStatus) -> Result<(), TransitionError> {
+    let allowed: bool = match from {
+        Some(from) => allowed_transitions(from).contains(to),
+        None => false,
+    };
+
+    if !allowed {
+        return Err(TransitionError::Illegal {
+            from: from.clone(),
+            to: to.clone(),
+        });
+    }
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state_machine::*;
+
+    #[test]
+    fn booking_transitions() {
+        let confirmed = Some(BookingStatus::Confirmed);
+        assert!(check(&confirmed, &BookingStatus::CheckedIn).is_ok());
+        assert!(check(&confirmed, &BookingStatus::NoShow).is_ok());
+        assert!(check(&confirmed, &BookingStatus::Complete).is_err());
+        assert!(check(&Some(BookingStatus::CheckedIn), &BookingStatus::Complete).is_ok());
+        assert!(check(&Some(BookingStatus::CheckedIn), &BookingStatus::Cancelled).is_err());
+        assert!(check(&None, &BookingStatus::Cancelled).is_err());
+        assert!(check(&Some(BookingStatus::Cancelled), &BookingStatus::Confirmed).is_ok());
+        assert!(check(&Some(BookingStatus::PendingPayment), &BookingStatus::NoShow).is_err());
+
+        let error = check(&Some(BookingStatus::Complete), &BookingStatus::Cancelled).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "A Complete booking cannot be changed to Cancelled, allowed statuses are: none"
+        );
+    }
+}
@@ -0,0 +1,163 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::config;
+use crate::storage::room_booking::{BookingRules, RoomBooking, DATE_FORMAT};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Describes the shortest and longest stay which may be booked in a room type, either all year
+/// or during a season
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct StayRule {
+    /// The id of the room type the rule applies to
+    pub room_type_id: u8,
+    /// The fewest nights which may be booked
+    #[serde(default)]
+    pub min_nights: Option<u32>,
+    /// The most nights which may be booked
+    #[serde(default)]
+    pub max_nights: Option<u32>,
+    /// The first day of the season the rule applies to, as MM-DD, for stays checking in during
+    /// the season. The rule applies all year if no season is given.
+    #[serde(default)]
+    pub season_start: Option<String>,
+    /// The last day of the season the rule applies to, inclusive, as MM-DD. Seasons may span the
+    /// new year, e.g. 12-01 to 02-28.
+    #[serde(default)]
+    pub season_end: Option<String>,
+}
+
+impl StayRule {
+    /// Checks whether the rule applies to a stay checking in on a date.
+    fn applies_on(&self, check_in_date: NaiveDate) -> bool {
+        let day: String = check_in_date.format("%m-%d").to_string();
+
+        return match (&self.season_start, &self.season_end) {
+            (Some(start), Some(end)) if start <= end => day >= *start && day <= *end,
+            (Some(start), Some(end)) => day >= *start || day <= *end,
+            _ => true,
+        };
+    }
+
+    /// Describes when the rule applies, for use in validation errors.
+    fn season(&self) -> String {
+        return match (&self.season_start, &self.season_end) {
+            (Some(start), Some(end)) => format!(" checking in between {} and {}", start, end),
+            _ => String::new(),
+        };
+    }
+}
+
+/// Returns the minimum and maximum stay rules.
+///
+/// # Examples
+///
+/// ```
+/// let rules = stay_rules::rules();
+/// ```
+pub fn rules() -> Vec<StayRule> {
+    return config::hotel().stay_rules;
+}
+
+/// Checks the length of a booking's stay against the minimum and maximum stay rules of its room
+/// type. Every rule applying to the stay is checked. Day use bookings are not checked. Returns a
+/// list describing each rule broken, which is empty if the stay is allowed.
+///
+/// # Arguments
+///
+/// * `booking` - The booking to check
+///
+/// # Examples
+///
+/// ```
+/// let errors = stay_rules::validate(&booking);
+/// ```
+pub fn validate(booking: &RoomBooking) -> Vec<String> {
+    return check(&rules(), booking);
+}
+
+/// Checks the length of a booking's stay against a list of stay rules.
+fn check(rules: &[StayRule], booking: &RoomBooking) -> Vec<String> {
+    let check_in_date: NaiveDate =
+        match NaiveDate::parse_from_str(&booking.check_in_date, DATE_FORMAT) {
+            Ok(check_in_date) => check_in_date,
+            Err(_) => return Vec::new(),
+        };
+
+    if booking.day_use {
+        return Vec::new();
+    }
+
+    let nights: u32 = booking.nights().len() as u32;
+    let mut errors: Vec<String> = Vec::new();
+
+    for rule in rules
+        .iter()
+        .filter(|rule| rule.room_type_id == booking.room_type_id)
+        .filter(|rule| rule.applies_on(check_in_date))
+    {
+        if let Some(min_nights) = rule.min_nights.filter(|min_nights| nights < *min_nights) {
+            errors.push(format!(
+                "Stays in room type {}{} must be at least {} nights",
+                rule.room_type_id,
+                rule.season(),
+                min_nights
+            ));
+        }
+
+        if let Some(max_nights) = rule.max_nights.filter(|max_nights| nights > *max_nights) {
+            errors.push(format!(
+                "Stays in room type {}{} must not be longer than {} nights",
+                rule.room_type_id,
+                rule.season(),
+                max_nights
+            ));
+        }
+    }
+
+    return errors;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stay_rules::*;
+
+    #[test]
+    fn seasonal_stay_rules() {
+        let rules = vec![
+            StayRule {
+                room_type_id: 1,
+                min_nights: None,
+                max_nights: Some(7),
+                season_start: None,
+                season_end: None,
+            },
+            StayRule {
+                room_type_id: 1,
+                min_nights: Some(3),
+                max_nights: None,
+                season_start: Some("12-20".to_string()),
+                season_end: Some("01-05".to_string()),
+            },
+        ];
+        let mut booking: RoomBooking = rocket::serde::json::from_str(
+            r#"{"customerId":1,"roomTypeId":1,"checkInDate":"2020-12-30",
+            "checkOutDate":"2021-01-01","status":null}"#,
+        )
+        .unwrap();
+        assert_eq!(check(&rules, &booking).len(), 1);
+
+        booking.check_in_date = "2020-12-01".to_string();
+        booking.check_out_date = "2020-12-03".to_string();
+        assert!(check(&rules, &booking).is_empty());
+
+        booking.check_out_date = "2020-12-09".to_string();
+        assert_eq!(check(&rules, &booking).len(), 1);
+
+        booking.room_type_id = 2;
+        assert!(check(&rules, &booking).is_empty());
+    }
+}
@@ -3,256 +3,4289 @@
     SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
 */
 
-use self::room_booking::{BookingStatus, RoomBooking};
+use self::anonymization::Anonymization;
+use self::availability::{Availability, AvailabilityQuery, AvailabilityResult};
+use self::booking_addon::BookingAddon;
+use self::booking_change::{BookingChange, ChangeSet, ChangeType};
+use self::booking_cursor::BookingCursor;
+use self::booking_delta::{BookingDelta, BookingTombstone};
+use self::booking_filter::BookingFilter;
+use self::booking_history::BookingHistoryEntry;
+use self::booking_page::BookingPage;
+use self::bulk_status::BulkStatusResult;
+use self::cancellation::{Cancellation, CancellationReason};
+use self::cdc_record::{CdcData, CdcOperation, CdcPage, CdcRecord};
+use self::corporate_account::CorporateAccount;
+use self::create_error::CreateError;
+use self::customer_export::CustomerExport;
+use self::exchange_rate::ExchangeRateOverride;
+use self::inventory_adjustment::{adjusted_inventory, InventoryAdjustment};
+use self::metrics::{MetricsRecorder, StorageMetrics};
+use self::outbox_event::{EventTopic, OutboxEvent};
+use self::payment::{Balance, Payment, Refund, RefundReservation};
+use self::payment_event::PaymentEvent;
+use self::promo_code::{Discount, PromoCode};
+use self::recurring_booking::SeriesUpdate;
+use self::refund_error::RefundError;
+use self::retention::{RetainedBooking, RetentionAction, RetentionReport};
+use self::room_booking::{BookingRules, BookingStatus, RoomBooking, DATE_FORMAT};
+use self::screening_decision::{ScreeningAction, ScreeningDecision};
+use self::sell_restriction::SellRestriction;
+use crate::addons;
+use crate::clock;
+use crate::config::{self, RoomInventory};
+use crate::local_time;
+use crate::pagination::Pagination;
+use crate::pricing;
+use crate::response_cache::ResponseCache;
+use crate::screening::{self, Finding};
+use crate::state_machine::{self, TransitionError};
+use chrono::{DateTime, Duration, NaiveDate, SecondsFormat, Utc};
 use once_cell::sync::Lazy;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::cell::Cell;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::fs::{metadata, File};
 use std::io::{Read, Write};
-use std::{collections::HashMap, sync::Mutex};
+use std::path::PathBuf;
+use std::sync::{LockResult, Mutex, MutexGuard};
+use std::time::{Instant, SystemTime};
+pub mod anonymization;
+#[cfg(test)]
+pub mod arbitrary;
+pub mod availability;
+pub use booking_types::billing_milestone;
+pub use booking_types::booking_addon;
+pub use booking_types::booking_change;
+pub mod booking_conflict;
+pub mod booking_cursor;
+pub mod booking_delta;
+pub mod booking_filter;
+pub mod booking_history;
+pub mod booking_page;
+pub mod booking_summary;
+pub mod booking_validation;
+pub mod bulk_status;
+pub mod cancellation;
+pub mod cdc_record;
+pub mod corporate_account;
+pub mod create_error;
+pub mod customer_export;
+pub mod exchange_rate;
+pub mod inventory_adjustment;
+pub mod isolated;
+pub mod ledger;
+pub mod metrics;
+pub use booking_types::outbox_event;
+pub mod payment;
+pub mod payment_event;
+pub use booking_types::pricing_record;
+pub mod promo_code;
+pub mod recurring_booking;
+pub mod refund_error;
+pub mod retention;
 pub mod room_booking;
+pub mod screening_decision;
+pub mod sell_restriction;
+pub mod snapshot;
 
 /// The path used to store a snapshot of the stored booking data.
 static SNAPSHOT_PATH: &str = "booking.dat";
-/// A lazily initialised HashMap containing the list of bookings held by the system.
-static BOOKING_LIST: Lazy<Mutex<HashMap<u32, RoomBooking>>> = Lazy::new(|| {
-    let map: HashMap<u32, RoomBooking> = HashMap::new();
-    Mutex::new(map)
-});
+/// The path used to store a snapshot of the booking change log.
+static CHANGES_PATH: &str = "changes.dat";
+/// The path used to store a snapshot of the change log of records other than bookings.
+static CDC_PATH: &str = "cdc.dat";
+/// The path used to store a snapshot of the promo codes.
+static PROMO_CODES_PATH: &str = "promo_codes.dat";
+/// The path used to store a snapshot of the corporate accounts.
+static ACCOUNTS_PATH: &str = "accounts.dat";
+/// The path used to store a snapshot of the sell restrictions.
+static RESTRICTIONS_PATH: &str = "restrictions.dat";
+/// The path used to store a snapshot of the inventory adjustments.
+static ADJUSTMENTS_PATH: &str = "inventory_adjustments.dat";
+/// The path used to store a snapshot of the exchange rate overrides.
+static EXCHANGE_RATES_PATH: &str = "exchange_rates.dat";
+/// The path used to store a snapshot of the event outbox.
+static OUTBOX_PATH: &str = "outbox.dat";
+/// The path used to store a snapshot of the dead-letter store.
+static DEAD_LETTERS_PATH: &str = "dead_letters.dat";
+/// The path used to store a snapshot of the payment events received from the payment provider.
+static PAYMENT_EVENTS_PATH: &str = "payment_events.dat";
+/// The path used to store the anonymized bookings archived by the retention policy.
+static ARCHIVE_PATH: &str = "archive.dat";
+/// The path used to store a snapshot of the screening decisions made for new bookings.
+static SCREENING_PATH: &str = "screening.dat";
+/// The path used to store the highest booking id assigned.
+static BOOKING_IDS_PATH: &str = "booking_ids.dat";
+/// The path used to store the ids of the bookings held for review which have not been approved.
+static REVIEWS_PATH: &str = "reviews.dat";
+/// Holds the data stored by the system, along with the directory its snapshots are saved in.
+struct Store {
+    /// The directory snapshots are saved in, relative to the working directory if not absolute
+    directory: PathBuf,
+    /// The list of bookings held by the system, keyed by booking id
+    booking_list: Mutex<HashMap<u32, RoomBooking>>,
+    /// The highest booking id assigned. Ids are never reused, even once the booking is removed, so
+    /// sync clients and the change log never confuse a new booking with a removed one
+    last_booking_id: Mutex<u32>,
+    /// Every change made to a booking, ordered by sequence number
+    change_log: Mutex<Vec<BookingChange>>,
+    /// Every change made to a record other than a booking, ordered by sequence number. Shares its
+    /// sequence numbers with ```change_log```.
+    cdc_log: Mutex<Vec<CdcRecord>>,
+    /// The events waiting to be delivered, ordered by sequence number
+    outbox: Mutex<Vec<OutboxEvent>>,
+    /// The events which could not be delivered, ordered by sequence number
+    dead_letters: Mutex<Vec<OutboxEvent>>,
+    /// The payment events received, in the order they were received
+    payment_events: Mutex<Vec<PaymentEvent>>,
+    /// The screening decisions made for new bookings, in the order they were made
+    screening_log: Mutex<Vec<ScreeningDecision>>,
+    /// The ids of the bookings held for review by screening which have not been approved,
+    /// including those cancelled while held, so they cannot be reinstated without review
+    review_list: Mutex<HashSet<u32>>,
+    /// The refunds reserved while they are requested from the payment provider. These are not
+    /// saved, as a refund in progress is released if the request fails
+    refund_reservations: Mutex<Vec<RefundReservation>>,
+    /// The promo codes, keyed by code
+    promo_code_list: Mutex<HashMap<String, PromoCode>>,
+    /// The corporate accounts, keyed by account id
+    account_list: Mutex<HashMap<u32, CorporateAccount>>,
+    /// The sell restrictions, keyed by restriction id
+    restriction_list: Mutex<HashMap<u32, SellRestriction>>,
+    /// The inventory adjustments, keyed by adjustment id
+    adjustment_list: Mutex<HashMap<u32, InventoryAdjustment>>,
+    /// The exchange rate overrides, keyed by currency
+    exchange_rate_list: Mutex<HashMap<String, ExchangeRateOverride>>,
+    /// The time each booking was last modified, keyed by booking id
+    modified_list: Mutex<HashMap<u32, SystemTime>>,
+    /// The responses computed from the stored data, invalidated whenever a snapshot is saved
+    responses: ResponseCache,
+    /// The work done by the store, such as lock waits, scans and snapshot writes
+    metrics: MetricsRecorder,
+}
+
+impl Store {
+    /// Creates an empty store, saving its snapshots in the provided directory.
+    fn new(directory: PathBuf) -> Store {
+        return Store {
+            directory,
+            booking_list: Mutex::new(HashMap::new()),
+            last_booking_id: Mutex::new(0),
+            change_log: Mutex::new(Vec::new()),
+            cdc_log: Mutex::new(Vec::new()),
+            outbox: Mutex::new(Vec::new()),
+            dead_letters: Mutex::new(Vec::new()),
+            payment_events: Mutex::new(Vec::new()),
+            screening_log: Mutex::new(Vec::new()),
+            review_list: Mutex::new(HashSet::new()),
+            refund_reservations: Mutex::new(Vec::new()),
+            promo_code_list: Mutex::new(HashMap::new()),
+            account_list: Mutex::new(HashMap::new()),
+            restriction_list: Mutex::new(HashMap::new()),
+            adjustment_list: Mutex::new(HashMap::new()),
+            exchange_rate_list: Mutex::new(HashMap::new()),
+            modified_list: Mutex::new(HashMap::new()),
+            responses: ResponseCache::new(),
+            metrics: MetricsRecorder::new(),
+        };
+    }
+}
+
+/// A lazily initialised store used by the service, saving its snapshots in the working directory.
+static STORE: Lazy<Store> = Lazy::new(|| Store::new(PathBuf::new()));
+
+thread_local! {
+    /// The store used on the current thread in place of ```STORE```, if any. Set by
+    /// ```IsolatedStorage```.
+    static SCOPED_STORE: Cell<Option<&'static Store>> = const { Cell::new(None) };
+}
+
+/// Returns the store in use on the current thread.
+fn store() -> &'static Store {
+    return SCOPED_STORE.with(|scoped| scoped.get()).unwrap_or(&STORE);
+}
+
+/// Locks one of the lists held by the store, recording how long the lock took to acquire.
+///
+/// # Arguments
+///
+/// * `list` - The list to lock
+fn lock_list<T>(list: &Mutex<T>) -> LockResult<MutexGuard<'_, T>> {
+    let started: Instant = Instant::now();
+    let guard: LockResult<MutexGuard<'_, T>> = list.lock();
+    store().metrics.lock_waits.record(started.elapsed());
+    return guard;
+}
 
-/// Checks whether a storage snapshot exists in the path defined by SNAPSHOT_PATH.
+/// Records a scan of every stored booking, returning the bookings to scan.
+///
+/// # Arguments
+///
+/// * `booking_list` - The stored bookings, keyed by booking id
+fn scan(booking_list: &HashMap<u32, RoomBooking>) -> &HashMap<u32, RoomBooking> {
+    store().metrics.record_scan(booking_list.len());
+    return booking_list;
+}
+
+/// Returns the path a snapshot file is saved at, in the directory of the store in use.
+fn path(name: &str) -> PathBuf {
+    return store().directory.join(name);
+}
+
+/// Checks whether any of the snapshot files read by ```load_snapshot``` exists, so data saved
+/// without any bookings, such as promo codes, the change logs, the event outbox or the highest
+/// booking id assigned, is still loaded.
 pub fn snapshot_exists() -> bool {
-    return metadata(SNAPSHOT_PATH).is_ok();
+    return [
+        SNAPSHOT_PATH,
+        CHANGES_PATH,
+        CDC_PATH,
+        PROMO_CODES_PATH,
+        ACCOUNTS_PATH,
+        RESTRICTIONS_PATH,
+        ADJUSTMENTS_PATH,
+        EXCHANGE_RATES_PATH,
+        OUTBOX_PATH,
+        DEAD_LETTERS_PATH,
+        PAYMENT_EVENTS_PATH,
+        SCREENING_PATH,
+        BOOKING_IDS_PATH,
+        REVIEWS_PATH,
+    ]
+    .iter()
+    .any(|name: &&str| metadata(path(name)).is_ok());
 }
 
-/// Loads the snapshot from the path defined by ```SNAPSHOT_PATH``` into the ```BOOKING_LIST``` HashMap.
+/// Loads the snapshot from the path defined by ```SNAPSHOT_PATH``` into the stored bookings,
+/// the change log from the path defined by ```CHANGES_PATH```, the promo codes from the path
+/// defined by ```PROMO_CODES_PATH```, the corporate accounts from the path defined by
+/// ```ACCOUNTS_PATH```, the sell restrictions from the path defined by ```RESTRICTIONS_PATH```,
+/// the inventory adjustments from the path defined by ```ADJUSTMENTS_PATH```, the exchange rate
+/// overrides from the path defined by ```EXCHANGE_RATES_PATH```, the change log of other records
+/// from the path defined by ```CDC_PATH```, the event outbox from the path defined by
+/// ```OUTBOX_PATH```, the dead-letter store from the path defined by ```DEAD_LETTERS_PATH```, the
+/// payment events from the path defined by ```PAYMENT_EVENTS_PATH```, the screening decisions
+/// from the path defined by ```SCREENING_PATH```, the bookings held for review from the path
+/// defined by ```REVIEWS_PATH```, and the highest booking id assigned from the path defined by
+/// ```BOOKING_IDS_PATH```, if each exists.
 pub fn load_snapshot() -> Result<(), Box<dyn std::error::Error>> {
+    if metadata(path(RESTRICTIONS_PATH)).is_ok() {
+        *lock_list(&store().restriction_list).unwrap() = read_file(RESTRICTIONS_PATH)?;
+    }
+
+    if metadata(path(EXCHANGE_RATES_PATH)).is_ok() {
+        *lock_list(&store().exchange_rate_list).unwrap() = read_file(EXCHANGE_RATES_PATH)?;
+    }
+
+    if metadata(path(ADJUSTMENTS_PATH)).is_ok() {
+        *lock_list(&store().adjustment_list).unwrap() = read_file(ADJUSTMENTS_PATH)?;
+    }
+
+    if metadata(path(CDC_PATH)).is_ok() {
+        *lock_list(&store().cdc_log).unwrap() = read_file(CDC_PATH)?;
+    }
+
+    if metadata(path(OUTBOX_PATH)).is_ok() {
+        *lock_list(&store().outbox).unwrap() = read_file(OUTBOX_PATH)?;
+    }
+
+    if metadata(path(DEAD_LETTERS_PATH)).is_ok() {
+        *lock_list(&store().dead_letters).unwrap() = read_file(DEAD_LETTERS_PATH)?;
+    }
+
+    if metadata(path(PAYMENT_EVENTS_PATH)).is_ok() {
+        *lock_list(&store().payment_events).unwrap() = read_file(PAYMENT_EVENTS_PATH)?;
+    }
+
+    if metadata(path(SCREENING_PATH)).is_ok() {
+        *lock_list(&store().screening_log).unwrap() = read_file(SCREENING_PATH)?;
+    }
+
+    if metadata(path(REVIEWS_PATH)).is_ok() {
+        *lock_list(&store().review_list).unwrap() = read_file(REVIEWS_PATH)?;
+    }
+
+    if metadata(path(PROMO_CODES_PATH)).is_ok() {
+        *lock_list(&store().promo_code_list).unwrap() = read_file(PROMO_CODES_PATH)?;
+    }
+
+    if metadata(path(ACCOUNTS_PATH)).is_ok() {
+        *lock_list(&store().account_list).unwrap() = read_file(ACCOUNTS_PATH)?;
+    }
+
+    if metadata(path(SNAPSHOT_PATH)).is_err() {
+        return Ok(());
+    }
+
+    let snapshot: HashMap<u32, RoomBooking> =
+        snapshot::decode_bookings(SNAPSHOT_PATH, &read_content(SNAPSHOT_PATH)?)?;
+
+    // Modification times aren't included in the snapshot, so use the time it was last saved.
+    let saved_at: SystemTime = metadata(path(SNAPSHOT_PATH))?.modified()?;
+    *lock_list(&store().modified_list).unwrap() =
+        snapshot.keys().map(|id| (*id, saved_at)).collect();
+
+    if metadata(path(CHANGES_PATH)).is_ok() {
+        *lock_list(&store().change_log).unwrap() = read_file(CHANGES_PATH)?;
+    }
+
+    // Files saved before the highest booking id was saved fall back to the ids still recorded.
+    let saved_id: u32 = match metadata(path(BOOKING_IDS_PATH)) {
+        Ok(_) => read_file(BOOKING_IDS_PATH)?,
+        Err(_) => u32::MIN,
+    };
+    let recorded_id: u32 = lock_list(&store().change_log)
+        .unwrap()
+        .iter()
+        .filter_map(|change| change.booking.booking_id)
+        .chain(lock_list(&store().cdc_log).unwrap().iter().filter_map(
+            |record| match &record.data {
+                CdcData::Booking(booking) => booking.booking_id,
+                _ => None,
+            },
+        ))
+        .fold(saved_id, |a, b| a.max(b));
+    *lock_list(&store().last_booking_id).unwrap() =
+        snapshot.keys().fold(recorded_id, |a, b| a.max(*b));
+
+    *lock_list(&store().booking_list).unwrap() = snapshot;
+    return Ok(());
+}
+
+/// Reads the content of a snapshot file, in the directory of the store in use.
+fn read_content(name: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
     let mut file_content = Vec::new();
-    let mut file: File = File::open(SNAPSHOT_PATH)?;
+    let mut file: File = File::open(path(name))?;
     file.read_to_end(&mut file_content)?;
+    return Ok(file_content);
+}
+
+/// Reads and deserializes binary data from a snapshot file, in the directory of the store in use.
+/// Returns an error if the file was not saved in the current layout version.
+fn read_file<T: DeserializeOwned>(name: &str) -> Result<T, Box<dyn std::error::Error>> {
+    return snapshot::decode(name, &read_content(name)?);
+}
 
-    let snapshot: HashMap<u32, RoomBooking> = bincode::deserialize(&file_content)
-        .map_err(|error| Box::new(error) as Box<dyn std::error::Error>)?;
+/// Serializes data to binary, preceded by the current layout version, and writes it to a snapshot
+/// file, in the directory of the store in use, replacing any existing content. Every snapshot is
+/// saved after the stored data changes, so any cached responses are discarded.
+fn write_file<T: Serialize>(name: &str, data: &T) -> bool {
+    store().responses.invalidate();
+    let started: Instant = Instant::now();
 
-    *BOOKING_LIST.lock().unwrap() = snapshot;
-    return Ok(());
+    let written: Option<usize> = match snapshot::encode(data) {
+        Ok(content) => match File::create(path(name)) {
+            Ok(mut file) => file.write_all(&content).is_ok().then_some(content.len()),
+            Err(_) => None,
+        },
+        Err(_) => None,
+    };
+
+    store().metrics.record_snapshot(written, started.elapsed());
+    return written.is_some();
+}
+
+/// Returns the work done by the storage layer since the server started, such as the time spent
+/// waiting for locks, the number of bookings scanned, and the size and duration of snapshot
+/// writes.
+///
+/// # Examples
+///
+/// ```
+/// let metrics = storage::metrics();
+/// ```
+pub fn metrics() -> StorageMetrics {
+    let bookings: usize = match lock_list(&store().booking_list) {
+        Ok(booking_list) => booking_list.len(),
+        Err(_) => 0,
+    };
+
+    return store().metrics.snapshot(bookings);
+}
+
+/// Returns the response cached under a key, computing and caching it for the configured time if
+/// it is not cached or has expired. Cached responses are discarded whenever the stored data
+/// changes, so only responses computed from the stored data should be cached.
+///
+/// # Arguments
+///
+/// * `key` - The key the response is cached under, naming the endpoint and its parameters
+/// * `compute` - Computes the response if it is not cached
+///
+/// # Examples
+///
+/// ```
+/// let report = storage::cached(&format!("cancellations:{:?}", filter), || report(&filter));
+/// ```
+pub fn cached<T: Clone + Send + Sync + 'static>(key: &str, compute: impl FnOnce() -> T) -> T {
+    let ttl: std::time::Duration =
+        std::time::Duration::from_secs(config::hotel().response_cache_seconds);
+    return store().responses.get_or_compute(key, ttl, compute);
+}
+
+/// Discards every cached response, so each is computed again when next requested, e.g. after the
+/// hotel settings the responses were computed from are reloaded.
+///
+/// # Examples
+///
+/// ```
+/// storage::discard_cached();
+/// ```
+pub fn discard_cached() {
+    store().responses.invalidate();
+}
+
+/// Records the current time as the last modification time of a booking, setting the booking's
+/// ```updated_at``` time. The booking must have a booking id.
+fn touch(booking: &mut RoomBooking) {
+    let now: DateTime<Utc> = clock::now();
+    booking.updated_at = Some(now.to_rfc3339_opts(SecondsFormat::Secs, true));
+
+    if let (Some(booking_id), Ok(mut modified_list)) =
+        (booking.booking_id, lock_list(&store().modified_list))
+    {
+        modified_list.insert(booking_id, SystemTime::from(now));
+    }
+}
+
+/// Fetch the time a booking was last modified.
+///
+/// # Arguments
+///
+/// * `booking_id` - The booking id of the booking.
+///
+/// # Examples
+///
+/// ```
+/// modified = last_modified(1);
+/// ```
+pub fn last_modified(booking_id: u32) -> Option<SystemTime> {
+    return match lock_list(&store().modified_list) {
+        Ok(modified_list) => modified_list.get(&booking_id).cloned(),
+        Err(_) => None,
+    };
+}
+
+/// Fetch the time any booking was last modified.
+///
+/// # Examples
+///
+/// ```
+/// modified = last_modified_any();
+/// ```
+pub fn last_modified_any() -> Option<SystemTime> {
+    return match lock_list(&store().modified_list) {
+        Ok(modified_list) => modified_list.values().max().cloned(),
+        Err(_) => None,
+    };
+}
+
+/// Saves a snapshot of every booking immediately. Bookings are saved whenever they change, so this
+/// is only needed to force a save, e.g. when measuring snapshot performance. Returns false if the
+/// snapshot could not be written.
+///
+/// # Examples
+///
+/// ```
+/// saved = save_bookings();
+/// ```
+pub fn save_bookings() -> bool {
+    return match lock_list(&store().booking_list) {
+        Ok(booking_list) => save_snapshot(&booking_list),
+        Err(_) => false,
+    };
 }
 
-/// Saves a snapshot of the ```BOOKING_LIST``` HashMap to the path defined by ```SNAPSHOT_PATH```.
+/// Saves a snapshot of the stored bookings to the path defined by ```SNAPSHOT_PATH```, and the
+/// highest booking id assigned to the path defined by ```BOOKING_IDS_PATH```.
 /// Data is converted to binary for improved storage efficiency.
 fn save_snapshot(booking_list: &HashMap<u32, RoomBooking>) -> bool {
-    let snapshot: Vec<u8> = bincode::serialize(&booking_list).unwrap_or_else(|_| {
-        return Vec::new();
-    });
+    let last_booking_id: u32 = match lock_list(&store().last_booking_id) {
+        Ok(last_booking_id) => *last_booking_id,
+        Err(_) => return false,
+    };
 
-    let mut file = match File::create(SNAPSHOT_PATH) {
-        Ok(file) => file,
-        Err(_) => {
-            return false;
-        }
+    return write_file(BOOKING_IDS_PATH, &last_booking_id)
+        && write_file(SNAPSHOT_PATH, booking_list);
+}
+
+/// Assigns the next booking id, above every id assigned before, including those of bookings which
+/// have since been removed.
+///
+/// # Arguments
+///
+/// * `booking_list` - The stored bookings, locked while the id is assigned
+fn next_booking_id(booking_list: &HashMap<u32, RoomBooking>) -> u32 {
+    let mut last_booking_id: std::sync::MutexGuard<'_, u32> =
+        lock_list(&store().last_booking_id).unwrap_or_else(|poisoned| poisoned.into_inner());
+    *last_booking_id = booking_list.keys().fold(*last_booking_id, |a, b| a.max(*b)) + 1;
+    return *last_booking_id;
+}
+
+/// Appends a change to the change log, assigning it the next sequence number, and saves the
+/// change log to the path defined by ```CHANGES_PATH```. The change is delivered as an event under
+/// the topic of its change type, unless the booking is a sandbox booking.
+fn record_change(change_type: ChangeType, booking: &RoomBooking) {
+    let topic: EventTopic = EventTopic::of(&change_type);
+    record_change_as(change_type, topic, booking);
+}
+
+/// Appends a change to the change log, as ```record_change```, delivering it as an event under the
+/// provided topic, e.g. so payments recorded as updates are delivered as payments.
+fn record_change_as(change_type: ChangeType, topic: EventTopic, booking: &RoomBooking) {
+    let mut change_log: std::sync::MutexGuard<'_, Vec<BookingChange>> =
+        match lock_list(&store().change_log) {
+            Ok(guard) => guard,
+            Err(_) => return,
+        };
+    let cdc_log: std::sync::MutexGuard<'_, Vec<CdcRecord>> = match lock_list(&store().cdc_log) {
+        Ok(guard) => guard,
+        Err(_) => return,
     };
 
-    match file.write_all(&snapshot) {
-        Ok(_) => return true,
-        Err(_) => return false,
+    let sequence: u64 = next_sequence(&change_log, &cdc_log);
+    let change = BookingChange {
+        sequence,
+        recorded_at: clock::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        change_type,
+        booking: booking.clone(),
     };
+
+    if config::hotel().events.is_some() && !booking.sandbox {
+        if let Ok(mut outbox) = lock_list(&store().outbox) {
+            outbox.push(OutboxEvent::new(change.clone(), topic));
+            write_file(OUTBOX_PATH, &*outbox);
+        }
+    }
+
+    change_log.push(change);
+    write_file(CHANGES_PATH, &*change_log);
 }
 
-/// Create a new booking.
+/// Returns the sequence number to assign to the next change, which follows the last change made
+/// to any record.
+fn next_sequence(change_log: &[BookingChange], cdc_log: &[CdcRecord]) -> u64 {
+    let last_change: u64 = change_log.last().map_or(0, |change| change.sequence);
+    let last_record: u64 = cdc_log.last().map_or(0, |record| record.sequence);
+    return last_change.max(last_record) + 1;
+}
+
+/// Appends a change made to a record other than a booking to the change log of other records,
+/// assigning it the next sequence number, and saves the log to the path defined by
+/// ```CDC_PATH```.
 ///
 /// # Arguments
 ///
-/// * `booking` - A RoomBooking object containing details of the booking. ```booking_id``` and
-/// ```status``` should be excluded as these are added automatically.
+/// * `operation` - The kind of change made
+/// * `key` - The key of the record, e.g. the code of a promo code
+/// * `data` - The record after the change, or before it was deleted
+fn record_cdc(operation: CdcOperation, key: String, data: CdcData) {
+    // Locked in the same order as record_change_as, which also assigns sequence numbers.
+    let change_log: std::sync::MutexGuard<'_, Vec<BookingChange>> =
+        match lock_list(&store().change_log) {
+            Ok(guard) => guard,
+            Err(_) => return,
+        };
+    let mut cdc_log: std::sync::MutexGuard<'_, Vec<CdcRecord>> = match lock_list(&store().cdc_log) {
+        Ok(guard) => guard,
+        Err(_) => return,
+    };
+
+    let sequence: u64 = next_sequence(&change_log, &cdc_log);
+    cdc_log.push(CdcRecord {
+        sequence,
+        recorded_at: clock::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        operation,
+        key,
+        data,
+    });
+    write_file(CDC_PATH, &*cdc_log);
+}
+
+/// Fetch the events waiting in the outbox to be delivered, in the order they were recorded.
+///
+/// # Arguments
+///
+/// * `limit` - The maximum number of events to return, if any
 ///
 /// # Examples
 ///
 /// ```
-/// booking = RoomBooking {
-///     customer_id: 1,
-///     room_type_id: 1,
-///     check_in_date: "2020-01-01".to_string(),
-///     check_out-date: "2020-01-08".to_string()
-/// }
+/// events = fetch_outbox(Some(100));
+/// ```
+pub fn fetch_outbox(limit: Option<usize>) -> Vec<OutboxEvent> {
+    return match lock_list(&store().outbox) {
+        Ok(outbox) => outbox
+            .iter()
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+}
+
+/// Removes a delivered event from the outbox. Returns false if the event is not in the outbox.
+///
+/// # Arguments
+///
+/// * `sequence` - The sequence number of the event's booking change
+///
+/// # Examples
 ///
-/// create(booking);
 /// ```
-pub fn create(mut booking: RoomBooking) -> Result<RoomBooking, ()> {
-    if booking.booking_id != None || booking.status != None {
-        return Err(());
+/// complete_event(1);
+/// ```
+pub fn complete_event(sequence: u64) -> bool {
+    let mut outbox: std::sync::MutexGuard<'_, Vec<OutboxEvent>> = match lock_list(&store().outbox) {
+        Ok(guard) => guard,
+        Err(_) => return false,
+    };
+
+    let original_length: usize = outbox.len();
+    outbox.retain(|event| event.change.sequence != sequence);
+    if outbox.len() == original_length {
+        return false;
     }
 
-    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
-        match BOOKING_LIST.lock() {
-            Ok(guard) => guard,
-            Err(_) => return Err(()),
-        };
+    write_file(OUTBOX_PATH, &*outbox);
+    return true;
+}
 
-    let max_id = booking_list.keys().fold(std::u32::MIN, |a, b| a.max(*b));
-    let next_id = max_id + 1;
-    booking.set_booking_id(next_id);
-    booking.set_status(BookingStatus::Confirmed);
-    booking_list.insert(next_id, booking.clone());
-    save_snapshot(&*booking_list);
-    return Ok(booking);
+/// Records that an event has been delivered to a webhook, so the webhook is skipped if delivery to
+/// another webhook fails and is retried. Returns false if the event is not in the outbox.
+///
+/// # Arguments
+///
+/// * `sequence` - The sequence number of the event's booking change
+/// * `url` - The URL of the webhook the event was delivered to
+///
+/// # Examples
+///
+/// ```
+/// mark_delivered(1, "http://events.example.com/bookings");
+/// ```
+pub fn mark_delivered(sequence: u64, url: &str) -> bool {
+    let mut outbox: std::sync::MutexGuard<'_, Vec<OutboxEvent>> = match lock_list(&store().outbox) {
+        Ok(guard) => guard,
+        Err(_) => return false,
+    };
+
+    let event: &mut OutboxEvent = match outbox
+        .iter_mut()
+        .find(|event| event.change.sequence == sequence)
+    {
+        Some(event) => event,
+        None => return false,
+    };
+
+    if !event.delivered_to.iter().any(|delivered| delivered == url) {
+        event.delivered_to.push(url.to_string());
+    }
+
+    write_file(OUTBOX_PATH, &*outbox);
+    return true;
 }
 
-/// Update the status of a booking.
+/// Records a failed attempt to deliver an event, keeping it in the outbox to be retried. Once the
+/// event has failed the maximum number of attempts, it is moved to the dead-letter store instead.
+/// Returns true if the event was moved to the dead-letter store.
 ///
 /// # Arguments
 ///
-/// * `booking_id` - The id of the booking to update
-/// * `status` - The BookingStatus enum to be applied to the booking
+/// * `sequence` - The sequence number of the event's booking change
+/// * `error` - A description of the failure
+/// * `max_attempts` - The number of failed attempts after which the event is moved to the
+///   dead-letter store
 ///
 /// # Examples
 ///
 /// ```
-/// status(1, BookingStatus::Complete);
+/// fail_event(1, "The webhook responded with 503", 10);
 /// ```
-pub fn status(booking_id: u32, status: BookingStatus) -> bool {
-    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
-        match BOOKING_LIST.lock() {
+pub fn fail_event(sequence: u64, error: &str, max_attempts: u32) -> bool {
+    let mut outbox: std::sync::MutexGuard<'_, Vec<OutboxEvent>> = match lock_list(&store().outbox) {
+        Ok(guard) => guard,
+        Err(_) => return false,
+    };
+
+    let index: usize = match outbox
+        .iter()
+        .position(|event| event.change.sequence == sequence)
+    {
+        Some(index) => index,
+        None => return false,
+    };
+
+    outbox[index].attempts += 1;
+    outbox[index].last_error = Some(error.to_string());
+
+    if outbox[index].attempts < max_attempts {
+        write_file(OUTBOX_PATH, &*outbox);
+        return false;
+    }
+
+    let mut dead_letters: std::sync::MutexGuard<'_, Vec<OutboxEvent>> =
+        match lock_list(&store().dead_letters) {
             Ok(guard) => guard,
             Err(_) => return false,
         };
 
-    let booking: &mut RoomBooking = match booking_list.get_mut(&booking_id) {
-        Some(booking) => booking,
-        None => return false,
+    let mut event: OutboxEvent = outbox.remove(index);
+    event.dead_lettered_at = Some(clock::now().to_rfc3339_opts(SecondsFormat::Secs, true));
+    dead_letters.push(event);
+    write_file(DEAD_LETTERS_PATH, &*dead_letters);
+    write_file(OUTBOX_PATH, &*outbox);
+    return true;
+}
+
+/// Fetch the events in the dead-letter store, in the order they were recorded.
+///
+/// # Examples
+///
+/// ```
+/// events = fetch_dead_letters();
+/// ```
+pub fn fetch_dead_letters() -> Vec<OutboxEvent> {
+    return match lock_list(&store().dead_letters) {
+        Ok(dead_letters) => dead_letters.clone(),
+        Err(_) => Vec::new(),
+    };
+}
+
+/// Fetch an event from the dead-letter store.
+///
+/// # Arguments
+///
+/// * `sequence` - The sequence number of the event's booking change
+///
+/// # Examples
+///
+/// ```
+/// event = fetch_dead_letter(1);
+/// ```
+pub fn fetch_dead_letter(sequence: u64) -> Option<OutboxEvent> {
+    return match lock_list(&store().dead_letters) {
+        Ok(dead_letters) => dead_letters
+            .iter()
+            .find(|event| event.change.sequence == sequence)
+            .cloned(),
+        Err(_) => None,
     };
+}
+
+/// Moves an event from the dead-letter store back to the outbox, to be delivered again. The
+/// event's failed attempts are reset, and it is delivered in order with the events in the outbox.
+/// Returns the event, or None if it is not in the dead-letter store.
+///
+/// # Arguments
+///
+/// * `sequence` - The sequence number of the event's booking change
+///
+/// # Examples
+///
+/// ```
+/// event = redeliver_dead_letter(1);
+/// ```
+pub fn redeliver_dead_letter(sequence: u64) -> Option<OutboxEvent> {
+    let mut outbox: std::sync::MutexGuard<'_, Vec<OutboxEvent>> =
+        lock_list(&store().outbox).ok()?;
+    let mut dead_letters: std::sync::MutexGuard<'_, Vec<OutboxEvent>> =
+        lock_list(&store().dead_letters).ok()?;
+
+    let index: usize = dead_letters
+        .iter()
+        .position(|event| event.change.sequence == sequence)?;
+
+    let mut event: OutboxEvent = dead_letters.remove(index);
+    event.attempts = 0;
+    event.dead_lettered_at = None;
+
+    let position: usize = outbox.partition_point(|other| other.change.sequence < sequence);
+    outbox.insert(position, event.clone());
+    write_file(OUTBOX_PATH, &*outbox);
+    write_file(DEAD_LETTERS_PATH, &*dead_letters);
+    return Some(event);
+}
+
+/// Stores an event received from the payment provider, to be applied to its booking in the
+/// background. Returns false if an event with the same id has already been received, or None if
+/// storage is unavailable.
+///
+/// # Arguments
+///
+/// * `event` - The payment event
+///
+/// # Examples
+///
+/// ```
+/// received = receive_payment_event(event);
+/// ```
+pub fn receive_payment_event(mut event: PaymentEvent) -> Option<bool> {
+    let mut payment_events: std::sync::MutexGuard<'_, Vec<PaymentEvent>> =
+        lock_list(&store().payment_events).ok()?;
+
+    if payment_events
+        .iter()
+        .any(|other| other.event_id == event.event_id)
+    {
+        return Some(false);
+    }
+
+    event.received_at = Some(clock::now().to_rfc3339_opts(SecondsFormat::Secs, true));
+    event.processed_at = None;
+    event.error = None;
+    payment_events.push(event);
+    write_file(PAYMENT_EVENTS_PATH, &*payment_events);
+    return Some(true);
+}
+
+/// Fetch the payment events waiting to be applied to their booking, in the order they were
+/// received.
+///
+/// # Examples
+///
+/// ```
+/// events = fetch_pending_payment_events();
+/// ```
+pub fn fetch_pending_payment_events() -> Vec<PaymentEvent> {
+    return match lock_list(&store().payment_events) {
+        Ok(payment_events) => payment_events
+            .iter()
+            .filter(|event| event.processed_at.is_none())
+            .cloned()
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+}
+
+/// Marks a payment event as processed, recording why it could not be applied to its booking, if
+/// it could not. Returns false if the event has not been received.
+///
+/// # Arguments
+///
+/// * `event_id` - The payment provider's id for the event
+/// * `error` - A description of why the event could not be applied, if it could not
+///
+/// # Examples
+///
+/// ```
+/// complete_payment_event("evt_1", None);
+/// ```
+pub fn complete_payment_event(event_id: &str, error: Option<String>) -> bool {
+    let mut payment_events: std::sync::MutexGuard<'_, Vec<PaymentEvent>> =
+        match lock_list(&store().payment_events) {
+            Ok(guard) => guard,
+            Err(_) => return false,
+        };
+
+    let event: &mut PaymentEvent = match payment_events
+        .iter_mut()
+        .find(|event| event.event_id == event_id)
+    {
+        Some(event) => event,
+        None => return false,
+    };
+
+    event.processed_at = Some(clock::now().to_rfc3339_opts(SecondsFormat::Secs, true));
+    event.error = error;
+    write_file(PAYMENT_EVENTS_PATH, &*payment_events);
+    return true;
+}
+
+/// Create a new booking. Returns the booking as stored, or an error describing why it could not be
+/// created. The booking is screened against the configured screening rules, and is held pending
+/// review or rejected if it matches a rule with that action.
+///
+/// # Arguments
+///
+/// * `booking` - A RoomBooking object containing details of the booking. ```booking_id``` and
+///   ```status``` should be excluded as these are added automatically.
+/// * `allow_duplicate` - Whether to create the booking even if it is likely a duplicate of a
+///   booking created within the configured duplicate window
+///
+/// # Examples
+///
+/// ```
+/// booking = RoomBooking {
+///     customer_id: 1,
+///     room_type_id: 1,
+///     check_in_date: "2020-01-01".to_string(),
+///     check_out-date: "2020-01-08".to_string()
+/// }
+///
+/// create(booking, false);
+/// ```
+pub fn create(mut booking: RoomBooking, allow_duplicate: bool) -> Result<RoomBooking, CreateError> {
+    check_new_fields(&booking)?;
+
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(CreateError::Unavailable),
+        };
+
+    let findings: Vec<Finding> = admit(&booking_list, &[], &booking, allow_duplicate)?;
+    if findings
+        .iter()
+        .any(|finding| finding.action == ScreeningAction::Reject)
+    {
+        record_screening(&findings, &booking);
+        return Err(CreateError::Rejected);
+    }
+
+    if let Err(error) = redeem_promo_code(std::slice::from_mut(&mut booking)) {
+        return Err(CreateError::PromoCode(error));
+    }
+
+    let next_id: u32 = next_booking_id(&booking_list);
+    booking.set_booking_id(next_id);
+    set_new_status(&mut booking, &findings);
+    booking.billing_milestones = pricing::billing_milestones(&booking);
+    booking.rate_plans = pricing::rate_plans(&booking);
+    booking.pricing = pricing::record(&booking);
+    addons::reprice(&mut booking.addons);
+    touch(&mut booking);
+    booking.created_at = booking.updated_at.clone();
+    booking_list.insert(next_id, booking.clone());
+    record_screening(&findings, &booking);
+    record_change(ChangeType::Created, &booking);
+    save_snapshot(&booking_list);
+    return Ok(booking);
+}
+
+/// Checks whether a new booking would be created, running the same checks as ```create```, without
+/// storing the booking, redeeming its promo code or recording its screening. Returns the booking
+/// as it would be created, without a booking id, or the error it would be rejected with.
+///
+/// # Arguments
+///
+/// * `booking` - The booking to check
+/// * `allow_duplicate` - Whether the booking would be created even if it is likely a duplicate of
+///   a booking created within the configured duplicate window
+///
+/// # Examples
+///
+/// ```
+/// let booking = check_create(booking, false)?;
+/// ```
+pub fn check_create(
+    mut booking: RoomBooking,
+    allow_duplicate: bool,
+) -> Result<RoomBooking, CreateError> {
+    check_new_fields(&booking)?;
+
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(CreateError::Unavailable),
+        };
+
+    let findings: Vec<Finding> = admit(&booking_list, &[], &booking, allow_duplicate)?;
+    if findings
+        .iter()
+        .any(|finding| finding.action == ScreeningAction::Reject)
+    {
+        return Err(CreateError::Rejected);
+    }
+
+    booking.discount = check_promo_code(&booking).map_err(CreateError::PromoCode)?;
+    booking.promo_code = booking.promo_code.map(|code| code.to_uppercase());
+    set_new_status(&mut booking, &findings);
+    booking.billing_milestones = pricing::billing_milestones(&booking);
+    booking.rate_plans = pricing::rate_plans(&booking);
+    addons::reprice(&mut booking.addons);
+    return Ok(booking);
+}
+
+/// Checks a new booking does not set fields assigned by the service, and passes validation.
+/// Returns a description of each problem found.
+///
+/// # Arguments
+///
+/// * `booking` - The booking being created
+fn check_new_fields(booking: &RoomBooking) -> Result<(), CreateError> {
+    let mut errors: Vec<String> = Vec::new();
+    for (field, is_set) in [
+        ("bookingId", booking.booking_id.is_some()),
+        ("status", booking.status.is_some()),
+        ("seriesId", booking.series_id.is_some()),
+        ("payments", !booking.payments.is_empty()),
+        ("refunds", !booking.refunds.is_empty()),
+        ("createdAt", booking.created_at.is_some()),
+        ("updatedAt", booking.updated_at.is_some()),
+        ("cancelledAt", booking.cancelled_at.is_some()),
+    ] {
+        if is_set {
+            errors.push(format!("{} must not be set", field));
+        }
+    }
+
+    errors.extend(booking.validate());
+    return match errors.is_empty() {
+        true => Ok(()),
+        false => Err(CreateError::Invalid(errors)),
+    };
+}
+
+/// Checks a new booking is not a likely duplicate, unless duplicates are allowed, that a room is
+/// available for it, and screens it. Returns the screening rules the booking matched.
+///
+/// # Arguments
+///
+/// * `booking_list` - The stored bookings to check against
+/// * `pending` - Bookings being created alongside this one, which are not yet stored
+/// * `booking` - The booking being created
+/// * `allow_duplicate` - Whether likely duplicates are allowed
+fn admit(
+    booking_list: &HashMap<u32, RoomBooking>,
+    pending: &[RoomBooking],
+    booking: &RoomBooking,
+    allow_duplicate: bool,
+) -> Result<Vec<Finding>, CreateError> {
+    if !allow_duplicate {
+        if let Some(booking_id) = find_duplicate(booking_list, booking) {
+            return Err(CreateError::Duplicate(booking_id));
+        }
+    }
+
+    if !room_available(booking_list, pending, booking) {
+        return Err(CreateError::NoRoomAvailable);
+    }
+
+    return Ok(screening::screen(booking, booking_list));
+}
+
+/// Sets the status of a new booking, holding it pending review if it matched a screening rule with
+/// the Hold action.
+///
+/// # Arguments
+///
+/// * `booking` - The booking being created
+/// * `findings` - The screening rules the booking matched
+fn set_new_status(booking: &mut RoomBooking, findings: &[Finding]) {
+    if findings
+        .iter()
+        .any(|finding| finding.action == ScreeningAction::Hold)
+    {
+        booking.set_status(BookingStatus::PendingReview);
+    } else {
+        set_initial_status(booking);
+    }
+}
+
+/// Records the screening rules a new booking matched, and the action taken for each, for audit.
+/// A booking held pending review is remembered until it is approved.
+///
+/// # Arguments
+///
+/// * `findings` - The rules the booking matched
+/// * `booking` - The booking screened, without a booking id if it was rejected
+fn record_screening(findings: &[Finding], booking: &RoomBooking) {
+    if findings.is_empty() {
+        return;
+    }
+
+    let mut screening_log: std::sync::MutexGuard<'_, Vec<ScreeningDecision>> =
+        match lock_list(&store().screening_log) {
+            Ok(guard) => guard,
+            Err(_) => return,
+        };
+
+    let decided_at: String = clock::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    screening_log.extend(findings.iter().map(|finding| ScreeningDecision {
+        booking_id: booking.booking_id,
+        customer_id: booking.customer_id,
+        rule: finding.rule.clone(),
+        action: finding.action,
+        reason: finding.reason.clone(),
+        decided_at: decided_at.clone(),
+    }));
+    write_file(SCREENING_PATH, &*screening_log);
+    drop(screening_log);
+
+    if booking.status == Some(BookingStatus::PendingReview) {
+        if let Some(booking_id) = booking.booking_id {
+            update_reviews(|review_list| review_list.insert(booking_id));
+        }
+    }
+}
+
+/// Updates the bookings held for review, saving them to the path defined by ```REVIEWS_PATH``` if
+/// they changed.
+///
+/// # Arguments
+///
+/// * `update` - Updates the ids of the bookings held for review, returning whether they changed
+fn update_reviews<F: FnOnce(&mut HashSet<u32>) -> bool>(update: F) {
+    if let Ok(mut review_list) = lock_list(&store().review_list) {
+        if update(&mut review_list) {
+            write_file(REVIEWS_PATH, &*review_list);
+        }
+    }
+}
+
+/// Checks whether a booking was held for review by screening, and has not been approved.
+///
+/// # Arguments
+///
+/// * `booking_id` - The id of the booking
+fn awaiting_review(booking_id: u32) -> bool {
+    return match lock_list(&store().review_list) {
+        Ok(review_list) => review_list.contains(&booking_id),
+        Err(_) => true,
+    };
+}
+
+/// Fetch the screening decisions made for new bookings, in the order they were made.
+///
+/// # Examples
+///
+/// ```
+/// decisions = fetch_screening_decisions();
+/// ```
+pub fn fetch_screening_decisions() -> Vec<ScreeningDecision> {
+    return match lock_list(&store().screening_log) {
+        Ok(screening_log) => screening_log.clone(),
+        Err(_) => Vec::new(),
+    };
+}
+
+/// Finds a booking created within the configured duplicate window which the provided booking is
+/// likely a duplicate of, i.e. a booking still holding a room, made by the same customer, for the
+/// same room type and overlapping dates. Returns the id of the existing booking, if any.
+///
+/// # Arguments
+///
+/// * `booking_list` - The stored bookings to check against
+/// * `booking` - The booking being created
+fn find_duplicate(booking_list: &HashMap<u32, RoomBooking>, booking: &RoomBooking) -> Option<u32> {
+    let window: u32 = config::hotel().duplicate_window_minutes;
+    if window == 0 {
+        return None;
+    }
+
+    let since: String = (clock::now() - Duration::minutes(window as i64))
+        .to_rfc3339_opts(SecondsFormat::Secs, true);
+    let change_log: std::sync::MutexGuard<'_, Vec<BookingChange>> =
+        lock_list(&store().change_log).ok()?;
+
+    // The change log is in the order changes were made, so only its most recent entries are read.
+    return change_log
+        .iter()
+        .rev()
+        .take_while(|change| change.recorded_at >= since)
+        .filter(|change| change.change_type == ChangeType::Created)
+        .filter_map(|change| booking_list.get(&change.booking.booking_id?))
+        .find(|existing| {
+            existing.holds_room()
+                && existing.customer_id == booking.customer_id
+                && existing.room_type_id == booking.room_type_id
+                && (existing.check_in_date == booking.check_in_date
+                    || (existing.check_in_date < booking.check_out_date
+                        && booking.check_in_date < existing.check_out_date))
+        })
+        .and_then(|existing| existing.booking_id);
+}
+
+/// Sets the status of a new booking. Bookings are pending payment if the hotel requires a deposit,
+/// with the deposit due within the hotel's payment window, and are confirmed otherwise.
+fn set_initial_status(booking: &mut RoomBooking) {
+    let hotel = config::hotel();
+
+    if !hotel.deposit_required {
+        booking.set_status(BookingStatus::Confirmed);
+        return;
+    }
+
+    booking.set_status(BookingStatus::PendingPayment);
+    booking.payment_due_by = Some(payment_due_by());
+}
+
+/// Returns the time a booking moving to pending payment now must be paid by, at the end of the
+/// hotel's payment window (RFC 3339).
+fn payment_due_by() -> String {
+    let window: i64 = config::hotel().payment_window_hours as i64;
+    return (clock::now() + Duration::hours(window)).to_rfc3339_opts(SecondsFormat::Secs, true);
+}
+
+/// Redeems the promo code entered for each booking, setting the discount it gives. Either the
+/// code is redeemed for every booking, or none are. Returns a description of the problem if a code
+/// does not exist, or may not be redeemed for a booking.
+///
+/// # Arguments
+///
+/// * `bookings` - The bookings being created
+fn redeem_promo_code(bookings: &mut [RoomBooking]) -> Result<(), String> {
+    let mut promo_code_list: std::sync::MutexGuard<'_, HashMap<String, PromoCode>> =
+        match lock_list(&store().promo_code_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err("Promo code storage is unavailable".to_string()),
+        };
+
+    let today: NaiveDate = local_time::today();
+    let mut redeemed: HashMap<String, PromoCode> = HashMap::new();
+
+    for booking in bookings.iter_mut() {
+        booking.discount = None;
+        let code: String = match &booking.promo_code {
+            Some(code) => code.to_uppercase(),
+            None => continue,
+        };
+
+        let mut promo_code: PromoCode =
+            match redeemed.get(&code).or_else(|| promo_code_list.get(&code)) {
+                Some(promo_code) => promo_code.clone(),
+                None => return Err(format!("{} is not a valid promo code", code)),
+            };
+
+        promo_code.check(booking, today)?;
+        promo_code.redemptions += 1;
+        booking.promo_code = Some(code.clone());
+        booking.discount = Some(promo_code.discount.clone());
+        redeemed.insert(code, promo_code);
+    }
+
+    if !redeemed.is_empty() {
+        for (code, promo_code) in &redeemed {
+            record_cdc(
+                CdcOperation::Update,
+                code.clone(),
+                CdcData::PromoCode(promo_code.clone()),
+            );
+        }
+
+        promo_code_list.extend(redeemed);
+        write_file(PROMO_CODES_PATH, &*promo_code_list);
+    }
+
+    return Ok(());
+}
+
+/// Checks the promo code entered for a booking may be redeemed, without redeeming it. Returns the
+/// discount the code gives, None if no code was entered, or a description of the problem if the
+/// code may not be redeemed.
+///
+/// # Arguments
+///
+/// * `booking` - The booking to check
+///
+/// # Examples
+///
+/// ```
+/// booking.discount = check_promo_code(&booking)?;
+/// ```
+pub fn check_promo_code(booking: &RoomBooking) -> Result<Option<Discount>, String> {
+    let code: String = match &booking.promo_code {
+        Some(code) => code.to_uppercase(),
+        None => return Ok(None),
+    };
+
+    let promo_code: PromoCode = match fetch_promo_code(&code) {
+        Some(promo_code) => promo_code,
+        None => return Err(format!("{} is not a valid promo code", code)),
+    };
+
+    promo_code.check(booking, local_time::today())?;
+    return Ok(Some(promo_code.discount));
+}
+
+/// Create a new promo code. Codes are stored in upper case, and are matched regardless of case.
+/// Returns the promo code, or a list describing each problem found with it.
+///
+/// # Arguments
+///
+/// * `promo_code` - The promo code to create. ```redemptions``` is ignored, as new codes have not
+///   been redeemed.
+///
+/// # Examples
+///
+/// ```
+/// promo_code = create_promo_code(promo_code)?;
+/// ```
+pub fn create_promo_code(mut promo_code: PromoCode) -> Result<PromoCode, Vec<String>> {
+    let errors: Vec<String> = promo_code.validate();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut promo_code_list: std::sync::MutexGuard<'_, HashMap<String, PromoCode>> =
+        match lock_list(&store().promo_code_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(vec!["Promo code storage is unavailable".to_string()]),
+        };
+
+    promo_code.code = promo_code.code.trim().to_uppercase();
+    promo_code.redemptions = 0;
+    if promo_code_list.contains_key(&promo_code.code) {
+        return Err(vec![format!(
+            "Promo code {} already exists",
+            promo_code.code
+        )]);
+    }
+
+    promo_code_list.insert(promo_code.code.clone(), promo_code.clone());
+    write_file(PROMO_CODES_PATH, &*promo_code_list);
+    record_cdc(
+        CdcOperation::Insert,
+        promo_code.code.clone(),
+        CdcData::PromoCode(promo_code.clone()),
+    );
+    return Ok(promo_code);
+}
+
+/// Fetch every promo code, ordered by code.
+///
+/// # Examples
+///
+/// ```
+/// promo_codes = fetch_promo_codes();
+/// ```
+pub fn fetch_promo_codes() -> Vec<PromoCode> {
+    let mut promo_codes: Vec<PromoCode> = match lock_list(&store().promo_code_list) {
+        Ok(promo_code_list) => promo_code_list.values().cloned().collect(),
+        Err(_) => return Vec::new(),
+    };
+
+    promo_codes.sort_by(|a, b| a.code.cmp(&b.code));
+    return promo_codes;
+}
+
+/// Fetch the promo code with the provided code, regardless of case.
+///
+/// # Arguments
+///
+/// * `code` - The promo code
+///
+/// # Examples
+///
+/// ```
+/// promo_code = fetch_promo_code("SUMMER10");
+/// ```
+pub fn fetch_promo_code(code: &str) -> Option<PromoCode> {
+    return lock_list(&store().promo_code_list)
+        .ok()?
+        .get(&code.to_uppercase())
+        .cloned();
+}
+
+/// Delete the promo code with the provided code, regardless of case. Bookings which redeemed the
+/// code keep their discount. Returns the deleted promo code, or None if the code does not exist.
+///
+/// # Arguments
+///
+/// * `code` - The promo code
+///
+/// # Examples
+///
+/// ```
+/// let deleted = delete_promo_code("SUMMER10");
+/// ```
+pub fn delete_promo_code(code: &str) -> Option<PromoCode> {
+    let mut promo_code_list: std::sync::MutexGuard<'_, HashMap<String, PromoCode>> =
+        match lock_list(&store().promo_code_list) {
+            Ok(guard) => guard,
+            Err(_) => return None,
+        };
+
+    let removed: PromoCode = promo_code_list.remove(&code.to_uppercase())?;
+
+    write_file(PROMO_CODES_PATH, &*promo_code_list);
+    record_cdc(
+        CdcOperation::Delete,
+        removed.code.clone(),
+        CdcData::PromoCode(removed.clone()),
+    );
+    return Some(removed);
+}
+
+/// Checks the corporate account a booking is made against exists, and its rate plan includes the
+/// booking's room type. Returns a list describing each problem found, which is empty if the
+/// booking is not made against an account, or the account is valid for the booking.
+///
+/// # Arguments
+///
+/// * `booking` - The booking to check
+///
+/// # Examples
+///
+/// ```
+/// let errors = check_account(&booking);
+/// ```
+pub fn check_account(booking: &RoomBooking) -> Vec<String> {
+    let account_id: u32 = match booking.account_id {
+        Some(account_id) => account_id,
+        None => return Vec::new(),
+    };
+
+    return match fetch_account(account_id) {
+        Some(account) if account.rate_for(booking.room_type_id).is_none() => vec![format!(
+            "Room type {} is not included in the rate plan of account {}",
+            booking.room_type_id, account_id
+        )],
+        Some(_) => Vec::new(),
+        None => vec![format!("Account {} does not exist", account_id)],
+    };
+}
+
+/// Create a new corporate account. Returns the account, or a list describing each problem found
+/// with it.
+///
+/// # Arguments
+///
+/// * `account` - The account to create. ```account_id``` should be excluded, as this is added
+///   automatically.
+///
+/// # Examples
+///
+/// ```
+/// account = create_account(account)?;
+/// ```
+pub fn create_account(mut account: CorporateAccount) -> Result<CorporateAccount, Vec<String>> {
+    let mut errors: Vec<String> = account.validate();
+    if account.account_id.is_some() {
+        errors.push("accountId is assigned automatically, and must not be set".to_string());
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut account_list: std::sync::MutexGuard<'_, HashMap<u32, CorporateAccount>> =
+        match lock_list(&store().account_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(vec!["Account storage is unavailable".to_string()]),
+        };
+
+    let next_id: u32 = account_list.keys().fold(u32::MIN, |a, b| a.max(*b)) + 1;
+    account.account_id = Some(next_id);
+    account_list.insert(next_id, account.clone());
+    write_file(ACCOUNTS_PATH, &*account_list);
+    record_cdc(
+        CdcOperation::Insert,
+        next_id.to_string(),
+        CdcData::CorporateAccount(account.clone()),
+    );
+    return Ok(account);
+}
+
+/// Update the details and rate plan of a corporate account. Existing bookings are charged the
+/// updated rates. Returns the updated account, None if the account does not exist, or a list
+/// describing each problem found with the account.
+///
+/// # Arguments
+///
+/// * `account_id` - The id of the account to update
+/// * `account` - The updated account details
+///
+/// # Examples
+///
+/// ```
+/// account = update_account(1, account);
+/// ```
+pub fn update_account(
+    account_id: u32,
+    mut account: CorporateAccount,
+) -> Option<Result<CorporateAccount, Vec<String>>> {
+    let mut account_list: std::sync::MutexGuard<'_, HashMap<u32, CorporateAccount>> =
+        lock_list(&store().account_list).ok()?;
+    let stored: &mut CorporateAccount = account_list.get_mut(&account_id)?;
+
+    let errors: Vec<String> = account.validate();
+    if !errors.is_empty() {
+        return Some(Err(errors));
+    }
+
+    account.account_id = Some(account_id);
+    *stored = account.clone();
+    write_file(ACCOUNTS_PATH, &*account_list);
+    record_cdc(
+        CdcOperation::Update,
+        account_id.to_string(),
+        CdcData::CorporateAccount(account.clone()),
+    );
+    return Some(Ok(account));
+}
+
+/// Fetch every corporate account, ordered by account id.
+///
+/// # Examples
+///
+/// ```
+/// accounts = fetch_accounts();
+/// ```
+pub fn fetch_accounts() -> Vec<CorporateAccount> {
+    let mut accounts: Vec<CorporateAccount> = match lock_list(&store().account_list) {
+        Ok(account_list) => account_list.values().cloned().collect(),
+        Err(_) => return Vec::new(),
+    };
+
+    accounts.sort_by_key(|account| account.account_id);
+    return accounts;
+}
+
+/// Fetch the corporate account with the provided account id.
+///
+/// # Arguments
+///
+/// * `account_id` - The id of the account
+///
+/// # Examples
+///
+/// ```
+/// account = fetch_account(1);
+/// ```
+pub fn fetch_account(account_id: u32) -> Option<CorporateAccount> {
+    return lock_list(&store().account_list)
+        .ok()?
+        .get(&account_id)
+        .cloned();
+}
+
+/// Checks a booking against the sell restrictions, such as blackout periods. Returns a list
+/// describing each restriction which prevents the booking from being made, which is empty if the
+/// booking is not restricted.
+///
+/// # Arguments
+///
+/// * `booking` - The booking to check
+///
+/// # Examples
+///
+/// ```
+/// errors = check_restrictions(&booking);
+/// ```
+pub fn check_restrictions(booking: &RoomBooking) -> Vec<String> {
+    return fetch_restrictions()
+        .iter()
+        .filter_map(|restriction| restriction.check(booking))
+        .collect();
+}
+
+/// Create a new sell restriction.
+///
+/// # Arguments
+///
+/// * `restriction` - The restriction. ```restriction_id``` should be excluded, as it is added
+///   automatically.
+///
+/// # Examples
+///
+/// ```
+/// restriction = create_restriction(restriction)?;
+/// ```
+pub fn create_restriction(
+    mut restriction: SellRestriction,
+) -> Result<SellRestriction, Vec<String>> {
+    let errors: Vec<String> = restriction.validate();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut restriction_list: std::sync::MutexGuard<'_, HashMap<u32, SellRestriction>> =
+        match lock_list(&store().restriction_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(vec!["Restriction storage is unavailable".to_string()]),
+        };
+
+    let next_id: u32 = restriction_list.keys().fold(u32::MIN, |a, b| a.max(*b)) + 1;
+    restriction.restriction_id = Some(next_id);
+    restriction_list.insert(next_id, restriction.clone());
+    write_file(RESTRICTIONS_PATH, &*restriction_list);
+    record_cdc(
+        CdcOperation::Insert,
+        next_id.to_string(),
+        CdcData::SellRestriction(restriction.clone()),
+    );
+    return Ok(restriction);
+}
+
+/// Fetch every sell restriction, ordered by start date.
+///
+/// # Examples
+///
+/// ```
+/// restrictions = fetch_restrictions();
+/// ```
+pub fn fetch_restrictions() -> Vec<SellRestriction> {
+    let mut restrictions: Vec<SellRestriction> = match lock_list(&store().restriction_list) {
+        Ok(restriction_list) => restriction_list.values().cloned().collect(),
+        Err(_) => return Vec::new(),
+    };
+
+    restrictions
+        .sort_by(|a, b| (&a.start_date, a.restriction_id).cmp(&(&b.start_date, b.restriction_id)));
+    return restrictions;
+}
+
+/// Fetch the sell restriction with the provided id.
+///
+/// # Arguments
+///
+/// * `restriction_id` - The id of the restriction
+///
+/// # Examples
+///
+/// ```
+/// restriction = fetch_restriction(1);
+/// ```
+pub fn fetch_restriction(restriction_id: u32) -> Option<SellRestriction> {
+    return lock_list(&store().restriction_list)
+        .ok()?
+        .get(&restriction_id)
+        .cloned();
+}
+
+/// Delete a sell restriction. Bookings made before the restriction was deleted are unaffected.
+/// Returns the deleted restriction, or None if the restriction does not exist.
+///
+/// # Arguments
+///
+/// * `restriction_id` - The id of the restriction
+///
+/// # Examples
+///
+/// ```
+/// delete_restriction(1);
+/// ```
+pub fn delete_restriction(restriction_id: u32) -> Option<SellRestriction> {
+    let mut restriction_list: std::sync::MutexGuard<'_, HashMap<u32, SellRestriction>> =
+        match lock_list(&store().restriction_list) {
+            Ok(guard) => guard,
+            Err(_) => return None,
+        };
+
+    let removed: SellRestriction = restriction_list.remove(&restriction_id)?;
+
+    write_file(RESTRICTIONS_PATH, &*restriction_list);
+    record_cdc(
+        CdcOperation::Delete,
+        restriction_id.to_string(),
+        CdcData::SellRestriction(removed.clone()),
+    );
+    return Some(removed);
+}
+
+/// Set the exchange rate used for a currency, instead of the rate from the exchange rate source,
+/// replacing any earlier override for the currency.
+///
+/// # Arguments
+///
+/// * `rate` - The exchange rate override
+///
+/// # Examples
+///
+/// ```
+/// rate = set_exchange_rate(rate)?;
+/// ```
+pub fn set_exchange_rate(
+    mut rate: ExchangeRateOverride,
+) -> Result<ExchangeRateOverride, Vec<String>> {
+    let errors: Vec<String> = rate.validate();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut exchange_rate_list: std::sync::MutexGuard<'_, HashMap<String, ExchangeRateOverride>> =
+        match lock_list(&store().exchange_rate_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(vec!["Exchange rate storage is unavailable".to_string()]),
+        };
+
+    rate.set_at = Some(clock::now().to_rfc3339_opts(SecondsFormat::Secs, true));
+    let operation: CdcOperation =
+        match exchange_rate_list.insert(rate.currency.clone(), rate.clone()) {
+            Some(_) => CdcOperation::Update,
+            None => CdcOperation::Insert,
+        };
+    write_file(EXCHANGE_RATES_PATH, &*exchange_rate_list);
+    record_cdc(
+        operation,
+        rate.currency.clone(),
+        CdcData::ExchangeRate(rate.clone()),
+    );
+    return Ok(rate);
+}
+
+/// Fetch every exchange rate override, ordered by currency.
+///
+/// # Examples
+///
+/// ```
+/// rates = fetch_exchange_rates();
+/// ```
+pub fn fetch_exchange_rates() -> Vec<ExchangeRateOverride> {
+    let mut rates: Vec<ExchangeRateOverride> = match lock_list(&store().exchange_rate_list) {
+        Ok(exchange_rate_list) => exchange_rate_list.values().cloned().collect(),
+        Err(_) => return Vec::new(),
+    };
+
+    rates.sort_by(|a, b| a.currency.cmp(&b.currency));
+    return rates;
+}
+
+/// Fetch the exchange rate override for a currency, if any.
+///
+/// # Arguments
+///
+/// * `currency` - The ISO 4217 code of the currency
+///
+/// # Examples
+///
+/// ```
+/// rate = fetch_exchange_rate("EUR");
+/// ```
+pub fn fetch_exchange_rate(currency: &str) -> Option<ExchangeRateOverride> {
+    return lock_list(&store().exchange_rate_list)
+        .ok()?
+        .get(currency)
+        .cloned();
+}
+
+/// Delete the exchange rate override for a currency, so the rate from the exchange rate source is
+/// used. Returns the deleted override, or None if the currency has no override.
+///
+/// # Arguments
+///
+/// * `currency` - The ISO 4217 code of the currency
+///
+/// # Examples
+///
+/// ```
+/// delete_exchange_rate("EUR");
+/// ```
+pub fn delete_exchange_rate(currency: &str) -> Option<ExchangeRateOverride> {
+    let mut exchange_rate_list: std::sync::MutexGuard<'_, HashMap<String, ExchangeRateOverride>> =
+        match lock_list(&store().exchange_rate_list) {
+            Ok(guard) => guard,
+            Err(_) => return None,
+        };
+
+    let removed: ExchangeRateOverride = exchange_rate_list.remove(currency)?;
+
+    write_file(EXCHANGE_RATES_PATH, &*exchange_rate_list);
+    record_cdc(
+        CdcOperation::Delete,
+        removed.currency.clone(),
+        CdcData::ExchangeRate(removed.clone()),
+    );
+    return Some(removed);
+}
+
+/// Create a new inventory adjustment, changing the number of rooms of a room type which may be
+/// sold during a period. Bookings which are already made are unaffected.
+///
+/// # Arguments
+///
+/// * `adjustment` - The adjustment. ```adjustment_id``` should be excluded, as it is added
+///   automatically.
+///
+/// # Examples
+///
+/// ```
+/// adjustment = create_adjustment(adjustment)?;
+/// ```
+pub fn create_adjustment(
+    mut adjustment: InventoryAdjustment,
+) -> Result<InventoryAdjustment, Vec<String>> {
+    let errors: Vec<String> = adjustment.validate();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut adjustment_list: std::sync::MutexGuard<'_, HashMap<u32, InventoryAdjustment>> =
+        match lock_list(&store().adjustment_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(vec!["Inventory storage is unavailable".to_string()]),
+        };
+
+    let next_id: u32 = adjustment_list.keys().fold(u32::MIN, |a, b| a.max(*b)) + 1;
+    adjustment.adjustment_id = Some(next_id);
+    adjustment_list.insert(next_id, adjustment.clone());
+    write_file(ADJUSTMENTS_PATH, &*adjustment_list);
+    record_cdc(
+        CdcOperation::Insert,
+        next_id.to_string(),
+        CdcData::InventoryAdjustment(adjustment.clone()),
+    );
+    return Ok(adjustment);
+}
+
+/// Fetch every inventory adjustment, ordered by start date.
+///
+/// # Examples
+///
+/// ```
+/// adjustments = fetch_adjustments();
+/// ```
+pub fn fetch_adjustments() -> Vec<InventoryAdjustment> {
+    let mut adjustments: Vec<InventoryAdjustment> = match lock_list(&store().adjustment_list) {
+        Ok(adjustment_list) => adjustment_list.values().cloned().collect(),
+        Err(_) => return Vec::new(),
+    };
+
+    adjustments
+        .sort_by(|a, b| (&a.start_date, a.adjustment_id).cmp(&(&b.start_date, b.adjustment_id)));
+    return adjustments;
+}
+
+/// Fetch the inventory adjustment with the provided id.
+///
+/// # Arguments
+///
+/// * `adjustment_id` - The id of the adjustment
+///
+/// # Examples
+///
+/// ```
+/// adjustment = fetch_adjustment(1);
+/// ```
+pub fn fetch_adjustment(adjustment_id: u32) -> Option<InventoryAdjustment> {
+    return lock_list(&store().adjustment_list)
+        .ok()?
+        .get(&adjustment_id)
+        .cloned();
+}
+
+/// Delete an inventory adjustment, returning the room type's inventory to its configured number
+/// of rooms for the period. Returns the deleted adjustment, or None if the adjustment does not
+/// exist.
+///
+/// # Arguments
+///
+/// * `adjustment_id` - The id of the adjustment
+///
+/// # Examples
+///
+/// ```
+/// delete_adjustment(1);
+/// ```
+pub fn delete_adjustment(adjustment_id: u32) -> Option<InventoryAdjustment> {
+    let mut adjustment_list: std::sync::MutexGuard<'_, HashMap<u32, InventoryAdjustment>> =
+        match lock_list(&store().adjustment_list) {
+            Ok(guard) => guard,
+            Err(_) => return None,
+        };
+
+    let removed: InventoryAdjustment = adjustment_list.remove(&adjustment_id)?;
+
+    write_file(ADJUSTMENTS_PATH, &*adjustment_list);
+    record_cdc(
+        CdcOperation::Delete,
+        adjustment_id.to_string(),
+        CdcData::InventoryAdjustment(removed.clone()),
+    );
+    return Some(removed);
+}
+
+/// Checks a room is available for the whole of a booking, including any overbooking allowance.
+///
+/// # Arguments
+///
+/// * `booking_list` - The stored bookings to check against
+/// * `pending` - Bookings which are being created alongside the booking, but are not yet stored
+/// * `booking` - The booking to check
+fn room_available(
+    booking_list: &HashMap<u32, RoomBooking>,
+    pending: &[RoomBooking],
+    booking: &RoomBooking,
+) -> bool {
+    return room_availability(booking_list, pending, booking) != Availability::Full;
+}
+
+/// Checks whether a room is available for the whole of a booking. Day use bookings are checked
+/// against the day use room pool, by counting the other day use bookings which overlap them.
+/// Overnight bookings are checked against the number of rooms of their room type on each night of
+/// the stay, after any inventory adjustments, and its overbooking allowance, by counting the other
+/// overnight bookings of that room type on each night.
+///
+/// # Arguments
+///
+/// * `booking_list` - The stored bookings to check against
+/// * `pending` - Bookings which are being created alongside the booking, but are not yet stored
+/// * `booking` - The booking to check
+fn room_availability(
+    booking_list: &HashMap<u32, RoomBooking>,
+    pending: &[RoomBooking],
+    booking: &RoomBooking,
+) -> Availability {
+    let others = scan(booking_list)
+        .values()
+        .chain(pending.iter())
+        .filter(|other| other.holds_room() && other.booking_id != booking.booking_id)
+        // Sandbox bookings are checked against every booking, but do not hold a real room.
+        .filter(|other| booking.sandbox || !other.sandbox)
+        .filter(|other| {
+            other.check_in_date <= booking.check_out_date
+                && booking.check_in_date <= other.check_out_date
+        });
+
+    if booking.day_use {
+        let overlapping: usize = others
+            .filter(|other| booking.overlaps_day_use(other))
+            .count();
+
+        return match overlapping < config::hotel().day_use_rooms as usize {
+            true => Availability::Available,
+            false => Availability::Full,
+        };
+    }
+
+    let inventory: RoomInventory = match config::inventory_for(booking.room_type_id) {
+        Some(inventory) => inventory,
+        None => return Availability::Available,
+    };
+
+    let mut occupied: HashMap<String, usize> = HashMap::new();
+    for other in others.filter(|other| other.room_type_id == booking.room_type_id) {
+        for night in other.nights() {
+            *occupied.entry(night).or_insert(0) += 1;
+        }
+    }
+
+    let adjustments: Vec<InventoryAdjustment> = fetch_adjustments();
+
+    return booking
+        .nights()
+        .iter()
+        .map(|night| {
+            Availability::of(
+                occupied.get(night).copied().unwrap_or(0),
+                &adjusted_inventory(&inventory, &adjustments, night),
+            )
+        })
+        .max()
+        .unwrap_or(Availability::Available);
+}
+
+/// Describes why no room is available for a booking, noting when the overbooking allowance of
+/// its room type is also full.
+///
+/// # Arguments
+///
+/// * `booking` - The booking which could not be made
+fn no_rooms_error(booking: &RoomBooking) -> String {
+    let overbookable: bool = !booking.day_use
+        && config::inventory_for(booking.room_type_id)
+            .is_some_and(|inventory| inventory.sellable() > inventory.rooms);
+
+    return match overbookable {
+        true => "No rooms are available for the requested dates, including the overbooking \
+            allowance"
+            .to_string(),
+        false => "No rooms are available for the requested dates".to_string(),
+    };
+}
+
+/// Create a series of recurring bookings, linked by a newly assigned series id. Either every
+/// booking in the series is created, or none are. Each booking is checked for duplicates and
+/// screened as ```create``` does, against the stored bookings, so bookings in the same series are
+/// not treated as duplicates of each other.
+///
+/// # Arguments
+///
+/// * `bookings` - The bookings in the series, as returned by ```RecurringBooking::occurrences```.
+///   ```booking_id```, ```status``` and ```series_id``` should be excluded.
+///
+/// # Examples
+///
+/// ```
+/// bookings = create_series(recurring.occurrences().unwrap())?;
+/// ```
+pub fn create_series(mut bookings: Vec<RoomBooking>) -> Result<Vec<RoomBooking>, CreateError> {
+    if bookings.is_empty() {
+        return Err(CreateError::Invalid(vec![
+            "The series must include at least one booking".to_string(),
+        ]));
+    }
+
+    for booking in &bookings {
+        check_new_fields(booking)?;
+    }
+
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(CreateError::Unavailable),
+        };
+
+    let mut findings: Vec<Vec<Finding>> = Vec::new();
+    for (index, booking) in bookings.iter().enumerate() {
+        findings.push(admit(&booking_list, &bookings[..index], booking, false)?);
+    }
+
+    if let Some(index) = findings.iter().position(|findings| {
+        findings
+            .iter()
+            .any(|finding| finding.action == ScreeningAction::Reject)
+    }) {
+        record_screening(&findings[index], &bookings[index]);
+        return Err(CreateError::Rejected);
+    }
+
+    if let Err(error) = redeem_promo_code(&mut bookings) {
+        return Err(CreateError::PromoCode(error));
+    }
+
+    let series_id: u32 = scan(&booking_list)
+        .values()
+        .filter_map(|booking| booking.series_id)
+        .fold(u32::MIN, |a, b| a.max(b))
+        + 1;
+
+    let created: Vec<RoomBooking> = bookings
+        .into_iter()
+        .zip(findings)
+        .map(|(mut booking, findings)| {
+            let next_id: u32 = next_booking_id(&booking_list);
+            booking.set_booking_id(next_id);
+            set_new_status(&mut booking, &findings);
+            booking.series_id = Some(series_id);
+            booking.billing_milestones = pricing::billing_milestones(&booking);
+            booking.rate_plans = pricing::rate_plans(&booking);
+            booking.pricing = pricing::record(&booking);
+            addons::reprice(&mut booking.addons);
+            touch(&mut booking);
+            booking.created_at = booking.updated_at.clone();
+            booking_list.insert(next_id, booking.clone());
+            record_screening(&findings, &booking);
+            record_change(ChangeType::Created, &booking);
+            return booking;
+        })
+        .collect();
+
+    save_snapshot(&booking_list);
+    return Ok(created);
+}
+
+/// Cancel every confirmed booking in a series.
+///
+/// # Arguments
+///
+/// * `series_id` - The id of the series to cancel
+///
+/// # Examples
+///
+/// ```
+/// results = cancel_series(1);
+/// ```
+pub fn cancel_series(series_id: u32) -> Vec<BulkStatusResult> {
+    let booking_ids: Vec<u32> = fetch_by_series_id(series_id)
+        .iter()
+        .filter(|booking| booking.status == Some(BookingStatus::Confirmed))
+        .filter_map(|booking| booking.booking_id)
+        .collect();
+
+    return bulk_status(&booking_ids, BookingStatus::Cancelled);
+}
+
+/// Update every confirmed booking in a series. Either every confirmed booking is updated, or none
+/// are if the update would make any booking invalid.
+///
+/// # Arguments
+///
+/// * `series_id` - The id of the series to update
+/// * `update` - The changes to apply to each booking
+///
+/// # Examples
+///
+/// ```
+/// bookings = update_series(1, &update);
+/// ```
+pub fn update_series(series_id: u32, update: &SeriesUpdate) -> Result<Vec<RoomBooking>, ()> {
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(()),
+        };
+
+    let mut updated: Vec<RoomBooking> = scan(&booking_list)
+        .values()
+        .filter(|booking| booking.series_id == Some(series_id))
+        .filter(|booking| booking.status == Some(BookingStatus::Confirmed))
+        .cloned()
+        .collect();
+    updated.sort_by_key(|booking| booking.booking_id);
+
+    for booking in updated.iter_mut() {
+        update.apply(booking);
+        if !booking.validate().is_empty() {
+            return Err(());
+        }
+
+        if update.room_type_id.is_some() {
+            booking.billing_milestones = pricing::billing_milestones(booking);
+            booking.rate_plans = pricing::rate_plans(booking);
+            booking.pricing = pricing::record(booking);
+        }
+    }
+
+    for booking in updated.iter_mut() {
+        if let Some(booking_id) = booking.booking_id {
+            touch(booking);
+            booking_list.insert(booking_id, booking.clone());
+            record_change(ChangeType::Updated, booking);
+        }
+    }
+
+    if !updated.is_empty() {
+        save_snapshot(&booking_list);
+    }
+
+    return Ok(updated);
+}
+
+/// Import a list of bookings, such as those exported from a legacy system. Each booking is
+/// validated and imported independently, so an invalid booking does not prevent the others from
+/// being imported. Imported bookings are assigned a new booking id, and are confirmed unless a
+/// status is provided.
+///
+/// Each booking is screened as ```create``` does. Bookings matching a rule which rejects bookings
+/// are not imported, and those matching a rule which holds bookings are held pending review,
+/// unless a status is provided. Imported bookings are not checked for duplicates, as every booking
+/// in an import is created moments apart, and the system they were exported from may hold several
+/// bookings of the same room type for one customer.
+///
+/// # Arguments
+///
+/// * `bookings` - A list of bookings to import. ```booking_id``` should be excluded.
+///
+/// # Examples
+///
+/// ```
+/// results = import(bookings);
+/// ```
+pub fn import(bookings: Vec<RoomBooking>) -> Vec<Result<RoomBooking, Vec<String>>> {
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => {
+                return bookings
+                    .iter()
+                    .map(|_| Err(vec!["Booking storage is unavailable".to_string()]))
+                    .collect()
+            }
+        };
+
+    let results: Vec<Result<RoomBooking, Vec<String>>> = bookings
+        .into_iter()
+        .map(|mut booking| {
+            let errors: Vec<String> = import_errors(&booking_list, &[], &booking);
+            if !errors.is_empty() {
+                return Err(errors);
+            }
+
+            let findings: Vec<Finding> = screening::screen(&booking, &booking_list);
+            if findings
+                .iter()
+                .any(|finding| finding.action == ScreeningAction::Reject)
+            {
+                record_screening(&findings, &booking);
+                return Err(vec![CreateError::Rejected.to_string()]);
+            }
+
+            let next_id: u32 = next_booking_id(&booking_list);
+            booking.set_booking_id(next_id);
+            if booking.status.is_none() {
+                match findings
+                    .iter()
+                    .any(|finding| finding.action == ScreeningAction::Hold)
+                {
+                    true => booking.set_status(BookingStatus::PendingReview),
+                    false => booking.set_status(BookingStatus::Confirmed),
+                }
+            }
+            booking.billing_milestones = pricing::billing_milestones(&booking);
+            booking.rate_plans = pricing::rate_plans(&booking);
+            booking.pricing = pricing::record(&booking);
+            addons::price(&mut booking.addons);
+
+            touch(&mut booking);
+            if booking.created_at.is_none() {
+                booking.created_at = booking.updated_at.clone();
+            }
+            booking_list.insert(next_id, booking.clone());
+            record_screening(&findings, &booking);
+            record_change(ChangeType::Created, &booking);
+            return Ok(booking);
+        })
+        .collect();
+
+    if results.iter().any(|result| result.is_ok()) {
+        save_snapshot(&booking_list);
+    }
+
+    return results;
+}
+
+/// Checks whether a list of bookings would be imported, running the same checks as ```import```,
+/// without storing any of them. Each booking is checked against the stored bookings, and the
+/// bookings before it in the list which would be imported. Returns the booking as it would be
+/// imported, without a booking id, or a list describing each problem found, for each booking.
+///
+/// # Arguments
+///
+/// * `bookings` - A list of bookings to check. ```booking_id``` should be excluded.
+///
+/// # Examples
+///
+/// ```
+/// results = check_import(bookings);
+/// ```
+pub fn check_import(bookings: Vec<RoomBooking>) -> Vec<Result<RoomBooking, Vec<String>>> {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => {
+                return bookings
+                    .iter()
+                    .map(|_| Err(vec!["Booking storage is unavailable".to_string()]))
+                    .collect()
+            }
+        };
+
+    // Bookings which would be imported are given a provisional id, so later bookings are checked
+    // against them in the same way as stored bookings.
+    let mut next_id: u32 = booking_list.keys().fold(u32::MIN, |a, b| a.max(*b)) + 1;
+    let mut pending: Vec<RoomBooking> = Vec::new();
+    return bookings
+        .into_iter()
+        .map(|mut booking| {
+            let errors: Vec<String> = import_errors(&booking_list, &pending, &booking);
+            if !errors.is_empty() {
+                return Err(errors);
+            }
+
+            if booking.status.is_none() {
+                booking.set_status(BookingStatus::Confirmed);
+            }
+
+            let mut provisional: RoomBooking = booking.clone();
+            provisional.set_booking_id(next_id);
+            pending.push(provisional);
+            next_id += 1;
+            return Ok(booking);
+        })
+        .collect();
+}
+
+/// Checks a booking being imported passes validation, does not set a booking id and that a room
+/// is available for it. Returns a description of each problem found.
+///
+/// # Arguments
+///
+/// * `booking_list` - The stored bookings to check against
+/// * `pending` - Bookings which are being imported alongside the booking, but are not yet stored
+/// * `booking` - The booking being imported
+fn import_errors(
+    booking_list: &HashMap<u32, RoomBooking>,
+    pending: &[RoomBooking],
+    booking: &RoomBooking,
+) -> Vec<String> {
+    let mut errors: Vec<String> = booking.validate();
+    if booking.booking_id.is_some() {
+        errors.push("bookingId is assigned automatically, and must not be set".to_string());
+    }
+
+    if errors.is_empty() && !room_available(booking_list, pending, booking) {
+        errors.push(no_rooms_error(booking));
+    }
+
+    return errors;
+}
+
+/// Attach an add-on to a booking, priced from the add-on catalog. Returns the updated booking, or
+/// a list describing each problem found with the add-on.
+///
+/// # Arguments
+///
+/// * `booking_id` - The id of the booking to attach the add-on to
+/// * `addon` - The add-on to attach
+///
+/// # Examples
+///
+/// ```
+/// booking = add_addon(1, addon);
+/// ```
+pub fn add_addon(booking_id: u32, addon: BookingAddon) -> Result<RoomBooking, Vec<String>> {
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(vec!["Booking storage is unavailable".to_string()]),
+        };
+
+    let booking: &mut RoomBooking = match booking_list.get_mut(&booking_id) {
+        Some(booking) => booking,
+        None => return Err(vec!["Booking not found".to_string()]),
+    };
+
+    let original_length: usize = booking.addons.len();
+    let mut addons: Vec<BookingAddon> = booking.addons.clone();
+    addons.push(addon);
+
+    let errors: Vec<String> = addons::validate(&addons);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    addons::reprice(&mut addons[original_length..]);
+    booking.addons = addons;
+    touch(booking);
+    let booking: RoomBooking = booking.clone();
+
+    record_change(ChangeType::Updated, &booking);
+    save_snapshot(&booking_list);
+    return Ok(booking);
+}
+
+/// Remove an add-on from a booking. Returns the updated booking, or None if the booking does not
+/// exist or does not have the add-on.
+///
+/// # Arguments
+///
+/// * `booking_id` - The id of the booking to remove the add-on from
+/// * `code` - The code of the add-on to remove
+///
+/// # Examples
+///
+/// ```
+/// booking = remove_addon(1, "parking");
+/// ```
+pub fn remove_addon(booking_id: u32, code: &str) -> Option<RoomBooking> {
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        lock_list(&store().booking_list).ok()?;
+    let booking: &mut RoomBooking = booking_list.get_mut(&booking_id)?;
+
+    let original_length: usize = booking.addons.len();
+    booking.addons.retain(|addon| addon.code != code);
+    if booking.addons.len() == original_length {
+        return None;
+    }
+
+    touch(booking);
+    let booking: RoomBooking = booking.clone();
+    record_change(ChangeType::Updated, &booking);
+    save_snapshot(&booking_list);
+    return Some(booking);
+}
+
+/// Update the status of a booking. Returns an error if the booking does not exist, or its current
+/// status may not move to the requested status.
+///
+/// # Arguments
+///
+/// * `booking_id` - The id of the booking to update
+/// * `status` - The BookingStatus enum to be applied to the booking
+///
+/// # Examples
+///
+/// ```
+/// status(1, BookingStatus::Complete);
+/// ```
+pub fn status(booking_id: u32, status: BookingStatus) -> Result<(), TransitionError> {
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(TransitionError::Unavailable),
+        };
+
+    apply_status(&mut booking_list, booking_id, status, None)?;
+    save_snapshot(&booking_list);
+    return Ok(());
+}
+
+/// Cancel a booking, recording the reason it was cancelled. Returns an error if the booking does
+/// not exist, or its current status may not be cancelled.
+///
+/// # Arguments
+///
+/// * `booking_id` - The id of the booking to cancel
+/// * `cancellation` - The reason the booking was cancelled, if given
+///
+/// # Examples
+///
+/// ```
+/// cancel(1, Some(cancellation));
+/// ```
+pub fn cancel(booking_id: u32, cancellation: Option<Cancellation>) -> Result<(), TransitionError> {
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(TransitionError::Unavailable),
+        };
+
+    apply_status(
+        &mut booking_list,
+        booking_id,
+        BookingStatus::Cancelled,
+        cancellation,
+    )?;
+    save_snapshot(&booking_list);
+    return Ok(());
+}
+
+/// Update the status of a booking, if it currently has the expected status. Used where a status
+/// change is only meaningful from a single status, e.g. confirming payment for a booking which is
+/// pending payment.
+///
+/// # Arguments
+///
+/// * `booking_id` - The id of the booking to update
+/// * `from` - The status the booking must currently have
+/// * `to` - The BookingStatus enum to be applied to the booking
+///
+/// # Examples
+///
+/// ```
+/// status_from(1, BookingStatus::Cancelled, BookingStatus::Confirmed);
+/// ```
+pub fn status_from(
+    booking_id: u32,
+    from: BookingStatus,
+    to: BookingStatus,
+) -> Result<(), TransitionError> {
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(TransitionError::Unavailable),
+        };
+
+    let current: Option<BookingStatus> = match booking_list.get(&booking_id) {
+        Some(booking) => booking.status.clone(),
+        None => return Err(TransitionError::NotFound),
+    };
+
+    if current != Some(from.clone()) {
+        return Err(TransitionError::WrongStatus {
+            expected: from,
+            actual: current,
+        });
+    }
+
+    apply_status(&mut booking_list, booking_id, to, None)?;
+    save_snapshot(&booking_list);
+    return Ok(());
+}
+
+/// Approve a booking held pending review by a screening rule. The booking moves to pending payment
+/// if the hotel requires a deposit, with the deposit due within the hotel's payment window, and is
+/// confirmed otherwise. Returns the booking's new status.
+///
+/// # Arguments
+///
+/// * `booking_id` - The id of the booking to approve
+///
+/// # Examples
+///
+/// ```
+/// status = approve(1)?;
+/// ```
+pub fn approve(booking_id: u32) -> Result<BookingStatus, TransitionError> {
+    let status: BookingStatus = match config::hotel().deposit_required {
+        true => BookingStatus::PendingPayment,
+        false => BookingStatus::Confirmed,
+    };
+
+    status_from(booking_id, BookingStatus::PendingReview, status.clone())?;
+    return Ok(status);
+}
+
+/// Reinstate a cancelled booking, if a room is still available for its dates. A booking cancelled
+/// while held for review by screening moves back to pending review. Otherwise, the booking moves to
+/// pending payment if its deposit is still owed, with the deposit due within the hotel's payment
+/// window, and is confirmed otherwise. Returns the booking's new status.
+///
+/// # Arguments
+///
+/// * `booking_id` - The id of the booking to reinstate
+///
+/// # Examples
+///
+/// ```
+/// status = reinstate(1)?;
+/// ```
+pub fn reinstate(booking_id: u32) -> Result<BookingStatus, TransitionError> {
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(TransitionError::Unavailable),
+        };
+
+    let booking: &RoomBooking = match booking_list.get(&booking_id) {
+        Some(booking) => booking,
+        None => return Err(TransitionError::NotFound),
+    };
+
+    if booking.status != Some(BookingStatus::Cancelled) {
+        return Err(TransitionError::WrongStatus {
+            expected: BookingStatus::Cancelled,
+            actual: booking.status.clone(),
+        });
+    }
+
+    let mut confirmed: RoomBooking = booking.clone();
+    confirmed.set_status(BookingStatus::Confirmed);
+    let status: BookingStatus = if awaiting_review(booking_id) {
+        BookingStatus::PendingReview
+    } else if deposit_required(booking) && Balance::new(&confirmed).deposit_outstanding > 0 {
+        BookingStatus::PendingPayment
+    } else {
+        BookingStatus::Confirmed
+    };
+
+    apply_status(&mut booking_list, booking_id, status.clone(), None)?;
+    save_snapshot(&booking_list);
+    return Ok(status);
+}
+
+/// Checks whether a booking must have its deposit paid before it is confirmed. A deposit is
+/// required while the hotel requires one, and for every booking which was given a payment due
+/// date, i.e. it was pending payment at some point, even if the hotel has since stopped requiring
+/// deposits.
+///
+/// # Arguments
+///
+/// * `booking` - The booking
+fn deposit_required(booking: &RoomBooking) -> bool {
+    return booking.status == Some(BookingStatus::PendingPayment)
+        || booking.payment_due_by.is_some()
+        || config::hotel().deposit_required;
+}
+
+/// Record a payment received for a booking. A booking pending payment is confirmed once its
+/// deposit has been paid. Returns the booking's balance after the payment, or an error if the
+/// booking does not exist, is no longer active, or the payment is more than the amount still to
+/// pay.
+///
+/// # Arguments
+///
+/// * `booking_id` - The id of the booking
+/// * `payment` - The payment received
+///
+/// # Examples
+///
+/// ```
+/// balance = record_payment(1, payment)?;
+/// ```
+pub fn record_payment(booking_id: u32, mut payment: Payment) -> Result<Balance, String> {
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(TransitionError::Unavailable.to_string()),
+        };
+
+    let mut booking: RoomBooking = match booking_list.get(&booking_id) {
+        Some(booking) => booking.clone(),
+        None => return Err(TransitionError::NotFound.to_string()),
+    };
+
+    if !booking.holds_room() || booking.status == Some(BookingStatus::Complete) {
+        return Err("Payments can only be recorded for active bookings".to_string());
+    }
+
+    let outstanding: u64 = Balance::new(&booking).outstanding;
+    if payment.amount == 0 {
+        return Err("amount must be greater than 0".to_string());
+    } else if payment.amount > outstanding {
+        return Err(format!(
+            "amount must not be more than the outstanding balance of {}",
+            outstanding
+        ));
+    }
+
+    payment.received_at = Some(clock::now().to_rfc3339_opts(SecondsFormat::Secs, true));
+    booking.payments.push(payment);
+    touch(&mut booking);
+    booking_list.insert(booking_id, booking.clone());
+    record_change_as(ChangeType::Updated, EventTopic::Payment, &booking);
+
+    let balance: Balance = Balance::new(&booking);
+    if booking.status == Some(BookingStatus::PendingPayment) && balance.deposit_outstanding == 0 {
+        apply_status(
+            &mut booking_list,
+            booking_id,
+            BookingStatus::Confirmed,
+            None,
+        )
+        .map_err(|error| error.to_string())?;
+    }
+
+    save_snapshot(&booking_list);
+    return Ok(balance);
+}
+
+/// Removes a payment recorded for a booking which the payment provider has reported as failed,
+/// e.g. after the payment was reversed. Returns the booking's balance, which is unchanged if no
+/// payment with the provider's reference was recorded.
+///
+/// # Arguments
+///
+/// * `booking_id` - The booking id of the booking
+/// * `reference` - The payment provider's reference for the payment
+///
+/// # Examples
+///
+/// ```
+/// balance = reverse_payment(1, "pay_1");
+/// ```
+pub fn reverse_payment(booking_id: u32, reference: &str) -> Result<Balance, String> {
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(TransitionError::Unavailable.to_string()),
+        };
+
+    let mut booking: RoomBooking = match booking_list.get(&booking_id) {
+        Some(booking) => booking.clone(),
+        None => return Err(TransitionError::NotFound.to_string()),
+    };
+
+    let original_length: usize = booking.payments.len();
+    booking
+        .payments
+        .retain(|payment| payment.reference.as_deref() != Some(reference));
+    if booking.payments.len() == original_length {
+        return Ok(Balance::new(&booking));
+    }
+
+    touch(&mut booking);
+    booking_list.insert(booking_id, booking.clone());
+    record_change_as(ChangeType::Updated, EventTopic::Payment, &booking);
+    save_snapshot(&booking_list);
+    return Ok(Balance::new(&booking));
+}
+
+/// Returns the amount which may be refunded for a booking, which is the amount paid less any
+/// earlier refunds, the refunds reserved while they are in progress, and the amount kept under the
+/// cancellation policy.
+///
+/// # Arguments
+///
+/// * `booking` - The booking
+/// * `change_log` - The booking change log, used to find when the booking was cancelled
+/// * `reservations` - The refunds reserved for every booking
+fn refundable_amount(
+    booking: &RoomBooking,
+    change_log: &[BookingChange],
+    reservations: &[RefundReservation],
+) -> u64 {
+    let mut previous: Option<BookingStatus> = None;
+    let mut cancelled_at: Option<DateTime<Utc>> = None;
+    for change in change_log
+        .iter()
+        .filter(|change| change.booking.booking_id == booking.booking_id)
+    {
+        let current: Option<BookingStatus> = change.booking.status.clone();
+        if current == Some(BookingStatus::Cancelled) && previous != current {
+            cancelled_at = DateTime::parse_from_rfc3339(&change.recorded_at)
+                .ok()
+                .map(|recorded_at| recorded_at.with_timezone(&Utc));
+        }
+        previous = current;
+    }
+
+    let reserved: u64 = reservations
+        .iter()
+        .filter(|reservation| Some(reservation.booking_id) == booking.booking_id)
+        .map(|reservation| reservation.amount)
+        .sum();
+
+    let balance: Balance = Balance::new(booking);
+    return balance
+        .paid
+        .saturating_sub(balance.refunded)
+        .saturating_sub(reserved)
+        .saturating_sub(payment::retained(booking, cancelled_at));
+}
+
+/// Returns the amount which may be refunded for a booking, which is the amount paid less any
+/// earlier refunds, refunds in progress and the amount kept under the cancellation policy. Returns
+/// an error if the booking does not exist.
+///
+/// # Arguments
+///
+/// * `booking_id` - The booking id of the booking
+///
+/// # Examples
+///
+/// ```
+/// refundable = refundable(1)?;
+/// ```
+pub fn refundable(booking_id: u32) -> Result<u64, RefundError> {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        lock_list(&store().booking_list).map_err(|_| RefundError::Unavailable)?;
+    let booking: &RoomBooking = booking_list.get(&booking_id).ok_or(RefundError::NotFound)?;
+    let reservations: std::sync::MutexGuard<'_, Vec<RefundReservation>> =
+        lock_list(&store().refund_reservations).map_err(|_| RefundError::Unavailable)?;
+    let change_log: std::sync::MutexGuard<'_, Vec<BookingChange>> =
+        lock_list(&store().change_log).map_err(|_| RefundError::Unavailable)?;
+
+    return Ok(refundable_amount(booking, &change_log, &reservations));
+}
+
+/// Reserves a refund against a booking, before it is requested from the payment provider. The
+/// refund must not be more than the refundable amount, which takes account of any other refunds
+/// reserved, so refunds made at the same time cannot together refund more than the cancellation
+/// policy allows. The reservation must be completed with ```complete_refund``` once the refund is
+/// made, or released with ```release_refund``` if it fails.
+///
+/// # Arguments
+///
+/// * `booking_id` - The booking id of the booking
+/// * `amount` - The amount to refund
+///
+/// # Examples
+///
+/// ```
+/// let reservation = reserve_refund(1, 2500)?;
+/// ```
+pub fn reserve_refund(booking_id: u32, amount: u64) -> Result<RefundReservation, RefundError> {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        lock_list(&store().booking_list).map_err(|_| RefundError::Unavailable)?;
+    let booking: &RoomBooking = booking_list.get(&booking_id).ok_or(RefundError::NotFound)?;
+    let mut reservations: std::sync::MutexGuard<'_, Vec<RefundReservation>> =
+        lock_list(&store().refund_reservations).map_err(|_| RefundError::Unavailable)?;
+    let change_log: std::sync::MutexGuard<'_, Vec<BookingChange>> =
+        lock_list(&store().change_log).map_err(|_| RefundError::Unavailable)?;
+
+    let refundable: u64 = refundable_amount(booking, &change_log, &reservations);
+    if amount == 0 {
+        return Err(RefundError::InvalidAmount);
+    } else if amount > refundable {
+        return Err(RefundError::ExceedsRefundable(refundable));
+    }
+
+    let reservation: RefundReservation = RefundReservation {
+        booking_id,
+        amount,
+        sandbox: booking.sandbox,
+        idempotency_key: format!("refund-{}-{:016x}", booking_id, rand::random::<u64>()),
+    };
+    reservations.push(reservation.clone());
+    return Ok(reservation);
+}
+
+/// Releases a refund reservation, when the refund could not be made, so the amount may be
+/// refunded again.
+///
+/// # Arguments
+///
+/// * `reservation` - The reservation to release
+///
+/// # Examples
+///
+/// ```
+/// release_refund(&reservation);
+/// ```
+pub fn release_refund(reservation: &RefundReservation) {
+    if let Ok(mut reservations) = lock_list(&store().refund_reservations) {
+        reservations.retain(|held| held.idempotency_key != reservation.idempotency_key);
+    }
+}
+
+/// Records a refund made against a reservation, releasing the reservation. The amount was checked
+/// when it was reserved, so the refund is recorded even if the booking has changed since, as the
+/// money has already been returned. A refund with the same payment provider reference as an
+/// earlier refund, e.g. one the provider has already reported, is not recorded again. Returns the
+/// refund as recorded.
+///
+/// # Arguments
+///
+/// * `reservation` - The reservation the refund was made against
+/// * `refund` - The refund made
+///
+/// # Examples
+///
+/// ```
+/// refund = complete_refund(&reservation, refund)?;
+/// ```
+pub fn complete_refund(
+    reservation: &RefundReservation,
+    mut refund: Refund,
+) -> Result<Refund, RefundError> {
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        lock_list(&store().booking_list).map_err(|_| RefundError::Unavailable)?;
+
+    release_refund(reservation);
+
+    let mut booking: RoomBooking = match booking_list.get(&reservation.booking_id) {
+        Some(booking) => booking.clone(),
+        None => return Err(RefundError::NotFound),
+    };
+
+    if let Some(earlier) = booking
+        .refunds
+        .iter()
+        .find(|earlier| refund.reference.is_some() && earlier.reference == refund.reference)
+    {
+        return Ok(earlier.clone());
+    }
+
+    refund.amount = reservation.amount;
+    refund.refunded_at = Some(clock::now().to_rfc3339_opts(SecondsFormat::Secs, true));
+    booking.refunds.push(refund.clone());
+    touch(&mut booking);
+    booking_list.insert(reservation.booking_id, booking.clone());
+    record_change_as(ChangeType::Updated, EventTopic::Payment, &booking);
+    save_snapshot(&booking_list);
+    return Ok(refund);
+}
+
+/// Record a refund reported by the payment provider for a booking. The refund must not be more
+/// than the amount paid for the booking, less any earlier refunds. A refund with the same payment
+/// provider reference as an earlier refund is not recorded again, as the provider reports refunds
+/// requested through the service as well as its own. Returns the refund as recorded.
+///
+/// # Arguments
+///
+/// * `booking_id` - The booking id of the booking
+/// * `refund` - The refund made
+///
+/// # Examples
+///
+/// ```
+/// refund = record_refund(1, refund)?;
+/// ```
+pub fn record_refund(booking_id: u32, mut refund: Refund) -> Result<Refund, String> {
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(TransitionError::Unavailable.to_string()),
+        };
+
+    let mut booking: RoomBooking = match booking_list.get(&booking_id) {
+        Some(booking) => booking.clone(),
+        None => return Err(TransitionError::NotFound.to_string()),
+    };
+
+    if let Some(earlier) = booking
+        .refunds
+        .iter()
+        .find(|earlier| refund.reference.is_some() && earlier.reference == refund.reference)
+    {
+        return Ok(earlier.clone());
+    }
+
+    let balance: Balance = Balance::new(&booking);
+    let refundable: u64 = balance.paid.saturating_sub(balance.refunded);
+    if refund.amount == 0 {
+        return Err("amount must be greater than 0".to_string());
+    } else if refund.amount > refundable {
+        return Err(format!(
+            "amount must not be more than the {} paid and not yet refunded",
+            refundable
+        ));
+    }
+
+    refund.refunded_at = Some(clock::now().to_rfc3339_opts(SecondsFormat::Secs, true));
+    booking.refunds.push(refund.clone());
+    touch(&mut booking);
+    booking_list.insert(booking_id, booking.clone());
+    record_change_as(ChangeType::Updated, EventTopic::Payment, &booking);
+    save_snapshot(&booking_list);
+    return Ok(refund);
+}
+
+/// Cancel every booking pending payment whose deposit was due before the provided time.
+///
+/// # Arguments
+///
+/// * `now` - The time to compare each booking's payment due time against
+///
+/// # Examples
+///
+/// ```
+/// expired = expire_unpaid(clock::now());
+/// ```
+pub fn expire_unpaid(now: DateTime<Utc>) -> usize {
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return 0,
+        };
+
+    let booking_ids: Vec<u32> = scan(&booking_list)
+        .values()
+        .filter(|booking| booking.payment_expired(now))
+        .filter_map(|booking| booking.booking_id)
+        .collect();
+
+    let expired: usize = booking_ids
+        .iter()
+        .filter(|booking_id| {
+            let cancellation = Cancellation {
+                reason: CancellationReason::PaymentNotReceived,
+                comment: None,
+            };
+            return apply_status(
+                &mut booking_list,
+                **booking_id,
+                BookingStatus::Cancelled,
+                Some(cancellation),
+            )
+            .is_ok();
+        })
+        .count();
+
+    if expired > 0 {
+        save_snapshot(&booking_list);
+    }
+
+    return expired;
+}
+
+/// Create or update a booking from a reservation made through the channel manager. If a booking
+/// already exists for the reservation, its room type, dates and number of guests are updated to
+/// match, and otherwise a confirmed booking is created. Returns the booking, or a description of
+/// the problem if the booking is invalid, no room is available, or it is rejected by screening.
+///
+/// New bookings are screened as ```create``` does, and held pending review if they match a rule
+/// which holds bookings. They are not checked for duplicates, as the channel has already accepted
+/// the reservation, and repeated deliveries of the same reservation are matched by
+/// ```channel_reservation_id``` instead.
+///
+/// # Arguments
+///
+/// * `booking` - The booking details of the reservation, with ```channel_reservation_id``` set
+///
+/// # Examples
+///
+/// ```
+/// booking = sync_channel_reservation(booking)?;
+/// ```
+pub fn sync_channel_reservation(mut booking: RoomBooking) -> Result<RoomBooking, String> {
+    if booking.channel_reservation_id.is_none() {
+        return Err("channelReservationId must be set".to_string());
+    }
+
+    let errors: Vec<String> = booking.validate();
+    if !errors.is_empty() {
+        return Err(errors.join(", "));
+    }
+
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err("Booking storage is unavailable".to_string()),
+        };
+
+    let existing: Option<RoomBooking> = scan(&booking_list)
+        .values()
+        .find(|other| other.channel_reservation_id == booking.channel_reservation_id)
+        .cloned();
+
+    let mut findings: Vec<Finding> = Vec::new();
+    let change_type: ChangeType = match existing {
+        Some(mut existing) => {
+            if existing.room_type_id == booking.room_type_id
+                && existing.check_in_date == booking.check_in_date
+                && existing.check_out_date == booking.check_out_date
+                && existing.guests == booking.guests
+                && existing.children == booking.children
+            {
+                return Ok(existing);
+            }
+
+            if !existing.holds_room() {
+                return Err("Bookings which are no longer active cannot be modified".to_string());
+            }
+
+            existing.room_type_id = booking.room_type_id;
+            existing.check_in_date = booking.check_in_date;
+            existing.check_out_date = booking.check_out_date;
+            existing.guests = booking.guests;
+            existing.children = booking.children;
+            booking = existing;
+            ChangeType::Updated
+        }
+        None => {
+            findings = screening::screen(&booking, &booking_list);
+            if findings
+                .iter()
+                .any(|finding| finding.action == ScreeningAction::Reject)
+            {
+                record_screening(&findings, &booking);
+                return Err(CreateError::Rejected.to_string());
+            }
+
+            let next_id: u32 = next_booking_id(&booking_list);
+            booking.set_booking_id(next_id);
+            match findings
+                .iter()
+                .any(|finding| finding.action == ScreeningAction::Hold)
+            {
+                true => booking.set_status(BookingStatus::PendingReview),
+                false => booking.set_status(BookingStatus::Confirmed),
+            }
+            ChangeType::Created
+        }
+    };
+
+    if !room_available(&booking_list, &[], &booking) {
+        return Err(no_rooms_error(&booking));
+    }
+
+    let booking_id: u32 = booking.booking_id.unwrap_or_default();
+    booking.billing_milestones = pricing::billing_milestones(&booking);
+    booking.rate_plans = pricing::rate_plans(&booking);
+    booking.pricing = pricing::record(&booking);
+    addons::price(&mut booking.addons);
+    touch(&mut booking);
+    if change_type == ChangeType::Created {
+        booking.created_at = booking.updated_at.clone();
+    }
+    booking_list.insert(booking_id, booking.clone());
+    record_screening(&findings, &booking);
+    record_change(change_type, &booking);
+    save_snapshot(&booking_list);
+    return Ok(booking);
+}
+
+/// Cancel the booking made for a reservation through the channel manager. Returns an error if no
+/// booking exists for the reservation, or it may not be cancelled.
+///
+/// # Arguments
+///
+/// * `reservation_id` - The id of the reservation in the channel manager
+///
+/// # Examples
+///
+/// ```
+/// cancel_channel_reservation("ABC123");
+/// ```
+pub fn cancel_channel_reservation(reservation_id: &str) -> Result<(), TransitionError> {
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Err(TransitionError::Unavailable),
+        };
+
+    let booking_id: u32 = match scan(&booking_list)
+        .values()
+        .find(|booking| booking.channel_reservation_id.as_deref() == Some(reservation_id))
+    {
+        Some(booking) => booking.booking_id.unwrap_or_default(),
+        None => return Err(TransitionError::NotFound),
+    };
+
+    let cancellation = Cancellation {
+        reason: CancellationReason::Other,
+        comment: Some("Cancelled through the channel manager".to_string()),
+    };
+    apply_status(
+        &mut booking_list,
+        booking_id,
+        BookingStatus::Cancelled,
+        Some(cancellation),
+    )?;
+
+    save_snapshot(&booking_list);
+    return Ok(());
+}
+
+/// Check the availability of several stays, and price each stay which is valid. Each stay is
+/// checked independently, against the stored bookings only. Returns None if booking storage is
+/// unavailable.
+///
+/// # Arguments
+///
+/// * `queries` - The stays to check
+///
+/// # Examples
+///
+/// ```
+/// results = check_availability(&queries);
+/// ```
+pub fn check_availability(queries: &[AvailabilityQuery]) -> Option<Vec<AvailabilityResult>> {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        lock_list(&store().booking_list).ok()?;
+
+    return Some(
+        queries
+            .iter()
+            .map(|query| {
+                let mut booking: RoomBooking = query.to_booking();
+                let mut errors: Vec<String> = booking.validate();
+                let mut result = AvailabilityResult {
+                    room_type_id: query.room_type_id,
+                    check_in_date: query.check_in_date.clone(),
+                    check_out_date: query.check_out_date.clone(),
+                    availability: None,
+                    quote: None,
+                    errors: Vec::new(),
+                };
+
+                if errors.is_empty() {
+                    result.availability = Some(room_availability(&booking_list, &[], &booking));
+                    booking.billing_milestones = pricing::billing_milestones(&booking);
+
+                    match pricing::quote(&booking) {
+                        Ok(quote) => result.quote = Some(quote),
+                        Err(error) => errors.push(error),
+                    }
+                }
+
+                result.errors = errors;
+                return result;
+            })
+            .collect(),
+    );
+}
+
+/// Count the overnight bookings of a room type holding a room on each night, keyed by date.
+/// Sandbox bookings do not hold a real room, so are not counted.
+///
+/// # Arguments
+///
+/// * `room_type_id` - The id of the room type
+///
+/// # Examples
+///
+/// ```
+/// occupied = occupied_rooms(1);
+/// ```
+pub fn occupied_rooms(room_type_id: u8) -> HashMap<String, usize> {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return HashMap::new(),
+        };
+
+    let mut occupied: HashMap<String, usize> = HashMap::new();
+    for booking in scan(&booking_list)
+        .values()
+        .filter(|booking| booking.holds_room() && booking.room_type_id == room_type_id)
+        .filter(|booking| !booking.sandbox)
+    {
+        for night in booking.nights() {
+            *occupied.entry(night).or_insert(0) += 1;
+        }
+    }
+
+    return occupied;
+}
+
+/// Update the status of several bookings at once. Each booking is updated independently, so a
+/// failure to update one booking does not prevent the others from being updated.
+///
+/// # Arguments
+///
+/// * `booking_ids` - The ids of the bookings to update
+/// * `status` - The BookingStatus enum to be applied to each booking
+///
+/// # Examples
+///
+/// ```
+/// results = bulk_status(&[1, 2, 3], BookingStatus::Cancelled);
+/// ```
+pub fn bulk_status(booking_ids: &[u32], status: BookingStatus) -> Vec<BulkStatusResult> {
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => {
+                return booking_ids
+                    .iter()
+                    .map(|booking_id| {
+                        BulkStatusResult::new(*booking_id, Err(TransitionError::Unavailable))
+                    })
+                    .collect()
+            }
+        };
+
+    let results: Vec<BulkStatusResult> = booking_ids
+        .iter()
+        .map(|booking_id| {
+            let result = apply_status(&mut booking_list, *booking_id, status.clone(), None);
+            return BulkStatusResult::new(*booking_id, result);
+        })
+        .collect();
+
+    if results.iter().any(|result| result.success) {
+        save_snapshot(&booking_list);
+    }
+
+    return results;
+}
+
+/// Applies a status to a booking in the booking list, recording the change. Returns an error if
+/// the booking does not exist, the state machine does not allow the change, or the change would
+/// make a booking which no longer holds a room, such as a cancelled booking, hold one when no room
+/// is available. The cancellation reason is recorded if the booking is cancelled, and cleared
+/// otherwise. Bookings may only be marked as a no-show from their check in date, in the hotel's
+/// timezone, when the no-show fee is applied.
+fn apply_status(
+    booking_list: &mut HashMap<u32, RoomBooking>,
+    booking_id: u32,
+    status: BookingStatus,
+    cancellation: Option<Cancellation>,
+) -> Result<(), TransitionError> {
+    let mut booking: RoomBooking = match booking_list.get(&booking_id) {
+        Some(booking) => booking.clone(),
+        None => return Err(TransitionError::NotFound),
+    };
+
+    state_machine::check(&booking.status, &status)?;
+
+    let change_type: ChangeType = match status {
+        BookingStatus::Cancelled => ChangeType::Cancelled,
+        BookingStatus::NoShow => ChangeType::NoShow,
+        _ => ChangeType::Updated,
+    };
+
+    if status == BookingStatus::CheckedIn {
+        let outstanding: u64 = Balance::new(&booking).outstanding;
+        if outstanding > 0 {
+            return Err(TransitionError::OutstandingBalance(outstanding));
+        }
+    }
+
+    if status == BookingStatus::NoShow {
+        if local_time::today().format(DATE_FORMAT).to_string() < booking.check_in_date {
+            return Err(TransitionError::BeforeCheckIn);
+        }
+
+        booking.no_show_fee = Some(pricing::no_show_fee(&booking));
+    }
+
+    booking.cancellation = match status {
+        BookingStatus::Cancelled => cancellation,
+        _ => None,
+    };
+
+    if status == BookingStatus::PendingPayment {
+        booking.payment_due_by = Some(payment_due_by());
+    }
+
+    // Bookings cancelled while held for review may only be reinstated to pending review.
+    let confirming: bool = matches!(
+        status,
+        BookingStatus::Confirmed | BookingStatus::PendingPayment
+    );
+    let approving: bool = confirming && booking.status == Some(BookingStatus::PendingReview);
+    if confirming && !approving && awaiting_review(booking_id) {
+        return Err(TransitionError::AwaitingReview);
+    }
+
+    let held_room: bool = booking.holds_room();
+    let requires_deposit: bool = deposit_required(&booking);
+    booking.set_status(status);
+
+    // The deposit is checked once the status is applied, as cancelled bookings are not priced.
+    if booking.status == Some(BookingStatus::Confirmed) && requires_deposit {
+        let outstanding: u64 = Balance::new(&booking).deposit_outstanding;
+        if outstanding > 0 {
+            return Err(TransitionError::DepositOutstanding(outstanding));
+        }
+    }
+
+    if !held_room && booking.holds_room() && !room_available(booking_list, &[], &booking) {
+        return Err(TransitionError::NoRoomAvailable);
+    }
+
+    touch(&mut booking);
+    booking.cancelled_at = match booking.status {
+        Some(BookingStatus::Cancelled) => booking.updated_at.clone(),
+        _ => None,
+    };
+    booking_list.insert(booking_id, booking.clone());
+    record_change(change_type, &booking);
+    if approving {
+        update_reviews(|review_list| review_list.remove(&booking_id));
+    }
+    return Ok(());
+}
+
+/// Fetch a booking using a booking id.
+///
+/// # Arguments
+///
+/// * `booking_id` - The booking id of the booking to return.
+///
+/// # Examples
+///
+/// ```
+/// booking = fetch_by_id(1);
+/// ```
+pub fn fetch_by_id(booking_id: u32) -> Option<RoomBooking> {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return None,
+        };
+
+    let result: Option<RoomBooking> = booking_list.get(&booking_id).cloned();
+    return result;
+}
+
+/// Fetch a list of bookings made by a specific customer.
+///
+/// # Arguments
+///
+/// * `customer_id` - The customer id of the bookings to return.
+///
+/// # Examples
+///
+/// ```
+/// bookings = fetch_by_customer_id(1);
+/// ```
+pub fn fetch_by_customer_id(customer_id: u32) -> Vec<RoomBooking> {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Vec::new(),
+        };
+
+    let results: Vec<RoomBooking> = scan(&booking_list)
+        .values()
+        .filter(|booking: &&RoomBooking| booking.customer_id == customer_id)
+        .cloned()
+        .collect();
+
+    results
+}
+
+/// Fetch a list of bookings with a specific check in date.
+///
+/// # Arguments
+///
+/// * `date` - A string containing the check in date of the bookings to return.
+///
+/// # Examples
+///
+/// ```
+/// bookings = fetch_by_check_in_date("2020-01-01".to_string());
+/// ```
+pub fn fetch_by_check_in_date(date: &str) -> Vec<RoomBooking> {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Vec::new(),
+        };
+
+    let results: Vec<RoomBooking> = scan(&booking_list)
+        .values()
+        .filter(|booking: &&RoomBooking| booking.check_in_date == date)
+        .cloned()
+        .collect();
+
+    results
+}
+
+/// Fetch a list of bookings with a specific check out date.
+///
+/// # Arguments
+///
+/// * `date` - A string containing the check out date of the bookings to return.
+///
+/// # Examples
+///
+/// ```
+/// bookings = fetch_by_check_out_date("2020-01-08");
+/// ```
+pub fn fetch_by_check_out_date(date: &str) -> Vec<RoomBooking> {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Vec::new(),
+        };
+
+    let results: Vec<RoomBooking> = scan(&booking_list)
+        .values()
+        .filter(|booking: &&RoomBooking| booking.check_out_date == date)
+        .cloned()
+        .collect();
+
+    results
+}
+
+/// Fetch a list of bookings made by a specific customer.
+///
+/// # Arguments
+///
+/// * `customer_id` - The customer id of the bookings to return.
+///
+/// # Examples
+///
+/// ```
+/// bookings = fetch_by_customer_id(1);
+/// ```
+pub fn fetch_by_room_type_id(room_type_id: u8) -> Vec<RoomBooking> {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Vec::new(),
+        };
+
+    let results: Vec<RoomBooking> = scan(&booking_list)
+        .values()
+        .filter(|booking: &&RoomBooking| booking.room_type_id == room_type_id)
+        .cloned()
+        .collect();
+
+    results
+}
+
+/// Fetch the bookings in a series of recurring bookings, ordered by booking id.
+///
+/// # Arguments
+///
+/// * `series_id` - The id of the series to return.
+///
+/// # Examples
+///
+/// ```
+/// bookings = fetch_by_series_id(1);
+/// ```
+pub fn fetch_by_series_id(series_id: u32) -> Vec<RoomBooking> {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Vec::new(),
+        };
+
+    let mut results: Vec<RoomBooking> = scan(&booking_list)
+        .values()
+        .filter(|booking: &&RoomBooking| booking.series_id == Some(series_id))
+        .cloned()
+        .collect();
+    results.sort_by_key(|booking| booking.booking_id);
+
+    return results;
+}
+
+/// Fetch the bookings made against a corporate account, ordered by booking id.
+///
+/// # Arguments
+///
+/// * `account_id` - The id of the account to return bookings for.
+///
+/// # Examples
+///
+/// ```
+/// bookings = fetch_by_account_id(1);
+/// ```
+pub fn fetch_by_account_id(account_id: u32) -> Vec<RoomBooking> {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Vec::new(),
+        };
+
+    let mut results: Vec<RoomBooking> = scan(&booking_list)
+        .values()
+        .filter(|booking: &&RoomBooking| booking.account_id == Some(account_id))
+        .cloned()
+        .collect();
+    results.sort_by_key(|booking| booking.booking_id);
+
+    return results;
+}
+
+/// Fetch a list of bookings satisfying every criterion in the provided filter, ordered by
+/// booking id.
+///
+/// # Arguments
+///
+/// * `filter` - A BookingFilter containing the criteria the returned bookings must match.
+///
+/// # Examples
+///
+/// ```
+/// filter = BookingFilter { customer_id: Some(1), room_type_id: Some(3), ..Default::default() };
+/// bookings = fetch_filtered(&filter);
+/// ```
+pub fn fetch_filtered(filter: &BookingFilter) -> Vec<RoomBooking> {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Vec::new(),
+        };
+
+    let mut results: Vec<RoomBooking> = scan(&booking_list)
+        .values()
+        .filter(|booking: &&RoomBooking| filter.matches(booking))
+        .cloned()
+        .collect();
+
+    results.sort_by_key(|booking: &RoomBooking| booking.booking_id);
+    results
+}
+
+/// Fetch the booking ids of the bookings satisfying every criterion in the provided filter,
+/// ordered by booking id, without cloning the bookings. Used with a ```BookingCursor``` to stream
+/// large listings.
+///
+/// # Arguments
+///
+/// * `filter` - A BookingFilter containing the criteria the bookings must match.
+///
+/// # Examples
+///
+/// ```
+/// booking_ids = fetch_filtered_ids(&BookingFilter::default());
+/// ```
+pub fn fetch_filtered_ids(filter: &BookingFilter) -> Vec<u32> {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Vec::new(),
+        };
+
+    let mut booking_ids: Vec<u32> = scan(&booking_list)
+        .iter()
+        .filter(|(_, booking)| filter.matches(booking))
+        .map(|(booking_id, _)| *booking_id)
+        .collect();
+
+    booking_ids.sort_unstable();
+    return booking_ids;
+}
+
+/// Fetch a page of the booking ids of the bookings satisfying every criterion in the provided
+/// filter, ordered by booking id, along with the total number of matching bookings and the cursor
+/// for the next page. Only the ids inside the requested window are kept while storage is
+/// scanned, so memory use depends on the page size rather than the number of bookings.
+///
+/// # Arguments
+///
+/// * `filter` - A BookingFilter containing the criteria the bookings must match.
+/// * `pagination` - The window of matching bookings to return. The cursor is a booking id.
+///
+/// # Examples
+///
+/// ```
+/// page = fetch_page(&BookingFilter::default(), &Pagination::new(Some(100), None));
+/// ```
+pub fn fetch_page(filter: &BookingFilter, pagination: &Pagination) -> BookingPage {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return BookingPage::default(),
+        };
+
+    let window: Option<usize> = pagination.window();
+    let mut total: usize = 0;
+    let mut remaining: usize = 0;
+    // A max-heap holding the lowest booking ids seen, dropping the highest once the window is full.
+    let mut lowest: BinaryHeap<u32> = BinaryHeap::new();
+
+    for (booking_id, booking) in scan(&booking_list).iter() {
+        if !filter.matches(booking) {
+            continue;
+        }
+
+        total += 1;
+        if pagination.after.is_some_and(|after| *booking_id <= after) {
+            continue;
+        }
+
+        remaining += 1;
+        lowest.push(*booking_id);
+        if window.is_some_and(|window| lowest.len() > window) {
+            lowest.pop();
+        }
+    }
+
+    drop(booking_list);
+
+    let booking_ids: Vec<u32> = pagination.apply(lowest.into_sorted_vec());
+    let next_cursor: Option<u32> = match window {
+        Some(window) if remaining > window => booking_ids.last().copied(),
+        _ => None,
+    };
+
+    return BookingPage {
+        booking_ids,
+        total,
+        next_cursor,
+    };
+}
+
+/// Iterate over the bookings satisfying every criterion in the provided filter, ordered by
+/// booking id. Bookings are fetched in batches as the iterator advances, and storage is only
+/// locked while each batch is fetched, so the whole dataset is never cloned at once. Bookings
+/// changed after the iterator was created are returned as they are when reached, and bookings
+/// deleted are skipped.
+///
+/// # Arguments
+///
+/// * `filter` - A BookingFilter containing the criteria the bookings must match.
+///
+/// # Examples
+///
+/// ```
+/// for booking in iter_filtered(&BookingFilter::default()) { ... }
+/// ```
+pub fn iter_filtered(filter: &BookingFilter) -> impl Iterator<Item = RoomBooking> + Send {
+    return BookingCursor::new(fetch_filtered_ids(filter));
+}
+
+/// Fetch the bookings with the provided booking ids, in the order given. Bookings which do not
+/// exist are skipped.
+///
+/// # Arguments
+///
+/// * `booking_ids` - The ids of the bookings to fetch
+///
+/// # Examples
+///
+/// ```
+/// bookings = fetch_many(&[1, 2, 3]);
+/// ```
+pub fn fetch_many(booking_ids: &[u32]) -> Vec<RoomBooking> {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return Vec::new(),
+        };
+
+    return booking_ids
+        .iter()
+        .filter_map(|booking_id| booking_list.get(booking_id).cloned())
+        .collect();
+}
+
+/// Count the bookings satisfying every criterion in the provided filter, without cloning them.
+///
+/// # Arguments
+///
+/// * `filter` - A BookingFilter containing the criteria the counted bookings must match.
+///
+/// # Examples
+///
+/// ```
+/// count = count_filtered(&BookingFilter::default());
+/// ```
+pub fn count_filtered(filter: &BookingFilter) -> usize {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        match lock_list(&store().booking_list) {
+            Ok(guard) => guard,
+            Err(_) => return 0,
+        };
+
+    return scan(&booking_list)
+        .values()
+        .filter(|booking: &&RoomBooking| filter.matches(booking))
+        .count();
+}
+
+/// Fetch the changes made after the provided sequence number, in the order they were made.
+///
+/// # Arguments
+///
+/// * `since` - The sequence number of the last change already seen. Use 0 to fetch all changes.
+/// * `limit` - The maximum number of changes to return, if any.
+///
+/// # Examples
+///
+/// ```
+/// changes = fetch_changes(42, Some(100));
+/// ```
+pub fn fetch_changes(since: u64, limit: Option<usize>) -> ChangeSet {
+    let change_log: std::sync::MutexGuard<'_, Vec<BookingChange>> =
+        match lock_list(&store().change_log) {
+            Ok(guard) => guard,
+            Err(_) => {
+                return ChangeSet {
+                    changes: Vec::new(),
+                    next_cursor: since,
+                }
+            }
+        };
+
+    // Sequence numbers are assigned in order, so the first unseen change can be found directly.
+    let start: usize = change_log.partition_point(|change| change.sequence <= since);
+    let changes: Vec<BookingChange> = change_log[start..]
+        .iter()
+        .take(limit.unwrap_or(usize::MAX))
+        .cloned()
+        .collect();
+    let next_cursor: u64 = changes.last().map_or(since, |change| change.sequence);
+
+    return ChangeSet {
+        changes,
+        next_cursor,
+    };
+}
+
+/// Fetch the changes made to every record after the provided sequence number, in the order they
+/// were made, merging the booking change log with the change log of other records. Sequence
+/// numbers are shared between the logs, so each change appears once, in a single order, across
+/// every kind of record.
+///
+/// # Arguments
+///
+/// * `after` - The sequence number of the last change already seen. Use 0 to fetch all changes.
+/// * `limit` - The maximum number of changes to return, if any.
+///
+/// # Examples
+///
+/// ```
+/// page = fetch_cdc(42, Some(100));
+/// ```
+pub fn fetch_cdc(after: u64, limit: Option<usize>) -> CdcPage {
+    let empty = || CdcPage {
+        records: Vec::new(),
+        next_cursor: after,
+    };
+    let change_log: std::sync::MutexGuard<'_, Vec<BookingChange>> =
+        match lock_list(&store().change_log) {
+            Ok(guard) => guard,
+            Err(_) => return empty(),
+        };
+    let cdc_log: std::sync::MutexGuard<'_, Vec<CdcRecord>> = match lock_list(&store().cdc_log) {
+        Ok(guard) => guard,
+        Err(_) => return empty(),
+    };
+
+    let mut changes = change_log[change_log.partition_point(|change| change.sequence <= after)..]
+        .iter()
+        .peekable();
+    let mut others = cdc_log[cdc_log.partition_point(|record| record.sequence <= after)..]
+        .iter()
+        .peekable();
+    let mut records: Vec<CdcRecord> = Vec::new();
+
+    while records.len() < limit.unwrap_or(usize::MAX) {
+        let record: CdcRecord = match (changes.peek(), others.peek()) {
+            (Some(change), Some(other)) if change.sequence < other.sequence => {
+                CdcRecord::from_change(changes.next().unwrap())
+            }
+            (_, Some(_)) => others.next().unwrap().clone(),
+            (Some(_), None) => CdcRecord::from_change(changes.next().unwrap()),
+            (None, None) => break,
+        };
+        records.push(record);
+    }
+
+    let next_cursor: u64 = records.last().map_or(after, |record| record.sequence);
+    return CdcPage {
+        records,
+        next_cursor,
+    };
+}
+
+/// Fetch the bookings created, updated and deleted since a sync token, in their current state, so
+/// an offline client can reconcile its copy of the bookings. Bookings created and then deleted
+/// since the token are only listed as deleted. If the token is 0, every stored booking is listed
+/// as created.
+///
+/// # Arguments
+///
+/// * `token` - The sync token returned by the previous sync, or 0 for a full sync
+///
+/// # Examples
+///
+/// ```
+/// delta = fetch_delta(42);
+/// ```
+pub fn fetch_delta(token: u64) -> Option<BookingDelta> {
+    // Locked in the same order as apply_retention, so the delta is consistent with the token.
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        lock_list(&store().booking_list).ok()?;
+    let change_log: std::sync::MutexGuard<'_, Vec<BookingChange>> =
+        lock_list(&store().change_log).ok()?;
+    let cdc_log: std::sync::MutexGuard<'_, Vec<CdcRecord>> = lock_list(&store().cdc_log).ok()?;
+
+    let mut delta = BookingDelta {
+        created: Vec::new(),
+        updated: Vec::new(),
+        deleted: Vec::new(),
+        sync_token: next_sequence(&change_log, &cdc_log) - 1,
+    };
+
+    if token == 0 {
+        delta.created = scan(&booking_list).values().cloned().collect();
+        delta.created.sort_by_key(|booking| booking.booking_id);
+        return Some(delta);
+    }
+
+    let mut created: HashMap<u32, bool> = HashMap::new();
+    let start: usize = change_log.partition_point(|change| change.sequence <= token);
+    for change in &change_log[start..] {
+        if let Some(booking_id) = change.booking.booking_id {
+            *created.entry(booking_id).or_default() |= change.change_type == ChangeType::Created;
+        }
+    }
+
+    let start: usize = cdc_log.partition_point(|record| record.sequence <= token);
+    for record in &cdc_log[start..] {
+        if let (CdcOperation::Delete, CdcData::Booking(booking)) = (record.operation, &record.data)
+        {
+            if let Some(booking_id) = booking.booking_id {
+                created.remove(&booking_id);
+                delta.deleted.push(BookingTombstone {
+                    booking_id,
+                    deleted_at: record.recorded_at.clone(),
+                });
+            }
+        }
+    }
+
+    let mut booking_ids: Vec<(u32, bool)> = created.into_iter().collect();
+    booking_ids.sort();
+    for (booking_id, created) in booking_ids {
+        let booking: RoomBooking = match booking_list.get(&booking_id) {
+            Some(booking) => booking.clone(),
+            None => continue,
+        };
+
+        match created {
+            true => delta.created.push(booking),
+            false => delta.updated.push(booking),
+        }
+    }
+
+    delta.deleted.sort_by_key(|tombstone| tombstone.booking_id);
+    return Some(delta);
+}
+
+/// Fetch the history of a booking from the change log, listing every change made to the booking
+/// in the order it was made. Returns None if the booking does not exist.
+///
+/// # Arguments
+///
+/// * `booking_id` - The id of the booking to return the history of.
+///
+/// # Examples
+///
+/// ```
+/// history = fetch_history(1);
+/// ```
+pub fn fetch_history(booking_id: u32) -> Option<Vec<BookingHistoryEntry>> {
+    fetch_by_id(booking_id)?;
+
+    let change_log: std::sync::MutexGuard<'_, Vec<BookingChange>> =
+        match lock_list(&store().change_log) {
+            Ok(guard) => guard,
+            Err(_) => return None,
+        };
+
+    let changes: Vec<BookingChange> = change_log
+        .iter()
+        .filter(|change| change.booking.booking_id == Some(booking_id))
+        .cloned()
+        .collect();
+
+    return Some(BookingHistoryEntry::from_changes(&changes));
+}
+
+/// Fetch every stored record referencing a customer: their bookings, and the change log entries
+/// for those bookings. Used to respond to subject access requests.
+///
+/// # Arguments
+///
+/// * `customer_id` - The customer id
+///
+/// # Examples
+///
+/// ```
+/// export = export_customer(1);
+/// ```
+pub fn export_customer(customer_id: u32) -> Option<CustomerExport> {
+    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        lock_list(&store().booking_list).ok()?;
+    let change_log: std::sync::MutexGuard<'_, Vec<BookingChange>> =
+        lock_list(&store().change_log).ok()?;
+
+    let mut bookings: Vec<RoomBooking> = scan(&booking_list)
+        .values()
+        .filter(|booking| booking.customer_id == customer_id)
+        .cloned()
+        .collect();
+    bookings.sort_by_key(|booking| booking.booking_id);
+
+    let changes: Vec<BookingChange> = change_log
+        .iter()
+        .filter(|change| {
+            change.booking.customer_id == customer_id
+                || bookings
+                    .iter()
+                    .any(|booking| booking.booking_id == change.booking.booking_id)
+        })
+        .cloned()
+        .collect();
+
+    return Some(CustomerExport {
+        customer_id,
+        exported_at: clock::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        bookings,
+        changes,
+    });
+}
+
+/// Erases the personal data held for a customer, anonymizing each of their bookings, and the
+/// change log entries and undelivered events for those bookings. The dates, room types, statuses
+/// and prices of the bookings are kept, so aggregate statistics are unaffected. The erasure is
+/// recorded in the change log against each booking. Returns None if no data is held for the
+/// customer, or the customer id is ```ANONYMOUS_CUSTOMER_ID```.
+///
+/// # Arguments
+///
+/// * `customer_id` - The customer id
+///
+/// # Examples
+///
+/// ```
+/// anonymization = anonymize_customer(1);
+/// ```
+pub fn anonymize_customer(customer_id: u32) -> Option<Anonymization> {
+    if customer_id == anonymization::ANONYMOUS_CUSTOMER_ID {
+        return None;
+    }
+
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        lock_list(&store().booking_list).ok()?;
+
+    let mut booking_ids: Vec<u32> = scan(&booking_list)
+        .values()
+        .filter(|booking| booking.customer_id == customer_id)
+        .filter_map(|booking| booking.booking_id)
+        .collect();
+    booking_ids.sort();
+
+    let references = |booking: &RoomBooking| -> bool {
+        return booking.customer_id == customer_id
+            || booking
+                .booking_id
+                .is_some_and(|booking_id| booking_ids.contains(&booking_id));
+    };
+
+    let mut changes: usize = 0;
+    if let Ok(mut change_log) = lock_list(&store().change_log) {
+        for change in change_log
+            .iter_mut()
+            .filter(|change| references(&change.booking))
+        {
+            anonymization::anonymize(&mut change.booking);
+            changes += 1;
+        }
+        write_file(CHANGES_PATH, &*change_log);
+    }
+
+    let store: &Store = store();
+    for (events, path) in [
+        (&store.outbox, OUTBOX_PATH),
+        (&store.dead_letters, DEAD_LETTERS_PATH),
+    ] {
+        if let Ok(mut events) = events.lock() {
+            for event in events
+                .iter_mut()
+                .filter(|event| references(&event.change.booking))
+            {
+                anonymization::anonymize(&mut event.change.booking);
+            }
+            write_file(path, &*events);
+        }
+    }
 
-    if booking.status != Some(BookingStatus::Confirmed) {
-        return false;
+    if booking_ids.is_empty() && changes == 0 {
+        return None;
     }
 
-    booking.set_status(status);
-    save_snapshot(&*booking_list);
-    return true;
+    for booking_id in &booking_ids {
+        if let Some(booking) = booking_list.get_mut(booking_id) {
+            anonymization::anonymize(booking);
+            touch(booking);
+            record_change(ChangeType::Anonymized, booking);
+        }
+    }
+
+    save_snapshot(&booking_list);
+    return Some(Anonymization {
+        customer_id,
+        bookings: booking_ids.len(),
+        changes,
+    });
 }
 
-/// Fetch a booking using a booking id.
+/// Removes the bookings which checked out before a cutoff date, along with their change log
+/// entries. Archived bookings are anonymized and appended to the archive saved in the path
+/// defined by ```ARCHIVE_PATH```, and purged bookings are deleted. A delete is recorded in the
+/// change log of other records for each booking removed, holding the anonymized booking, so sync
+/// clients and change-data-capture consumers learn of the removal. Nothing is removed in a dry
+/// run, but the report lists the bookings which would be. Returns None if storage is unavailable.
 ///
 /// # Arguments
 ///
-/// * `booking_id` - The booking id of the booking to return.
+/// * `cutoff` - Bookings which checked out before this date are removed
+/// * `action` - Whether bookings are archived or purged
+/// * `dry_run` - Whether to only report the bookings which would be removed
 ///
 /// # Examples
 ///
 /// ```
-/// booking = fetch_by_id(1);
+/// report = apply_retention(cutoff, RetentionAction::Archive, true);
 /// ```
-pub fn fetch_by_id(booking_id: u32) -> Option<RoomBooking> {
-    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
-        match BOOKING_LIST.lock() {
-            Ok(guard) => guard,
-            Err(_) => return None,
+pub fn apply_retention(
+    cutoff: NaiveDate,
+    action: RetentionAction,
+    dry_run: bool,
+) -> Option<RetentionReport> {
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        lock_list(&store().booking_list).ok()?;
+    let mut change_log: std::sync::MutexGuard<'_, Vec<BookingChange>> =
+        lock_list(&store().change_log).ok()?;
+    let mut cdc_log: std::sync::MutexGuard<'_, Vec<CdcRecord>> =
+        lock_list(&store().cdc_log).ok()?;
+
+    let cutoff_date: String = cutoff.format(DATE_FORMAT).to_string();
+    let mut expired: Vec<RoomBooking> = scan(&booking_list)
+        .values()
+        .filter(|booking| booking.check_out_date < cutoff_date)
+        .cloned()
+        .collect();
+    expired.sort_by_key(|booking| booking.booking_id);
+
+    let booking_ids: Vec<Option<u32>> = expired.iter().map(|booking| booking.booking_id).collect();
+    let changes: usize = change_log
+        .iter()
+        .filter(|change| booking_ids.contains(&change.booking.booking_id))
+        .count();
+
+    let report = RetentionReport {
+        cutoff_date,
+        action,
+        dry_run,
+        bookings: expired
+            .iter()
+            .map(|booking| RetainedBooking {
+                booking_id: booking.booking_id,
+                check_out_date: booking.check_out_date.clone(),
+                status: booking.status.clone(),
+            })
+            .collect(),
+        changes,
+    };
+
+    if dry_run || expired.is_empty() {
+        return Some(report);
+    }
+
+    for booking in expired.iter_mut() {
+        anonymization::anonymize(booking);
+    }
+
+    if action == RetentionAction::Archive {
+        let mut archive: Vec<RoomBooking> = match metadata(path(ARCHIVE_PATH)) {
+            Ok(_) => read_file(ARCHIVE_PATH).ok()?,
+            Err(_) => Vec::new(),
         };
 
-    let result: Option<RoomBooking> = booking_list.get(&booking_id).cloned();
-    return result;
+        archive.extend(expired.iter().cloned());
+
+        if !write_file(ARCHIVE_PATH, &archive) {
+            return None;
+        }
+    }
+
+    remove_bookings(&mut booking_list, &mut change_log, &mut cdc_log, expired);
+    return Some(report);
 }
 
-/// Fetch a list of bookings made by a specific customer.
+/// Removes the sandbox bookings created before a time, along with their change log entries. A
+/// delete is recorded in the change log of other records for each booking removed, so sync
+/// clients learn of the removal. Returns the ids of the bookings removed, or None if storage is
+/// unavailable.
 ///
 /// # Arguments
 ///
-/// * `customer_id` - The customer id of the bookings to return.
+/// * `created_before` - Sandbox bookings created before this time (RFC 3339) are removed
 ///
 /// # Examples
 ///
 /// ```
-/// bookings = fetch_by_customer_id(1);
+/// removed = wipe_sandbox(&cutoff);
 /// ```
-pub fn fetch_by_customer_id(customer_id: u32) -> Vec<RoomBooking> {
-    let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
-        match BOOKING_LIST.lock() {
-            Ok(guard) => guard,
-            Err(_) => return Vec::new(),
-        };
+pub fn wipe_sandbox(created_before: &str) -> Option<Vec<u32>> {
+    let mut booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
+        lock_list(&store().booking_list).ok()?;
+    let mut change_log: std::sync::MutexGuard<'_, Vec<BookingChange>> =
+        lock_list(&store().change_log).ok()?;
+    let mut cdc_log: std::sync::MutexGuard<'_, Vec<CdcRecord>> =
+        lock_list(&store().cdc_log).ok()?;
 
-    let results: Vec<RoomBooking> = booking_list
+    let mut expired: Vec<RoomBooking> = scan(&booking_list)
         .values()
-        .filter(|booking: &&RoomBooking| booking.customer_id == customer_id)
+        .filter(|booking| booking.sandbox)
+        .filter(|booking| {
+            booking
+                .created_at
+                .as_ref()
+                .is_none_or(|created_at| created_at.as_str() < created_before)
+        })
         .cloned()
         .collect();
+    expired.sort_by_key(|booking| booking.booking_id);
 
-    results
+    let booking_ids: Vec<u32> = expired
+        .iter()
+        .filter_map(|booking| booking.booking_id)
+        .collect();
+    if !expired.is_empty() {
+        remove_bookings(&mut booking_list, &mut change_log, &mut cdc_log, expired);
+    }
+
+    return Some(booking_ids);
 }
 
-/// Fetch a list of bookings with a specific check in date.
+/// Removes bookings from storage, along with their change log entries, recording a delete in the
+/// change log of other records for each booking removed, and saves the bookings and logs.
 ///
 /// # Arguments
 ///
-/// * `date` - A string containing the check in date of the bookings to return.
+/// * `booking_list` - The stored bookings
+/// * `change_log` - The change log of bookings
+/// * `cdc_log` - The change log of other records
+/// * `removed` - The bookings to remove, as recorded in the deletes
+fn remove_bookings(
+    booking_list: &mut HashMap<u32, RoomBooking>,
+    change_log: &mut Vec<BookingChange>,
+    cdc_log: &mut Vec<CdcRecord>,
+    removed: Vec<RoomBooking>,
+) {
+    let booking_ids: Vec<Option<u32>> = removed.iter().map(|booking| booking.booking_id).collect();
+
+    // Sequence numbers are assigned before the change log entries are removed, so they continue
+    // to increase even if the latest change was to a removed booking.
+    let first_sequence: u64 = next_sequence(change_log, cdc_log);
+    let recorded_at: String = clock::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    for (sequence, booking) in (first_sequence..).zip(removed) {
+        cdc_log.push(CdcRecord {
+            sequence,
+            recorded_at: recorded_at.clone(),
+            operation: CdcOperation::Delete,
+            key: booking
+                .booking_id
+                .map_or(String::new(), |id| id.to_string()),
+            data: CdcData::Booking(Box::new(booking)),
+        });
+    }
+
+    booking_list.retain(|booking_id, _| !booking_ids.contains(&Some(*booking_id)));
+    change_log.retain(|change| !booking_ids.contains(&change.booking.booking_id));
+
+    if let Ok(mut modified_list) = lock_list(&store().modified_list) {
+        modified_list.retain(|booking_id, _| !booking_ids.contains(&Some(*booking_id)));
+    }
+
+    update_reviews(|review_list| {
+        let held: usize = review_list.len();
+        review_list.retain(|booking_id| !booking_ids.contains(&Some(*booking_id)));
+        return review_list.len() != held;
+    });
+
+    save_snapshot(booking_list);
+    write_file(CHANGES_PATH, &*change_log);
+    write_file(CDC_PATH, &*cdc_log);
+}
+
+/// Compacts the change log, keeping only the most recent change for each booking, and saves the
+/// bookings and change log. Changes keep their original sequence numbers, so existing sync
+/// cursors remain valid. The history of each booking is discarded, other than its latest change.
+/// Returns the number of changes removed.
 ///
 /// # Examples
 ///
 /// ```
-/// bookings = fetch_by_check_in_date("2020-01-01".to_string());
+/// removed = compact();
 /// ```
-pub fn fetch_by_check_in_date(date: &str) -> Vec<RoomBooking> {
+pub fn compact() -> Result<usize, Box<dyn std::error::Error>> {
     let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
-        match BOOKING_LIST.lock() {
-            Ok(guard) => guard,
-            Err(_) => return Vec::new(),
-        };
+        lock_list(&store().booking_list).map_err(|_| "Booking storage is unavailable")?;
+    let mut change_log: std::sync::MutexGuard<'_, Vec<BookingChange>> =
+        lock_list(&store().change_log).map_err(|_| "Change log is unavailable")?;
 
-    let results: Vec<RoomBooking> = booking_list
-        .values()
-        .filter(|booking: &&RoomBooking| booking.check_in_date == date)
-        .cloned()
-        .collect();
+    let mut latest: HashMap<u32, u64> = HashMap::new();
+    for change in change_log.iter() {
+        if let Some(booking_id) = change.booking.booking_id {
+            latest.insert(booking_id, change.sequence);
+        }
+    }
 
-    results
+    let original_length: usize = change_log.len();
+    change_log.retain(|change| match change.booking.booking_id {
+        Some(booking_id) => latest.get(&booking_id) == Some(&change.sequence),
+        None => false,
+    });
+
+    if !save_snapshot(&booking_list) || !write_file(CHANGES_PATH, &*change_log) {
+        return Err("Unable to save snapshot".into());
+    }
+
+    return Ok(original_length - change_log.len());
 }
 
-/// Fetch a list of bookings made by a specific customer.
-///
-/// # Arguments
-///
-/// * `customer_id` - The customer id of the bookings to return.
+/// Checks the integrity of the stored bookings and change log. Returns a list describing each
+/// problem found, which is empty if no problems were found.
 ///
 /// # Examples
 ///
 /// ```
-/// bookings = fetch_by_customer_id(1);
+/// problems = verify();
 /// ```
-pub fn fetch_by_room_type_id(room_type_id: u8) -> Vec<RoomBooking> {
+pub fn verify() -> Vec<String> {
+    let mut problems: Vec<String> = Vec::new();
+
     let booking_list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> =
-        match BOOKING_LIST.lock() {
+        match lock_list(&store().booking_list) {
             Ok(guard) => guard,
-            Err(_) => return Vec::new(),
+            Err(_) => return vec!["Booking storage is unavailable".to_string()],
         };
 
-    let results: Vec<RoomBooking> = booking_list
-        .values()
-        .filter(|booking: &&RoomBooking| booking.room_type_id == room_type_id)
-        .cloned()
-        .collect();
+    let mut booking_ids: Vec<&u32> = booking_list.keys().collect();
+    booking_ids.sort();
 
-    results
-}
+    for booking_id in booking_ids {
+        let booking: &RoomBooking = &booking_list[booking_id];
 
-/// Fetch a list of all room bookings.
-///
-/// # Examples
-///
-/// ```
-/// bookings = fetch_all();
-/// ```
-pub fn fetch_all() -> Vec<RoomBooking> {
-    let list: std::sync::MutexGuard<'_, HashMap<u32, RoomBooking>> = match BOOKING_LIST.lock() {
-        Ok(guard) => guard,
-        Err(_) => {
-            return Vec::new();
+        if booking.booking_id != Some(*booking_id) {
+            problems.push(format!(
+                "Booking {} is stored with booking id {:?}",
+                booking_id, booking.booking_id
+            ));
         }
-    };
 
-    return list.values().cloned().collect();
+        if booking.status.is_none() {
+            problems.push(format!("Booking {} has no status", booking_id));
+        }
+
+        for error in booking.validate() {
+            problems.push(format!("Booking {}: {}", booking_id, error));
+        }
+    }
+
+    let change_log: std::sync::MutexGuard<'_, Vec<BookingChange>> =
+        match lock_list(&store().change_log) {
+            Ok(guard) => guard,
+            Err(_) => {
+                problems.push("Change log is unavailable".to_string());
+                return problems;
+            }
+        };
+
+    for pair in change_log.windows(2) {
+        if pair[1].sequence <= pair[0].sequence {
+            problems.push(format!(
+                "Change {} is recorded after change {}",
+                pair[1].sequence, pair[0].sequence
+            ));
+        }
+    }
+
+    return problems;
 }
 
 #[cfg(test)]
 mod tests {
     use super::room_booking::RoomBooking;
+    use crate::clock::ManualClock;
+    use crate::config::WebhookSubscription;
+    use crate::storage::isolated::IsolatedStorage;
+    use crate::storage::pricing_record::PricingRecord;
     use crate::storage::*;
+    use std::sync::Arc;
 
     /// Describes a single room booking
     fn dummmy_booking() -> RoomBooking {
@@ -263,6 +4296,33 @@ mod tests {
             check_in_date: "2020-01-01".to_string(),
             check_out_date: "2020-01-08".to_string(),
             status: None,
+            expected_arrival_time: None,
+            late_check_out_time: None,
+            day_use: false,
+            start_time: None,
+            end_time: None,
+            billing_milestones: Vec::new(),
+            series_id: None,
+            cancellation: None,
+            no_show_fee: None,
+            payment_due_by: None,
+            addons: Vec::new(),
+            promo_code: None,
+            discount: None,
+            guests: 1,
+            tax_exemptions: Vec::new(),
+            account_id: None,
+            agent_id: None,
+            channel_reservation_id: None,
+            children: 0,
+            rate_plans: Vec::new(),
+            pricing: None,
+            payments: Vec::new(),
+            refunds: Vec::new(),
+            created_at: None,
+            updated_at: None,
+            cancelled_at: None,
+            sandbox: false,
         };
     }
 
@@ -275,12 +4335,57 @@ mod tests {
             check_in_date: "2020-01-01".to_string(),
             check_out_date: "2020-01-08".to_string(),
             status: Some(BookingStatus::Confirmed),
+            expected_arrival_time: None,
+            late_check_out_time: None,
+            day_use: false,
+            start_time: None,
+            end_time: None,
+            billing_milestones: Vec::new(),
+            series_id: None,
+            cancellation: None,
+            no_show_fee: None,
+            payment_due_by: None,
+            addons: Vec::new(),
+            promo_code: None,
+            discount: None,
+            guests: 1,
+            tax_exemptions: Vec::new(),
+            account_id: None,
+            agent_id: None,
+            channel_reservation_id: None,
+            children: 0,
+            rate_plans: Vec::new(),
+            pricing: None,
+            payments: Vec::new(),
+            refunds: Vec::new(),
+            created_at: None,
+            updated_at: None,
+            cancelled_at: None,
+            sandbox: false,
         };
     }
 
+    /// Creates the dummy booking at a fixed time, returning the booking as it is expected to be
+    /// stored.
+    fn create_at_fixed_time() -> (Result<RoomBooking, CreateError>, RoomBooking) {
+        let created_at = DateTime::parse_from_rfc3339("2019-12-01T09:00:00Z").unwrap();
+        let clock: Arc<ManualClock> = Arc::new(ManualClock::new(created_at.with_timezone(&Utc)));
+
+        let mut expected: RoomBooking = dummmy_booking_success();
+        expected.created_at = Some("2019-12-01T09:00:00Z".to_string());
+        expected.updated_at = expected.created_at.clone();
+
+        return (
+            clock::scoped(clock, || create(dummmy_booking(), true)),
+            expected,
+        );
+    }
+
     #[test]
     fn create_booking() {
-        assert_eq!(create(dummmy_booking()), Ok(dummmy_booking_success()));
+        let _storage = IsolatedStorage::new();
+        let (created, expected) = create_at_fixed_time();
+        assert_eq!(created, Ok(expected));
 
         let failed_booking = RoomBooking {
             booking_id: Some(5),
@@ -289,34 +4394,702 @@ mod tests {
             check_in_date: "2020-01-01".to_string(),
             check_out_date: "2020-01-08".to_string(),
             status: None,
+            expected_arrival_time: None,
+            late_check_out_time: None,
+            day_use: false,
+            start_time: None,
+            end_time: None,
+            billing_milestones: Vec::new(),
+            series_id: None,
+            cancellation: None,
+            no_show_fee: None,
+            payment_due_by: None,
+            addons: Vec::new(),
+            promo_code: None,
+            discount: None,
+            guests: 1,
+            tax_exemptions: Vec::new(),
+            account_id: None,
+            agent_id: None,
+            channel_reservation_id: None,
+            children: 0,
+            rate_plans: Vec::new(),
+            pricing: None,
+            payments: Vec::new(),
+            refunds: Vec::new(),
+            created_at: None,
+            updated_at: None,
+            cancelled_at: None,
+            sandbox: false,
         };
 
-        assert!(create(failed_booking).is_err());
+        assert!(create(failed_booking, true).is_err());
     }
 
     #[test]
     fn fetch_booking() {
-        // Ensure a booking exists before continuing tests.
-        while let None = fetch_by_id(1) {
-            std::thread::sleep(std::time::Duration::from_secs(1));
-        }
+        let _storage = IsolatedStorage::new();
+        assert!(fetch_by_id(1).is_none());
+        let (created, expected) = create_at_fixed_time();
+        assert!(created.is_ok());
 
         let booking: RoomBooking = fetch_by_id(1).unwrap();
-        assert_eq!(booking, dummmy_booking_success());
+        assert_eq!(booking, expected);
     }
 
     #[test]
-    fn update_booking_status() {
-        // Wait for a booking to exist before continuing. Ensures we create a booking with
-        // the expected id for this test.
-        while let None = fetch_by_id(1) {
-            std::thread::sleep(std::time::Duration::from_secs(1));
+    fn iterate_filtered_bookings() {
+        let _storage = IsolatedStorage::new();
+        for _ in 0..3 {
+            assert!(create(dummmy_booking(), true).is_ok());
         }
 
-        assert!(create(dummmy_booking()).is_ok());
+        let filter = BookingFilter {
+            customer_id: Some(dummmy_booking().customer_id),
+            ..Default::default()
+        };
+        let booking_ids: Vec<Option<u32>> = iter_filtered(&filter)
+            .map(|booking| booking.booking_id)
+            .collect();
+
+        assert_eq!(booking_ids, vec![Some(1), Some(2), Some(3)]);
+    }
+
+    #[test]
+    fn stamp_booking_times() {
+        let _storage = IsolatedStorage::new();
+        let created = DateTime::parse_from_rfc3339("2019-12-01T09:00:00Z").unwrap();
+        let clock: Arc<ManualClock> = Arc::new(ManualClock::new(created.with_timezone(&Utc)));
+
+        clock::scoped(clock.clone(), || {
+            let booking: RoomBooking = create(dummmy_booking(), true).unwrap();
+            assert_eq!(booking.created_at.as_deref(), Some("2019-12-01T09:00:00Z"));
+            assert_eq!(booking.updated_at, booking.created_at);
+            assert_eq!(booking.cancelled_at, None);
+
+            clock.advance(Duration::hours(1));
+            assert!(cancel(1, None).is_ok());
+            let booking: RoomBooking = fetch_by_id(1).unwrap();
+            assert_eq!(booking.created_at.as_deref(), Some("2019-12-01T09:00:00Z"));
+            assert_eq!(booking.updated_at.as_deref(), Some("2019-12-01T10:00:00Z"));
+            assert_eq!(booking.cancelled_at, booking.updated_at);
+
+            clock.advance(Duration::hours(1));
+            assert!(status(1, BookingStatus::Confirmed).is_ok());
+            let booking: RoomBooking = fetch_by_id(1).unwrap();
+            assert_eq!(booking.updated_at.as_deref(), Some("2019-12-01T11:00:00Z"));
+            assert_eq!(booking.cancelled_at, None);
+        });
+    }
+
+    #[test]
+    fn update_booking_status() {
+        let _storage = IsolatedStorage::new();
+        assert!(create(dummmy_booking(), true).is_ok());
+        assert!(create(dummmy_booking(), true).is_ok());
 
-        assert_eq!(status(2, BookingStatus::Complete), true);
+        assert_eq!(
+            status(2, BookingStatus::Complete),
+            Err(TransitionError::Illegal {
+                from: Some(BookingStatus::Confirmed),
+                to: BookingStatus::Complete,
+            })
+        );
+        assert!(status(2, BookingStatus::CheckedIn).is_ok());
+        assert!(status(2, BookingStatus::Complete).is_ok());
         let booking: RoomBooking = fetch_by_id(2).unwrap();
         assert_eq!(booking.status, Some(BookingStatus::Complete));
     }
+
+    #[test]
+    fn approve_reviewed_booking() {
+        let _storage = IsolatedStorage::new();
+        assert!(create(dummmy_booking(), true).is_ok());
+        lock_list(&store().booking_list)
+            .unwrap()
+            .get_mut(&1)
+            .unwrap()
+            .set_status(BookingStatus::PendingReview);
+
+        assert_eq!(approve(1), Ok(BookingStatus::Confirmed));
+        assert_eq!(
+            fetch_by_id(1).unwrap().status,
+            Some(BookingStatus::Confirmed)
+        );
+        assert_eq!(
+            approve(1),
+            Err(TransitionError::WrongStatus {
+                expected: BookingStatus::PendingReview,
+                actual: Some(BookingStatus::Confirmed),
+            })
+        );
+    }
+
+    #[test]
+    fn validate_booking() {
+        assert!(dummmy_booking().validate().is_empty());
+
+        let mut booking: RoomBooking = dummmy_booking();
+        booking.check_in_date = "2020-02-30".to_string();
+        booking.check_out_date = "2020-01-01".to_string();
+        assert_eq!(booking.validate().len(), 1);
+
+        booking.check_in_date = "2020-01-02".to_string();
+        assert_eq!(
+            booking.validate(),
+            vec!["checkOutDate must be after checkInDate".to_string()]
+        );
+
+        let mut booking: RoomBooking = dummmy_booking();
+        booking.guests = 2;
+        booking.children = 2;
+        assert_eq!(
+            booking.validate(),
+            vec!["At least one guest must be an adult".to_string()]
+        );
+    }
+
+    #[test]
+    fn validate_booking_times() {
+        let mut booking: RoomBooking = dummmy_booking();
+        booking.expected_arrival_time = Some("18:30".to_string());
+        booking.late_check_out_time = Some("13:00".to_string());
+        assert!(booking.validate().is_empty());
+
+        booking.expected_arrival_time = Some("09:00".to_string());
+        booking.late_check_out_time = Some("25:00".to_string());
+        assert_eq!(booking.validate().len(), 2);
+
+        booking.expected_arrival_time = None;
+        booking.late_check_out_time = Some("10:00".to_string());
+        assert_eq!(booking.validate().len(), 1);
+    }
+
+    #[test]
+    fn validate_day_use_booking() {
+        let mut booking: RoomBooking = dummmy_booking();
+        booking.day_use = true;
+        booking.check_out_date = booking.check_in_date.clone();
+        booking.start_time = Some("09:00".to_string());
+        booking.end_time = Some("17:00".to_string());
+        assert!(booking.validate().is_empty());
+
+        let mut other: RoomBooking = booking.clone();
+        other.start_time = Some("16:00".to_string());
+        other.end_time = Some("20:00".to_string());
+        assert!(booking.overlaps_day_use(&other));
+
+        other.start_time = Some("17:00".to_string());
+        assert!(!booking.overlaps_day_use(&other));
+
+        booking.end_time = Some("08:00".to_string());
+        booking.check_out_date = "2020-01-02".to_string();
+        assert_eq!(booking.validate().len(), 2);
+    }
+
+    #[test]
+    fn booking_nights() {
+        let mut booking: RoomBooking = dummmy_booking();
+        booking.check_out_date = "2020-01-03".to_string();
+        assert_eq!(booking.nights(), vec!["2020-01-01", "2020-01-02"]);
+
+        booking.set_status(BookingStatus::NoShow);
+        assert!(!booking.holds_room());
+    }
+
+    #[test]
+    fn deliver_outbox_event() {
+        let _storage = IsolatedStorage::new();
+        let sequence: u64 = 1;
+        lock_list(&store().outbox).unwrap().push(OutboxEvent::new(
+            BookingChange {
+                sequence,
+                recorded_at: String::new(),
+                change_type: ChangeType::Created,
+                booking: dummmy_booking_success(),
+            },
+            EventTopic::Created,
+        ));
+
+        assert!(mark_delivered(sequence, "http://a.example.com"));
+        assert!(mark_delivered(sequence, "http://a.example.com"));
+        assert!(!fail_event(sequence, "Connection refused", 2));
+        let event: Option<OutboxEvent> = fetch_outbox(None)
+            .into_iter()
+            .find(|event| event.change.sequence == sequence);
+        assert_eq!(event.clone().map(|event| event.attempts), Some(1));
+        assert_eq!(
+            event.map(|event| event.delivered_to),
+            Some(vec!["http://a.example.com".to_string()])
+        );
+
+        assert!(fail_event(sequence, "Connection refused", 2));
+        assert!(fetch_dead_letter(sequence).is_some());
+        assert!(!complete_event(sequence));
+
+        assert!(redeliver_dead_letter(sequence).is_some());
+        assert!(fetch_dead_letter(sequence).is_none());
+        assert!(complete_event(sequence));
+        assert!(!complete_event(sequence));
+    }
+
+    #[test]
+    fn fetch_changes_to_every_record() {
+        let _storage = IsolatedStorage::new();
+        assert!(create(dummmy_booking(), true).is_ok());
+        let restriction: SellRestriction = create_restriction(SellRestriction {
+            restriction_id: None,
+            restriction_type: sell_restriction::RestrictionType::Blackout,
+            room_type_id: Some(1),
+            start_date: "2020-02-01".to_string(),
+            end_date: "2020-02-02".to_string(),
+            reason: None,
+        })
+        .unwrap();
+        assert!(cancel(1, None).is_ok());
+        assert!(delete_restriction(restriction.restriction_id.unwrap()).is_some());
+
+        let page: CdcPage = fetch_cdc(0, None);
+        let sequences: Vec<u64> = page.records.iter().map(|record| record.sequence).collect();
+        assert_eq!(sequences, vec![1, 2, 3, 4]);
+        assert_eq!(page.next_cursor, 4);
+        assert!(matches!(page.records[1].data, CdcData::SellRestriction(_)));
+        assert_eq!(page.records[2].operation, CdcOperation::Update);
+        assert_eq!(page.records[3].operation, CdcOperation::Delete);
+
+        let page: CdcPage = fetch_cdc(1, Some(2));
+        assert_eq!(page.records.len(), 2);
+        assert_eq!(page.records[0].key, "1");
+        assert_eq!(page.next_cursor, 3);
+        assert_eq!(fetch_changes(0, None).next_cursor, 3);
+    }
+
+    #[test]
+    fn fetch_booking_delta() {
+        let _storage = IsolatedStorage::new();
+        assert!(create(dummmy_booking(), true).is_ok());
+        assert!(create(dummmy_booking(), true).is_ok());
+        assert_eq!(fetch_delta(0).unwrap().created.len(), 2);
+
+        let token: u64 = fetch_delta(0).unwrap().sync_token;
+        assert!(cancel(1, None).is_ok());
+        let mut booking: RoomBooking = dummmy_booking();
+        booking.check_in_date = "2020-03-01".to_string();
+        booking.check_out_date = "2020-03-03".to_string();
+        assert!(create(booking, true).is_ok());
+
+        let delta: BookingDelta = fetch_delta(token).unwrap();
+        assert_eq!(delta.created[0].booking_id, Some(3));
+        assert_eq!(delta.updated[0].booking_id, Some(1));
+        assert!(delta.deleted.is_empty());
+
+        let cutoff = NaiveDate::from_ymd_opt(2020, 2, 1).unwrap();
+        assert!(apply_retention(cutoff, RetentionAction::Purge, false).is_some());
+
+        let delta: BookingDelta = fetch_delta(token).unwrap();
+        assert_eq!(delta.created.len(), 1);
+        assert!(delta.updated.is_empty());
+        let deleted: Vec<u32> = delta.deleted.iter().map(|t| t.booking_id).collect();
+        assert_eq!(deleted, vec![1, 2]);
+        assert_eq!(delta.sync_token, 6);
+        assert!(fetch_delta(delta.sync_token).unwrap().deleted.is_empty());
+    }
+
+    #[test]
+    fn wipe_sandbox_bookings() {
+        let _storage = IsolatedStorage::new();
+        let created = DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z").unwrap();
+        let clock: Arc<ManualClock> = Arc::new(ManualClock::new(created.with_timezone(&Utc)));
+
+        clock::scoped(clock.clone(), || {
+            assert!(create(dummmy_booking(), true).is_ok());
+            assert!(create(dummmy_booking(), true).is_ok());
+            clock.advance(Duration::hours(2));
+            assert!(create(dummmy_booking(), true).is_ok());
+        });
+        for booking_id in [2, 3] {
+            if let Some(booking) = lock_list(&store().booking_list)
+                .unwrap()
+                .get_mut(&booking_id)
+            {
+                booking.sandbox = true;
+            }
+        }
+
+        assert_eq!(wipe_sandbox("2024-01-01T13:00:00Z"), Some(vec![2]));
+        assert_eq!(wipe_sandbox("2024-01-01T13:00:00Z"), Some(vec![]));
+        assert!(fetch_by_id(1).is_some());
+        assert!(fetch_by_id(2).is_none());
+        assert!(fetch_by_id(3).is_some());
+    }
+
+    #[test]
+    fn never_reuse_booking_ids() {
+        let storage = IsolatedStorage::new();
+        let create_sandbox = || -> Option<u32> {
+            let booking_id: Option<u32> = create(dummmy_booking(), true).unwrap().booking_id;
+            lock_list(&store().booking_list)
+                .unwrap()
+                .get_mut(&booking_id?)?
+                .sandbox = true;
+            return booking_id;
+        };
+
+        assert!(create(dummmy_booking(), true).is_ok());
+        assert_eq!(create_sandbox(), Some(2));
+        assert_eq!(wipe_sandbox("2100-01-01T00:00:00Z"), Some(vec![2]));
+        assert_eq!(create_sandbox(), Some(3));
+        assert_eq!(wipe_sandbox("2100-01-01T00:00:00Z"), Some(vec![3]));
+
+        std::fs::remove_file(storage.directory().join(CHANGES_PATH)).unwrap();
+        std::fs::remove_file(storage.directory().join(CDC_PATH)).unwrap();
+        lock_list(&store().change_log).unwrap().clear();
+        lock_list(&store().cdc_log).unwrap().clear();
+        *lock_list(&store().last_booking_id).unwrap() = 0;
+        assert!(load_snapshot().is_ok());
+        assert_eq!(create(dummmy_booking(), true).unwrap().booking_id, Some(4));
+    }
+
+    #[test]
+    fn find_any_snapshot_file() {
+        for name in [CDC_PATH, OUTBOX_PATH, BOOKING_IDS_PATH, REVIEWS_PATH] {
+            let _storage = IsolatedStorage::new();
+            assert!(!snapshot_exists());
+            assert!(write_file(name, &Vec::<u32>::new()));
+            assert!(snapshot_exists());
+        }
+    }
+
+    #[test]
+    fn replay_skips_sandbox_changes() {
+        let _storage = IsolatedStorage::new();
+        assert!(create(dummmy_booking(), true).is_ok());
+        lock_list(&store().booking_list)
+            .unwrap()
+            .get_mut(&1)
+            .unwrap()
+            .sandbox = true;
+        assert!(cancel(1, None).is_ok());
+
+        let webhook = WebhookSubscription {
+            url: "http://127.0.0.1:9/events".to_string(),
+            topics: Vec::new(),
+            room_type_ids: Vec::new(),
+        };
+        let report = rocket::async_test(crate::outbox::replay(&webhook, 2, 2));
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.delivered, 0);
+        assert_eq!(report.error, None);
+    }
+
+    #[test]
+    fn expire_pending_payment() {
+        let mut booking: RoomBooking = dummmy_booking_success();
+        booking.set_status(BookingStatus::PendingPayment);
+        booking.payment_due_by = Some("2020-01-01T12:00:00Z".to_string());
+
+        let before = DateTime::parse_from_rfc3339("2020-01-01T11:59:59Z").unwrap();
+        let after = DateTime::parse_from_rfc3339("2020-01-01T12:00:00Z").unwrap();
+        assert!(!booking.payment_expired(before.with_timezone(&Utc)));
+        assert!(booking.payment_expired(after.with_timezone(&Utc)));
+    }
+
+    #[test]
+    fn no_show_from_check_in_date() {
+        let _storage = IsolatedStorage::new();
+        let booking_id: u32 = 1;
+        let mut booking: RoomBooking = dummmy_booking_success();
+        booking.set_booking_id(booking_id);
+        let mut booking_list: HashMap<u32, RoomBooking> = HashMap::from([(booking_id, booking)]);
+
+        // The dummy booking checks in on 2020-01-01, in the hotel's timezone.
+        let day_before = DateTime::parse_from_rfc3339("2019-12-31T12:00:00Z").unwrap();
+        let clock: Arc<ManualClock> = Arc::new(ManualClock::new(day_before.with_timezone(&Utc)));
+
+        clock::scoped(clock.clone(), || {
+            assert_eq!(
+                apply_status(&mut booking_list, booking_id, BookingStatus::NoShow, None),
+                Err(TransitionError::BeforeCheckIn)
+            );
+
+            clock.advance(Duration::days(1));
+            assert_eq!(
+                apply_status(&mut booking_list, booking_id, BookingStatus::NoShow, None),
+                Ok(())
+            );
+        });
+    }
+
+    #[test]
+    fn retain_under_cancellation_policy() {
+        let mut booking: RoomBooking = dummmy_booking_success();
+        assert_eq!(payment::retained(&booking, None), 0);
+
+        booking.set_status(BookingStatus::NoShow);
+        booking.no_show_fee = Some(5000);
+        assert_eq!(payment::retained(&booking, None), 5000);
+
+        booking.set_status(BookingStatus::Cancelled);
+        let in_time = DateTime::parse_from_rfc3339("2019-12-30T12:00:00Z").unwrap();
+        let late = DateTime::parse_from_rfc3339("2020-01-01T12:00:00Z").unwrap();
+        let deposit: u64 = Balance::new(&booking).deposit;
+        assert_eq!(
+            payment::retained(&booking, Some(in_time.with_timezone(&Utc))),
+            0
+        );
+        assert_eq!(
+            payment::retained(&booking, Some(late.with_timezone(&Utc))),
+            deposit
+        );
+        assert_eq!(payment::retained(&booking, None), deposit);
+
+        booking.cancellation = Some(Cancellation {
+            reason: CancellationReason::HotelInitiated,
+            comment: None,
+        });
+        assert_eq!(
+            payment::retained(&booking, Some(late.with_timezone(&Utc))),
+            0
+        );
+    }
+
+    #[test]
+    fn filter_booking() {
+        let mut booking: RoomBooking = dummmy_booking_success();
+        booking.check_in_date = "2020-01-05".to_string();
+
+        let filter = BookingFilter {
+            customer_id: Some(1),
+            room_type_id: Some(3),
+            status: Some("Confirmed".to_string()),
+            from: Some("2020-01-01".to_string()),
+            to: Some("2020-01-31".to_string()),
+            sandbox: Some(false),
+        };
+        assert!(filter.matches(&booking));
+
+        let filter = BookingFilter {
+            customer_id: Some(1),
+            to: Some("2020-01-04".to_string()),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&booking));
+
+        let filter = BookingFilter {
+            status: Some("Unknown".to_string()),
+            ..Default::default()
+        };
+        assert!(!filter.is_valid());
+    }
+
+    #[test]
+    fn keep_saved_status_positions() {
+        let saved = |status: BookingStatus| -> Vec<u8> {
+            return bincode::serialize(&status).unwrap();
+        };
+
+        assert_eq!(saved(BookingStatus::Confirmed), vec![0, 0, 0, 0]);
+        assert_eq!(saved(BookingStatus::Complete), vec![1, 0, 0, 0]);
+        assert_eq!(saved(BookingStatus::Cancelled), vec![2, 0, 0, 0]);
+    }
+
+    #[test]
+    fn reserve_refunds() {
+        let _storage = IsolatedStorage::new();
+        let mut booking: RoomBooking = dummmy_booking_success();
+        booking.status = Some(BookingStatus::NoShow);
+        booking.no_show_fee = Some(3000);
+        booking.payments.push(Payment {
+            amount: 10000,
+            reference: None,
+            received_at: None,
+        });
+        store().booking_list.lock().unwrap().insert(1, booking);
+
+        assert_eq!(refundable(1), Ok(7000));
+        assert_eq!(
+            reserve_refund(1, 8000),
+            Err(RefundError::ExceedsRefundable(7000))
+        );
+        assert_eq!(reserve_refund(1, 0), Err(RefundError::InvalidAmount));
+        assert_eq!(reserve_refund(9, 100), Err(RefundError::NotFound));
+
+        let first: RefundReservation = reserve_refund(1, 5000).unwrap();
+        assert_eq!(
+            reserve_refund(1, 5000),
+            Err(RefundError::ExceedsRefundable(2000))
+        );
+
+        release_refund(&first);
+        assert_eq!(refundable(1), Ok(7000));
+
+        let second: RefundReservation = reserve_refund(1, 5000).unwrap();
+        assert_ne!(second.idempotency_key, first.idempotency_key);
+        let refund = Refund {
+            amount: 5000,
+            reason: None,
+            reference: Some("re_1".to_string()),
+            refunded_at: None,
+        };
+        assert!(complete_refund(&second, refund).is_ok());
+        assert_eq!(fetch_by_id(1).unwrap().refunds.len(), 1);
+        assert_eq!(refundable(1), Ok(2000));
+    }
+
+    #[test]
+    fn reinstate_pending_deposit() {
+        let _storage = IsolatedStorage::new();
+        let mut booking: RoomBooking = dummmy_booking_success();
+        booking.status = Some(BookingStatus::Cancelled);
+        booking.payment_due_by = Some("2019-12-02T00:00:00Z".to_string());
+        booking.pricing = Some(PricingRecord {
+            priced_at: "2019-12-01T00:00:00Z".to_string(),
+            currency: "GBP".to_string(),
+            nightly_prices: Vec::new(),
+            room_lines: vec![pricing::PriceLine::new("Room".to_string(), 1, 10000)],
+            taxes: Vec::new(),
+        });
+        store()
+            .booking_list
+            .lock()
+            .unwrap()
+            .insert(1, booking.clone());
+
+        assert_eq!(
+            status_from(1, BookingStatus::Cancelled, BookingStatus::Confirmed),
+            Err(TransitionError::DepositOutstanding(10000))
+        );
+        assert_eq!(reinstate(1), Ok(BookingStatus::PendingPayment));
+        assert_ne!(
+            fetch_by_id(1).unwrap().payment_due_by,
+            booking.payment_due_by
+        );
+        assert_eq!(
+            reinstate(1),
+            Err(TransitionError::WrongStatus {
+                expected: BookingStatus::Cancelled,
+                actual: Some(BookingStatus::PendingPayment),
+            })
+        );
+
+        booking.payment_due_by = None;
+        store().booking_list.lock().unwrap().insert(1, booking);
+        assert_eq!(reinstate(1), Ok(BookingStatus::Confirmed));
+    }
+
+    #[test]
+    fn reinstate_held_booking_for_review() {
+        let storage = IsolatedStorage::new();
+        let mut booking: RoomBooking = dummmy_booking_success();
+        booking.status = Some(BookingStatus::PendingReview);
+        store()
+            .booking_list
+            .lock()
+            .unwrap()
+            .insert(1, booking.clone());
+        record_screening(
+            &[Finding {
+                rule: "velocity".to_string(),
+                action: ScreeningAction::Hold,
+                reason: "Too many bookings".to_string(),
+            }],
+            &booking,
+        );
+
+        assert_eq!(status(1, BookingStatus::Cancelled), Ok(()));
+        assert_eq!(
+            status_from(1, BookingStatus::Cancelled, BookingStatus::Confirmed),
+            Err(TransitionError::AwaitingReview)
+        );
+        assert_eq!(reinstate(1), Ok(BookingStatus::PendingReview));
+
+        lock_list(&store().review_list).unwrap().clear();
+        assert!(load_snapshot().is_ok());
+        assert!(storage.directory().join(REVIEWS_PATH).exists());
+        assert_eq!(approve(1), Ok(BookingStatus::Confirmed));
+        assert_eq!(status(1, BookingStatus::Cancelled), Ok(()));
+        assert_eq!(reinstate(1), Ok(BookingStatus::Confirmed));
+    }
+
+    #[test]
+    fn confirm_payment_once_deposit_paid() {
+        let _storage = IsolatedStorage::new();
+        let mut booking: RoomBooking = dummmy_booking_success();
+        booking.status = Some(BookingStatus::PendingPayment);
+        booking.pricing = Some(PricingRecord {
+            priced_at: "2019-12-01T00:00:00Z".to_string(),
+            currency: "GBP".to_string(),
+            nightly_prices: Vec::new(),
+            room_lines: vec![pricing::PriceLine::new("Room".to_string(), 1, 10000)],
+            taxes: Vec::new(),
+        });
+        store()
+            .booking_list
+            .lock()
+            .unwrap()
+            .insert(1, booking.clone());
+
+        assert_eq!(
+            status_from(1, BookingStatus::PendingPayment, BookingStatus::Confirmed),
+            Err(TransitionError::DepositOutstanding(10000))
+        );
+        assert_eq!(
+            fetch_by_id(1).unwrap().status,
+            Some(BookingStatus::PendingPayment)
+        );
+
+        booking.payments.push(Payment {
+            amount: 10000,
+            reference: None,
+            received_at: None,
+        });
+        store().booking_list.lock().unwrap().insert(1, booking);
+
+        assert_eq!(
+            status_from(1, BookingStatus::PendingPayment, BookingStatus::Confirmed),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn check_duplicates_when_creating_series() {
+        let _storage = IsolatedStorage::new();
+        assert!(create(dummmy_booking(), false).is_ok());
+
+        let mut later: RoomBooking = dummmy_booking();
+        later.check_in_date = "2020-01-15".to_string();
+        later.check_out_date = "2020-01-22".to_string();
+        assert_eq!(
+            create_series(vec![later.clone(), dummmy_booking()]),
+            Err(CreateError::Duplicate(1))
+        );
+        assert_eq!(fetch_by_id(2), None);
+
+        let created: Vec<RoomBooking> = create_series(vec![later]).unwrap();
+        assert_eq!(created[0].series_id, Some(1));
+        assert_eq!(created[0].status, Some(BookingStatus::Confirmed));
+    }
+
+    #[test]
+    fn allow_duplicate_imported_and_channel_bookings() {
+        let _storage = IsolatedStorage::new();
+        assert!(create(dummmy_booking(), false).is_ok());
+
+        let imported: Vec<Result<RoomBooking, Vec<String>>> = import(vec![dummmy_booking()]);
+        assert_eq!(imported[0].as_ref().unwrap().booking_id, Some(2));
+
+        let mut reservation: RoomBooking = dummmy_booking();
+        reservation.channel_reservation_id = Some("CH-1".to_string());
+        let synced: RoomBooking = sync_channel_reservation(reservation.clone()).unwrap();
+        assert_eq!(synced.booking_id, Some(3));
+        assert_eq!(
+            sync_channel_reservation(reservation).unwrap().booking_id,
+            Some(3)
+        );
+
+        assert_eq!(
+            create(dummmy_booking(), false),
+            Err(CreateError::Duplicate(3))
+        );
+    }
 }
@@ -0,0 +1,81 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::room_booking::RoomBooking;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The customer id recorded against bookings once their customer has been anonymized.
+pub const ANONYMOUS_CUSTOMER_ID: u32 = 0;
+
+/// Describes the outcome of anonymizing the data held for a customer
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Anonymization {
+    pub customer_id: u32,
+    /// The number of bookings anonymized
+    pub bookings: usize,
+    /// The number of change log entries anonymized, not including the entries recording the
+    /// erasure
+    pub changes: usize,
+}
+
+/// Removes the personal data from a booking, leaving the dates, room type, status and prices used
+/// for aggregate statistics. The customer id is replaced with ```ANONYMOUS_CUSTOMER_ID```, and the
+/// expected arrival time, cancellation comment and channel manager reservation id are removed.
+///
+/// # Arguments
+///
+/// * `booking` - The booking to anonymize
+///
+/// # Examples
+///
+/// ```
+/// anonymize(&mut booking);
+/// ```
+pub fn anonymize(booking: &mut RoomBooking) {
+    booking.customer_id = ANONYMOUS_CUSTOMER_ID;
+    booking.expected_arrival_time = None;
+    booking.channel_reservation_id = None;
+
+    if let Some(cancellation) = booking.cancellation.as_mut() {
+        cancellation.comment = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::anonymization::*;
+    use crate::storage::cancellation::{Cancellation, CancellationReason};
+    use rocket::serde::json::{from_value, json};
+
+    #[test]
+    fn anonymize_booking() {
+        let mut booking: RoomBooking = from_value(json!({
+            "bookingId": 3,
+            "customerId": 12,
+            "roomTypeId": 1,
+            "checkInDate": "2020-01-01",
+            "checkOutDate": "2020-01-03",
+            "expectedArrivalTime": "18:00",
+            "guests": 2,
+        }))
+        .unwrap();
+        booking.cancellation = Some(Cancellation {
+            reason: CancellationReason::Illness,
+            comment: Some("Guest in hospital".to_string()),
+        });
+
+        anonymize(&mut booking);
+        assert_eq!(booking.customer_id, ANONYMOUS_CUSTOMER_ID);
+        assert_eq!(booking.expected_arrival_time, None);
+        assert_eq!(booking.guests, 2);
+        assert_eq!(
+            booking.cancellation.map(|cancellation| cancellation.reason),
+            Some(CancellationReason::Illness)
+        );
+    }
+}
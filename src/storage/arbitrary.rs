@@ -0,0 +1,381 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::billing_milestone::BillingMilestone;
+use super::booking_addon::BookingAddon;
+use super::booking_change::{BookingChange, ChangeType};
+use super::cancellation::{Cancellation, CancellationReason};
+use super::payment::{Payment, Refund};
+use super::pricing_record::{AppliedTax, NightlyPrice, PricingRecord};
+use super::promo_code::{Discount, DiscountType};
+use super::room_booking::{BookingStatus, RoomBooking, DATE_FORMAT, TIME_FORMAT};
+use crate::addons::AddonCharge;
+use crate::pricing::PriceLine;
+use crate::tax::TaxCharge;
+use chrono::{Datelike, NaiveDate, NaiveTime};
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+
+/// Generates text, mixing plain ASCII with arbitrary unicode, including control characters,
+/// combining marks, right-to-left scripts and characters outside the basic multilingual plane.
+pub fn text() -> impl Strategy<Value = String> {
+    return prop_oneof![
+        "[a-zA-Z0-9 ]{0,12}",
+        any::<String>(),
+        Just(String::new()),
+        Just("\u{0}\u{7f}\u{feff}".to_string()),
+        Just("Zoë Ó Séaghdha 👨‍👩‍👧 مرحبا e\u{301}".to_string()),
+    ];
+}
+
+/// Generates dates (YYYY-MM-DD), including the earliest and latest dates which can be
+/// represented, the Unix epoch and leap days.
+pub fn date() -> impl Strategy<Value = String> {
+    let boundaries = prop_oneof![
+        Just(NaiveDate::MIN),
+        Just(NaiveDate::MAX),
+        Just(NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
+        Just(NaiveDate::from_ymd_opt(2000, 2, 29).unwrap()),
+        Just(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()),
+        Just(NaiveDate::from_ymd_opt(9999, 12, 31).unwrap()),
+    ];
+    let any_date = (NaiveDate::MIN.num_days_from_ce()..=NaiveDate::MAX.num_days_from_ce())
+        .prop_map(|days| NaiveDate::from_num_days_from_ce_opt(days).unwrap());
+
+    return prop_oneof![boundaries, any_date].prop_map(|date| date.format(DATE_FORMAT).to_string());
+}
+
+/// Generates times (HH:MM), including midnight and the last minute of the day.
+pub fn time() -> impl Strategy<Value = String> {
+    return prop_oneof![Just(0), Just(23 * 60 + 59), 0..24 * 60u32].prop_map(|minutes| {
+        return NaiveTime::from_hms_opt(minutes / 60, minutes % 60, 0)
+            .unwrap()
+            .format(TIME_FORMAT)
+            .to_string();
+    });
+}
+
+/// Generates a booking status.
+pub fn status() -> impl Strategy<Value = BookingStatus> {
+    return prop_oneof![
+        Just(BookingStatus::Confirmed),
+        Just(BookingStatus::CheckedIn),
+        Just(BookingStatus::Complete),
+        Just(BookingStatus::Cancelled),
+        Just(BookingStatus::NoShow),
+        Just(BookingStatus::PendingPayment),
+        Just(BookingStatus::PendingReview),
+    ];
+}
+
+/// Generates the reason and comment given when a booking is cancelled.
+pub fn cancellation() -> impl Strategy<Value = Cancellation> {
+    let reason = prop_oneof![
+        Just(CancellationReason::ChangeOfPlans),
+        Just(CancellationReason::FoundCheaperRate),
+        Just(CancellationReason::TravelDisruption),
+        Just(CancellationReason::Illness),
+        Just(CancellationReason::BookedInError),
+        Just(CancellationReason::HotelInitiated),
+        Just(CancellationReason::Other),
+        Just(CancellationReason::PaymentNotReceived),
+    ];
+
+    return (reason, option::of(text()))
+        .prop_map(|(reason, comment)| Cancellation { reason, comment });
+}
+
+/// Generates an add-on attached to a booking.
+pub fn booking_addon() -> impl Strategy<Value = BookingAddon> {
+    let charge = prop_oneof![Just(AddonCharge::PerStay), Just(AddonCharge::PerNight)];
+
+    return (
+        text(),
+        any::<u32>(),
+        option::of(any::<u64>()),
+        option::of(charge),
+    )
+        .prop_map(|(code, quantity, unit_price, charge)| BookingAddon {
+            code,
+            quantity,
+            unit_price,
+            charge,
+        });
+}
+
+/// Generates the rates and taxes in effect when a booking was priced.
+pub fn pricing_record() -> impl Strategy<Value = PricingRecord> {
+    let nightly_price =
+        (date(), option::of(text()), any::<u64>()).prop_map(|(date, rate_plan, rate)| {
+            NightlyPrice {
+                date,
+                rate_plan,
+                rate,
+            }
+        });
+    let room_line = (text(), any::<u64>(), any::<u64>(), any::<u64>()).prop_map(
+        |(description, quantity, unit_price, amount)| PriceLine {
+            description,
+            quantity,
+            unit_price,
+            amount,
+        },
+    );
+    let charge = prop_oneof![
+        Just(TaxCharge::Percentage),
+        Just(TaxCharge::PerPersonPerNight)
+    ];
+    let tax = (text(), charge, any::<u64>()).prop_map(|(name, charge, rate)| AppliedTax {
+        name,
+        charge,
+        rate,
+    });
+
+    return (
+        text(),
+        text(),
+        vec(nightly_price, 0..4),
+        vec(room_line, 0..3),
+        vec(tax, 0..3),
+    )
+        .prop_map(
+            |(priced_at, currency, nightly_prices, room_lines, taxes)| PricingRecord {
+                priced_at,
+                currency,
+                nightly_prices,
+                room_lines,
+                taxes,
+            },
+        );
+}
+
+/// Generates a room booking. Field values are not validated, so a generated booking may not be
+/// one the service would accept, but every booking can be stored and serialized.
+pub fn room_booking() -> impl Strategy<Value = RoomBooking> {
+    let milestone = (date(), date(), any::<u32>(), any::<u64>()).prop_map(
+        |(period_start, period_end, nights, amount)| BillingMilestone {
+            period_start,
+            period_end,
+            nights,
+            amount,
+        },
+    );
+    let discount_type = prop_oneof![
+        Just(DiscountType::Percentage),
+        Just(DiscountType::FixedAmount)
+    ];
+    let discount = (discount_type, any::<u64>()).prop_map(|(discount_type, value)| Discount {
+        discount_type,
+        value,
+    });
+    let payment = (any::<u64>(), option::of(text()), option::of(text())).prop_map(
+        |(amount, reference, received_at)| Payment {
+            amount,
+            reference,
+            received_at,
+        },
+    );
+    let refund = (
+        any::<u64>(),
+        option::of(text()),
+        option::of(text()),
+        option::of(text()),
+    )
+        .prop_map(|(amount, reason, reference, refunded_at)| Refund {
+            amount,
+            reason,
+            reference,
+            refunded_at,
+        });
+
+    let stay = (
+        option::of(any::<u32>()),
+        any::<u32>(),
+        any::<u8>(),
+        date(),
+        date(),
+        option::of(status()),
+        option::of(time()),
+        option::of(time()),
+        any::<bool>(),
+        option::of(time()),
+        option::of(time()),
+    );
+    let terms = (
+        vec(milestone, 0..3),
+        option::of(any::<u32>()),
+        option::of(cancellation()),
+        option::of(any::<u64>()),
+        option::of(text()),
+        vec(booking_addon(), 0..3),
+        option::of(text()),
+        option::of(discount),
+        any::<u32>(),
+        any::<u32>(),
+    );
+    let references = (
+        vec(text(), 0..3),
+        option::of(any::<u32>()),
+        option::of(any::<u32>()),
+        option::of(text()),
+        vec(text(), 0..3),
+        option::of(pricing_record()),
+        vec(payment, 0..3),
+        vec(refund, 0..3),
+    );
+    let timestamps = (
+        option::of(text()),
+        option::of(text()),
+        option::of(text()),
+        any::<bool>(),
+    );
+
+    return (stay, terms, references, timestamps).prop_map(
+        |(stay, terms, references, timestamps)| {
+            let (
+                booking_id,
+                customer_id,
+                room_type_id,
+                check_in_date,
+                check_out_date,
+                status,
+                expected_arrival_time,
+                late_check_out_time,
+                day_use,
+                start_time,
+                end_time,
+            ) = stay;
+            let (
+                billing_milestones,
+                series_id,
+                cancellation,
+                no_show_fee,
+                payment_due_by,
+                addons,
+                promo_code,
+                discount,
+                guests,
+                children,
+            ) = terms;
+            let (
+                tax_exemptions,
+                account_id,
+                agent_id,
+                channel_reservation_id,
+                rate_plans,
+                pricing,
+                payments,
+                refunds,
+            ) = references;
+            let (created_at, updated_at, cancelled_at, sandbox) = timestamps;
+
+            return RoomBooking {
+                booking_id,
+                customer_id,
+                room_type_id,
+                check_in_date,
+                check_out_date,
+                status,
+                expected_arrival_time,
+                late_check_out_time,
+                day_use,
+                start_time,
+                end_time,
+                billing_milestones,
+                series_id,
+                cancellation,
+                no_show_fee,
+                payment_due_by,
+                addons,
+                promo_code,
+                discount,
+                guests,
+                children,
+                tax_exemptions,
+                account_id,
+                agent_id,
+                channel_reservation_id,
+                rate_plans,
+                pricing,
+                payments,
+                refunds,
+                created_at,
+                updated_at,
+                cancelled_at,
+                sandbox,
+            };
+        },
+    );
+}
+
+/// Generates a change recorded against a booking.
+pub fn booking_change() -> impl Strategy<Value = BookingChange> {
+    let change_type = prop_oneof![
+        Just(ChangeType::Created),
+        Just(ChangeType::Updated),
+        Just(ChangeType::Cancelled),
+        Just(ChangeType::NoShow),
+        Just(ChangeType::Anonymized),
+    ];
+
+    return (any::<u64>(), text(), change_type, room_booking()).prop_map(
+        |(sequence, recorded_at, change_type, booking)| BookingChange {
+            sequence,
+            recorded_at,
+            change_type,
+            booking,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::arbitrary::*;
+    use crate::storage::isolated::IsolatedStorage;
+    use crate::storage::*;
+    use rocket::serde::json::serde_json;
+
+    proptest! {
+        #[test]
+        fn json_round_trip(booking in room_booking()) {
+            let json: String = serde_json::to_string(&booking).unwrap();
+            prop_assert_eq!(serde_json::from_str::<RoomBooking>(&json).unwrap(), booking);
+        }
+
+        #[test]
+        fn binary_round_trip(booking in room_booking()) {
+            let binary: Vec<u8> = bincode::serialize(&booking).unwrap();
+            prop_assert_eq!(bincode::deserialize::<RoomBooking>(&binary).unwrap(), booking);
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn snapshot_round_trip(
+            bookings in vec(room_booking(), 0..8),
+            changes in vec(booking_change(), 0..4),
+        ) {
+            let _storage = IsolatedStorage::new();
+            let booking_list: HashMap<u32, RoomBooking> = bookings
+                .into_iter()
+                .enumerate()
+                .map(|(index, booking)| (index as u32 + 1, booking))
+                .collect();
+
+            prop_assert!(write_file(CHANGES_PATH, &changes));
+            *store().booking_list.lock().unwrap() = booking_list.clone();
+            prop_assert!(save_bookings());
+
+            store().booking_list.lock().unwrap().clear();
+            store().change_log.lock().unwrap().clear();
+            prop_assert!(load_snapshot().is_ok());
+            prop_assert_eq!(&*store().booking_list.lock().unwrap(), &booking_list);
+            prop_assert_eq!(&*store().change_log.lock().unwrap(), &changes);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::inventory_adjustment::{adjusted_inventory, InventoryAdjustment};
+use super::room_booking::{default_guests, RoomBooking, DATE_FORMAT};
+use crate::config::RoomInventory;
+use crate::pricing::PriceBreakdown;
+use chrono::{Datelike, NaiveDate};
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Defines whether a room is available for a booking, ordered from most to least available
+#[derive(
+    Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord, Debug,
+)]
+pub enum Availability {
+    /// A room is free on every night of the booking
+    Available,
+    /// Every room is booked on at least one night, but the overbooking allowance is not, so the
+    /// booking may be made as an overbooking
+    Overbooked,
+    /// Every room, and the overbooking allowance, is booked on at least one night
+    Full,
+}
+
+impl Availability {
+    /// Returns the availability of a room type on a night with a number of rooms already booked.
+    ///
+    /// # Arguments
+    ///
+    /// * `booked` - The number of bookings holding a room of the room type on the night
+    /// * `inventory` - The room inventory of the room type
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let availability = Availability::of(18, &inventory);
+    /// ```
+    pub fn of(booked: usize, inventory: &RoomInventory) -> Availability {
+        if booked < inventory.rooms as usize {
+            return Availability::Available;
+        } else if booked < inventory.sellable() as usize {
+            return Availability::Overbooked;
+        }
+
+        return Availability::Full;
+    }
+}
+
+/// Describes the bookings and remaining capacity of a room type on a date
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarDay {
+    /// The date (YYYY-MM-DD)
+    pub date: String,
+    /// The number of overnight bookings holding a room on the night of the date
+    pub booked: u32,
+    /// The number of rooms which may be sold on the night, after any inventory adjustments and
+    /// including the overbooking allowance, or null if the room type is not limited
+    pub sellable: Option<u32>,
+    /// The number of rooms which may still be sold on the night, or null if the room type is not
+    /// limited
+    pub remaining: Option<u32>,
+    pub availability: Availability,
+}
+
+/// Describes the bookings and remaining capacity of a room type on each date of a month
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailabilityCalendar {
+    pub room_type_id: u8,
+    /// The month (YYYY-MM)
+    pub month: String,
+    /// Each date of the month, in order
+    pub days: Vec<CalendarDay>,
+}
+
+impl AvailabilityCalendar {
+    /// Creates the availability calendar of a room type for a month. Returns None if the month is
+    /// not valid.
+    ///
+    /// # Arguments
+    ///
+    /// * `room_type_id` - The id of the room type
+    /// * `month` - The month (YYYY-MM)
+    /// * `occupied` - The number of bookings holding a room of the room type, keyed by date
+    /// * `inventory` - The room inventory of the room type, or None if it is not limited
+    /// * `adjustments` - The inventory adjustments, applied to the room inventory on each date
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let calendar =
+    ///     AvailabilityCalendar::new(1, "2020-01", &occupied, Some(inventory), &adjustments);
+    /// ```
+    pub fn new(
+        room_type_id: u8,
+        month: &str,
+        occupied: &HashMap<String, usize>,
+        inventory: Option<RoomInventory>,
+        adjustments: &[InventoryAdjustment],
+    ) -> Option<AvailabilityCalendar> {
+        let first: NaiveDate =
+            NaiveDate::parse_from_str(&format!("{}-01", month), DATE_FORMAT).ok()?;
+
+        let days: Vec<CalendarDay> = first
+            .iter_days()
+            .take_while(|date| date.month() == first.month())
+            .map(|date| {
+                let date: String = date.format(DATE_FORMAT).to_string();
+                let booked: usize = occupied.get(&date).copied().unwrap_or(0);
+                let inventory: Option<RoomInventory> = inventory
+                    .as_ref()
+                    .map(|inventory| adjusted_inventory(inventory, adjustments, &date));
+
+                return CalendarDay {
+                    date,
+                    booked: booked as u32,
+                    sellable: inventory.as_ref().map(|inventory| inventory.sellable()),
+                    remaining: inventory
+                        .as_ref()
+                        .map(|inventory| inventory.sellable().saturating_sub(booked as u32)),
+                    availability: match &inventory {
+                        Some(inventory) => Availability::of(booked, inventory),
+                        None => Availability::Available,
+                    },
+                };
+            })
+            .collect();
+
+        return Some(AvailabilityCalendar {
+            room_type_id,
+            month: month.to_string(),
+            days,
+        });
+    }
+}
+
+/// Describes a stay to check the availability and price of
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+#[schemars(example = "example_query")]
+pub struct AvailabilityQuery {
+    pub room_type_id: u8,
+    pub check_in_date: String,
+    pub check_out_date: String,
+    /// The number of guests staying, defaulting to 1
+    #[serde(default = "default_guests")]
+    pub guests: u32,
+    /// The number of the guests who are children
+    #[serde(default)]
+    pub children: u32,
+}
+
+/// Returns the example stay shown in the API documentation, as it would be sent to check
+/// availability.
+fn example_query() -> AvailabilityQuery {
+    return AvailabilityQuery {
+        room_type_id: 2,
+        check_in_date: "2030-06-01".to_string(),
+        check_out_date: "2030-06-04".to_string(),
+        guests: 2,
+        children: 0,
+    };
+}
+
+impl AvailabilityQuery {
+    /// Creates an overnight booking for the stay, which is not stored, to check its availability
+    /// and price.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let booking = query.to_booking();
+    /// ```
+    pub fn to_booking(&self) -> RoomBooking {
+        return RoomBooking {
+            booking_id: None,
+            customer_id: 0,
+            room_type_id: self.room_type_id,
+            check_in_date: self.check_in_date.clone(),
+            check_out_date: self.check_out_date.clone(),
+            status: None,
+            expected_arrival_time: None,
+            late_check_out_time: None,
+            day_use: false,
+            start_time: None,
+            end_time: None,
+            billing_milestones: Vec::new(),
+            series_id: None,
+            cancellation: None,
+            no_show_fee: None,
+            payment_due_by: None,
+            addons: Vec::new(),
+            promo_code: None,
+            discount: None,
+            guests: self.guests,
+            tax_exemptions: Vec::new(),
+            account_id: None,
+            agent_id: None,
+            channel_reservation_id: None,
+            children: self.children,
+            rate_plans: Vec::new(),
+            pricing: None,
+            payments: Vec::new(),
+            refunds: Vec::new(),
+            created_at: None,
+            updated_at: None,
+            cancelled_at: None,
+            sandbox: false,
+        };
+    }
+}
+
+/// Describes the availability and price of a stay
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailabilityResult {
+    pub room_type_id: u8,
+    pub check_in_date: String,
+    pub check_out_date: String,
+    /// Whether a room is available for the stay, or null if the stay is not valid
+    pub availability: Option<Availability>,
+    /// The indicative price of the stay, before any promo code or negotiated rate, or null if it
+    /// cannot be priced
+    pub quote: Option<PriceBreakdown>,
+    /// Each reason the stay may not be booked, or cannot be priced
+    pub errors: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::availability::*;
+
+    #[test]
+    fn availability_calendar() {
+        let inventory = RoomInventory {
+            room_type_id: 1,
+            rooms: 2,
+            overbooking_percent: 50,
+        };
+        let occupied: HashMap<String, usize> =
+            HashMap::from([("2020-02-01".to_string(), 2), ("2020-02-29".to_string(), 3)]);
+
+        let calendar =
+            AvailabilityCalendar::new(1, "2020-02", &occupied, Some(inventory), &[]).unwrap();
+        assert_eq!(calendar.days.len(), 29);
+        assert_eq!(calendar.days[0].remaining, Some(1));
+        assert_eq!(calendar.days[0].availability, Availability::Overbooked);
+        assert_eq!(calendar.days[1].availability, Availability::Available);
+        assert_eq!(calendar.days[28].remaining, Some(0));
+        assert_eq!(calendar.days[28].availability, Availability::Full);
+
+        let calendar = AvailabilityCalendar::new(2, "2020-02", &occupied, None, &[]).unwrap();
+        assert_eq!(calendar.days[28].remaining, None);
+        assert_eq!(calendar.days[28].availability, Availability::Available);
+    }
+}
@@ -0,0 +1,161 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::room_booking::{BookingStatus, RoomBooking};
+use crate::state_machine::{self, TransitionError};
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Defines why a change to a booking conflicts with the booking's current state
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub enum ConflictReason {
+    /// The booking's current status may not move to the requested status
+    IllegalTransition,
+    /// The action only applies to bookings with another status
+    WrongStatus,
+    /// The booking cannot be marked as a no-show before its check in date
+    BeforeCheckIn,
+    /// No room is available for the booking's dates
+    NoRoomAvailable,
+    /// The booking has not been paid in full
+    OutstandingBalance,
+    /// The booking's deposit has not been paid in full
+    DepositOutstanding,
+    /// The booking was held for review, and has not been approved
+    AwaitingReview,
+}
+
+/// Describes the current state of a booking a change conflicts with, so the client can decide how
+/// to resolve the conflict
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BookingConflict {
+    pub reason: ConflictReason,
+    /// The status the change requested, if it requested a status
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requested_status: Option<BookingStatus>,
+    /// The status the change requires the booking to have, if it only applies to one status
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_status: Option<BookingStatus>,
+    /// The statuses the booking may currently move to
+    pub allowed_statuses: Vec<BookingStatus>,
+    /// The amount still to pay, if the booking or its deposit has not been paid in full
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outstanding: Option<u64>,
+    /// The booking in its current state
+    pub booking: RoomBooking,
+}
+
+impl BookingConflict {
+    /// Creates the description of a conflict from the error returned by a status change. Returns
+    /// None if the error is not a conflict, e.g. the booking was not found.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The error returned by the status change
+    /// * `booking` - The booking in its current state
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let conflict = BookingConflict::new(&error, booking);
+    /// ```
+    pub fn new(error: &TransitionError, booking: RoomBooking) -> Option<BookingConflict> {
+        let (reason, requested_status, required_status, outstanding) = match error {
+            TransitionError::Illegal { to, .. } => (
+                ConflictReason::IllegalTransition,
+                Some(to.clone()),
+                None,
+                None,
+            ),
+            TransitionError::WrongStatus { expected, .. } => (
+                ConflictReason::WrongStatus,
+                None,
+                Some(expected.clone()),
+                None,
+            ),
+            TransitionError::BeforeCheckIn => (
+                ConflictReason::BeforeCheckIn,
+                Some(BookingStatus::NoShow),
+                None,
+                None,
+            ),
+            TransitionError::NoRoomAvailable => (ConflictReason::NoRoomAvailable, None, None, None),
+            TransitionError::OutstandingBalance(outstanding) => (
+                ConflictReason::OutstandingBalance,
+                None,
+                None,
+                Some(*outstanding),
+            ),
+            TransitionError::DepositOutstanding(outstanding) => (
+                ConflictReason::DepositOutstanding,
+                Some(BookingStatus::Confirmed),
+                None,
+                Some(*outstanding),
+            ),
+            TransitionError::AwaitingReview => (ConflictReason::AwaitingReview, None, None, None),
+            TransitionError::NotFound | TransitionError::Unavailable => return None,
+        };
+
+        let allowed_statuses: Vec<BookingStatus> =
+            booking.status.as_ref().map_or(Vec::new(), |status| {
+                state_machine::allowed_transitions(status).to_vec()
+            });
+
+        return Some(BookingConflict {
+            reason,
+            requested_status,
+            required_status,
+            allowed_statuses,
+            outstanding,
+            booking,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::booking_conflict::*;
+    use rocket::serde::json::{from_value, json, to_value};
+
+    #[test]
+    fn describe_conflict() {
+        let booking: RoomBooking = from_value(json!({
+            "bookingId": 2,
+            "customerId": 1,
+            "roomTypeId": 1,
+            "checkInDate": "2024-01-01",
+            "checkOutDate": "2024-01-03",
+            "status": "Cancelled",
+        }))
+        .unwrap();
+
+        let error = TransitionError::Illegal {
+            from: Some(BookingStatus::Cancelled),
+            to: BookingStatus::Complete,
+        };
+        let conflict: BookingConflict = BookingConflict::new(&error, booking.clone()).unwrap();
+        assert_eq!(conflict.reason, ConflictReason::IllegalTransition);
+        assert_eq!(
+            conflict.allowed_statuses,
+            vec![
+                BookingStatus::Confirmed,
+                BookingStatus::PendingPayment,
+                BookingStatus::PendingReview
+            ]
+        );
+
+        let value = to_value(&conflict).unwrap();
+        assert_eq!(value["requestedStatus"], "Complete");
+        assert_eq!(value["booking"]["status"], "Cancelled");
+        assert!(value.get("outstanding").is_none());
+
+        assert_eq!(
+            BookingConflict::new(&TransitionError::NotFound, booking),
+            None
+        );
+    }
+}
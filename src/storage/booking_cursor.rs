@@ -0,0 +1,61 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::room_booking::RoomBooking;
+use crate::storage;
+
+/// The number of bookings fetched from storage at a time by a cursor.
+static BATCH_SIZE: usize = 500;
+
+/// Iterates over a list of bookings, fetching them from storage in batches as they are needed, so
+/// a large listing is never held in memory at once. Storage is only locked while each batch is
+/// fetched. Bookings deleted after the cursor was created are skipped.
+pub struct BookingCursor {
+    booking_ids: Vec<u32>,
+    position: usize,
+    batch: std::vec::IntoIter<RoomBooking>,
+}
+
+impl BookingCursor {
+    /// Creates a cursor over the bookings with the provided booking ids, in the order given.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_ids` - The ids of the bookings to iterate over, e.g. from
+    ///   ```storage::fetch_filtered_ids```
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let cursor = BookingCursor::new(storage::fetch_filtered_ids(&filter));
+    /// ```
+    pub fn new(booking_ids: Vec<u32>) -> BookingCursor {
+        return BookingCursor {
+            booking_ids,
+            position: 0,
+            batch: Vec::new().into_iter(),
+        };
+    }
+}
+
+impl Iterator for BookingCursor {
+    type Item = RoomBooking;
+
+    fn next(&mut self) -> Option<RoomBooking> {
+        loop {
+            if let Some(booking) = self.batch.next() {
+                return Some(booking);
+            }
+
+            if self.position >= self.booking_ids.len() {
+                return None;
+            }
+
+            let end: usize = (self.position + BATCH_SIZE).min(self.booking_ids.len());
+            self.batch = storage::fetch_many(&self.booking_ids[self.position..end]).into_iter();
+            self.position = end;
+        }
+    }
+}
@@ -0,0 +1,33 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::room_booking::RoomBooking;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes a booking which has been deleted, so clients holding a copy can remove it
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BookingTombstone {
+    pub booking_id: u32,
+    /// The time the booking was deleted (RFC 3339)
+    pub deleted_at: String,
+}
+
+/// Describes the bookings created, updated and deleted since a sync token, in their current
+/// state, ordered by booking id
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BookingDelta {
+    /// Bookings created since the token, including any since updated
+    pub created: Vec<RoomBooking>,
+    /// Bookings which existed before the token, and have since been updated
+    pub updated: Vec<RoomBooking>,
+    /// Bookings deleted since the token
+    pub deleted: Vec<BookingTombstone>,
+    /// The token to send with the next sync request
+    pub sync_token: u64,
+}
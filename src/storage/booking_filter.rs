@@ -0,0 +1,91 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::room_booking::{BookingStatus, RoomBooking};
+use rocket::FromForm;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes a set of criteria used to filter the list of bookings. Every criterion is optional,
+/// and a booking must satisfy all of the criteria provided to be included.
+#[derive(Clone, Default, FromForm, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct BookingFilter {
+    /// Only include bookings made by this customer
+    pub customer_id: Option<u32>,
+    /// Only include bookings for this room type
+    pub room_type_id: Option<u8>,
+    /// Only include bookings with this status, e.g. 'Confirmed'
+    pub status: Option<String>,
+    /// Only include bookings with a check in date on or after this date (YYYY-MM-DD)
+    pub from: Option<String>,
+    /// Only include bookings with a check in date on or before this date (YYYY-MM-DD)
+    pub to: Option<String>,
+    /// Only include sandbox bookings if true, or real bookings if false
+    pub sandbox: Option<bool>,
+}
+
+impl BookingFilter {
+    /// Checks the filter contains only valid values. Returns false if the status provided does
+    /// not correspond to a BookingStatus.
+    pub fn is_valid(&self) -> bool {
+        match &self.status {
+            Some(status) => status.parse::<BookingStatus>().is_ok(),
+            None => true,
+        }
+    }
+
+    /// Checks whether a booking satisfies every criterion in the filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking` - The booking to check against the filter
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let filter = BookingFilter { customer_id: Some(1), ..Default::default() };
+    /// filter.matches(&booking);
+    /// ```
+    pub fn matches(&self, booking: &RoomBooking) -> bool {
+        if let Some(customer_id) = self.customer_id {
+            if booking.customer_id != customer_id {
+                return false;
+            }
+        }
+
+        if let Some(room_type_id) = self.room_type_id {
+            if booking.room_type_id != room_type_id {
+                return false;
+            }
+        }
+
+        if let Some(status) = &self.status {
+            if booking.status != status.parse::<BookingStatus>().ok() {
+                return false;
+            }
+        }
+
+        if let Some(from) = &self.from {
+            if booking.check_in_date.as_str() < from.as_str() {
+                return false;
+            }
+        }
+
+        if let Some(to) = &self.to {
+            if booking.check_in_date.as_str() > to.as_str() {
+                return false;
+            }
+        }
+
+        if let Some(sandbox) = self.sandbox {
+            if booking.sandbox != sandbox {
+                return false;
+            }
+        }
+
+        return true;
+    }
+}
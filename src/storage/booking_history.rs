@@ -0,0 +1,118 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::booking_change::{BookingChange, ChangeType};
+use rocket::serde::json::{to_value, Value};
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes a change to a single field of a booking
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    /// The name of the field, e.g. 'status'
+    pub field: String,
+    /// The value of the field before the change
+    pub from: Value,
+    /// The value of the field after the change
+    pub to: Value,
+}
+
+/// Describes a single entry in the history of a booking
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BookingHistoryEntry {
+    /// The sequence number of the change in the change log
+    pub sequence: u64,
+    /// The time the change was made, in RFC 3339 format
+    pub recorded_at: String,
+    pub change_type: ChangeType,
+    /// Each field changed, including status transitions. Empty when the booking was created.
+    pub changes: Vec<FieldChange>,
+}
+
+impl BookingHistoryEntry {
+    /// Builds the history of a booking from its entries in the change log, comparing each entry
+    /// against the previous one to find the fields changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `changes` - The change log entries for a single booking, in sequence order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let history = BookingHistoryEntry::from_changes(&changes);
+    /// ```
+    pub fn from_changes(changes: &[BookingChange]) -> Vec<BookingHistoryEntry> {
+        let mut previous: Option<Value> = None;
+
+        return changes
+            .iter()
+            .map(|change| {
+                let current: Value = to_value(&change.booking).unwrap_or(Value::Null);
+                let field_changes: Vec<FieldChange> = match (&previous, change.change_type.clone())
+                {
+                    (_, ChangeType::Created) | (None, _) => Vec::new(),
+                    (Some(previous), _) => diff(previous, &current),
+                };
+                previous = Some(current);
+
+                return BookingHistoryEntry {
+                    sequence: change.sequence,
+                    recorded_at: change.recorded_at.clone(),
+                    change_type: change.change_type.clone(),
+                    changes: field_changes,
+                };
+            })
+            .collect();
+    }
+}
+
+/// Returns each top level field with a different value in the two JSON objects provided.
+fn diff(previous: &Value, current: &Value) -> Vec<FieldChange> {
+    let (previous, current) = match (previous, current) {
+        (Value::Object(previous), Value::Object(current)) => (previous, current),
+        _ => return Vec::new(),
+    };
+
+    return current
+        .iter()
+        .filter_map(|(field, to)| {
+            let from: Value = previous.get(field).cloned().unwrap_or(Value::Null);
+            if from == *to {
+                return None;
+            }
+
+            return Some(FieldChange {
+                field: field.clone(),
+                from,
+                to: to.clone(),
+            });
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::booking_history::*;
+    use rocket::serde::json::json;
+
+    #[test]
+    fn diff_fields() {
+        let previous: Value = json!({"status": "Confirmed", "roomTypeId": 3});
+        let current: Value = json!({"status": "Cancelled", "roomTypeId": 3});
+
+        assert_eq!(
+            diff(&previous, &current),
+            vec![FieldChange {
+                field: "status".to_string(),
+                from: json!("Confirmed"),
+                to: json!("Cancelled"),
+            }]
+        );
+    }
+}
@@ -0,0 +1,17 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+/// Describes a page of bookings matching a filter, along with the cursor to use when requesting
+/// the next page
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct BookingPage {
+    /// The ids of the bookings on the page, in order
+    pub booking_ids: Vec<u32>,
+    /// The total number of bookings matching the filter
+    pub total: usize,
+    /// The id of the last booking on the page, if a limit was requested and there are more
+    /// bookings after it
+    pub next_cursor: Option<u32>,
+}
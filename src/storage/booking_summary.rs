@@ -0,0 +1,141 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::room_booking::{BookingStatus, RoomBooking};
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes the dates, status and room type of a booking, without the customer's details or
+/// pricing, e.g. for the scheduling board
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomBookingSummary {
+    pub booking_id: Option<u32>,
+    pub room_type_id: u8,
+    /// The check in date (YYYY-MM-DD)
+    pub check_in_date: String,
+    /// The check out date (YYYY-MM-DD)
+    pub check_out_date: String,
+    pub status: Option<BookingStatus>,
+}
+
+impl RoomBookingSummary {
+    /// Creates the summary of a booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking` - The booking to summarise
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let summary = RoomBookingSummary::new(&booking);
+    /// ```
+    pub fn new(booking: &RoomBooking) -> RoomBookingSummary {
+        return RoomBookingSummary {
+            booking_id: booking.booking_id,
+            room_type_id: booking.room_type_id,
+            check_in_date: booking.check_in_date.clone(),
+            check_out_date: booking.check_out_date.clone(),
+            status: booking.status.clone(),
+        };
+    }
+}
+
+/// Defines how much of each booking is returned by the booking lists
+#[derive(Clone, Copy, Serialize, PartialEq, Debug)]
+pub enum View {
+    /// The full booking is returned
+    Full,
+    /// Only the summary of the booking is returned
+    Summary,
+}
+
+impl View {
+    /// Parses the name of a view, regardless of case. Returns the full view if no name is
+    /// provided, or None if the name is not a valid view.
+    ///
+    /// # Arguments
+    ///
+    /// * `view` - The name of the view, e.g. 'summary'
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let view = View::parse(Some("summary"));
+    /// ```
+    pub fn parse(view: Option<&str>) -> Option<View> {
+        return match view.map(|view| view.to_lowercase()).as_deref() {
+            None | Some("full") => Some(View::Full),
+            Some("summary") => Some(View::Summary),
+            Some(_) => None,
+        };
+    }
+
+    /// Returns a booking as it is shown in this view.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking` - The booking to show
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let shown = View::Summary.show(booking);
+    /// ```
+    pub fn show(&self, booking: RoomBooking) -> BookingView {
+        return match self {
+            View::Full => BookingView::Full(Box::new(booking)),
+            View::Summary => BookingView::Summary(RoomBookingSummary::new(&booking)),
+        };
+    }
+}
+
+/// A booking as shown in a booking list, either in full or as a summary
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(untagged)]
+pub enum BookingView {
+    Full(Box<RoomBooking>),
+    Summary(RoomBookingSummary),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::booking_summary::*;
+    use rocket::serde::json::{from_value, json, to_value};
+
+    #[test]
+    fn show_summary() {
+        let booking: RoomBooking = from_value(json!({
+            "bookingId": 4,
+            "customerId": 1,
+            "roomTypeId": 2,
+            "checkInDate": "2024-01-01",
+            "checkOutDate": "2024-01-03",
+            "status": "Confirmed",
+        }))
+        .unwrap();
+
+        assert_eq!(View::parse(Some("SUMMARY")), Some(View::Summary));
+        assert_eq!(View::parse(None), Some(View::Full));
+        assert_eq!(View::parse(Some("compact")), None);
+
+        let value = to_value(View::Summary.show(booking.clone())).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "bookingId": 4,
+                "roomTypeId": 2,
+                "checkInDate": "2024-01-01",
+                "checkOutDate": "2024-01-03",
+                "status": "Confirmed",
+            })
+        );
+
+        let value = to_value(View::Full.show(booking)).unwrap();
+        assert_eq!(value["customerId"], 1);
+    }
+}
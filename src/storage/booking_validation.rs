@@ -0,0 +1,63 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::room_booking::BookingStatus;
+use crate::pricing::PriceBreakdown;
+use crate::responders::FieldError;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes what would happen if a booking were created, without creating it
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BookingValidation {
+    /// Whether the booking would be created
+    pub valid: bool,
+    /// Why the booking would be rejected, if it would be
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The problem found with each field, if the booking fails validation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+    /// The path of the recently created booking the booking would duplicate, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conflicts_with: Option<String>,
+    /// The status the booking would be created with, e.g. 'PendingPayment' if a deposit must be
+    /// paid, or 'PendingReview' if it would be held for review
+    pub status: Option<BookingStatus>,
+    /// The price the booking would be charged, if it can be priced
+    pub price: Option<PriceBreakdown>,
+    /// The deposit which must be paid before the booking is confirmed, or 0 if none is required
+    pub deposit: u64,
+    /// The time the deposit would be due by (RFC 3339), if the booking would be pending payment
+    pub payment_due_by: Option<String>,
+}
+
+impl BookingValidation {
+    /// Creates the description of a booking which would be rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - Why the booking would be rejected
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let validation = BookingValidation::rejected(error.to_string());
+    /// ```
+    pub fn rejected(error: String) -> BookingValidation {
+        return BookingValidation {
+            valid: false,
+            error: Some(error),
+            fields: Vec::new(),
+            conflicts_with: None,
+            status: None,
+            price: None,
+            deposit: 0,
+            payment_due_by: None,
+        };
+    }
+}
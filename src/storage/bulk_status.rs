@@ -0,0 +1,45 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::room_booking::BookingStatus;
+use crate::state_machine::TransitionError;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes a status update to be applied to several bookings
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkStatusUpdate {
+    pub booking_ids: Vec<u32>,
+    pub status: BookingStatus,
+}
+
+/// Describes the outcome of a status update for a single booking
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkStatusResult {
+    pub booking_id: u32,
+    /// True if the status was updated
+    pub success: bool,
+    /// Why the status was not updated, if the update failed
+    pub error: Option<String>,
+}
+
+impl BulkStatusResult {
+    /// Creates the result of a status update for a single booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking_id` - The id of the booking
+    /// * `result` - The outcome of the status update
+    pub fn new(booking_id: u32, result: Result<(), TransitionError>) -> BulkStatusResult {
+        return BulkStatusResult {
+            booking_id,
+            success: result.is_ok(),
+            error: result.err().map(|error| error.to_string()),
+        };
+    }
+}
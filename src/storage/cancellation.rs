@@ -0,0 +1,78 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::room_booking::{BookingRules, RoomBooking};
+pub use booking_types::cancellation::{Cancellation, CancellationReason};
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes the cancelled bookings for a single cancellation reason
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReasonCount {
+    /// The reason for the cancellations, or null for bookings cancelled without a reason
+    pub reason: Option<CancellationReason>,
+    /// The number of bookings cancelled for the reason
+    pub bookings: usize,
+    /// The total number of nights in the bookings cancelled for the reason
+    pub nights: usize,
+}
+
+/// Describes the cancelled bookings for each cancellation reason
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CancellationReport {
+    /// The total number of cancelled bookings
+    pub bookings: usize,
+    /// The cancelled bookings for each reason, ordered by the number of bookings, most first
+    pub reasons: Vec<ReasonCount>,
+}
+
+impl CancellationReport {
+    /// Creates a cancellation report from the cancelled bookings. Sandbox bookings are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `bookings` - The cancelled bookings to report on, e.g. from ```storage::iter_filtered```
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let report = CancellationReport::new(storage::iter_filtered(&filter));
+    /// ```
+    pub fn new<I: IntoIterator<Item = RoomBooking>>(bookings: I) -> CancellationReport {
+        let mut reasons: Vec<ReasonCount> = Vec::new();
+        let mut total: usize = 0;
+
+        for booking in bookings.into_iter().filter(|booking| !booking.sandbox) {
+            total += 1;
+            let reason: Option<CancellationReason> = booking
+                .cancellation
+                .as_ref()
+                .map(|cancellation| cancellation.reason);
+            let nights: usize = booking.nights().len();
+
+            match reasons.iter_mut().find(|count| count.reason == reason) {
+                Some(count) => {
+                    count.bookings += 1;
+                    count.nights += nights;
+                }
+                None => reasons.push(ReasonCount {
+                    reason,
+                    bookings: 1,
+                    nights,
+                }),
+            }
+        }
+
+        reasons.sort_by_key(|count| std::cmp::Reverse(count.bookings));
+
+        return CancellationReport {
+            bookings: total,
+            reasons,
+        };
+    }
+}
@@ -0,0 +1,124 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::booking_change::{BookingChange, ChangeType};
+use super::corporate_account::CorporateAccount;
+use super::exchange_rate::ExchangeRateOverride;
+use super::inventory_adjustment::InventoryAdjustment;
+use super::promo_code::PromoCode;
+use super::room_booking::RoomBooking;
+use super::sell_restriction::SellRestriction;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Defines the kinds of mutation which may be applied to a record
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub enum CdcOperation {
+    /// A new record was created
+    Insert,
+    /// An existing record was modified
+    Update,
+    /// A record was deleted
+    Delete,
+}
+
+/// The state of the record a mutation was applied to, tagged with the kind of record. Deleted
+/// records hold their state before deletion.
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub enum CdcData {
+    Booking(Box<RoomBooking>),
+    PromoCode(PromoCode),
+    CorporateAccount(CorporateAccount),
+    SellRestriction(SellRestriction),
+    InventoryAdjustment(InventoryAdjustment),
+    ExchangeRate(ExchangeRateOverride),
+}
+
+/// Describes a single mutation applied to a booking or other stored record
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CdcRecord {
+    /// A number identifying the mutation, which increases with each mutation made to any record
+    pub sequence: u64,
+    /// The time the mutation was made (RFC 3339)
+    pub recorded_at: String,
+    pub operation: CdcOperation,
+    /// The key of the record, e.g. the booking id, or the code of a promo code
+    pub key: String,
+    pub data: CdcData,
+}
+
+impl CdcRecord {
+    /// Creates the record describing a booking change, which is an insert if the booking was
+    /// created, or an update otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `change` - The booking change
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let record = CdcRecord::from_change(&change);
+    /// ```
+    pub fn from_change(change: &BookingChange) -> CdcRecord {
+        let operation: CdcOperation = match change.change_type {
+            ChangeType::Created => CdcOperation::Insert,
+            _ => CdcOperation::Update,
+        };
+
+        return CdcRecord {
+            sequence: change.sequence,
+            recorded_at: change.recorded_at.clone(),
+            operation,
+            key: change
+                .booking
+                .booking_id
+                .map_or(String::new(), |id| id.to_string()),
+            data: CdcData::Booking(Box::new(change.booking.clone())),
+        };
+    }
+}
+
+/// Describes a list of mutations, along with the cursor to use when requesting the next set
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CdcPage {
+    pub records: Vec<CdcRecord>,
+    /// The sequence number of the last mutation returned, or the requested cursor if there were
+    /// no new mutations
+    pub next_cursor: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::cdc_record::*;
+    use rocket::serde::json::{from_value, json, to_value};
+
+    #[test]
+    fn describe_booking_change() {
+        let change: BookingChange = from_value(json!({
+            "sequence": 7,
+            "changeType": "Cancelled",
+            "booking": {
+                "bookingId": 3,
+                "customerId": 1,
+                "roomTypeId": 1,
+                "checkInDate": "2024-01-01",
+                "checkOutDate": "2024-01-03",
+            },
+        }))
+        .unwrap();
+
+        let record: CdcRecord = CdcRecord::from_change(&change);
+        assert_eq!(record.sequence, 7);
+        assert_eq!(record.operation, CdcOperation::Update);
+        assert_eq!(record.key, "3");
+
+        let value = to_value(&record).unwrap();
+        assert_eq!(value["data"]["Booking"]["bookingId"], 3);
+    }
+}
@@ -0,0 +1,211 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::room_booking::{BookingStatus, RoomBooking};
+use crate::pricing::{self, RoomRate};
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes the rates negotiated by a corporate account for a room type, in the minor unit of the
+/// hotel's currency
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NegotiatedRate {
+    pub room_type_id: u8,
+    /// The rate charged per night
+    pub nightly_rate: u64,
+    /// The rate charged per month for long stays
+    pub monthly_rate: u64,
+}
+
+/// Describes a corporate account, whose bookings are charged at negotiated rates and invoiced
+/// monthly
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+#[schemars(example = "example_account")]
+pub struct CorporateAccount {
+    pub account_id: Option<u32>,
+    /// The name of the company
+    pub name: String,
+    /// The reference quoted on invoices sent to the company, e.g. a purchase order number
+    pub billing_reference: String,
+    /// The rate plan of the account. Bookings made against the account may only be for the room
+    /// types listed here.
+    pub rates: Vec<NegotiatedRate>,
+}
+
+/// Returns the example account shown in the API documentation, as it would be sent to create an
+/// account.
+fn example_account() -> CorporateAccount {
+    return CorporateAccount {
+        account_id: None,
+        name: "Northwind Traders".to_string(),
+        billing_reference: "NW-2030".to_string(),
+        rates: vec![NegotiatedRate {
+            room_type_id: 2,
+            nightly_rate: 9500,
+            monthly_rate: 210000,
+        }],
+    };
+}
+
+/// Describes a booking made against a corporate account, and the price charged for it
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountBooking {
+    pub booking_id: Option<u32>,
+    pub customer_id: u32,
+    pub room_type_id: u8,
+    pub check_in_date: String,
+    pub check_out_date: String,
+    pub status: Option<BookingStatus>,
+    /// The price of the booking, in the minor unit of the hotel's currency
+    pub total: u64,
+}
+
+/// Describes the bookings made against a corporate account which check out in a month, used to
+/// invoice the account
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountReport {
+    pub account_id: Option<u32>,
+    pub name: String,
+    pub billing_reference: String,
+    pub bookings: Vec<AccountBooking>,
+    /// The total price of the bookings, in the minor unit of the hotel's currency
+    pub total: u64,
+}
+
+impl CorporateAccount {
+    /// Checks the account details are valid. Returns a list describing each problem found, which
+    /// is empty if the account is valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let errors = account.validate();
+    /// ```
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors: Vec<String> = Vec::new();
+
+        if self.name.trim().is_empty() {
+            errors.push("name must not be empty".to_string());
+        }
+
+        if self.rates.is_empty() {
+            errors.push("rates must include at least one room type".to_string());
+        }
+
+        for (index, rate) in self.rates.iter().enumerate() {
+            if self.rates[..index]
+                .iter()
+                .any(|other| other.room_type_id == rate.room_type_id)
+            {
+                errors.push(format!(
+                    "Room type {} must only be included once in rates",
+                    rate.room_type_id
+                ));
+            }
+        }
+
+        return errors;
+    }
+
+    /// Returns the rates negotiated for a room type, or None if the room type is not included in
+    /// the account's rate plan.
+    ///
+    /// # Arguments
+    ///
+    /// * `room_type_id` - The id of the room type
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let rate = account.rate_for(1);
+    /// ```
+    pub fn rate_for(&self, room_type_id: u8) -> Option<RoomRate> {
+        return self
+            .rates
+            .iter()
+            .find(|rate| rate.room_type_id == room_type_id)
+            .map(|rate| RoomRate {
+                room_type_id: rate.room_type_id,
+                nightly_rate: rate.nightly_rate,
+                monthly_rate: rate.monthly_rate,
+            });
+    }
+}
+
+impl AccountReport {
+    /// Creates a report of the bookings made against a corporate account, pricing each booking.
+    /// Bookings which cannot be priced are included with a price of 0, and sandbox bookings are
+    /// left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The account to report on
+    /// * `bookings` - The bookings made against the account
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let report = AccountReport::new(account, &bookings);
+    /// ```
+    pub fn new(account: CorporateAccount, bookings: &[RoomBooking]) -> AccountReport {
+        let bookings: Vec<AccountBooking> = bookings
+            .iter()
+            .filter(|booking| !booking.sandbox)
+            .map(|booking| AccountBooking {
+                booking_id: booking.booking_id,
+                customer_id: booking.customer_id,
+                room_type_id: booking.room_type_id,
+                check_in_date: booking.check_in_date.clone(),
+                check_out_date: booking.check_out_date.clone(),
+                status: booking.status.clone(),
+                total: pricing::quote(booking).map_or(0, |quote| quote.total),
+            })
+            .collect();
+
+        return AccountReport {
+            account_id: account.account_id,
+            name: account.name,
+            billing_reference: account.billing_reference,
+            total: bookings.iter().map(|booking| booking.total).sum(),
+            bookings,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::corporate_account::*;
+
+    #[test]
+    fn validate_account() {
+        let rate = NegotiatedRate {
+            room_type_id: 1,
+            nightly_rate: 8000,
+            monthly_rate: 160000,
+        };
+        let mut account = CorporateAccount {
+            account_id: None,
+            name: "Example Ltd".to_string(),
+            billing_reference: "PO-1234".to_string(),
+            rates: vec![rate.clone()],
+        };
+
+        assert!(account.validate().is_empty());
+        assert_eq!(
+            account.rate_for(1).map(|rate| rate.nightly_rate),
+            Some(8000)
+        );
+        assert!(account.rate_for(2).is_none());
+
+        account.rates.push(rate);
+        account.name = String::new();
+        assert_eq!(account.validate().len(), 2);
+    }
+}
@@ -0,0 +1,65 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use std::fmt;
+
+/// Describes why a booking could not be created
+#[derive(Clone, PartialEq, Debug)]
+pub enum CreateError {
+    /// The booking sets fields which are assigned by the service, or fails validation, with a
+    /// description of each problem found
+    Invalid(Vec<String>),
+    /// No room is available for the booking's dates
+    NoRoomAvailable,
+    /// The booking's promo code does not exist, or may not be redeemed for the booking
+    PromoCode(String),
+    /// The booking is likely a duplicate of the recently created booking with the provided id
+    Duplicate(u32),
+    /// The booking matched a screening rule which rejects bookings. The rule and reason are
+    /// recorded for audit, but not returned, so the rules cannot be probed
+    Rejected,
+    /// The booking storage could not be accessed
+    Unavailable,
+}
+
+impl fmt::Display for CreateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            CreateError::Invalid(errors) => write!(f, "{}", errors.join(", ")),
+            CreateError::NoRoomAvailable => {
+                write!(f, "No rooms are available for the booking's dates")
+            }
+            CreateError::PromoCode(error) => write!(f, "{}", error),
+            CreateError::Duplicate(booking_id) => write!(
+                f,
+                "The booking appears to duplicate booking {}, created moments ago",
+                booking_id
+            ),
+            CreateError::Rejected => write!(f, "The booking could not be accepted"),
+            CreateError::Unavailable => write!(f, "Booking storage is unavailable"),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::create_error::*;
+
+    #[test]
+    fn describe_error() {
+        let error = CreateError::Invalid(vec![
+            "status must not be set".to_string(),
+            "checkOutDate must be after checkInDate".to_string(),
+        ]);
+        assert_eq!(
+            error.to_string(),
+            "status must not be set, checkOutDate must be after checkInDate"
+        );
+        assert_eq!(
+            CreateError::NoRoomAvailable.to_string(),
+            "No rooms are available for the booking's dates"
+        );
+    }
+}
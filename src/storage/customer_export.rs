@@ -0,0 +1,25 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::booking_change::BookingChange;
+use super::room_booking::RoomBooking;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes every stored record referencing a customer, returned in response to a subject access
+/// request
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerExport {
+    pub customer_id: u32,
+    /// The time the export was made, in RFC 3339 format
+    pub exported_at: String,
+    /// The customer's bookings, ordered by booking id
+    pub bookings: Vec<RoomBooking>,
+    /// Every change recorded in the change log for the customer's bookings, ordered by sequence
+    /// number
+    pub changes: Vec<BookingChange>,
+}
@@ -0,0 +1,62 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes an exchange rate set manually, used instead of the rate from the exchange rate
+/// source, e.g. to fix the rate offered to guests for a period
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeRateOverride {
+    /// The ISO 4217 code of the currency, e.g. 'EUR'. This is set from the path.
+    #[serde(default)]
+    pub currency: String,
+    /// The number of units of the currency equal to one unit of the hotel's currency
+    pub rate: f64,
+    /// When the rate was set, as an RFC 3339 timestamp. This is set automatically.
+    #[serde(default)]
+    pub set_at: Option<String>,
+}
+
+impl ExchangeRateOverride {
+    /// Checks the override details are valid. Returns a list describing each problem found,
+    /// which is empty if the override is valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let errors = rate.validate();
+    /// ```
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors: Vec<String> = Vec::new();
+
+        if self.currency.len() != 3 || !self.currency.chars().all(|c| c.is_ascii_uppercase()) {
+            errors.push("currency must be a 3 letter ISO 4217 code, e.g. EUR".to_string());
+        }
+
+        if !self.rate.is_finite() || self.rate <= 0.0 {
+            errors.push("rate must be greater than 0".to_string());
+        }
+
+        return errors;
+    }
+}
+
+/// Describes the exchange rate used to convert prices from the hotel's currency
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeRate {
+    /// The ISO 4217 code of the hotel's currency
+    pub base: String,
+    /// The ISO 4217 code of the currency converted to
+    pub currency: String,
+    /// The number of units of the currency equal to one unit of the hotel's currency
+    pub rate: f64,
+    /// Where the rate came from, either 'override' for a rate set manually, or the name of the
+    /// exchange rate source, e.g. 'ecb'
+    pub source: String,
+}
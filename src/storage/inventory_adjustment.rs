@@ -0,0 +1,150 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::room_booking::DATE_FORMAT;
+use crate::config::RoomInventory;
+use chrono::NaiveDate;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes a change to the number of rooms of a room type which may be sold during a period,
+/// e.g. taking rooms out of service for maintenance
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+#[schemars(example = "example_adjustment")]
+pub struct InventoryAdjustment {
+    pub adjustment_id: Option<u32>,
+    pub room_type_id: u8,
+    /// The first night of the period (YYYY-MM-DD)
+    pub start_date: String,
+    /// The last night of the period, inclusive (YYYY-MM-DD)
+    pub end_date: String,
+    /// The number of rooms added to the room type's inventory, or taken out of it if negative
+    pub rooms: i32,
+    /// A description of why the inventory is adjusted, e.g. refurbishment
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Returns the example adjustment shown in the API documentation, as it would be sent to create an
+/// adjustment.
+fn example_adjustment() -> InventoryAdjustment {
+    return InventoryAdjustment {
+        adjustment_id: None,
+        room_type_id: 2,
+        start_date: "2030-02-10".to_string(),
+        end_date: "2030-02-14".to_string(),
+        rooms: -3,
+        reason: Some("Refurbishment of the second floor".to_string()),
+    };
+}
+
+impl InventoryAdjustment {
+    /// Checks the adjustment details are valid. Returns a list describing each problem found,
+    /// which is empty if the adjustment is valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let errors = adjustment.validate();
+    /// ```
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors: Vec<String> = Vec::new();
+
+        let start_date = NaiveDate::parse_from_str(&self.start_date, DATE_FORMAT);
+        if start_date.is_err() {
+            errors.push("startDate must be a valid date in the format YYYY-MM-DD".to_string());
+        }
+
+        let end_date = NaiveDate::parse_from_str(&self.end_date, DATE_FORMAT);
+        if end_date.is_err() {
+            errors.push("endDate must be a valid date in the format YYYY-MM-DD".to_string());
+        }
+
+        if let (Ok(start_date), Ok(end_date)) = (start_date, end_date) {
+            if end_date < start_date {
+                errors.push("endDate must not be before startDate".to_string());
+            }
+        }
+
+        if self.rooms == 0 {
+            errors.push("rooms must not be 0".to_string());
+        }
+
+        return errors;
+    }
+}
+
+/// Returns the room inventory of a room type on a night, after applying each adjustment made to
+/// it on that night. The overbooking allowance is applied to the adjusted number of rooms, which
+/// is never less than 0.
+///
+/// # Arguments
+///
+/// * `inventory` - The configured room inventory of the room type
+/// * `adjustments` - The inventory adjustments, for any room type
+/// * `date` - The night (YYYY-MM-DD)
+///
+/// # Examples
+///
+/// ```
+/// let inventory = adjusted_inventory(&inventory, &adjustments, "2020-01-01");
+/// ```
+pub fn adjusted_inventory(
+    inventory: &RoomInventory,
+    adjustments: &[InventoryAdjustment],
+    date: &str,
+) -> RoomInventory {
+    let change: i64 = adjustments
+        .iter()
+        .filter(|adjustment| adjustment.room_type_id == inventory.room_type_id)
+        .filter(|adjustment| date >= adjustment.start_date.as_str())
+        .filter(|adjustment| date <= adjustment.end_date.as_str())
+        .map(|adjustment| adjustment.rooms as i64)
+        .sum();
+
+    return RoomInventory {
+        rooms: (inventory.rooms as i64 + change).clamp(0, u32::MAX as i64) as u32,
+        ..inventory.clone()
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::inventory_adjustment::*;
+
+    #[test]
+    fn adjust_inventory() {
+        let inventory = RoomInventory {
+            room_type_id: 1,
+            rooms: 20,
+            overbooking_percent: 10,
+        };
+        let mut adjustment = InventoryAdjustment {
+            adjustment_id: None,
+            room_type_id: 1,
+            start_date: "2020-01-03".to_string(),
+            end_date: "2020-01-04".to_string(),
+            rooms: -5,
+            reason: None,
+        };
+        assert!(adjustment.validate().is_empty());
+
+        let adjusted = adjusted_inventory(&inventory, &[adjustment.clone()], "2020-01-04");
+        assert_eq!(adjusted.rooms, 15);
+        assert_eq!(adjusted.sellable(), 16);
+        assert_eq!(
+            adjusted_inventory(&inventory, &[adjustment.clone()], "2020-01-05").rooms,
+            20
+        );
+
+        adjustment.rooms = -30;
+        assert_eq!(
+            adjusted_inventory(&inventory, &[adjustment], "2020-01-03").rooms,
+            0
+        );
+    }
+}
@@ -0,0 +1,73 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::{Store, SCOPED_STORE};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of isolated stores created by the process, used to name their directories.
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+/// Replaces the stored data on the current thread with an empty store, saving its snapshots in a
+/// new temporary directory, until it is dropped. Storage functions called on other threads, such
+/// as background tasks, are not affected, so tests each using their own storage may run in
+/// parallel. Requests dispatched by a blocking Rocket local client run on the calling thread, so
+/// also use the isolated store.
+///
+/// Each isolated store is kept in memory until the process exits, so it is only intended for
+/// tests and benchmarks.
+///
+/// # Examples
+///
+/// ```
+/// let storage = IsolatedStorage::new();
+/// let booking = storage::create(booking)?;
+/// assert_eq!(booking.booking_id, Some(1));
+/// ```
+pub struct IsolatedStorage {
+    directory: PathBuf,
+    previous: Option<&'static Store>,
+}
+
+impl IsolatedStorage {
+    /// Creates an empty store in a new temporary directory, and uses it on the current thread.
+    pub fn new() -> IsolatedStorage {
+        let directory: PathBuf = std::env::temp_dir().join(format!(
+            "room_booking_service-{}-{}",
+            std::process::id(),
+            CREATED.fetch_add(1, Ordering::Relaxed)
+        ));
+        // A directory left behind by an earlier process with the same id is replaced.
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).expect("Failed to create storage directory");
+
+        let store: &'static Store = Box::leak(Box::new(Store::new(directory.clone())));
+        let previous: Option<&'static Store> =
+            SCOPED_STORE.with(|scoped| scoped.replace(Some(store)));
+
+        return IsolatedStorage {
+            directory,
+            previous,
+        };
+    }
+
+    /// Returns the directory the isolated store saves its snapshots in.
+    pub fn directory(&self) -> &Path {
+        return &self.directory;
+    }
+}
+
+impl Default for IsolatedStorage {
+    fn default() -> IsolatedStorage {
+        return IsolatedStorage::new();
+    }
+}
+
+impl Drop for IsolatedStorage {
+    fn drop(&mut self) {
+        SCOPED_STORE.with(|scoped| scoped.set(self.previous));
+        let _ = std::fs::remove_dir_all(&self.directory);
+    }
+}
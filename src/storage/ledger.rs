@@ -0,0 +1,207 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::room_booking::{BookingStatus, RoomBooking, DATE_FORMAT};
+use crate::config;
+use crate::local_time;
+use crate::pricing::{self, PriceBreakdown};
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// Describes the charges, payments, refunds and taxes of a single booking on a single day, as a
+/// row of the ledger export. The fields are written in order, and the layout must not change, as
+/// the finance system reads the columns by position.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntry {
+    /// The day the entry is for (YYYY-MM-DD)
+    pub date: String,
+    pub booking_id: u32,
+    pub customer_id: u32,
+    /// The id of the corporate account the booking is invoiced to, if any
+    pub account_id: Option<u32>,
+    /// The ISO 4217 code of the currency amounts are given in
+    pub currency: String,
+    /// The price of the booking before tax, for bookings which check out on the day
+    pub charges: u64,
+    /// The taxes charged on the booking, for bookings which check out on the day
+    pub taxes: u64,
+    /// The total of the payments received on the day
+    pub payments: u64,
+    /// The total of the refunds made on the day
+    pub refunds: u64,
+}
+
+impl LedgerEntry {
+    /// Creates the ledger entries for a day, with an entry for each booking which was charged,
+    /// paid for or refunded on the day, ordered by booking id. Bookings are charged on their check
+    /// out date, unless they were cancelled. Payments and refunds are included on the day they were
+    /// recorded, in the hotel's timezone. Bookings which cannot be priced are charged 0, and sandbox
+    /// bookings are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `bookings` - The bookings to include, e.g. from ```storage::iter_filtered```
+    /// * `date` - The day to report on
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let entries = LedgerEntry::for_date(storage::iter_filtered(&filter), date);
+    /// ```
+    pub fn for_date<I: IntoIterator<Item = RoomBooking>>(
+        bookings: I,
+        date: NaiveDate,
+    ) -> Vec<LedgerEntry> {
+        let day: String = date.format(DATE_FORMAT).to_string();
+        let currency: String = config::hotel().currency;
+
+        let mut entries: Vec<LedgerEntry> = bookings
+            .into_iter()
+            .filter(|booking| !booking.sandbox)
+            .filter_map(|booking| {
+                let (charges, taxes): (u64, u64) = match booking.check_out_date == day
+                    && booking.status != Some(BookingStatus::Cancelled)
+                {
+                    true => pricing::quote(&booking).map_or((0, 0), |quote| split_taxes(&quote)),
+                    false => (0, 0),
+                };
+                let payments: u64 = booking
+                    .payments
+                    .iter()
+                    .filter(|payment| recorded_on(&payment.received_at, date))
+                    .map(|payment| payment.amount)
+                    .sum();
+                let refunds: u64 = booking
+                    .refunds
+                    .iter()
+                    .filter(|refund| recorded_on(&refund.refunded_at, date))
+                    .map(|refund| refund.amount)
+                    .sum();
+
+                if charges == 0 && taxes == 0 && payments == 0 && refunds == 0 {
+                    return None;
+                }
+
+                return Some(LedgerEntry {
+                    date: day.clone(),
+                    booking_id: booking.booking_id?,
+                    customer_id: booking.customer_id,
+                    account_id: booking.account_id,
+                    currency: currency.clone(),
+                    charges,
+                    taxes,
+                    payments,
+                    refunds,
+                });
+            })
+            .collect();
+
+        entries.sort_by_key(|entry| entry.booking_id);
+        return entries;
+    }
+}
+
+/// Writes ledger entries as CSV, with a header row naming each column.
+///
+/// # Arguments
+///
+/// * `entries` - The ledger entries to write
+///
+/// # Examples
+///
+/// ```
+/// let content = ledger::to_csv(&entries)?;
+/// ```
+pub fn to_csv(entries: &[LedgerEntry]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    if entries.is_empty() {
+        writer.write_record([
+            "date",
+            "bookingId",
+            "customerId",
+            "accountId",
+            "currency",
+            "charges",
+            "taxes",
+            "payments",
+            "refunds",
+        ])?;
+    }
+
+    for entry in entries {
+        writer.serialize(entry)?;
+    }
+
+    return Ok(writer.into_inner()?);
+}
+
+/// Parses the day a ledger is requested for (YYYY-MM-DD), returning today's date in the hotel's
+/// timezone if no day is provided.
+///
+/// # Arguments
+///
+/// * `date` - The requested day, if any
+///
+/// # Examples
+///
+/// ```
+/// let date = ledger::parse_date(Some("2024-01-01"))?;
+/// ```
+pub fn parse_date(date: Option<&str>) -> Result<NaiveDate, String> {
+    return match date {
+        Some(date) => NaiveDate::parse_from_str(date, DATE_FORMAT)
+            .map_err(|_| "date must be in the format YYYY-MM-DD".to_string()),
+        None => Ok(local_time::today()),
+    };
+}
+
+/// Returns the price of a booking before tax, and the taxes charged on it.
+fn split_taxes(quote: &PriceBreakdown) -> (u64, u64) {
+    let taxes: u64 = quote.taxes.iter().map(|tax| tax.amount).sum();
+    return (quote.total.saturating_sub(taxes), taxes);
+}
+
+/// Checks whether an RFC 3339 timestamp falls on the provided day, in the hotel's timezone.
+fn recorded_on(timestamp: &Option<String>, date: NaiveDate) -> bool {
+    return timestamp
+        .as_deref()
+        .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+        .is_some_and(|timestamp| {
+            timestamp
+                .with_timezone(&config::hotel().timezone)
+                .date_naive()
+                == date
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::ledger::*;
+
+    #[test]
+    fn write_ledger_layout() {
+        let header =
+            "date,bookingId,customerId,accountId,currency,charges,taxes,payments,refunds\n";
+        assert_eq!(String::from_utf8(to_csv(&[]).unwrap()).unwrap(), header);
+
+        let entry = LedgerEntry {
+            date: "2024-01-01".to_string(),
+            booking_id: 1,
+            customer_id: 2,
+            account_id: None,
+            currency: "GBP".to_string(),
+            charges: 10000,
+            taxes: 2000,
+            payments: 12000,
+            refunds: 0,
+        };
+        assert_eq!(
+            String::from_utf8(to_csv(&[entry]).unwrap()).unwrap(),
+            format!("{}2024-01-01,1,2,,GBP,10000,2000,12000,0\n", header)
+        );
+    }
+}
@@ -0,0 +1,224 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// The upper bounds of the latency histogram buckets, in microseconds. Durations longer than the
+/// last bound are counted in a final, unbounded bucket.
+static BUCKET_BOUNDS_MICROS: [u64; 10] = [
+    10, 50, 100, 500, 1_000, 5_000, 10_000, 50_000, 100_000, 1_000_000,
+];
+
+/// Describes the number of durations recorded within a histogram bucket
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramBucket {
+    /// The longest duration counted in the bucket, in microseconds, or null for the bucket of
+    /// durations longer than every other bucket
+    pub upper_bound_micros: Option<u64>,
+    /// The number of durations longer than the previous bucket's bound, up to this bucket's bound
+    pub count: u64,
+}
+
+/// Describes the distribution of the durations of an operation
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyHistogram {
+    /// The number of durations recorded
+    pub count: u64,
+    /// The sum of every duration recorded, in microseconds
+    pub total_micros: u64,
+    /// The longest duration recorded, in microseconds
+    pub max_micros: u64,
+    pub buckets: Vec<HistogramBucket>,
+}
+
+/// Describes the work done by the storage layer since the server started, used to see when
+/// operations which scan every booking start to slow the service down
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageMetrics {
+    /// The number of bookings currently held
+    pub bookings: usize,
+    /// The time taken to acquire a lock on one of the stored lists
+    pub lock_waits: LatencyHistogram,
+    /// The number of scans made of every stored booking
+    pub scans: u64,
+    /// The number of bookings held when each scan started, added together. Scans which stop
+    /// early, e.g. once a match is found, are counted in full
+    pub items_scanned: u64,
+    /// The time taken to write each snapshot file
+    pub snapshot_writes: LatencyHistogram,
+    /// The number of bytes written to snapshot files
+    pub snapshot_bytes: u64,
+    /// The number of snapshot files which could not be written
+    pub snapshot_failures: u64,
+}
+
+/// Counts durations into the latency histogram buckets, without locking.
+pub struct Histogram {
+    buckets: [AtomicU64; BUCKET_BOUNDS_MICROS.len() + 1],
+    count: AtomicU64,
+    total_micros: AtomicU64,
+    max_micros: AtomicU64,
+}
+
+impl Histogram {
+    /// Creates a histogram with no durations recorded.
+    pub fn new() -> Histogram {
+        return Histogram {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            count: AtomicU64::new(0),
+            total_micros: AtomicU64::new(0),
+            max_micros: AtomicU64::new(0),
+        };
+    }
+
+    /// Records a single duration.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - The duration of the operation
+    pub fn record(&self, duration: Duration) {
+        let micros: u64 = duration.as_micros().min(u64::MAX as u128) as u64;
+        let bucket: usize = BUCKET_BOUNDS_MICROS
+            .iter()
+            .position(|bound| micros <= *bound)
+            .unwrap_or(BUCKET_BOUNDS_MICROS.len());
+
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.total_micros.fetch_add(micros, Ordering::Relaxed);
+        self.max_micros.fetch_max(micros, Ordering::Relaxed);
+    }
+
+    /// Returns the durations recorded so far.
+    pub fn snapshot(&self) -> LatencyHistogram {
+        return LatencyHistogram {
+            count: self.count.load(Ordering::Relaxed),
+            total_micros: self.total_micros.load(Ordering::Relaxed),
+            max_micros: self.max_micros.load(Ordering::Relaxed),
+            buckets: self
+                .buckets
+                .iter()
+                .enumerate()
+                .map(|(index, count)| HistogramBucket {
+                    upper_bound_micros: BUCKET_BOUNDS_MICROS.get(index).copied(),
+                    count: count.load(Ordering::Relaxed),
+                })
+                .collect(),
+        };
+    }
+}
+
+impl Default for Histogram {
+    fn default() -> Histogram {
+        return Histogram::new();
+    }
+}
+
+/// Records the work done by the storage layer, without locking, so recording does not itself
+/// slow down storage operations.
+pub struct MetricsRecorder {
+    pub lock_waits: Histogram,
+    scans: AtomicU64,
+    items_scanned: AtomicU64,
+    pub snapshot_writes: Histogram,
+    snapshot_bytes: AtomicU64,
+    snapshot_failures: AtomicU64,
+}
+
+impl MetricsRecorder {
+    /// Creates a recorder with no work recorded.
+    pub fn new() -> MetricsRecorder {
+        return MetricsRecorder {
+            lock_waits: Histogram::new(),
+            scans: AtomicU64::new(0),
+            items_scanned: AtomicU64::new(0),
+            snapshot_writes: Histogram::new(),
+            snapshot_bytes: AtomicU64::new(0),
+            snapshot_failures: AtomicU64::new(0),
+        };
+    }
+
+    /// Records a scan of every stored booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The number of bookings held when the scan started
+    pub fn record_scan(&self, items: usize) {
+        self.scans.fetch_add(1, Ordering::Relaxed);
+        self.items_scanned
+            .fetch_add(items as u64, Ordering::Relaxed);
+    }
+
+    /// Records an attempt to write a snapshot file.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The number of bytes written, or None if the file could not be written
+    /// * `duration` - The time taken to write the file
+    pub fn record_snapshot(&self, bytes: Option<usize>, duration: Duration) {
+        self.snapshot_writes.record(duration);
+        match bytes {
+            Some(bytes) => self
+                .snapshot_bytes
+                .fetch_add(bytes as u64, Ordering::Relaxed),
+            None => self.snapshot_failures.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+
+    /// Returns the work recorded so far.
+    ///
+    /// # Arguments
+    ///
+    /// * `bookings` - The number of bookings currently held
+    pub fn snapshot(&self, bookings: usize) -> StorageMetrics {
+        return StorageMetrics {
+            bookings,
+            lock_waits: self.lock_waits.snapshot(),
+            scans: self.scans.load(Ordering::Relaxed),
+            items_scanned: self.items_scanned.load(Ordering::Relaxed),
+            snapshot_writes: self.snapshot_writes.snapshot(),
+            snapshot_bytes: self.snapshot_bytes.load(Ordering::Relaxed),
+            snapshot_failures: self.snapshot_failures.load(Ordering::Relaxed),
+        };
+    }
+}
+
+impl Default for MetricsRecorder {
+    fn default() -> MetricsRecorder {
+        return MetricsRecorder::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::metrics::*;
+
+    #[test]
+    fn record_latencies() {
+        let histogram: Histogram = Histogram::new();
+        histogram.record(Duration::from_micros(5));
+        histogram.record(Duration::from_micros(10));
+        histogram.record(Duration::from_millis(2));
+        histogram.record(Duration::from_secs(3));
+
+        let snapshot: LatencyHistogram = histogram.snapshot();
+        assert_eq!(snapshot.count, 4);
+        assert_eq!(snapshot.total_micros, 3_002_015);
+        assert_eq!(snapshot.max_micros, 3_000_000);
+        assert_eq!(snapshot.buckets.len(), 11);
+        assert_eq!(snapshot.buckets[0].count, 2);
+        assert_eq!(snapshot.buckets[5].upper_bound_micros, Some(5_000));
+        assert_eq!(snapshot.buckets[5].count, 1);
+        assert_eq!(snapshot.buckets[10].upper_bound_micros, None);
+        assert_eq!(snapshot.buckets[10].count, 1);
+    }
+}
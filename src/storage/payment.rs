@@ -0,0 +1,146 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::cancellation::CancellationReason;
+use super::room_booking::{BookingStatus, RoomBooking, DATE_FORMAT};
+use crate::config::{self, HotelConfig};
+use crate::pricing;
+pub use booking_types::payment::{Payment, Refund};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes the amount paid for a booking, and the amount still to pay
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Balance {
+    pub booking_id: Option<u32>,
+    /// The ISO 4217 code of the currency amounts are given in
+    pub currency: String,
+    /// The total price of the booking, or 0 if it cannot be priced
+    pub total: u64,
+    /// The deposit required before the booking is confirmed
+    pub deposit: u64,
+    /// The total of the payments received
+    pub paid: u64,
+    /// The total of the refunds made
+    pub refunded: u64,
+    /// The amount still to pay, which must be paid before the guest checks in
+    pub outstanding: u64,
+    /// The amount of the deposit still to pay
+    pub deposit_outstanding: u64,
+}
+
+impl Balance {
+    /// Creates the balance of a booking from its current price, the payments received for it and
+    /// the refunds made.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking` - The booking
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let balance = Balance::new(&booking);
+    /// ```
+    pub fn new(booking: &RoomBooking) -> Balance {
+        let (currency, total): (String, u64) = match pricing::quote(booking) {
+            Ok(quote) => (quote.currency, quote.total),
+            Err(_) => (config::hotel().currency, 0),
+        };
+        let deposit: u64 = config::hotel().deposit.deposit_for(total);
+        let paid: u64 = booking.payments.iter().map(|payment| payment.amount).sum();
+        let refunded: u64 = booking.refunds.iter().map(|refund| refund.amount).sum();
+        let held: u64 = paid.saturating_sub(refunded);
+
+        return Balance {
+            booking_id: booking.booking_id,
+            currency,
+            total,
+            deposit,
+            paid,
+            refunded,
+            outstanding: total.saturating_sub(held),
+            deposit_outstanding: deposit.saturating_sub(held),
+        };
+    }
+}
+
+/// Describes a refund reserved against a booking while it is requested from the payment provider,
+/// so refunds made at the same time cannot together refund more than the refundable amount. The
+/// reservation is completed once the refund is made, or released if it fails.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RefundReservation {
+    pub booking_id: u32,
+    /// The amount reserved, in the minor unit of the hotel's currency
+    pub amount: u64,
+    /// Whether the booking is a sandbox booking, which is not refunded through the provider
+    pub sandbox: bool,
+    /// The key sent to the payment provider with each attempt to make the refund, so a retried
+    /// request is only refunded once
+    pub idempotency_key: String,
+}
+
+/// Returns the amount of the payments received for a booking which is kept under the cancellation
+/// policy, and cannot be refunded. A booking cancelled less than ```cancellation_window_hours```
+/// before check in keeps its deposit, and a booking where the guest did not arrive keeps its no
+/// show fee. Nothing is kept for bookings which are still active, were cancelled in time, or were
+/// cancelled by the hotel. A cancelled booking whose cancellation time is unknown keeps its
+/// deposit.
+///
+/// # Arguments
+///
+/// * `booking` - The booking
+/// * `cancelled_at` - When the booking was cancelled, if known
+///
+/// # Examples
+///
+/// ```
+/// let kept = payment::retained(&booking, cancelled_at);
+/// ```
+pub fn retained(booking: &RoomBooking, cancelled_at: Option<DateTime<Utc>>) -> u64 {
+    match booking.status {
+        Some(BookingStatus::NoShow) => return booking.no_show_fee.unwrap_or_default(),
+        Some(BookingStatus::Cancelled) => {}
+        _ => return 0,
+    }
+
+    let reason: Option<CancellationReason> = booking
+        .cancellation
+        .as_ref()
+        .map(|cancellation| cancellation.reason);
+    if matches!(
+        reason,
+        Some(CancellationReason::HotelInitiated) | Some(CancellationReason::PaymentNotReceived)
+    ) {
+        return 0;
+    }
+
+    let hotel: HotelConfig = config::hotel();
+    let check_in: Option<DateTime<Utc>> =
+        NaiveDate::parse_from_str(&booking.check_in_date, DATE_FORMAT)
+            .ok()
+            .and_then(|date| {
+                return date
+                    .and_time(hotel.check_in_time)
+                    .and_local_timezone(hotel.timezone)
+                    .earliest();
+            })
+            .map(|check_in| check_in.with_timezone(&Utc));
+
+    let in_time: bool = match (cancelled_at, check_in) {
+        (Some(cancelled_at), Some(check_in)) => {
+            check_in - cancelled_at >= Duration::hours(hotel.cancellation_window_hours as i64)
+        }
+        _ => false,
+    };
+
+    return match in_time {
+        true => 0,
+        false => Balance::new(booking).deposit,
+    };
+}
@@ -0,0 +1,46 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Defines the payment outcomes reported by the payment provider
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub enum PaymentEventType {
+    /// A payment for the booking was taken
+    PaymentSucceeded,
+    /// A payment for the booking failed, or was reversed after it was taken
+    PaymentFailed,
+    /// An amount was refunded for the booking
+    Refunded,
+}
+
+/// Describes an event sent by the payment provider to the payment webhook. Events are stored when
+/// received, and applied to their booking in the background.
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentEvent {
+    /// The payment provider's id for the event. Events with an id which has already been received
+    /// are ignored, as the provider may send an event more than once.
+    pub event_id: String,
+    pub event_type: PaymentEventType,
+    pub booking_id: u32,
+    /// The amount paid or refunded, in the minor unit of the hotel's currency
+    pub amount: u64,
+    /// The payment provider's reference for the payment or refund, if any
+    #[serde(default)]
+    pub reference: Option<String>,
+    /// When the event was received, as an RFC 3339 timestamp. This is set automatically.
+    #[serde(default)]
+    pub received_at: Option<String>,
+    /// When the event was applied to its booking, as an RFC 3339 timestamp, or null if it is
+    /// waiting to be applied. This is set automatically.
+    #[serde(default)]
+    pub processed_at: Option<String>,
+    /// Why the event could not be applied to its booking, if it could not
+    #[serde(default)]
+    pub error: Option<String>,
+}
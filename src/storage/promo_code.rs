@@ -0,0 +1,200 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::room_booking::{RoomBooking, DATE_FORMAT};
+pub use booking_types::discount::{Discount, DiscountType};
+use chrono::NaiveDate;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Describes a promo code, which gives a discount on bookings made while it is valid
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+#[schemars(example = "example_promo_code")]
+pub struct PromoCode {
+    /// The code entered by the guest, e.g. 'SUMMER10'
+    pub code: String,
+    pub discount: Discount,
+    /// The first date the code may be redeemed on (YYYY-MM-DD)
+    #[serde(default)]
+    pub valid_from: Option<String>,
+    /// The last date the code may be redeemed on (YYYY-MM-DD)
+    #[serde(default)]
+    pub valid_until: Option<String>,
+    /// The number of times the code may be redeemed. The code may be redeemed any number of times
+    /// if this is not set.
+    #[serde(default)]
+    pub max_redemptions: Option<u32>,
+    /// The number of times the code has been redeemed
+    #[serde(default)]
+    pub redemptions: u32,
+    /// The room types the code may be used for. The code may be used for any room type if this
+    /// is empty.
+    #[serde(default)]
+    pub room_type_ids: Vec<u8>,
+}
+
+/// Returns the example promo code shown in the API documentation, as it would be sent to create a
+/// promo code.
+fn example_promo_code() -> PromoCode {
+    return PromoCode {
+        code: "SUMMER10".to_string(),
+        discount: Discount {
+            discount_type: DiscountType::Percentage,
+            value: 10,
+        },
+        valid_from: Some("2030-06-01".to_string()),
+        valid_until: Some("2030-08-31".to_string()),
+        max_redemptions: Some(100),
+        redemptions: 0,
+        room_type_ids: vec![1, 2],
+    };
+}
+
+impl PromoCode {
+    /// Checks the promo code details are valid. Returns a list describing each problem found,
+    /// which is empty if the promo code is valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let errors = promo_code.validate();
+    /// ```
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors: Vec<String> = Vec::new();
+
+        if self.code.trim().is_empty() {
+            errors.push("code must not be empty".to_string());
+        }
+
+        match self.discount.discount_type {
+            DiscountType::Percentage if self.discount.value == 0 || self.discount.value > 100 => {
+                errors.push("Percentage discounts must be between 1 and 100".to_string())
+            }
+            DiscountType::FixedAmount if self.discount.value == 0 => {
+                errors.push("Fixed amount discounts must be more than 0".to_string())
+            }
+            _ => {}
+        }
+
+        let valid_from = self.valid_from.as_ref().map(|date| parse_date(date));
+        if let Some(Err(_)) = valid_from {
+            errors.push("validFrom must be a valid date in the format YYYY-MM-DD".to_string());
+        }
+
+        let valid_until = self.valid_until.as_ref().map(|date| parse_date(date));
+        if let Some(Err(_)) = valid_until {
+            errors.push("validUntil must be a valid date in the format YYYY-MM-DD".to_string());
+        }
+
+        if let (Some(Ok(valid_from)), Some(Ok(valid_until))) = (valid_from, valid_until) {
+            if valid_until < valid_from {
+                errors.push("validUntil must not be before validFrom".to_string());
+            }
+        }
+
+        return errors;
+    }
+
+    /// Checks the promo code may be redeemed for a booking on the provided date. Returns a
+    /// description of the problem if it may not.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking` - The booking the code is being redeemed for
+    /// * `date` - The date the code is being redeemed on
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// promo_code.check(&booking, local_time::today())?;
+    /// ```
+    pub fn check(&self, booking: &RoomBooking, date: NaiveDate) -> Result<(), String> {
+        let started: bool = match &self.valid_from {
+            Some(valid_from) => parse_date(valid_from).is_ok_and(|valid_from| valid_from <= date),
+            None => true,
+        };
+        let ended: bool = match &self.valid_until {
+            Some(valid_until) => parse_date(valid_until).map_or(true, |until| until < date),
+            None => false,
+        };
+
+        if !started || ended {
+            return Err(format!("Promo code {} is not currently valid", self.code));
+        }
+
+        if self
+            .max_redemptions
+            .is_some_and(|max_redemptions| self.redemptions >= max_redemptions)
+        {
+            return Err(format!("Promo code {} has been fully redeemed", self.code));
+        }
+
+        if !self.room_type_ids.is_empty() && !self.room_type_ids.contains(&booking.room_type_id) {
+            return Err(format!(
+                "Promo code {} may not be used for room type {}",
+                self.code, booking.room_type_id
+            ));
+        }
+
+        return Ok(());
+    }
+}
+
+/// Parses a promo code validity date.
+fn parse_date(date: &str) -> Result<NaiveDate, chrono::ParseError> {
+    return NaiveDate::parse_from_str(date, DATE_FORMAT);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::promo_code::*;
+
+    #[test]
+    fn check_promo_code() {
+        let mut promo_code = PromoCode {
+            code: "SUMMER10".to_string(),
+            discount: Discount {
+                discount_type: DiscountType::Percentage,
+                value: 10,
+            },
+            valid_from: Some("2020-06-01".to_string()),
+            valid_until: Some("2020-08-31".to_string()),
+            max_redemptions: Some(1),
+            redemptions: 0,
+            room_type_ids: vec![1],
+        };
+        assert!(promo_code.validate().is_empty());
+        assert_eq!(promo_code.discount.amount(12345), 1235);
+
+        let mut booking: RoomBooking = rocket::serde::json::from_str(
+            r#"{"customerId":1,"roomTypeId":1,"checkInDate":"2020-07-01",
+            "checkOutDate":"2020-07-03","status":null}"#,
+        )
+        .unwrap();
+        let date = |date: &str| NaiveDate::parse_from_str(date, DATE_FORMAT).unwrap();
+
+        assert!(promo_code.check(&booking, date("2020-06-01")).is_ok());
+        assert!(promo_code.check(&booking, date("2020-09-01")).is_err());
+
+        booking.room_type_id = 2;
+        assert!(promo_code.check(&booking, date("2020-07-01")).is_err());
+
+        booking.room_type_id = 1;
+        promo_code.redemptions = 1;
+        assert!(promo_code.check(&booking, date("2020-07-01")).is_err());
+
+        promo_code.discount = Discount {
+            discount_type: DiscountType::FixedAmount,
+            value: 5000,
+        };
+        assert_eq!(promo_code.discount.amount(4000), 4000);
+
+        promo_code.discount.discount_type = DiscountType::Percentage;
+        promo_code.valid_until = Some("2020-05-31".to_string());
+        assert_eq!(promo_code.validate().len(), 2);
+    }
+}
@@ -0,0 +1,205 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::room_booking::{RoomBooking, DATE_FORMAT};
+use chrono::{Duration, Months, NaiveDate};
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The largest number of bookings a single recurrence rule may create
+pub static MAX_OCCURRENCES: u32 = 104;
+
+/// Defines how often a recurring booking repeats
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// Describes how a recurring booking repeats, e.g. every week for 12 weeks
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+#[schemars(example = "example_rule")]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    /// The number of days, weeks or months between each booking, defaulting to 1
+    #[serde(default = "default_interval")]
+    pub interval: u32,
+    /// The total number of bookings to create, including the first
+    pub occurrences: u32,
+}
+
+/// Returns the example recurrence rule shown in the API documentation.
+fn example_rule() -> RecurrenceRule {
+    return RecurrenceRule {
+        frequency: Frequency::Weekly,
+        interval: 1,
+        occurrences: 12,
+    };
+}
+
+/// Returns the default interval of a recurrence rule.
+fn default_interval() -> u32 {
+    return 1;
+}
+
+/// Describes a series of bookings to create. The first booking of the series uses the dates of the
+/// booking provided, and each later booking keeps the same length of stay.
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringBooking {
+    pub booking: RoomBooking,
+    pub rule: RecurrenceRule,
+}
+
+/// Describes changes to apply to every confirmed booking in a series. Fields which are not
+/// provided are left unchanged.
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesUpdate {
+    pub room_type_id: Option<u8>,
+    pub expected_arrival_time: Option<String>,
+    pub late_check_out_time: Option<String>,
+}
+
+impl RecurrenceRule {
+    /// Checks the rule creates at least one, and no more than ```MAX_OCCURRENCES```, bookings.
+    pub fn is_valid(&self) -> bool {
+        return self.interval > 0 && self.occurrences > 0 && self.occurrences <= MAX_OCCURRENCES;
+    }
+
+    /// Returns the offset from the first booking's date of the occurrence provided.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - The date of the first booking
+    /// * `occurrence` - The occurrence to return the date of, starting from 0
+    fn shift(&self, date: NaiveDate, occurrence: u32) -> Option<NaiveDate> {
+        let steps: u32 = self.interval * occurrence;
+
+        return match self.frequency {
+            Frequency::Daily => date.checked_add_signed(Duration::days(steps as i64)),
+            Frequency::Weekly => date.checked_add_signed(Duration::weeks(steps as i64)),
+            Frequency::Monthly => date.checked_add_months(Months::new(steps)),
+        };
+    }
+}
+
+impl RecurringBooking {
+    /// Returns each booking in the series, without a booking id or series id. Returns None if the
+    /// rule or dates of the first booking are invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let bookings = recurring.occurrences();
+    /// ```
+    pub fn occurrences(&self) -> Option<Vec<RoomBooking>> {
+        if !self.rule.is_valid() {
+            return None;
+        }
+
+        let check_in_date = NaiveDate::parse_from_str(&self.booking.check_in_date, DATE_FORMAT);
+        let check_out_date = NaiveDate::parse_from_str(&self.booking.check_out_date, DATE_FORMAT);
+        let (check_in_date, check_out_date) = match (check_in_date, check_out_date) {
+            (Ok(check_in_date), Ok(check_out_date)) => (check_in_date, check_out_date),
+            _ => return None,
+        };
+        let stay: Duration = check_out_date - check_in_date;
+
+        return (0..self.rule.occurrences)
+            .map(|occurrence| {
+                let check_in_date: NaiveDate = self.rule.shift(check_in_date, occurrence)?;
+                let mut booking: RoomBooking = self.booking.clone();
+                booking.check_in_date = check_in_date.format(DATE_FORMAT).to_string();
+                booking.check_out_date = (check_in_date + stay).format(DATE_FORMAT).to_string();
+                return Some(booking);
+            })
+            .collect();
+    }
+}
+
+impl SeriesUpdate {
+    /// Applies the update to a booking.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking` - The booking to update
+    pub fn apply(&self, booking: &mut RoomBooking) {
+        if let Some(room_type_id) = self.room_type_id {
+            booking.room_type_id = room_type_id;
+        }
+
+        if let Some(expected_arrival_time) = &self.expected_arrival_time {
+            booking.expected_arrival_time = Some(expected_arrival_time.clone());
+        }
+
+        if let Some(late_check_out_time) = &self.late_check_out_time {
+            booking.late_check_out_time = Some(late_check_out_time.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::recurring_booking::*;
+
+    #[test]
+    fn recurring_occurrences() {
+        let recurring = RecurringBooking {
+            booking: RoomBooking {
+                booking_id: None,
+                customer_id: 1,
+                room_type_id: 3,
+                check_in_date: "2020-01-07".to_string(),
+                check_out_date: "2020-01-08".to_string(),
+                status: None,
+                expected_arrival_time: None,
+                late_check_out_time: None,
+                day_use: false,
+                start_time: None,
+                end_time: None,
+                billing_milestones: Vec::new(),
+                series_id: None,
+                cancellation: None,
+                no_show_fee: None,
+                payment_due_by: None,
+                addons: Vec::new(),
+                promo_code: None,
+                discount: None,
+                guests: 1,
+                tax_exemptions: Vec::new(),
+                account_id: None,
+                agent_id: None,
+                channel_reservation_id: None,
+                children: 0,
+                rate_plans: Vec::new(),
+                pricing: None,
+                payments: Vec::new(),
+                refunds: Vec::new(),
+                created_at: None,
+                updated_at: None,
+                cancelled_at: None,
+                sandbox: false,
+            },
+            rule: RecurrenceRule {
+                frequency: Frequency::Weekly,
+                interval: 1,
+                occurrences: 12,
+            },
+        };
+
+        let bookings: Vec<RoomBooking> = recurring.occurrences().unwrap();
+        assert_eq!(bookings.len(), 12);
+        assert_eq!(bookings[1].check_in_date, "2020-01-14");
+        assert_eq!(bookings[11].check_out_date, "2020-03-25");
+
+        let mut invalid: RecurringBooking = recurring.clone();
+        invalid.rule.occurrences = MAX_OCCURRENCES + 1;
+        assert!(invalid.occurrences().is_none());
+    }
+}
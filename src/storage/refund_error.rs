@@ -0,0 +1,53 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use std::fmt;
+
+/// Describes why a refund could not be made
+#[derive(Clone, PartialEq, Debug)]
+pub enum RefundError {
+    /// The refund amount is 0
+    InvalidAmount,
+    /// The refund is more than the refundable amount, which is provided. This is the amount paid,
+    /// less earlier refunds, refunds in progress, and the amount kept under the cancellation
+    /// policy
+    ExceedsRefundable(u64),
+    /// The booking does not exist
+    NotFound,
+    /// The booking storage could not be accessed
+    Unavailable,
+}
+
+impl fmt::Display for RefundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            RefundError::InvalidAmount => write!(f, "amount must be greater than 0"),
+            RefundError::ExceedsRefundable(refundable) => write!(
+                f,
+                "amount must not be more than the refundable amount of {}",
+                refundable
+            ),
+            RefundError::NotFound => write!(f, "Booking not found"),
+            RefundError::Unavailable => write!(f, "Booking storage is unavailable"),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::refund_error::*;
+
+    #[test]
+    fn describe_error() {
+        assert_eq!(
+            RefundError::ExceedsRefundable(2500).to_string(),
+            "amount must not be more than the refundable amount of 2500"
+        );
+        assert_eq!(
+            RefundError::InvalidAmount.to_string(),
+            "amount must be greater than 0"
+        );
+    }
+}
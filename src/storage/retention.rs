@@ -0,0 +1,42 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::room_booking::BookingStatus;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Defines what happens to bookings once they are older than the retention period
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub enum RetentionAction {
+    /// The booking is anonymized and moved to the archive, where it is kept for statistics
+    Archive,
+    /// The booking is deleted
+    Purge,
+}
+
+/// Describes a booking removed by the retention policy
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RetainedBooking {
+    pub booking_id: Option<u32>,
+    pub check_out_date: String,
+    pub status: Option<BookingStatus>,
+}
+
+/// Describes the bookings removed by a run of the retention policy
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionReport {
+    /// Bookings which checked out before this date (YYYY-MM-DD) are removed
+    pub cutoff_date: String,
+    pub action: RetentionAction,
+    /// Whether this was a dry run, in which case the bookings listed were not removed
+    pub dry_run: bool,
+    /// The bookings removed, ordered by booking id
+    pub bookings: Vec<RetainedBooking>,
+    /// The number of change log entries removed for the bookings
+    pub changes: usize,
+}
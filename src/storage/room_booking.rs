@@ -3,83 +3,307 @@
     SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
 */
 
-use rocket_okapi::okapi::schemars;
-use rocket_okapi::okapi::schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
-
-/// Defines the allowed values for the status of a booking
-#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
-pub enum BookingStatus {
-    /// A booking that has been paid for, but the user has not yet checked in
-    Confirmed,
-    /// A booking which has been completed by the user checking in to their room
-    Complete,
-    /// A booking that the user has cancelled
-    Cancelled,
+pub use booking_types::room_booking::*;
+
+use crate::addons;
+use crate::agents;
+use crate::config::{self, OccupancyLimit};
+use crate::stay_rules;
+use crate::storage;
+use crate::tax;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+
+/// The longest value accepted in a free text field of a booking, in characters.
+static MAX_TEXT_LENGTH: usize = 64;
+
+/// Parses an optional booking time, returning None if the time is missing or invalid.
+fn parse_time(time: &Option<String>) -> Option<NaiveTime> {
+    return time
+        .as_ref()
+        .and_then(|time| NaiveTime::parse_from_str(time, TIME_FORMAT).ok());
 }
 
-impl BookingStatus {
-    /// Converts a booking status string into the corresponding BookingStatus enum
+/// The rules the service applies to bookings, which depend on the hotel's configuration or on
+/// parsing booking dates and times, so are not part of the shared booking model.
+pub trait BookingRules {
+    /// Checks the booking details are valid. Returns a list describing each problem found, which
+    /// is empty if the booking is valid.
     ///
-    /// # Arguments
+    /// # Examples
     ///
-    /// * `value` - A string containing the value to convert
+    /// ```
+    /// let errors = booking.validate();
+    /// ```
+    fn validate(&self) -> Vec<String>;
+
+    /// Returns each night of an overnight booking, as a date in the format YYYY-MM-DD. Returns an
+    /// empty list for day use bookings, or if the booking's dates are invalid.
     ///
     /// # Examples
     ///
     /// ```
-    /// let status = BookingStatus::from_string("Complete");
+    /// let nights = booking.nights();
     /// ```
-    pub fn from_string(value: &str) -> Option<BookingStatus> {
-        match value {
-            "Confirmed" => Some(BookingStatus::Confirmed),
-            "Complete" => Some(BookingStatus::Complete),
-            "Cancelled" => Some(BookingStatus::Cancelled),
-            _ => None,
-        }
-    }
-}
+    fn nights(&self) -> Vec<String>;
 
-/// Describes a single room booking
-#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
-#[serde(rename_all = "camelCase")]
-pub struct RoomBooking {
-    pub booking_id: Option<u32>,
-    pub customer_id: u32,
-    pub room_type_id: u8,
-    pub check_in_date: String,
-    pub check_out_date: String,
-    pub status: Option<BookingStatus>,
-}
-
-impl RoomBooking {
-    /// Sets the booking id of the current booking.
+    /// Checks whether the deposit for a booking pending payment was due before the provided time.
     ///
     /// # Arguments
     ///
-    /// * `booking_id` - A u32 which holds the id of the booking
+    /// * `now` - The time to compare the payment due time against
     ///
     /// # Examples
     ///
     /// ```
-    /// booking.set_booking_id(1);
+    /// let expired = booking.payment_expired(clock::now());
     /// ```
-    pub fn set_booking_id(&mut self, booking_id: u32) {
-        self.booking_id = Some(booking_id);
-    }
+    fn payment_expired(&self, now: DateTime<Utc>) -> bool;
 
-    /// Sets the status of the current booking.
+    /// Checks whether this booking and another day use booking occupy a day use room at the same
+    /// time. Returns false if either booking is not a day use booking.
     ///
     /// # Arguments
     ///
-    /// * `booking_status` - A BookingStatus enum value
+    /// * `other` - The booking to compare against
     ///
     /// # Examples
     ///
     /// ```
-    /// booking.set_status(BookingStatus::Cancelled);
-    ///
-    pub fn set_status(&mut self, booking_status: BookingStatus) {
-        self.status = Some(booking_status);
+    /// let clash = booking.overlaps_day_use(&other);
+    /// ```
+    fn overlaps_day_use(&self, other: &RoomBooking) -> bool;
+}
+
+impl BookingRules for RoomBooking {
+    fn validate(&self) -> Vec<String> {
+        let mut errors: Vec<String> = Vec::new();
+
+        let check_in_date = NaiveDate::parse_from_str(&self.check_in_date, DATE_FORMAT);
+        if check_in_date.is_err() {
+            errors.push("checkInDate must be a valid date in the format YYYY-MM-DD".to_string());
+        }
+
+        let check_out_date = NaiveDate::parse_from_str(&self.check_out_date, DATE_FORMAT);
+        if check_out_date.is_err() {
+            errors.push("checkOutDate must be a valid date in the format YYYY-MM-DD".to_string());
+        }
+
+        if let (Ok(check_in_date), Ok(check_out_date)) = (check_in_date, check_out_date) {
+            let max_stay_nights: u32 = config::hotel().max_stay_nights;
+
+            if self.day_use {
+                if check_out_date != check_in_date {
+                    errors.push(
+                        "Day use bookings must have the same checkInDate and checkOutDate"
+                            .to_string(),
+                    );
+                }
+            } else if check_out_date <= check_in_date {
+                errors.push("checkOutDate must be after checkInDate".to_string());
+            } else if (check_out_date - check_in_date).num_days() > max_stay_nights as i64 {
+                errors.push(format!(
+                    "Stays must not be longer than {} nights",
+                    max_stay_nights
+                ));
+            }
+        }
+
+        errors.append(&mut validate_lengths(self));
+        errors.append(&mut validate_times(self));
+        errors.append(&mut validate_day_use(self));
+        if self.guests == 0 {
+            errors.push("guests must be at least 1".to_string());
+        }
+        if self.sandbox && config::hotel().sandbox.is_none() {
+            errors.push("sandbox must not be set, as the sandbox is not enabled".to_string());
+        }
+        errors.append(&mut validate_occupancy(self));
+
+        errors.append(&mut addons::validate(&self.addons));
+        errors.append(&mut tax::validate(&self.tax_exemptions));
+        errors.append(&mut storage::check_account(self));
+        errors.append(&mut storage::check_restrictions(self));
+        errors.append(&mut stay_rules::validate(self));
+        errors.append(&mut agents::validate(self.agent_id));
+
+        return errors;
+    }
+
+    fn nights(&self) -> Vec<String> {
+        let check_in_date = NaiveDate::parse_from_str(&self.check_in_date, DATE_FORMAT);
+        let check_out_date = NaiveDate::parse_from_str(&self.check_out_date, DATE_FORMAT);
+
+        return match (self.day_use, check_in_date, check_out_date) {
+            (false, Ok(check_in_date), Ok(check_out_date)) => check_in_date
+                .iter_days()
+                .take_while(|night| *night < check_out_date)
+                .map(|night| night.format(DATE_FORMAT).to_string())
+                .collect(),
+            _ => Vec::new(),
+        };
+    }
+
+    fn payment_expired(&self, now: DateTime<Utc>) -> bool {
+        if self.status != Some(BookingStatus::PendingPayment) {
+            return false;
+        }
+
+        return match &self.payment_due_by {
+            Some(due_by) => DateTime::parse_from_rfc3339(due_by).is_ok_and(|due_by| due_by <= now),
+            None => false,
+        };
+    }
+
+    fn overlaps_day_use(&self, other: &RoomBooking) -> bool {
+        if !self.day_use || !other.day_use || self.check_in_date != other.check_in_date {
+            return false;
+        }
+
+        return match (
+            parse_time(&self.start_time),
+            parse_time(&self.end_time),
+            parse_time(&other.start_time),
+            parse_time(&other.end_time),
+        ) {
+            (Some(start), Some(end), Some(other_start), Some(other_end)) => {
+                start < other_end && other_start < end
+            }
+            _ => false,
+        };
     }
 }
+
+/// Checks the free text fields of a booking, such as the promo code, are no longer than the
+/// longest value accepted.
+fn validate_lengths(booking: &RoomBooking) -> Vec<String> {
+    let mut fields: Vec<(&str, &str)> = Vec::new();
+    if let Some(promo_code) = &booking.promo_code {
+        fields.push(("promoCode", promo_code));
+    }
+    if let Some(reservation_id) = &booking.channel_reservation_id {
+        fields.push(("channelReservationId", reservation_id));
+    }
+    for exemption in &booking.tax_exemptions {
+        fields.push(("taxExemptions", exemption));
+    }
+
+    return fields
+        .into_iter()
+        .filter(|(_, value)| value.chars().count() > MAX_TEXT_LENGTH)
+        .map(|(field, _)| {
+            format!(
+                "{} must not be longer than {} characters",
+                field, MAX_TEXT_LENGTH
+            )
+        })
+        .collect();
+}
+
+/// Checks the expected arrival and late check out times are valid, and within the hotel's
+/// check in and check out policy.
+fn validate_times(booking: &RoomBooking) -> Vec<String> {
+    let mut errors: Vec<String> = Vec::new();
+    let hotel = config::hotel();
+
+    if let Some(arrival_time) = &booking.expected_arrival_time {
+        match NaiveTime::parse_from_str(arrival_time, TIME_FORMAT) {
+            Ok(time) if time < hotel.check_in_time => errors.push(format!(
+                "expectedArrivalTime must not be before the check in time of {}",
+                hotel.check_in_time.format(TIME_FORMAT)
+            )),
+            Ok(_) => {}
+            Err(_) => errors
+                .push("expectedArrivalTime must be a valid time in the format HH:MM".to_string()),
+        }
+    }
+
+    if let Some(check_out_time) = &booking.late_check_out_time {
+        match NaiveTime::parse_from_str(check_out_time, TIME_FORMAT) {
+            Ok(time) if time <= hotel.check_out_time => errors.push(format!(
+                "lateCheckOutTime must be after the check out time of {}",
+                hotel.check_out_time.format(TIME_FORMAT)
+            )),
+            Ok(time) if time > hotel.latest_check_out_time => errors.push(format!(
+                "lateCheckOutTime must not be after {}",
+                hotel.latest_check_out_time.format(TIME_FORMAT)
+            )),
+            Ok(_) => {}
+            Err(_) => {
+                errors.push("lateCheckOutTime must be a valid time in the format HH:MM".to_string())
+            }
+        }
+    }
+
+    return errors;
+}
+
+/// Checks a day use booking has a valid start and end time, and that overnight bookings do
+/// not.
+fn validate_day_use(booking: &RoomBooking) -> Vec<String> {
+    let mut errors: Vec<String> = Vec::new();
+
+    if !booking.day_use {
+        if booking.start_time.is_some() || booking.end_time.is_some() {
+            errors.push("startTime and endTime must only be set for day use bookings".to_string());
+        }
+
+        return errors;
+    }
+
+    let start_time = parse_time(&booking.start_time);
+    if start_time.is_none() {
+        errors.push("startTime must be a valid time in the format HH:MM".to_string());
+    }
+
+    let end_time = parse_time(&booking.end_time);
+    if end_time.is_none() {
+        errors.push("endTime must be a valid time in the format HH:MM".to_string());
+    }
+
+    if let (Some(start_time), Some(end_time)) = (start_time, end_time) {
+        if end_time <= start_time {
+            errors.push("endTime must be after startTime".to_string());
+        }
+    }
+
+    if booking.expected_arrival_time.is_some() || booking.late_check_out_time.is_some() {
+        errors.push(
+            "Day use bookings must use startTime and endTime instead of expectedArrivalTime and lateCheckOutTime"
+                .to_string(),
+        );
+    }
+
+    return errors;
+}
+
+/// Checks the number of adults and children staying is within the occupancy limits of the room
+/// type, if it has any.
+fn validate_occupancy(booking: &RoomBooking) -> Vec<String> {
+    let mut errors: Vec<String> = Vec::new();
+
+    if booking.children >= booking.guests && booking.guests > 0 {
+        errors.push("At least one guest must be an adult".to_string());
+    }
+
+    let limit: OccupancyLimit = match config::occupancy_for(booking.room_type_id) {
+        Some(limit) => limit,
+        None => return errors,
+    };
+
+    let adults: u32 = booking.guests.saturating_sub(booking.children);
+    if adults > limit.max_adults {
+        errors.push(format!(
+            "Room type {} allows at most {} adults",
+            booking.room_type_id, limit.max_adults
+        ));
+    }
+
+    if booking.children > limit.max_children {
+        errors.push(format!(
+            "Room type {} allows at most {} children",
+            booking.room_type_id, limit.max_children
+        ));
+    }
+
+    return errors;
+}
@@ -0,0 +1,37 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Defines the actions which may be taken when a new booking matches a screening rule
+#[derive(
+    Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord, Debug,
+)]
+pub enum ScreeningAction {
+    /// The booking is created as normal, and the match is recorded
+    Flag,
+    /// The booking is created pending review, and must be approved before it is confirmed
+    Hold,
+    /// The booking is not created
+    Reject,
+}
+
+/// Describes a screening rule a new booking matched, and the action taken
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreeningDecision {
+    /// The id of the booking, unless the booking was rejected
+    pub booking_id: Option<u32>,
+    pub customer_id: u32,
+    /// The name of the rule the booking matched, e.g. 'velocity'
+    pub rule: String,
+    pub action: ScreeningAction,
+    /// Why the booking matched the rule
+    pub reason: String,
+    /// The time the booking was screened (RFC 3339)
+    pub decided_at: String,
+}
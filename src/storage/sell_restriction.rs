@@ -0,0 +1,176 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::room_booking::{BookingRules, RoomBooking, DATE_FORMAT};
+use chrono::NaiveDate;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Defines the kinds of restriction which may be placed on sales
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub enum RestrictionType {
+    /// No bookings may include a night within the period
+    Blackout,
+    /// No bookings may check in on a date within the period
+    ClosedToArrival,
+    /// No bookings may check out on a date within the period
+    ClosedToDeparture,
+}
+
+/// Describes a restriction on the bookings which may be made during a period, e.g. a blackout
+/// during a convention
+#[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+#[schemars(example = "example_restriction")]
+pub struct SellRestriction {
+    pub restriction_id: Option<u32>,
+    pub restriction_type: RestrictionType,
+    /// The room type the restriction applies to, or null if it applies to every room type
+    #[serde(default)]
+    pub room_type_id: Option<u8>,
+    /// The first date of the period (YYYY-MM-DD)
+    pub start_date: String,
+    /// The last date of the period, inclusive (YYYY-MM-DD)
+    pub end_date: String,
+    /// A description of why sales are restricted, e.g. the name of the convention
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Returns the example sell restriction shown in the API documentation, as it would be sent to
+/// create a restriction.
+fn example_restriction() -> SellRestriction {
+    return SellRestriction {
+        restriction_id: None,
+        restriction_type: RestrictionType::Blackout,
+        room_type_id: None,
+        start_date: "2030-09-14".to_string(),
+        end_date: "2030-09-17".to_string(),
+        reason: Some("Annual trade convention".to_string()),
+    };
+}
+
+impl SellRestriction {
+    /// Checks the restriction details are valid. Returns a list describing each problem found,
+    /// which is empty if the restriction is valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let errors = restriction.validate();
+    /// ```
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors: Vec<String> = Vec::new();
+
+        let start_date = NaiveDate::parse_from_str(&self.start_date, DATE_FORMAT);
+        if start_date.is_err() {
+            errors.push("startDate must be a valid date in the format YYYY-MM-DD".to_string());
+        }
+
+        let end_date = NaiveDate::parse_from_str(&self.end_date, DATE_FORMAT);
+        if end_date.is_err() {
+            errors.push("endDate must be a valid date in the format YYYY-MM-DD".to_string());
+        }
+
+        if let (Ok(start_date), Ok(end_date)) = (start_date, end_date) {
+            if end_date < start_date {
+                errors.push("endDate must not be before startDate".to_string());
+            }
+        }
+
+        return errors;
+    }
+
+    /// Checks whether the restriction prevents a booking from being made. Returns a description
+    /// of the restriction if so.
+    ///
+    /// # Arguments
+    ///
+    /// * `booking` - The booking to check
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let error = restriction.check(&booking);
+    /// ```
+    pub fn check(&self, booking: &RoomBooking) -> Option<String> {
+        if self
+            .room_type_id
+            .is_some_and(|room_type_id| room_type_id != booking.room_type_id)
+        {
+            return None;
+        }
+
+        let within = |date: &String| -> bool {
+            return *date >= self.start_date && *date <= self.end_date;
+        };
+
+        let restricted: bool = match self.restriction_type {
+            RestrictionType::Blackout if booking.day_use => within(&booking.check_in_date),
+            RestrictionType::Blackout => booking.nights().iter().any(within),
+            RestrictionType::ClosedToArrival => within(&booking.check_in_date),
+            RestrictionType::ClosedToDeparture => within(&booking.check_out_date),
+        };
+
+        if !restricted {
+            return None;
+        }
+
+        let description: &str = match self.restriction_type {
+            RestrictionType::Blackout => "Bookings are not accepted",
+            RestrictionType::ClosedToArrival => "Check in is not allowed",
+            RestrictionType::ClosedToDeparture => "Check out is not allowed",
+        };
+
+        return Some(match &self.reason {
+            Some(reason) => format!(
+                "{} between {} and {} ({})",
+                description, self.start_date, self.end_date, reason
+            ),
+            None => format!(
+                "{} between {} and {}",
+                description, self.start_date, self.end_date
+            ),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::sell_restriction::*;
+    use rocket::serde::json::{from_value, json};
+
+    #[test]
+    fn check_restriction() {
+        let mut restriction = SellRestriction {
+            restriction_id: None,
+            restriction_type: RestrictionType::Blackout,
+            room_type_id: Some(1),
+            start_date: "2020-01-03".to_string(),
+            end_date: "2020-01-04".to_string(),
+            reason: None,
+        };
+        let mut booking: RoomBooking = from_value(json!({
+            "customerId": 1,
+            "roomTypeId": 1,
+            "checkInDate": "2020-01-01",
+            "checkOutDate": "2020-01-03",
+        }))
+        .unwrap();
+
+        assert!(restriction.validate().is_empty());
+        assert!(restriction.check(&booking).is_none());
+
+        restriction.restriction_type = RestrictionType::ClosedToDeparture;
+        assert!(restriction.check(&booking).is_some());
+
+        booking.room_type_id = 2;
+        assert!(restriction.check(&booking).is_none());
+
+        restriction.end_date = "2020-01-02".to_string();
+        assert_eq!(restriction.validate().len(), 1);
+    }
+}
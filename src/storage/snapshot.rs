@@ -0,0 +1,285 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use super::room_booking::{default_guests, BookingStatus, RoomBooking};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+
+/// The bytes each snapshot file starts with, before its layout version. Files saved before
+/// snapshots were versioned start with the number of bookings held, as a 64-bit integer, which
+/// could only match these bytes if billions of bookings were held.
+static MAGIC: &[u8; 4] = b"HRBS";
+/// The layout version snapshot files are saved in. This must be increased whenever a type saved in
+/// a snapshot changes shape, as data is saved by position rather than by name, and files saved in
+/// the previous layout converted when they are read.
+pub static VERSION: u32 = 1;
+/// The layout version of files saved before snapshots were versioned. Only the bookings were saved
+/// then.
+static UNVERSIONED: u32 = 0;
+
+/// The statuses a booking could have when snapshots were first saved, in their saved order.
+#[derive(Serialize, Deserialize)]
+enum OriginalBookingStatus {
+    Confirmed,
+    Complete,
+    Cancelled,
+}
+
+/// A booking as saved before snapshots were versioned.
+#[derive(Serialize, Deserialize)]
+struct OriginalRoomBooking {
+    booking_id: Option<u32>,
+    customer_id: u32,
+    room_type_id: u8,
+    check_in_date: String,
+    check_out_date: String,
+    status: Option<OriginalBookingStatus>,
+}
+
+impl OriginalRoomBooking {
+    /// Converts the booking to the current layout, leaving every setting added since unset.
+    fn upgrade(self) -> RoomBooking {
+        return RoomBooking {
+            booking_id: self.booking_id,
+            customer_id: self.customer_id,
+            room_type_id: self.room_type_id,
+            check_in_date: self.check_in_date,
+            check_out_date: self.check_out_date,
+            status: self.status.map(|status| match status {
+                OriginalBookingStatus::Confirmed => BookingStatus::Confirmed,
+                OriginalBookingStatus::Complete => BookingStatus::Complete,
+                OriginalBookingStatus::Cancelled => BookingStatus::Cancelled,
+            }),
+            expected_arrival_time: None,
+            late_check_out_time: None,
+            day_use: false,
+            start_time: None,
+            end_time: None,
+            billing_milestones: Vec::new(),
+            series_id: None,
+            cancellation: None,
+            no_show_fee: None,
+            payment_due_by: None,
+            addons: Vec::new(),
+            promo_code: None,
+            discount: None,
+            guests: default_guests(),
+            children: 0,
+            tax_exemptions: Vec::new(),
+            account_id: None,
+            agent_id: None,
+            channel_reservation_id: None,
+            rate_plans: Vec::new(),
+            pricing: None,
+            payments: Vec::new(),
+            refunds: Vec::new(),
+            created_at: None,
+            updated_at: None,
+            cancelled_at: None,
+            sandbox: false,
+        };
+    }
+}
+
+/// Returns the layout version of the content of a snapshot file, and the data which follows it.
+///
+/// # Arguments
+///
+/// * `content` - The content of the file
+fn split(content: &[u8]) -> (u32, &[u8]) {
+    if content.len() < MAGIC.len() + 4 || !content.starts_with(MAGIC) {
+        return (UNVERSIONED, content);
+    }
+
+    let (version, data) = content[MAGIC.len()..].split_at(4);
+    return (
+        u32::from_le_bytes([version[0], version[1], version[2], version[3]]),
+        data,
+    );
+}
+
+/// Returns an error describing why a snapshot file saved in a layout version cannot be read.
+///
+/// # Arguments
+///
+/// * `name` - The name of the file
+/// * `version` - The layout version the file was saved in
+fn unreadable(name: &str, version: u32) -> Box<dyn Error> {
+    if version > VERSION {
+        return format!(
+            "{} was saved in layout version {} by a newer release, which this release cannot read",
+            name, version
+        )
+        .into();
+    }
+
+    return format!(
+        "{} was saved in layout version {}, which cannot be converted",
+        name, version
+    )
+    .into();
+}
+
+/// Serializes data to binary, preceded by the current layout version, as saved to a snapshot
+/// file.
+///
+/// # Arguments
+///
+/// * `data` - The data to save
+///
+/// # Examples
+///
+/// ```
+/// let content = snapshot::encode(&booking_list)?;
+/// ```
+pub fn encode<T: Serialize>(data: &T) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut content: Vec<u8> = MAGIC.to_vec();
+    content.extend_from_slice(&VERSION.to_le_bytes());
+    bincode::serialize_into(&mut content, data)?;
+    return Ok(content);
+}
+
+/// Deserializes the content of a snapshot file saved in the current layout version. Returns an
+/// error if the file was saved in any other layout.
+///
+/// # Arguments
+///
+/// * `name` - The name of the file, used to describe errors
+/// * `content` - The content of the file
+///
+/// # Examples
+///
+/// ```
+/// let change_log: Vec<BookingChange> = snapshot::decode("changes.dat", &content)?;
+/// ```
+pub fn decode<T: DeserializeOwned>(name: &str, content: &[u8]) -> Result<T, Box<dyn Error>> {
+    let (version, data) = split(content);
+    if version != VERSION {
+        return Err(unreadable(name, version));
+    }
+
+    return Ok(bincode::deserialize(data)?);
+}
+
+/// Deserializes the content of the bookings snapshot file, keyed by booking id, converting
+/// bookings saved before snapshots were versioned to the current layout.
+///
+/// # Arguments
+///
+/// * `name` - The name of the file, used to describe errors
+/// * `content` - The content of the file
+///
+/// # Examples
+///
+/// ```
+/// let booking_list = snapshot::decode_bookings("booking.dat", &content)?;
+/// ```
+pub fn decode_bookings(
+    name: &str,
+    content: &[u8],
+) -> Result<HashMap<u32, RoomBooking>, Box<dyn Error>> {
+    let (version, data) = split(content);
+    if version != UNVERSIONED {
+        return decode(name, content);
+    }
+
+    let original: HashMap<u32, OriginalRoomBooking> = bincode::deserialize(data)?;
+    return Ok(original
+        .into_iter()
+        .map(|(booking_id, booking)| (booking_id, booking.upgrade()))
+        .collect());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::isolated::IsolatedStorage;
+    use crate::storage::snapshot::*;
+
+    fn original_bookings() -> HashMap<u32, OriginalRoomBooking> {
+        return HashMap::from([
+            (
+                1,
+                OriginalRoomBooking {
+                    booking_id: Some(1),
+                    customer_id: 7,
+                    room_type_id: 2,
+                    check_in_date: "2023-05-01".to_string(),
+                    check_out_date: "2023-05-03".to_string(),
+                    status: Some(OriginalBookingStatus::Complete),
+                },
+            ),
+            (
+                2,
+                OriginalRoomBooking {
+                    booking_id: Some(2),
+                    customer_id: 8,
+                    room_type_id: 1,
+                    check_in_date: "2023-06-01".to_string(),
+                    check_out_date: "2023-06-02".to_string(),
+                    status: Some(OriginalBookingStatus::Cancelled),
+                },
+            ),
+        ]);
+    }
+
+    #[test]
+    fn round_trip_versioned_snapshot() {
+        let content: Vec<u8> = encode(&vec![1u32, 2, 3]).unwrap();
+        assert!(content.starts_with(MAGIC));
+        assert_eq!(
+            decode::<Vec<u32>>("changes.dat", &content).unwrap(),
+            vec![1, 2, 3]
+        );
+
+        let unversioned: Vec<u8> = bincode::serialize(&vec![1u32, 2, 3]).unwrap();
+        assert!(decode::<Vec<u32>>("changes.dat", &unversioned).is_err());
+
+        let mut newer: Vec<u8> = MAGIC.to_vec();
+        newer.extend_from_slice(&(VERSION + 1).to_le_bytes());
+        newer.extend_from_slice(&unversioned);
+        assert!(decode::<Vec<u32>>("changes.dat", &newer)
+            .unwrap_err()
+            .to_string()
+            .contains("newer release"));
+    }
+
+    #[test]
+    fn convert_original_bookings() {
+        let content: Vec<u8> = bincode::serialize(&original_bookings()).unwrap();
+        let booking_list: HashMap<u32, RoomBooking> =
+            decode_bookings("booking.dat", &content).unwrap();
+
+        assert_eq!(booking_list.len(), 2);
+        assert_eq!(booking_list[&1].customer_id, 7);
+        assert_eq!(booking_list[&1].check_out_date, "2023-05-03");
+        assert_eq!(booking_list[&1].status, Some(BookingStatus::Complete));
+        assert_eq!(booking_list[&1].guests, 1);
+        assert_eq!(booking_list[&2].status, Some(BookingStatus::Cancelled));
+
+        let content: Vec<u8> = encode(&booking_list).unwrap();
+        assert_eq!(
+            decode_bookings("booking.dat", &content).unwrap(),
+            booking_list
+        );
+    }
+
+    #[test]
+    fn load_original_snapshot() {
+        let storage = IsolatedStorage::new();
+        let content: Vec<u8> = bincode::serialize(&original_bookings()).unwrap();
+        std::fs::write(storage.directory().join("booking.dat"), content).unwrap();
+
+        assert!(crate::storage::load_snapshot().is_ok());
+        assert_eq!(
+            crate::storage::fetch_by_id(1).unwrap().status,
+            Some(BookingStatus::Complete)
+        );
+
+        std::fs::write(storage.directory().join("booking.dat"), [1, 2, 3]).unwrap();
+        assert!(crate::storage::load_snapshot().is_err());
+    }
+}
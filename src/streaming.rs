@@ -0,0 +1,182 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::fields;
+use rocket::futures::stream;
+use rocket::http::ContentType;
+use rocket::request::{FromRequest, Outcome};
+use rocket::response::stream::ByteStream;
+use rocket::response::{self, Responder};
+use rocket::serde::json::{serde_json, Json};
+use rocket::Request;
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::okapi::openapi3::Responses;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use rocket_okapi::request::{OpenApiFromRequest, RequestHeaderInput};
+use rocket_okapi::response::OpenApiResponderInner;
+use serde::Serialize;
+use std::iter;
+
+/// The media type of newline delimited JSON, where each item is written on its own line.
+static NDJSON: &str = "application/x-ndjson";
+
+/// Defines the formats a streamed list can be written in, negotiated using the ```Accept```
+/// request header. Newline delimited JSON is used if the client accepts it, and a JSON array
+/// otherwise.
+#[derive(Clone, Copy, Serialize, PartialEq, Debug)]
+pub enum StreamFormat {
+    Json,
+    Ndjson,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for StreamFormat {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<StreamFormat, ()> {
+        let format: StreamFormat = match request.headers().get_one("Accept") {
+            Some(accept) if accept.contains(NDJSON) => StreamFormat::Ndjson,
+            _ => StreamFormat::Json,
+        };
+
+        return Outcome::Success(format);
+    }
+}
+
+impl<'r> OpenApiFromRequest<'r> for StreamFormat {
+    fn from_request_input(
+        _: &mut OpenApiGenerator,
+        _: String,
+        _: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        return Ok(RequestHeaderInput::None);
+    }
+}
+
+/// A list of items which is serialized one item at a time as the response body is sent, so the
+/// whole list is never held in memory. When a list of fields is provided, only those fields of
+/// each item are included.
+pub struct Streamed<T> {
+    items: Box<dyn Iterator<Item = T> + Send>,
+    format: StreamFormat,
+    fields: Option<Vec<String>>,
+}
+
+impl<T: Serialize + Send + 'static> Streamed<T> {
+    /// Creates a new streamed list.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - An iterator over the items to include in the list, e.g. a ```BookingCursor```
+    /// * `format` - The format to write the list in
+    /// * `fields` - A comma separated list of the (serialized) field names to include. All
+    ///   fields are included if not provided.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let list = Streamed::new(BookingCursor::new(booking_ids), format, Some("bookingId,status"));
+    /// ```
+    pub fn new<I: Iterator<Item = T> + Send + 'static>(
+        items: I,
+        format: StreamFormat,
+        fields: Option<&str>,
+    ) -> Streamed<T> {
+        return Streamed {
+            items: Box::new(items),
+            format,
+            fields: fields::parse(fields),
+        };
+    }
+
+    /// Returns the content type of the response body.
+    fn content_type(&self) -> ContentType {
+        return match self.format {
+            StreamFormat::Json => ContentType::JSON,
+            StreamFormat::Ndjson => ContentType::new("application", "x-ndjson"),
+        };
+    }
+
+    /// Returns an iterator over the chunks of the response body, serializing each item as it is
+    /// reached. Items which cannot be serialized are left out.
+    fn chunks(self) -> Box<dyn Iterator<Item = Vec<u8>> + Send> {
+        let fields: Option<Vec<String>> = self.fields;
+        let items = self.items.filter_map(move |item| {
+            return match &fields {
+                Some(fields) => {
+                    fields::select(&item, fields).and_then(|value| serde_json::to_vec(&value))
+                }
+                None => serde_json::to_vec(&item),
+            }
+            .ok();
+        });
+
+        return match self.format {
+            StreamFormat::Json => Box::new(
+                iter::once(b"[".to_vec())
+                    .chain(items.enumerate().map(|(index, mut item)| {
+                        if index > 0 {
+                            item.insert(0, b',');
+                        }
+                        return item;
+                    }))
+                    .chain(iter::once(b"]".to_vec())),
+            ),
+            StreamFormat::Ndjson => Box::new(items.map(|mut item| {
+                item.push(b'\n');
+                return item;
+            })),
+        };
+    }
+}
+
+impl<'r, T: Serialize + Send + 'static> Responder<'r, 'r> for Streamed<T> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'r> {
+        let content_type: ContentType = self.content_type();
+        let body = ByteStream::from(stream::iter(self.chunks()));
+
+        return (content_type, body).respond_to(request);
+    }
+}
+
+impl<T: Serialize + JsonSchema + Send> OpenApiResponderInner for Streamed<T> {
+    fn responses(gen: &mut OpenApiGenerator) -> rocket_okapi::Result<Responses> {
+        return Json::<Vec<T>>::responses(gen);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::streaming::*;
+    use rocket::serde::json::{json, Value};
+
+    fn body(list: Streamed<Value>) -> String {
+        return String::from_utf8(list.chunks().flatten().collect()).unwrap();
+    }
+
+    #[test]
+    fn write_chunks() {
+        let items: Vec<Value> = vec![
+            json!({"bookingId": 1, "status": "Confirmed"}),
+            json!({"bookingId": 2, "status": "Cancelled"}),
+        ];
+
+        let list = Streamed::new(items.clone().into_iter(), StreamFormat::Json, None);
+        assert_eq!(
+            serde_json::from_str::<Value>(&body(list)).unwrap(),
+            Value::Array(items.clone())
+        );
+
+        let list = Streamed::new(
+            items.clone().into_iter(),
+            StreamFormat::Ndjson,
+            Some("bookingId"),
+        );
+        assert_eq!(body(list), "{\"bookingId\":1}\n{\"bookingId\":2}\n");
+
+        let list = Streamed::new(Vec::<Value>::new().into_iter(), StreamFormat::Json, None);
+        assert_eq!(body(list), "[]");
+    }
+}
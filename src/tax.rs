@@ -0,0 +1,164 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::config;
+use crate::pricing::PriceLine;
+use crate::storage::pricing_record::AppliedTax;
+use crate::storage::room_booking::RoomBooking;
+pub use booking_types::pricing_record::TaxCharge;
+use serde::{Deserialize, Serialize};
+
+/// Describes a tax charged on bookings
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct TaxRule {
+    /// The name of the tax shown on quotes and invoices, which is also used to exempt a booking
+    /// from the tax, e.g. 'VAT'
+    pub name: String,
+    pub charge: TaxCharge,
+    /// The rate of the tax. Percentage taxes are given in hundredths of a percent, e.g. 2000 for
+    /// 20%, and other taxes in the minor unit of the hotel's currency.
+    pub rate: u64,
+    /// The room types which are exempt from the tax
+    #[serde(default)]
+    pub exempt_room_type_ids: Vec<u8>,
+}
+
+impl TaxRule {
+    /// Checks whether a booking is exempt from the tax, either because of its room type or
+    /// because the exemption was recorded against the booking.
+    fn exempts(&self, booking: &RoomBooking) -> bool {
+        return self.exempt_room_type_ids.contains(&booking.room_type_id)
+            || booking
+                .tax_exemptions
+                .iter()
+                .any(|exemption| exemption.eq_ignore_ascii_case(&self.name));
+    }
+}
+
+/// Returns the taxes charged on bookings.
+///
+/// # Examples
+///
+/// ```
+/// let rules = tax::rules();
+/// ```
+pub fn rules() -> Vec<TaxRule> {
+    return config::hotel().taxes;
+}
+
+/// Checks each tax a booking is exempt from is a configured tax. Returns a list describing each
+/// problem found, which is empty if the exemptions are valid.
+///
+/// # Arguments
+///
+/// * `exemptions` - The names of the taxes the booking is exempt from
+///
+/// # Examples
+///
+/// ```
+/// let errors = tax::validate(&booking.tax_exemptions);
+/// ```
+pub fn validate(exemptions: &[String]) -> Vec<String> {
+    let rules: Vec<TaxRule> = rules();
+
+    return exemptions
+        .iter()
+        .filter(|exemption| {
+            !rules
+                .iter()
+                .any(|rule| rule.name.eq_ignore_ascii_case(exemption))
+        })
+        .map(|exemption| format!("{} is not a known tax", exemption))
+        .collect();
+}
+
+/// Returns each tax charged on a booking, other than taxes the booking is exempt from, at its
+/// current rate.
+///
+/// # Arguments
+///
+/// * `booking` - The booking to tax
+///
+/// # Examples
+///
+/// ```
+/// let taxes = tax::applied(&booking);
+/// ```
+pub fn applied(booking: &RoomBooking) -> Vec<AppliedTax> {
+    return rules()
+        .into_iter()
+        .filter(|rule| !rule.exempts(booking))
+        .map(|rule| AppliedTax {
+            name: rule.name,
+            charge: rule.charge,
+            rate: rule.rate,
+        })
+        .collect();
+}
+
+/// Calculates each tax charged on a booking. Bookings which have been priced are charged the
+/// taxes recorded when they were priced, and other bookings the taxes they are not exempt from.
+/// Percentage taxes are charged on the taxable amount, and per person taxes on each guest for
+/// each night of the stay. Taxes which come to nothing are left out.
+///
+/// # Arguments
+///
+/// * `booking` - The booking to tax
+/// * `taxable` - The price of the booking the tax is charged on, after any discount
+/// * `nights` - The number of nights charged for, which is 0 if the guest did not stay
+///
+/// # Examples
+///
+/// ```
+/// let taxes = tax::lines(&booking, breakdown.total, nights);
+/// ```
+pub fn lines(booking: &RoomBooking, taxable: u64, nights: u64) -> Vec<PriceLine> {
+    let taxes: Vec<AppliedTax> = match &booking.pricing {
+        Some(record) => record.taxes.clone(),
+        None => applied(booking),
+    };
+
+    return taxes
+        .iter()
+        .map(|tax| match tax.charge {
+            TaxCharge::Percentage => {
+                PriceLine::new(tax.name.clone(), 1, (taxable * tax.rate + 5000) / 10000)
+            }
+            TaxCharge::PerPersonPerNight => {
+                PriceLine::new(tax.name.clone(), booking.guests as u64 * nights, tax.rate)
+            }
+        })
+        .filter(|line| line.amount > 0)
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tax::*;
+
+    #[test]
+    fn tax_exemptions() {
+        let rule = TaxRule {
+            name: "City tax".to_string(),
+            charge: TaxCharge::PerPersonPerNight,
+            rate: 250,
+            exempt_room_type_ids: vec![5],
+        };
+        let mut booking: RoomBooking = rocket::serde::json::from_str(
+            r#"{"customerId":1,"roomTypeId":1,"checkInDate":"2020-07-01",
+            "checkOutDate":"2020-07-03","status":null,"guests":2}"#,
+        )
+        .unwrap();
+        assert!(!rule.exempts(&booking));
+
+        booking.tax_exemptions = vec!["city tax".to_string()];
+        assert!(rule.exempts(&booking));
+
+        booking.tax_exemptions = Vec::new();
+        booking.room_type_id = 5;
+        assert!(rule.exempts(&booking));
+        assert_eq!(validate(&["VAT".to_string()]).len(), 1);
+    }
+}
@@ -0,0 +1,277 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use crate::redaction;
+use once_cell::sync::Lazy;
+use rocket::config::{MutualTls, TlsConfig};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::figment::Figment;
+use rocket::tokio::time::{interval, Duration};
+use rocket::Rocket;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// The certificates read after a rotation, which the server is relaunched with, if any.
+static ROTATED: Lazy<Mutex<Option<Certificates>>> = Lazy::new(|| Mutex::new(None));
+
+/// Defines where PEM encoded TLS material is read from
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum PemSource {
+    /// A file, relative to the working directory if not absolute
+    Path(String),
+    /// An environment variable, holding the PEM encoded material itself
+    Env(String),
+}
+
+impl PemSource {
+    /// Reads the PEM encoded material from its source. Returns an error if the source cannot be
+    /// read, or does not hold PEM encoded material.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let certs = settings.certs.read()?;
+    /// ```
+    pub fn read(&self) -> Result<Vec<u8>, String> {
+        let pem: Vec<u8> = match self {
+            PemSource::Path(path) => std::fs::read(path)
+                .map_err(|error| format!("Unable to read {}: {}", path, error))?,
+            PemSource::Env(name) => std::env::var(name)
+                .map_err(|error| format!("Unable to read {}: {}", name, error))?
+                .into_bytes(),
+        };
+
+        if !String::from_utf8_lossy(&pem).contains("-----BEGIN ") {
+            return Err(format!("{} does not hold PEM encoded material", self));
+        }
+
+        return Ok(pem);
+    }
+}
+
+impl std::fmt::Display for PemSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            PemSource::Path(path) => write!(f, "{}", path),
+            PemSource::Env(name) => write!(f, "${}", name),
+        };
+    }
+}
+
+/// Describes the certificates the listener serves HTTPS with. Settings are read from the
+/// ```certificates``` section of ```Rocket.toml```, or ```ROCKET_CERTIFICATES``` environment
+/// variables, and replace any ```tls``` section if provided.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct CertificatesConfig {
+    /// The source of the certificate chain, with the server's certificate first
+    pub certs: PemSource,
+    /// The source of the private key of the server's certificate
+    pub key: PemSource,
+    /// The source of the bundle of certificate authorities client certificates are verified
+    /// against, if clients are asked for a certificate (mTLS)
+    #[serde(default)]
+    pub client_ca_certs: Option<PemSource>,
+    /// Whether connections without a valid client certificate are refused
+    #[serde(default)]
+    pub require_client_certificate: bool,
+    /// How often, in seconds, the sources are checked for rotated certificates. Certificates are
+    /// not reloaded if this is 0
+    #[serde(default)]
+    pub reload_interval_seconds: u64,
+}
+
+/// Holds the PEM encoded TLS material read from the configured sources
+#[derive(Clone, PartialEq, Debug)]
+pub struct Certificates {
+    pub certs: Vec<u8>,
+    pub key: Vec<u8>,
+    pub client_ca_certs: Option<Vec<u8>>,
+}
+
+impl Certificates {
+    /// Reads the TLS material from each of the configured sources. Returns an error describing the
+    /// first source which could not be read.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The certificate settings
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let certificates = Certificates::read(&settings)?;
+    /// ```
+    pub fn read(settings: &CertificatesConfig) -> Result<Certificates, String> {
+        return Ok(Certificates {
+            certs: settings.certs.read()?,
+            key: settings.key.read()?,
+            client_ca_certs: match &settings.client_ca_certs {
+                Some(source) => Some(source.read()?),
+                None => None,
+            },
+        });
+    }
+
+    /// Returns the Rocket TLS configuration serving these certificates.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The certificate settings
+    pub fn tls_config(&self, settings: &CertificatesConfig) -> TlsConfig {
+        let config: TlsConfig = TlsConfig::from_bytes(&self.certs, &self.key);
+        return match &self.client_ca_certs {
+            Some(ca_certs) => config.with_mutual(
+                MutualTls::from_bytes(ca_certs).mandatory(settings.require_client_certificate),
+            ),
+            None => config,
+        };
+    }
+}
+
+/// Returns the Rocket configuration sources, with the TLS configuration replaced by the provided
+/// certificates, if any.
+///
+/// # Arguments
+///
+/// * `settings` - The certificate settings, and the certificates read from them
+///
+/// # Examples
+///
+/// ```
+/// let rocket = rocket::custom(tls::figment(Some((&settings, &certificates))));
+/// ```
+pub fn figment(certificates: Option<(&CertificatesConfig, &Certificates)>) -> Figment {
+    let figment: Figment = rocket::Config::figment();
+    return match certificates {
+        Some((settings, certificates)) => figment.merge(("tls", certificates.tls_config(settings))),
+        None => figment,
+    };
+}
+
+/// Returns the certificates read after a rotation, if the server was shut down to reload them,
+/// clearing them so the next shutdown is not treated as a reload.
+///
+/// # Examples
+///
+/// ```
+/// if let Some(rotated) = tls::take_rotated() {
+///     certificates = rotated;
+/// }
+/// ```
+pub fn take_rotated() -> Option<Certificates> {
+    return match ROTATED.lock() {
+        Ok(mut rotated) => rotated.take(),
+        Err(_) => None,
+    };
+}
+
+/// A fairing which starts a background task when the server launches, checking the certificate
+/// sources for rotated certificates. Rocket cannot replace the certificates of a running listener,
+/// so once a rotation is found the server is shut down gracefully, letting requests in flight
+/// complete, and is relaunched with the new certificates.
+pub struct CertificateReload {
+    pub settings: CertificatesConfig,
+    /// The certificates the server was launched with
+    pub loaded: Certificates,
+}
+
+#[rocket::async_trait]
+impl Fairing for CertificateReload {
+    fn info(&self) -> Info {
+        return Info {
+            name: "Certificate reload",
+            kind: Kind::Liftoff,
+        };
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<rocket::Orbit>) {
+        if self.settings.reload_interval_seconds == 0 {
+            return;
+        }
+
+        let settings: CertificatesConfig = self.settings.clone();
+        let loaded: Certificates = self.loaded.clone();
+        let shutdown = rocket.shutdown();
+
+        rocket::tokio::spawn(async move {
+            let mut check = interval(Duration::from_secs(settings.reload_interval_seconds));
+            let mut pending: Option<Certificates> = None;
+
+            loop {
+                rocket::tokio::select! {
+                    _ = check.tick() => {}
+                    _ = shutdown.clone() => return,
+                }
+
+                let current: Certificates = match Certificates::read(&settings) {
+                    Ok(current) => current,
+                    Err(error) => {
+                        redaction::error(&format!(
+                            "Unable to check for rotated certificates: {}",
+                            error
+                        ));
+                        continue;
+                    }
+                };
+
+                // Files are often replaced one at a time, so a rotation is only applied once the
+                // certificates are unchanged between two checks.
+                if current == loaded {
+                    pending = None;
+                    continue;
+                }
+
+                if pending.as_ref() != Some(&current) {
+                    pending = Some(current);
+                    continue;
+                }
+
+                redaction::info("Certificates rotated, relaunching...");
+                if let Ok(mut rotated) = ROTATED.lock() {
+                    *rotated = Some(current);
+                }
+                shutdown.clone().notify();
+                return;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tls::*;
+    use rocket::figment::providers::{Format, Toml};
+
+    #[test]
+    fn read_pem_source() {
+        let pem = "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n";
+        std::env::set_var("ROOM_BOOKING_TEST_CERT_PEM", pem);
+        std::env::set_var("ROOM_BOOKING_TEST_NOT_PEM", "not a certificate");
+
+        let source = PemSource::Env("ROOM_BOOKING_TEST_CERT_PEM".to_string());
+        assert_eq!(source.read(), Ok(pem.as_bytes().to_vec()));
+
+        let source = PemSource::Env("ROOM_BOOKING_TEST_NOT_PEM".to_string());
+        assert_eq!(
+            source.read(),
+            Err("$ROOM_BOOKING_TEST_NOT_PEM does not hold PEM encoded material".to_string())
+        );
+        assert!(PemSource::Path("missing.pem".to_string()).read().is_err());
+
+        let settings: CertificatesConfig = Figment::new()
+            .merge(Toml::string(
+                r#"
+                certs = { env = "ROOM_BOOKING_TEST_CERT_PEM" }
+                key = { path = "server.key" }
+                "#,
+            ))
+            .extract()
+            .unwrap();
+        assert_eq!(settings.key, PemSource::Path("server.key".to_string()));
+        assert_eq!(settings.client_ca_certs, None);
+        assert_eq!(settings.reload_interval_seconds, 0);
+    }
+}
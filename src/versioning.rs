@@ -0,0 +1,109 @@
+/*
+    SPDX-License-Identifier: GPL-3.0-or-later
+    SPDX-FileCopyrightText: Copyright © 2023 Carl Ansell <@carlansell94>
+*/
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::uri::Origin;
+use rocket::http::Header;
+use rocket::{Data, Request, Response};
+
+/// The API versions served by the service, oldest first.
+pub static SUPPORTED_VERSIONS: &[&str] = &["v1"];
+/// The version used for requests which don't specify one.
+pub static CURRENT_VERSION: &str = "v1";
+/// Paths which are not versioned, such as the documentation UIs.
+static UNVERSIONED_PATHS: &[&str] = &["/swagger-ui", "/rapidoc"];
+
+/// Returns the API version a path belongs to, if it starts with a version segment.
+///
+/// # Arguments
+///
+/// * `path` - The request path, e.g. ```/v1/bookings```
+fn path_version(path: &str) -> Option<&str> {
+    let segment: &str = path.trim_start_matches('/').split('/').next()?;
+
+    let is_version: bool = segment.len() > 1
+        && segment.starts_with('v')
+        && segment[1..].chars().all(|c| c.is_ascii_digit());
+
+    return match is_version {
+        true => Some(segment),
+        false => None,
+    };
+}
+
+/// Selects the API version requested using the ```Accept-Version``` header, which may be given
+/// with or without the leading 'v' (e.g. ```1``` or ```v1```). Returns the current version if the
+/// header is missing.
+///
+/// # Arguments
+///
+/// * `accept_version` - The value of the ```Accept-Version``` request header, if present
+fn requested_version(accept_version: Option<&str>) -> String {
+    return match accept_version {
+        Some(version) => format!("v{}", version.trim().trim_start_matches('v')),
+        None => CURRENT_VERSION.to_string(),
+    };
+}
+
+/// A fairing which routes requests made without a version prefix (e.g. ```/bookings```) to the
+/// version requested in the ```Accept-Version``` header, or the current version if none was
+/// requested. The version used to serve each request is returned in the ```API-Version```
+/// response header.
+pub struct Versioning;
+
+#[rocket::async_trait]
+impl Fairing for Versioning {
+    fn info(&self) -> Info {
+        return Info {
+            name: "API version negotiation",
+            kind: Kind::Request | Kind::Response,
+        };
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        let path: String = request.uri().path().to_string();
+
+        if path_version(&path).is_some()
+            || UNVERSIONED_PATHS
+                .iter()
+                .any(|unversioned| path.starts_with(unversioned))
+        {
+            return;
+        }
+
+        let version: String = requested_version(request.headers().get_one("Accept-Version"));
+        let uri: String = match request.uri().query() {
+            Some(query) => format!("/{}{}?{}", version, path, query),
+            None => format!("/{}{}", version, path),
+        };
+
+        if let Ok(uri) = Origin::parse_owned(uri) {
+            request.set_uri(uri);
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        if let Some(version) = path_version(request.uri().path().as_str()) {
+            if SUPPORTED_VERSIONS.contains(&version) {
+                response.set_header(Header::new("API-Version", version.to_string()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::versioning::*;
+
+    #[test]
+    fn negotiate_version() {
+        assert_eq!(path_version("/v1/bookings"), Some("v1"));
+        assert_eq!(path_version("/bookings"), None);
+        assert_eq!(path_version("/vip"), None);
+        assert_eq!(requested_version(Some("2")), "v2");
+        assert_eq!(requested_version(Some("v1")), "v1");
+        assert_eq!(requested_version(None), CURRENT_VERSION);
+    }
+}